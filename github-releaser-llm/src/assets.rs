@@ -0,0 +1,151 @@
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+use crate::concurrency;
+use crate::github_client::{GitHubClient, GitHubRelease};
+
+/// A file to attach to the release, from `--asset <path>[#<content-type>]`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Asset {
+    pub path: PathBuf,
+    pub content_type: String,
+}
+
+impl Asset {
+    /// File name the asset gets on the release.
+    pub fn name(&self) -> String {
+        self.path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    }
+}
+
+/// Parse an `--asset` value, inferring the content type from the extension when none is given.
+pub fn parse_asset(value: &str) -> Result<Asset, String> {
+    let (path, content_type) = match value.rsplit_once('#') {
+        Some((path, content_type)) if !content_type.is_empty() => (PathBuf::from(path), content_type.to_string()),
+        _ => {
+            let path = PathBuf::from(value);
+            let content_type = content_type_for(&path).to_string();
+            (path, content_type)
+        }
+    };
+
+    if !path.is_file() {
+        return Err(format!("asset {} not found", path.display()));
+    }
+    Ok(Asset { path, content_type })
+}
+
+/// Content type for a file extension; unknown ones are uploaded as plain bytes.
+pub fn content_type_for(path: &Path) -> &'static str {
+    let extension = path
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+
+    match extension.as_str() {
+        "zip" => "application/zip",
+        "gz" | "tgz" => "application/gzip",
+        "tar" => "application/x-tar",
+        "json" => "application/json",
+        "pdf" => "application/pdf",
+        "deb" => "application/vnd.debian.binary-package",
+        "txt" | "md" | "sha256" | "asc" => "text/plain",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Uploads one asset; a seam so tests don't need the uploads API.
+pub trait AssetUploader {
+    async fn upload(&self, asset: &Asset) -> Result<(), Box<dyn Error>>;
+}
+
+/// Uploads to a GitHub release.
+pub struct ReleaseUploader<'a> {
+    pub client: &'a GitHubClient,
+    pub release: &'a GitHubRelease,
+}
+
+impl AssetUploader for ReleaseUploader<'_> {
+    async fn upload(&self, asset: &Asset) -> Result<(), Box<dyn Error>> {
+        let data = std::fs::read(&asset.path)?;
+        self.client
+            .upload_asset(self.release, &asset.name(), &asset.content_type, data)
+            .await
+    }
+}
+
+/// Upload every asset with at most `limit` in flight, returning each one's result in order.
+///
+/// A failed upload doesn't stop the others.
+pub async fn upload_all<U: AssetUploader>(
+    uploader: &U,
+    assets: &[Asset],
+    limit: usize,
+) -> Vec<Result<(), Box<dyn Error>>> {
+    let uploads = assets.iter().map(|asset| uploader.upload(asset)).collect();
+    concurrency::run_bounded(uploads, limit).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+    use tokio::runtime::Runtime;
+
+    /// Counts uploads in flight and fails the asset named `broken.bin`.
+    #[derive(Default)]
+    struct FakeUploader {
+        in_flight: AtomicUsize,
+        max_in_flight: AtomicUsize,
+    }
+
+    impl AssetUploader for FakeUploader {
+        async fn upload(&self, asset: &Asset) -> Result<(), Box<dyn Error>> {
+            let now = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_in_flight.fetch_max(now, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            self.in_flight.fetch_sub(1, Ordering::SeqCst);
+
+            if asset.name() == "broken.bin" {
+                Err("upload failed".into())
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    fn asset(name: &str) -> Asset {
+        Asset { path: PathBuf::from("dist").join(name), content_type: "application/octet-stream".to_string() }
+    }
+
+    #[test]
+    fn given_more_assets_than_the_limit_when_uploading_then_caps_uploads_and_keeps_going_after_a_failure() {
+        let uploader = FakeUploader::default();
+        let assets = vec![asset("a.bin"), asset("broken.bin"), asset("c.bin"), asset("d.bin"), asset("e.bin")];
+
+        let rt = Runtime::new().unwrap();
+        let results = rt.block_on(upload_all(&uploader, &assets, 3));
+
+        assert_eq!(uploader.max_in_flight.load(Ordering::SeqCst), 3);
+        let succeeded: Vec<bool> = results.iter().map(Result::is_ok).collect();
+        assert_eq!(succeeded, vec![true, false, true, true, true]);
+    }
+
+    #[test]
+    fn given_no_content_type_when_parsing_asset_then_infers_it_from_the_extension() {
+        assert_eq!(content_type_for(Path::new("dist/app.tar.gz")), "application/gzip");
+        assert_eq!(content_type_for(Path::new("dist/checksums.SHA256")), "text/plain");
+        assert_eq!(content_type_for(Path::new("dist/app")), "application/octet-stream");
+
+        let manifest = parse_asset("Cargo.toml").unwrap();
+        assert_eq!(manifest.content_type, "application/octet-stream");
+        let manifest = parse_asset("Cargo.toml#application/toml").unwrap();
+        assert_eq!(manifest.content_type, "application/toml");
+        assert_eq!(manifest.name(), "Cargo.toml");
+        assert!(parse_asset("missing.zip").is_err());
+    }
+}
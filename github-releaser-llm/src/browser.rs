@@ -0,0 +1,147 @@
+use std::error::Error;
+use std::io::IsTerminal;
+use std::process::{Command, Stdio};
+
+/// Starts a program without waiting for it; abstracted so tests don't open a browser.
+pub trait Launcher {
+    fn launch(&self, program: &str, args: &[String]) -> Result<(), Box<dyn Error>>;
+}
+
+/// Spawns the real program, detached from the run's output.
+pub struct SystemLauncher;
+
+impl Launcher for SystemLauncher {
+    fn launch(&self, program: &str, args: &[String]) -> Result<(), Box<dyn Error>> {
+        Command::new(program)
+            .args(args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()?;
+        Ok(())
+    }
+}
+
+/// What decides whether and how a browser can be opened.
+#[derive(Debug, Default)]
+pub struct Environment {
+    /// `linux`, `macos`, `windows`, ... as in `std::env::consts::OS`
+    pub os: &'static str,
+    /// The `BROWSER` variable
+    pub browser: Option<String>,
+    /// Whether `DISPLAY` or `WAYLAND_DISPLAY` is set
+    pub display: bool,
+    /// Whether `CI` is set
+    pub ci: bool,
+    pub terminal: bool,
+}
+
+impl Environment {
+    pub fn current() -> Self {
+        Environment {
+            os: std::env::consts::OS,
+            browser: std::env::var("BROWSER").ok().filter(|browser| !browser.trim().is_empty()),
+            display: std::env::var_os("DISPLAY").is_some() || std::env::var_os("WAYLAND_DISPLAY").is_some(),
+            ci: std::env::var_os("CI").is_some(),
+            terminal: std::io::stdout().is_terminal(),
+        }
+    }
+}
+
+/// The program and arguments that open `url`, or `None` when there's no one at a screen to see it.
+pub fn open_command(url: &str, env: &Environment) -> Option<(String, Vec<String>)> {
+    if env.ci || !env.terminal {
+        return None;
+    }
+    match env.os {
+        "macos" => Some(("open".to_string(), vec![url.to_string()])),
+        // The empty argument is the window title `start` expects before the target
+        "windows" => Some(("cmd".to_string(), ["/C", "start", "", url].map(str::to_string).to_vec())),
+        _ => {
+            // Like Python's webbrowser: the first of a colon-separated list, with `%s` standing for the URL
+            if let Some(browser) = env.browser.as_deref().and_then(|browser| browser.split(':').next()) {
+                let mut words = browser.split_whitespace().map(str::to_string);
+                let program = words.next()?;
+                let mut args: Vec<String> = words.collect();
+                if args.iter().any(|arg| arg.contains("%s")) {
+                    args = args.into_iter().map(|arg| arg.replace("%s", url)).collect();
+                } else {
+                    args.push(url.to_string());
+                }
+                return Some((program, args));
+            }
+            env.display.then(|| ("xdg-open".to_string(), vec![url.to_string()]))
+        }
+    }
+}
+
+/// Open `url` in a browser, or print it when none can be opened.
+pub fn open(url: &str, launcher: &dyn Launcher, env: &Environment) {
+    match open_command(url, env) {
+        Some((program, args)) => {
+            if let Err(e) = launcher.launch(&program, &args) {
+                println!("Couldn't open a browser ({}). The release is at {}", e, url);
+            }
+        }
+        None => println!("The release is at {}", url),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    const URL: &str = "https://github.com/o/r/releases/tag/v1.0.0";
+
+    fn linux(browser: Option<&str>, display: bool) -> Environment {
+        Environment { os: "linux", browser: browser.map(str::to_string), display, ci: false, terminal: true }
+    }
+
+    #[derive(Default)]
+    struct RecordingLauncher {
+        launched: RefCell<Vec<(String, Vec<String>)>>,
+    }
+
+    impl Launcher for RecordingLauncher {
+        fn launch(&self, program: &str, args: &[String]) -> Result<(), Box<dyn Error>> {
+            self.launched.borrow_mut().push((program.to_string(), args.to_vec()));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn given_each_platform_when_picking_open_command_then_uses_its_opener() {
+        let macos = Environment { os: "macos", terminal: true, ..Default::default() };
+        let windows = Environment { os: "windows", terminal: true, ..Default::default() };
+
+        assert_eq!(open_command(URL, &macos), Some(("open".to_string(), vec![URL.to_string()])));
+        assert_eq!(open_command(URL, &windows).unwrap().1, vec!["/C", "start", "", URL]);
+        assert_eq!(open_command(URL, &linux(None, true)), Some(("xdg-open".to_string(), vec![URL.to_string()])));
+    }
+
+    #[test]
+    fn given_browser_variable_on_linux_when_picking_open_command_then_it_wins_over_xdg_open() {
+        assert_eq!(
+            open_command(URL, &linux(Some("firefox --new-tab:chromium"), false)),
+            Some(("firefox".to_string(), vec!["--new-tab".to_string(), URL.to_string()]))
+        );
+        assert_eq!(open_command(URL, &linux(Some("w3m %s"), false)).unwrap().1, vec![URL]);
+    }
+
+    #[test]
+    fn given_ci_or_no_screen_when_opening_then_only_prints_the_url() {
+        let launcher = RecordingLauncher::default();
+        let ci = Environment { ci: true, ..linux(None, true) };
+        let piped = Environment { terminal: false, ..linux(None, true) };
+
+        for env in [ci, piped, linux(None, false)] {
+            assert_eq!(open_command(URL, &env), None);
+            open(URL, &launcher, &env);
+        }
+        assert!(launcher.launched.borrow().is_empty());
+
+        open(URL, &launcher, &linux(None, true));
+        assert_eq!(launcher.launched.borrow()[0].0, "xdg-open");
+    }
+}
@@ -0,0 +1,164 @@
+use reqwest::Client;
+use ring::hmac;
+use serde_json::{json, Value};
+use std::error::Error;
+use std::time::Duration;
+
+use crate::formatter::Ticket;
+
+/// Header carrying the HMAC-SHA256 of the body when `--callback-secret` is set.
+pub const SIGNATURE_HEADER: &str = "X-Releaser-Signature-256";
+
+/// How long the callback endpoint gets to answer; it never holds up the run for longer.
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Posts the completion event of a run to `--callback-url`.
+pub struct Callback {
+    client: Client,
+    url: String,
+    secret: Option<String>,
+}
+
+impl Callback {
+    pub fn new(client: Client, url: &str) -> Self {
+        Callback {
+            client,
+            url: url.to_string(),
+            secret: None,
+        }
+    }
+
+    /// Sign every event with this secret.
+    pub fn with_secret(mut self, secret: Option<String>) -> Self {
+        self.secret = secret;
+        self
+    }
+
+    /// Post the event, failing on a transport error or a non-2xx answer.
+    pub async fn send(&self, event: &Value) -> Result<(), Box<dyn Error>> {
+        let body = event.to_string();
+        let mut request = self
+            .client
+            .post(&self.url)
+            .timeout(TIMEOUT)
+            .header("Content-Type", "application/json");
+        if let Some(secret) = &self.secret {
+            request = request.header(SIGNATURE_HEADER, signature(secret, &body));
+        }
+
+        let resp = request.body(body).send().await?;
+        if resp.status().is_success() {
+            Ok(())
+        } else {
+            Err(format!("callback returned {}", resp.status()).into())
+        }
+    }
+}
+
+/// A repository and its outcome with the tickets its notes reference, or its error.
+pub type RepositoryResult<'a> = (String, Result<(&'a str, &'a [Ticket]), String>);
+
+/// The completion event: the tag, whether every repository succeeded, and each repository's outcome
+/// and the tickets its notes reference, or its error.
+pub fn event(tag: &str, results: &[RepositoryResult]) -> Value {
+    let repositories: Vec<Value> = results
+        .iter()
+        .map(|(repository, result)| match result {
+            Ok((outcome, tickets)) => json!({"repository": repository, "outcome": outcome, "tickets": tickets}),
+            Err(error) => json!({"repository": repository, "error": error}),
+        })
+        .collect();
+
+    json!({
+        "tag": tag,
+        "success": results.iter().all(|(_, result)| result.is_ok()),
+        "repositories": repositories,
+    })
+}
+
+/// The completion event of a run that stopped before it got to the repositories.
+pub fn failed_run_event(tag: &str, error: &str) -> Value {
+    json!({
+        "tag": tag,
+        "success": false,
+        "error": error,
+        "repositories": [],
+    })
+}
+
+/// `sha256=` followed by the hex HMAC-SHA256 of `body`, like GitHub's own webhook signatures.
+pub fn signature(secret: &str, body: &str) -> String {
+    let key = hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes());
+    let tag = hmac::sign(&key, body.as_bytes());
+    let hex: String = tag.as_ref().iter().map(|byte| format!("{:02x}", byte)).collect();
+    format!("sha256={}", hex)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::Matcher;
+    use tokio::runtime::Runtime;
+
+    #[test]
+    fn given_secret_when_signing_then_matches_rfc_4231_vector() {
+        assert_eq!(
+            signature("Jefe", "what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn given_secret_when_sending_event_then_posts_payload_with_signature() {
+        let mut server = mockito::Server::new();
+
+        let tickets = vec![Ticket {
+            prefix: "PDE".to_string(),
+            number: "1".to_string(),
+            url: "https://onezelis.atlassian.net/browse/PDE-1".to_string(),
+        }];
+        let event = event(
+            "v1.2.3",
+            &[
+                ("Human-Glitch/llm-playground".to_string(), Ok(("released", tickets.as_slice()))),
+                ("Human-Glitch/api".to_string(), Err("No release notes found or notes are empty.".to_string())),
+            ],
+        );
+        let mock = server.mock("POST", "/hooks/releases")
+            .match_header(SIGNATURE_HEADER, signature("s3cret", &event.to_string()).as_str())
+            .match_body(Matcher::Json(json!({
+                "tag": "v1.2.3",
+                "success": false,
+                "repositories": [
+                    {
+                        "repository": "Human-Glitch/llm-playground",
+                        "outcome": "released",
+                        "tickets": [{"prefix": "PDE", "number": "1", "url": "https://onezelis.atlassian.net/browse/PDE-1"}]
+                    },
+                    {"repository": "Human-Glitch/api", "error": "No release notes found or notes are empty."}
+                ]
+            })))
+            .with_status(204)
+            .create();
+
+        let callback = Callback::new(Client::new(), &format!("{}/hooks/releases", server.url()))
+            .with_secret(Some("s3cret".to_string()));
+        let rt = Runtime::new().unwrap();
+        rt.block_on(async { callback.send(&event).await.unwrap() });
+
+        mock.assert();
+    }
+
+    #[test]
+    fn given_run_that_stopped_early_when_building_event_then_reports_its_error() {
+        assert_eq!(
+            failed_run_event("v1.2.3", "OPENAI_API_KEY is missing."),
+            json!({
+                "tag": "v1.2.3",
+                "success": false,
+                "error": "OPENAI_API_KEY is missing.",
+                "repositories": []
+            })
+        );
+    }
+}
@@ -0,0 +1,892 @@
+use regex::Regex;
+use std::error::Error;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::github_client::{CommitSummary, Comparison, GitHubClient, GitHubRelease, COMPARE_FILE_LIMIT};
+use crate::conventional::CommitSections;
+use crate::output;
+use crate::version::Version;
+
+/// Where the raw release notes come from before formatting.
+#[derive(Clone, Copy, Debug, Default, PartialEq, clap::ValueEnum)]
+pub enum NotesSource {
+    /// GitHub's auto-generated release notes
+    #[default]
+    Github,
+    /// The commits between the previous release and the new tag
+    Compare,
+}
+
+/// Commit subjects left out of compare-based notes unless they're wanted: merges and release bumps.
+pub const DEFAULT_EXCLUDE_PATTERNS: &[&str] = &["^Merge ", r"^chore\(release\)"];
+
+/// Drops noise commits from compare-based notes by matching their subject line.
+#[derive(Clone, Debug)]
+pub struct CommitFilter {
+    excludes: Vec<Regex>,
+}
+
+impl Default for CommitFilter {
+    fn default() -> Self {
+        CommitFilter::new(Vec::new())
+    }
+}
+
+impl CommitFilter {
+    /// The default exclusions plus the `extra` patterns.
+    pub fn new(extra: Vec<Regex>) -> Self {
+        let mut excludes: Vec<Regex> = DEFAULT_EXCLUDE_PATTERNS
+            .iter()
+            .map(|pattern| Regex::new(pattern).expect("default patterns are valid"))
+            .collect();
+        excludes.extend(extra);
+        CommitFilter { excludes }
+    }
+
+    fn keeps(&self, commit: &CommitSummary) -> bool {
+        let subject = commit.commit.message.lines().next().unwrap_or("").trim();
+        !self.excludes.iter().any(|pattern| pattern.is_match(subject))
+    }
+
+    /// The commits whose subject matches none of the patterns.
+    pub fn apply(&self, commits: Vec<CommitSummary>) -> Vec<CommitSummary> {
+        let total = commits.len();
+        let kept: Vec<CommitSummary> = commits.into_iter().filter(|commit| self.keeps(commit)).collect();
+        if kept.len() < total {
+            println!("  Skipped {} commits matching the exclude patterns.", total - kept.len());
+        }
+        kept
+    }
+}
+
+/// Trailers `--strip-trailers` removes from commit messages, matched case-insensitively.
+pub const TRAILER_KEYS: &[&str] = &[
+    "Co-authored-by",
+    "Signed-off-by",
+    "Reviewed-by",
+    "Acked-by",
+    "Tested-by",
+    "Reported-by",
+    "Suggested-by",
+    "Helped-by",
+    "Cc",
+    "Change-Id",
+];
+
+/// The message without its trailer lines, and the names of its co-authors.
+///
+/// The subject line is never treated as a trailer.
+pub fn strip_trailers(message: &str) -> (String, Vec<String>) {
+    let mut kept: Vec<&str> = Vec::new();
+    let mut co_authors: Vec<String> = Vec::new();
+
+    for (index, line) in message.lines().enumerate() {
+        let trailer = line.split_once(':').filter(|(key, _)| {
+            index > 0 && TRAILER_KEYS.iter().any(|known| known.eq_ignore_ascii_case(key.trim()))
+        });
+        match trailer {
+            Some((key, value)) => {
+                if key.trim().eq_ignore_ascii_case("Co-authored-by") {
+                    // `Name <email>`; only the name goes into the notes
+                    let name = value.split('<').next().unwrap_or("").trim();
+                    if !name.is_empty() && !co_authors.iter().any(|known| known == name) {
+                        co_authors.push(name.to_string());
+                    }
+                }
+            }
+            None => kept.push(line),
+        }
+    }
+
+    (kept.join("\n").trim_end().to_string(), co_authors)
+}
+
+/// Strip the trailers from every commit message.
+pub fn strip_commit_trailers(commits: Vec<CommitSummary>) -> Vec<CommitSummary> {
+    commits
+        .into_iter()
+        .map(|mut commit| {
+            commit.commit.message = strip_trailers(&commit.commit.message).0;
+            commit
+        })
+        .collect()
+}
+
+/// Read every commit's `Co-authored-by:` trailers, so its line credits them after the author.
+pub fn credit_co_authors(commits: Vec<CommitSummary>) -> Vec<CommitSummary> {
+    commits
+        .into_iter()
+        .map(|mut commit| {
+            commit.co_authors = strip_trailers(&commit.commit.message).1;
+            commit
+        })
+        .collect()
+}
+
+/// Parse an `--exclude-commit-pattern` value.
+pub fn parse_pattern(value: &str) -> Result<Regex, String> {
+    Regex::new(value).map_err(|e| e.to_string())
+}
+
+/// Parse a `--since-duration` value: a whole number of hours, days or weeks, e.g. `36h`, `14d` or `2w`.
+pub fn parse_since_duration(value: &str) -> Result<Duration, String> {
+    let invalid = || format!("expected a number of hours, days or weeks such as 14d, got {:?}", value);
+    let unit_secs = match value.chars().last() {
+        Some('h') => 3_600,
+        Some('d') => 86_400,
+        Some('w') => 604_800,
+        _ => return Err(invalid()),
+    };
+    let count: u64 = value[..value.len() - 1].parse().ok().filter(|count| *count > 0).ok_or_else(invalid)?;
+    let secs = count.checked_mul(unit_secs).ok_or_else(|| format!("{:?} is too long a duration", value))?;
+    Ok(Duration::from_secs(secs))
+}
+
+/// A hand-written notes file, such as known issues, formatted together with the raw notes.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExtraNotes {
+    /// The file name, shown in the separator above its contents
+    pub label: String,
+    pub text: String,
+}
+
+/// Read an `--extra-notes` file; one that's missing or unreadable is an error before anything runs.
+pub fn read_extra_notes(value: &str) -> Result<ExtraNotes, String> {
+    let path = Path::new(value);
+    let text = std::fs::read_to_string(path).map_err(|e| format!("extra notes file {} can't be read: {}", path.display(), e))?;
+    let label = path.file_name().map_or(value.to_string(), |name| name.to_string_lossy().into_owned());
+    Ok(ExtraNotes { label, text })
+}
+
+/// The raw notes followed by each extra notes file, in order, under a separator naming it.
+pub fn with_extra_notes(raw_notes: &str, extras: &[ExtraNotes]) -> String {
+    if extras.is_empty() {
+        return raw_notes.to_string();
+    }
+    let mut combined = raw_notes.trim_end().to_string();
+    for extra in extras {
+        combined.push_str(&format!("\n\n<!-- extra notes: {} -->\n{}", extra.label, extra.text.trim_end()));
+    }
+    combined
+}
+
+/// Make sure an explicitly given previous tag exists before it's used as a baseline.
+pub async fn validate_previous_tag(gh_client: &GitHubClient, previous_tag: &str) -> Result<(), Box<dyn Error>> {
+    if gh_client.tag_exists(previous_tag).await? {
+        Ok(())
+    } else {
+        Err(format!("Previous tag {} does not exist in {}.", previous_tag, gh_client.repository()).into())
+    }
+}
+
+/// The tag a release is compared against: the explicit one, else the latest published release.
+pub async fn resolve_previous_tag(
+    gh_client: &GitHubClient,
+    explicit: Option<&str>,
+    tag: &str,
+) -> Result<Option<String>, Box<dyn Error>> {
+    if let Some(previous_tag) = explicit {
+        return Ok(Some(previous_tag.to_string()));
+    }
+
+    let latest = gh_client.get_latest_release().await?;
+    // Re-running a release must not compare the tag with itself
+    Ok(latest.map(|release| release.tag_name).filter(|previous| previous != tag))
+}
+
+/// The release the commit about to be tagged should contain: the explicit previous tag, else the
+/// previous release on the tag's major.minor line, so a hotfix on an older line isn't compared with a newer one.
+pub async fn history_base(gh_client: &GitHubClient, explicit: Option<&str>, tag: &str) -> Result<Option<String>, Box<dyn Error>> {
+    if let Some(previous_tag) = explicit {
+        return Ok(Some(previous_tag.to_string()));
+    }
+    Ok(same_line_previous_tag(tag, &gh_client.list_releases().await?, gh_client.tag_prefix()))
+}
+
+/// Which release GitHub's generated notes start from when `--prev-tag` isn't given.
+#[derive(Clone, Copy, Debug, Default, PartialEq, clap::ValueEnum)]
+pub enum NotesBase {
+    /// Let GitHub pick, which is usually its latest release
+    #[default]
+    Github,
+    /// The newest earlier release of the same major.minor line, else the newest earlier release
+    SameLine,
+}
+
+/// The newest published release before `tag` on its major.minor line, else the newest before it on any line.
+pub fn same_line_previous_tag(tag: &str, releases: &[GitHubRelease], tag_prefix: &str) -> Option<String> {
+    let version = Version::parse_with_prefix(tag, tag_prefix)?;
+    let earlier: Vec<(Version, &str)> = releases
+        .iter()
+        .filter(|release| release.draft != Some(true) && release.prerelease != Some(true))
+        .filter_map(|release| Some((Version::parse_with_prefix(&release.tag_name, tag_prefix)?, release.tag_name.as_str())))
+        .filter(|(candidate, _)| *candidate < version)
+        .collect();
+
+    let same_line = earlier
+        .iter()
+        .filter(|(candidate, _)| (candidate.major, candidate.minor) == (version.major, version.minor))
+        .max_by(|(a, _), (b, _)| a.cmp(b));
+    same_line
+        .or_else(|| earlier.iter().max_by(|(a, _), (b, _)| a.cmp(b)))
+        .map(|(_, tag_name)| tag_name.to_string())
+}
+
+/// The `previous_tag_name` for generating the notes of `tag`: the explicit one, else what `base` picks.
+pub async fn generate_notes_base(
+    gh_client: &GitHubClient,
+    explicit: Option<&str>,
+    tag: &str,
+    base: NotesBase,
+) -> Result<Option<String>, Box<dyn Error>> {
+    if let Some(previous_tag) = explicit {
+        return Ok(Some(previous_tag.to_string()));
+    }
+    match base {
+        NotesBase::Github => Ok(None),
+        NotesBase::SameLine => {
+            let previous_tag = same_line_previous_tag(tag, &gh_client.list_releases().await?, gh_client.tag_prefix());
+            if let Some(previous_tag) = &previous_tag {
+                println!("  Generating the notes since {}.", previous_tag);
+            }
+            Ok(previous_tag)
+        }
+    }
+}
+
+/// Why a branch head with this compare `status` against `base` probably lacks the version line's history.
+///
+/// `ahead` and `identical` heads contain `base`, so they're fine.
+pub fn branch_history_warning(status: &str, base: &str, branch: &str) -> Option<String> {
+    match status {
+        "ahead" | "identical" => None,
+        "behind" => Some(format!("Branch {} is behind {}, so the new tag would miss commits of {}.", branch, base, base)),
+        "diverged" => Some(format!("Branch {} has diverged from {}; its latest commit doesn't contain {}.", branch, base, base)),
+        other => Some(format!("Branch {} compares as '{}' with {}.", branch, other, base)),
+    }
+}
+
+/// Check that the commit about to be tagged contains the previous release, warning (or failing when
+/// `strict`) when it doesn't.
+///
+/// Returns the compare status, when the comparison worked, so callers can tell an `identical` head.
+pub async fn check_branch_history(
+    gh_client: &GitHubClient,
+    base: &str,
+    branch: &str,
+    head_sha: &str,
+    strict: bool,
+) -> Result<Option<String>, Box<dyn Error>> {
+    let (status, problem) = match gh_client.compare(base, head_sha).await {
+        Ok(comparison) => {
+            let problem = comparison.status.as_deref().and_then(|status| branch_history_warning(status, base, branch));
+            (comparison.status, problem)
+        }
+        Err(e) => (None, Some(format!("Couldn't compare branch {} with {}: {}", branch, base, e))),
+    };
+
+    match problem {
+        None => Ok(status),
+        Some(problem) if strict => Err(format!("{} Check the release branch or drop --strict-branch.", problem).into()),
+        Some(problem) => {
+            println!("{}", output::warning(problem));
+            Ok(status)
+        }
+    }
+}
+
+/// The branch head is the previous release's commit, so a compare-based release would have no notes.
+#[derive(Debug, thiserror::Error)]
+#[error("no changes since {since}, nothing to release")]
+pub struct NoChanges {
+    pub since: String,
+}
+
+/// Commits that went into `tag` since the previous release (`since` or auto-detected).
+///
+/// Without a previous release every commit from the root of the history is included.
+pub async fn collect_commits(
+    gh_client: &GitHubClient,
+    since: Option<&str>,
+    tag: &str,
+) -> Result<Vec<CommitSummary>, Box<dyn Error>> {
+    match resolve_previous_tag(gh_client, since, tag).await? {
+        Some(previous_tag) => {
+            println!("  Collecting commits from {} to {}...", previous_tag, tag);
+            Ok(gh_client.compare(&previous_tag, tag).await?.commits)
+        }
+        None => {
+            println!("  No previous release found. Collecting all commits up to {}...", tag);
+            gh_client.list_commits(tag, None).await
+        }
+    }
+}
+
+/// The commits up to `tag` committed at or after `since`, a UTC timestamp, however far back the previous
+/// release is.
+pub async fn collect_commits_since(gh_client: &GitHubClient, since: &str, tag: &str) -> Result<Vec<CommitSummary>, Box<dyn Error>> {
+    println!("  Collecting commits committed since {} up to {}...", since, tag);
+    gh_client.list_commits(tag, Some(since)).await
+}
+
+/// Files changed, insertions and deletions between two refs, as in `git diff --stat`.
+#[derive(Debug, PartialEq)]
+pub struct DiffStats {
+    pub files: usize,
+    pub insertions: u64,
+    pub deletions: u64,
+    /// GitHub truncated the file list, so the counts are a lower bound
+    pub approximate: bool,
+}
+
+impl DiffStats {
+    pub fn from_comparison(comparison: &Comparison) -> Self {
+        DiffStats {
+            files: comparison.files.len(),
+            insertions: comparison.files.iter().map(|file| file.additions).sum(),
+            deletions: comparison.files.iter().map(|file| file.deletions).sum(),
+            approximate: comparison.files.len() >= COMPARE_FILE_LIMIT,
+        }
+    }
+
+    /// One line such as `37 files changed, 1,204 insertions(+), 318 deletions(-)`.
+    pub fn summary_line(&self) -> String {
+        let line = format!(
+            "{} {} changed, {} {}(+), {} {}(-)",
+            group_thousands(self.files as u64),
+            if self.files == 1 { "file" } else { "files" },
+            group_thousands(self.insertions),
+            if self.insertions == 1 { "insertion" } else { "insertions" },
+            group_thousands(self.deletions),
+            if self.deletions == 1 { "deletion" } else { "deletions" },
+        );
+        if self.approximate {
+            format!("{} (approximate)", line)
+        } else {
+            line
+        }
+    }
+}
+
+/// Diff stats for everything that changed from `base` to `tag`.
+pub async fn diff_stats(gh_client: &GitHubClient, base: &str, tag: &str) -> Result<DiffStats, Box<dyn Error>> {
+    Ok(DiffStats::from_comparison(&gh_client.compare(base, tag).await?))
+}
+
+/// `1204` as `1,204`.
+fn group_thousands(n: u64) -> String {
+    let digits = n.to_string();
+    let mut grouped = String::new();
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    grouped
+}
+
+/// Render one commit the way GitHub's generated notes list changes, crediting its co-authors after the author.
+///
+/// With `body`, the non-blank lines of the message body follow, indented under the line.
+pub fn commit_line(commit: &CommitSummary, body: bool) -> String {
+    let subject = commit.commit.message.lines().next().unwrap_or("").trim();
+
+    let mut authors: Vec<String> = match (&commit.author, &commit.commit.author) {
+        (Some(user), _) => vec![format!("@{}", user.login)],
+        (None, Some(author)) => vec![author.name.clone()],
+        (None, None) => Vec::new(),
+    };
+    authors.extend(commit.co_authors.iter().cloned());
+
+    let mut line = if authors.is_empty() {
+        format!("* {}", subject)
+    } else {
+        format!("* {} by {}", subject, authors.join(" and "))
+    };
+    if body {
+        for body_line in commit.commit.message.lines().skip(1).map(str::trim).filter(|line| !line.is_empty()) {
+            line.push_str("\n  ");
+            line.push_str(body_line);
+        }
+    }
+    line
+}
+
+/// Build raw release notes from a list of commits, under "What's Changed" or in `sections` by commit type.
+///
+/// With `sha_links` each line ends with the short SHA linking to the commit in that repository.
+///
+/// With `bodies`, each line is followed by its commit's message body.
+pub fn commits_to_notes(
+    commits: &[CommitSummary],
+    sha_links: Option<&GitHubClient>,
+    sections: Option<&CommitSections>,
+    bodies: bool,
+) -> String {
+    let line = |commit: &CommitSummary| match sha_links {
+        Some(gh_client) if !commit.sha.is_empty() => {
+            // The sha link goes on the subject line, ahead of the body
+            let line = commit_line(commit, bodies);
+            let (subject, body) = line.split_once('\n').map_or((line.as_str(), None), |(subject, body)| (subject, Some(body)));
+            let short_sha: String = commit.sha.chars().take(7).collect();
+            let linked = format!("{} ([{}]({}))", subject, short_sha, gh_client.commit_url(&commit.sha));
+            match body {
+                Some(body) => format!("{}\n{}", linked, body),
+                None => linked,
+            }
+        }
+        _ => commit_line(commit, bodies),
+    };
+    if let Some(sections) = sections {
+        return sections.render(commits, line);
+    }
+    let lines: Vec<String> = commits.iter().map(line).collect();
+    format!("## What's Changed\n{}", lines.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::Client;
+    use tokio::runtime::Runtime;
+
+    fn client_for(server: &mockito::Server) -> GitHubClient {
+        GitHubClient::new_with_base_url(Client::new(), "fake_token".to_string(), server.url())
+    }
+
+    fn commits(subjects: &[&str]) -> Vec<CommitSummary> {
+        subjects
+            .iter()
+            .map(|subject| serde_json::from_value(serde_json::json!({"commit": {"message": subject}})).unwrap())
+            .collect()
+    }
+
+    fn subjects(commits: &[CommitSummary]) -> Vec<&str> {
+        commits.iter().map(|commit| commit.commit.message.as_str()).collect()
+    }
+
+    #[test]
+    fn given_since_durations_when_parsing_then_accepts_hours_days_and_weeks() {
+        assert_eq!(parse_since_duration("14d"), Ok(Duration::from_secs(14 * 86_400)));
+        assert_eq!(parse_since_duration("36h"), Ok(Duration::from_secs(36 * 3_600)));
+        assert_eq!(parse_since_duration("2w"), Ok(Duration::from_secs(14 * 86_400)));
+
+        for invalid in ["14", "d", "0d", "-3d", "14m", "1.5d", "99999999999999999w"] {
+            assert!(parse_since_duration(invalid).is_err(), "{} should be rejected", invalid);
+        }
+    }
+
+    #[test]
+    fn given_since_when_collecting_commits_then_lists_the_window_instead_of_comparing() {
+        let mut server = mockito::Server::new();
+
+        let mock_commits = server.mock("GET", "/repos/Human-Glitch/llm-playground/commits")
+            .match_query(mockito::Matcher::AllOf(vec![
+                mockito::Matcher::UrlEncoded("sha".into(), "v1.1.0".into()),
+                mockito::Matcher::UrlEncoded("since".into(), "2024-03-01T00:00:00Z".into()),
+            ]))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"[{"sha": "def456", "commit": {"message": "PDE-3 New feature"}}, {"sha": "abc123", "commit": {"message": "PDE-2 Cutoff fix"}}]"#)
+            .create();
+
+        let github_client = client_for(&server);
+
+        let rt = Runtime::new().unwrap();
+        let commits = rt.block_on(async {
+            collect_commits_since(&github_client, "2024-03-01T00:00:00Z", "v1.1.0").await.unwrap()
+        });
+
+        // No releases or compare mocks: the window alone decides the commits, wherever the previous release is
+        assert_eq!(subjects(&commits), vec!["PDE-2 Cutoff fix", "PDE-3 New feature"]);
+        mock_commits.assert();
+    }
+
+    #[test]
+    fn given_compare_payload_when_computing_diff_stats_then_sums_every_file() {
+        let mut server = mockito::Server::new();
+        let mock = server.mock("GET", "/repos/Human-Glitch/llm-playground/compare/v1.0.0...v1.1.0")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"commits": [], "files": [
+                {"filename": "src/main.rs", "additions": 1000, "deletions": 300, "changes": 1300},
+                {"filename": "src/lib.rs", "additions": 204, "deletions": 17, "changes": 221},
+                {"filename": "README.md", "additions": 0, "deletions": 1, "changes": 1}
+            ]}"#)
+            .create();
+
+        let rt = Runtime::new().unwrap();
+        let stats = rt.block_on(async { diff_stats(&client_for(&server), "v1.0.0", "v1.1.0").await.unwrap() });
+
+        assert_eq!(stats, DiffStats { files: 3, insertions: 1204, deletions: 318, approximate: false });
+        assert_eq!(stats.summary_line(), "3 files changed, 1,204 insertions(+), 318 deletions(-)");
+        mock.assert();
+    }
+
+    #[test]
+    fn given_truncated_file_list_when_computing_diff_stats_then_marks_them_approximate() {
+        let files: Vec<serde_json::Value> = (0..COMPARE_FILE_LIMIT)
+            .map(|_| serde_json::json!({"additions": 1, "deletions": 0}))
+            .collect();
+        let comparison: Comparison = serde_json::from_value(serde_json::json!({"files": files})).unwrap();
+
+        let stats = DiffStats::from_comparison(&comparison);
+
+        assert!(stats.approximate);
+        assert_eq!(stats.summary_line(), "300 files changed, 300 insertions(+), 0 deletions(-) (approximate)");
+    }
+
+    #[test]
+    fn given_include_sha_when_building_notes_then_links_short_sha_on_each_line() {
+        let github_client = GitHubClient::new(Client::new(), "fake_token".to_string());
+        let commits: Vec<CommitSummary> = serde_json::from_str(
+            r#"[
+                {"sha": "abc1234def5678", "commit": {"message": "PDE-1 Fix bug"}, "author": {"login": "alice"}},
+                {"sha": "0123456789abcd", "commit": {"message": "PDE-2 Add feature\n\nDetails"}, "author": {"login": "bob"}}
+            ]"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            commits_to_notes(&commits, Some(&github_client), None, false),
+            "## What's Changed\n\
+            * PDE-1 Fix bug by @alice ([abc1234](https://github.com/Human-Glitch/llm-playground/commit/abc1234def5678))\n\
+            * PDE-2 Add feature by @bob ([0123456](https://github.com/Human-Glitch/llm-playground/commit/0123456789abcd))"
+        );
+    }
+
+    #[test]
+    fn given_merge_and_release_commits_when_filtering_then_drops_them_by_default() {
+        let commits = commits(&[
+            "PDE-1 Fix bug",
+            "Merge pull request #12 from feature/x",
+            "chore(release): v1.2.0",
+            "chore: bump deps\n\nMerge notes in the body don't count",
+        ]);
+
+        let kept = CommitFilter::default().apply(commits);
+
+        assert_eq!(subjects(&kept), vec!["PDE-1 Fix bug", "chore: bump deps\n\nMerge notes in the body don't count"]);
+    }
+
+    #[test]
+    fn given_custom_pattern_when_filtering_then_drops_matches_on_top_of_defaults() {
+        let commits = commits(&["PDE-1 Fix bug", "Merge branch 'main'", "chore: bump deps", "docs: typo"]);
+
+        let filter = CommitFilter::new(vec![parse_pattern("^(chore|docs):").unwrap()]);
+
+        assert_eq!(subjects(&filter.apply(commits)), vec!["PDE-1 Fix bug"]);
+        assert!(parse_pattern("(").is_err());
+    }
+
+    #[test]
+    fn given_commit_with_trailers_when_stripping_and_crediting_then_drops_them_and_credits_co_authors() {
+        let commit: CommitSummary = serde_json::from_value(serde_json::json!({
+            "sha": "abc1234",
+            "commit": {"message": "PDE-1 Fix login\n\nRetry the token refresh once.\n\nSigned-off-by: Alice <alice@example.com>\nCo-authored-by: Bob Smith <bob@example.com>\nco-authored-by: Carol <carol@example.com>\nReviewed-by: Dan <dan@example.com>\nCo-authored-by: Bob Smith <bob@example.com>"},
+            "author": {"login": "alice"}
+        }))
+        .unwrap();
+
+        let stripped = strip_commit_trailers(credit_co_authors(vec![commit]));
+
+        assert_eq!(stripped[0].commit.message, "PDE-1 Fix login\n\nRetry the token refresh once.");
+        assert_eq!(stripped[0].co_authors, vec!["Bob Smith", "Carol"]);
+        assert_eq!(commit_line(&stripped[0], false), "* PDE-1 Fix login by @alice and Bob Smith and Carol");
+        assert_eq!(strip_trailers("Cc: not a trailer on the subject").0, "Cc: not a trailer on the subject");
+    }
+
+    #[test]
+    fn given_commit_bodies_when_stripping_trailers_then_they_leave_the_notes() {
+        let commits: Vec<CommitSummary> = serde_json::from_value(serde_json::json!([{
+            "sha": "abc1234def5678",
+            "commit": {"message": "PDE-1 Fix login\n\nRetry the token refresh once.\n\nSigned-off-by: Alice <alice@example.com>\nCo-authored-by: Bob Smith <bob@example.com>"},
+            "author": {"login": "alice"}
+        }]))
+        .unwrap();
+
+        assert_eq!(
+            commits_to_notes(&commits, None, None, true),
+            "## What's Changed\n\
+            * PDE-1 Fix login by @alice\n\
+            \x20 Retry the token refresh once.\n\
+            \x20 Signed-off-by: Alice <alice@example.com>\n\
+            \x20 Co-authored-by: Bob Smith <bob@example.com>"
+        );
+        // Only the trailers go; the co-authors aren't credited without --credit-co-authors
+        assert_eq!(
+            commits_to_notes(&strip_commit_trailers(commits), None, None, true),
+            "## What's Changed\n* PDE-1 Fix login by @alice\n  Retry the token refresh once."
+        );
+    }
+
+    #[test]
+    fn given_previous_release_when_collecting_commits_then_compares_against_it() {
+        let mut server = mockito::Server::new();
+
+        let mock_latest = server.mock("GET", "/repos/Human-Glitch/llm-playground/releases/latest")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"id": 1, "tag_name": "v1.0.0", "prerelease": false}"#)
+            .create();
+        let mock_compare = server.mock("GET", "/repos/Human-Glitch/llm-playground/compare/v1.0.0...v1.1.0")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"status": "ahead", "commits": [{"sha": "abc123", "commit": {"message": "PDE-1 Fix bug"}, "author": {"login": "alice"}}]}"#)
+            .create();
+
+        let github_client = client_for(&server);
+
+        let rt = Runtime::new().unwrap();
+        let commits = rt.block_on(async {
+            collect_commits(&github_client, None, "v1.1.0").await.unwrap()
+        });
+
+        assert_eq!(commits.len(), 1);
+        assert_eq!(commit_line(&commits[0], false), "* PDE-1 Fix bug by @alice");
+        mock_latest.assert();
+        mock_compare.assert();
+    }
+
+    #[test]
+    fn given_first_release_when_collecting_commits_then_lists_whole_history() {
+        let mut server = mockito::Server::new();
+
+        let mock_latest = server.mock("GET", "/repos/Human-Glitch/llm-playground/releases/latest")
+            .with_status(404)
+            .with_body(r#"{"message": "Not Found"}"#)
+            .create();
+        let mock_commits = server.mock("GET", "/repos/Human-Glitch/llm-playground/commits")
+            .match_query(mockito::Matcher::UrlEncoded("sha".into(), "v1.0.0".into()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"[
+                {"sha": "def456", "commit": {"message": "PDE-2 Second"}, "author": {"login": "bob"}},
+                {"sha": "abc123", "commit": {"message": "Initial commit", "author": {"name": "Alice Doe", "date": "2024-01-01T00:00:00Z"}}, "author": null}
+            ]"#)
+            .create();
+
+        let github_client = client_for(&server);
+
+        let rt = Runtime::new().unwrap();
+        let commits = rt.block_on(async {
+            collect_commits(&github_client, None, "v1.0.0").await.unwrap()
+        });
+
+        assert_eq!(
+            commits_to_notes(&commits, None, None, false),
+            "## What's Changed\n* Initial commit by Alice Doe\n* PDE-2 Second by @bob"
+        );
+        mock_latest.assert();
+        mock_commits.assert();
+    }
+
+    #[test]
+    fn given_explicit_since_when_collecting_commits_then_skips_latest_release_lookup() {
+        let mut server = mockito::Server::new();
+
+        let mock_latest = server.mock("GET", "/repos/Human-Glitch/llm-playground/releases/latest")
+            .expect(0)
+            .create();
+        let mock_compare = server.mock("GET", "/repos/Human-Glitch/llm-playground/compare/v0.9.0...v1.1.0")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"status": "ahead", "commits": []}"#)
+            .create();
+
+        let github_client = client_for(&server);
+
+        let rt = Runtime::new().unwrap();
+        let commits = rt.block_on(async {
+            collect_commits(&github_client, Some("v0.9.0"), "v1.1.0").await.unwrap()
+        });
+
+        assert!(commits.is_empty());
+        mock_latest.assert();
+        mock_compare.assert();
+    }
+
+    #[test]
+    fn given_missing_previous_tag_when_validating_then_returns_error() {
+        let mut server = mockito::Server::new();
+
+        let mock_ref = server.mock("GET", "/repos/Human-Glitch/llm-playground/git/ref/tags/v0.0.1")
+            .with_status(404)
+            .with_body(r#"{"message": "Not Found"}"#)
+            .create();
+
+        let github_client = client_for(&server);
+
+        let rt = Runtime::new().unwrap();
+        let result = rt.block_on(async {
+            validate_previous_tag(&github_client, "v0.0.1").await
+        });
+
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Previous tag v0.0.1 does not exist in Human-Glitch/llm-playground."
+        );
+        mock_ref.assert();
+    }
+
+    #[test]
+    fn given_extra_notes_files_when_combining_then_appends_them_in_order_and_rejects_missing_ones() {
+        let path = std::env::temp_dir().join(format!("releaser-known-issues-{}.md", std::process::id()));
+        std::fs::write(&path, "## Known Issues\n* Exports time out on large files\n").unwrap();
+        let known_issues = read_extra_notes(path.to_str().unwrap());
+        std::fs::remove_file(&path).unwrap();
+        let upgrade = ExtraNotes { label: "upgrade.md".to_string(), text: "Run the migration first.".to_string() };
+
+        let combined = with_extra_notes("## What's Changed\n* PDE-1 Fix by @a\n", &[known_issues.unwrap(), upgrade]);
+
+        assert_eq!(
+            combined,
+            format!(
+                "## What's Changed\n* PDE-1 Fix by @a\n\n\
+                <!-- extra notes: {} -->\n## Known Issues\n* Exports time out on large files\n\n\
+                <!-- extra notes: upgrade.md -->\nRun the migration first.",
+                path.file_name().unwrap().to_string_lossy()
+            )
+        );
+        assert!(read_extra_notes(path.to_str().unwrap()).unwrap_err().contains("can't be read"));
+    }
+
+    #[test]
+    fn given_existing_previous_tag_when_validating_then_succeeds() {
+        let mut server = mockito::Server::new();
+
+        let mock_ref = server.mock("GET", "/repos/Human-Glitch/llm-playground/git/ref/tags/v1.0.5")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"ref": "refs/tags/v1.0.5", "object": {"sha": "abc123", "type": "tag"}}"#)
+            .create();
+
+        let github_client = client_for(&server);
+
+        let rt = Runtime::new().unwrap();
+        let result = rt.block_on(async {
+            validate_previous_tag(&github_client, "v1.0.5").await
+        });
+
+        assert!(result.is_ok());
+        mock_ref.assert();
+    }
+
+    #[test]
+    fn given_latest_release_is_same_tag_when_resolving_previous_tag_then_returns_none() {
+        let mut server = mockito::Server::new();
+
+        let mock_latest = server.mock("GET", "/repos/Human-Glitch/llm-playground/releases/latest")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"id": 1, "tag_name": "v1.0.0"}"#)
+            .create();
+
+        let github_client = client_for(&server);
+
+        let rt = Runtime::new().unwrap();
+        let previous = rt.block_on(async {
+            resolve_previous_tag(&github_client, None, "v1.0.0").await.unwrap()
+        });
+
+        assert!(previous.is_none());
+        mock_latest.assert();
+    }
+
+    fn releases(tags: &[(&str, bool)]) -> Vec<GitHubRelease> {
+        tags.iter()
+            .map(|(tag, prerelease)| {
+                serde_json::from_value(serde_json::json!({"id": 1, "tag_name": tag, "prerelease": prerelease})).unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn given_earlier_release_on_the_same_line_when_picking_notes_base_then_prefers_it_over_newer_lines() {
+        let releases = releases(&[("v1.3.0", false), ("v1.2.1", false), ("v1.2.2-rc.1", true), ("v1.2.0", false), ("v1.2.3", false)]);
+
+        assert_eq!(same_line_previous_tag("v1.2.2", &releases, "v").as_deref(), Some("v1.2.1"));
+        assert_eq!(same_line_previous_tag("v1.2.1", &releases, "v").as_deref(), Some("v1.2.0"));
+    }
+
+    #[test]
+    fn given_first_release_of_a_line_when_picking_notes_base_then_falls_back_to_the_newest_earlier_release() {
+        let releases = releases(&[("v1.3.0", false), ("v1.2.1", false), ("v2.0.0", false), ("nightly", false)]);
+
+        assert_eq!(same_line_previous_tag("v1.4.0", &releases, "v").as_deref(), Some("v1.3.0"));
+        assert_eq!(same_line_previous_tag("v1.0.0", &releases, "v"), None);
+        assert_eq!(same_line_previous_tag("nightly-2", &releases, "v"), None);
+    }
+
+    #[test]
+    fn given_prev_tag_when_resolving_notes_base_then_it_wins_without_listing_releases() {
+        let mut server = mockito::Server::new();
+        let mock_list = server.mock("GET", mockito::Matcher::Any).expect(0).create();
+
+        let github_client = client_for(&server);
+        let rt = Runtime::new().unwrap();
+        let base = rt.block_on(async {
+            generate_notes_base(&github_client, Some("v1.0.0"), "v1.2.2", NotesBase::SameLine).await.unwrap()
+        });
+
+        assert_eq!(base.as_deref(), Some("v1.0.0"));
+        mock_list.assert();
+    }
+
+    #[test]
+    fn given_hotfix_on_an_older_line_when_picking_history_base_then_uses_that_lines_previous_release() {
+        let mut server = mockito::Server::new();
+        let mock_list = server.mock("GET", "/repos/Human-Glitch/llm-playground/releases")
+            .match_query(mockito::Matcher::Any)
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"[{"id": 2, "tag_name": "v1.3.0", "prerelease": false}, {"id": 1, "tag_name": "v1.2.4", "prerelease": false}]"#)
+            .create();
+        let mock_latest = server.mock("GET", "/repos/Human-Glitch/llm-playground/releases/latest").expect(0).create();
+
+        let github_client = client_for(&server);
+        let rt = Runtime::new().unwrap();
+        let base = rt.block_on(async { history_base(&github_client, None, "v1.2.5").await.unwrap() });
+
+        assert_eq!(base.as_deref(), Some("v1.2.4"));
+        mock_list.assert();
+        mock_latest.assert();
+    }
+
+    #[test]
+    fn given_ahead_or_identical_head_when_checking_branch_history_then_nothing_is_wrong() {
+        assert_eq!(branch_history_warning("ahead", "v1.2.0", "release/v1.3.x"), None);
+        assert_eq!(branch_history_warning("identical", "v1.2.0", "release/v1.3.x"), None);
+    }
+
+    #[test]
+    fn given_diverged_head_when_checking_branch_history_then_warns_and_strict_fails() {
+        assert_eq!(
+            branch_history_warning("diverged", "v1.2.0", "release/v1.3.x").as_deref(),
+            Some("Branch release/v1.3.x has diverged from v1.2.0; its latest commit doesn't contain v1.2.0.")
+        );
+
+        let mut server = mockito::Server::new();
+        let mock = server.mock("GET", "/repos/Human-Glitch/llm-playground/compare/v1.2.0...abc123")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"status": "diverged", "commits": []}"#)
+            .expect(2)
+            .create();
+        let gh_client = client_for(&server);
+
+        let rt = Runtime::new().unwrap();
+        let (lenient, strict) = rt.block_on(async {
+            (
+                check_branch_history(&gh_client, "v1.2.0", "release/v1.3.x", "abc123", false).await,
+                check_branch_history(&gh_client, "v1.2.0", "release/v1.3.x", "abc123", true).await,
+            )
+        });
+
+        assert!(lenient.is_ok());
+        assert!(strict.unwrap_err().to_string().contains("drop --strict-branch"));
+        mock.assert();
+    }
+}
@@ -0,0 +1,64 @@
+use futures::future::join_all;
+use std::future::Future;
+use tokio::sync::Semaphore;
+
+/// Run `tasks` concurrently with at most `limit` in flight at once.
+///
+/// Results are returned in the same order as `tasks`, once all of them have finished.
+pub async fn run_bounded<F, T>(tasks: Vec<F>, limit: usize) -> Vec<T>
+where
+    F: Future<Output = T>,
+{
+    let semaphore = Semaphore::new(limit.max(1));
+
+    join_all(tasks.into_iter().map(|task| async {
+        let _permit = semaphore.acquire().await.expect("semaphore is never closed");
+        task.await
+    }))
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+    use tokio::runtime::Runtime;
+
+    #[test]
+    fn given_more_tasks_than_limit_when_running_bounded_then_caps_tasks_in_flight() {
+        let in_flight = AtomicUsize::new(0);
+        let max_in_flight = AtomicUsize::new(0);
+
+        let rt = Runtime::new().unwrap();
+        let results = rt.block_on(async {
+            let tasks = (0..6)
+                .map(|i| {
+                    let in_flight = &in_flight;
+                    let max_in_flight = &max_in_flight;
+                    async move {
+                        let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                        max_in_flight.fetch_max(now, Ordering::SeqCst);
+                        tokio::time::sleep(Duration::from_millis(10)).await;
+                        in_flight.fetch_sub(1, Ordering::SeqCst);
+                        i
+                    }
+                })
+                .collect();
+
+            run_bounded(tasks, 2).await
+        });
+
+        assert_eq!(max_in_flight.load(Ordering::SeqCst), 2);
+        // Results keep the order of the input tasks
+        assert_eq!(results, vec![0, 1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn given_zero_limit_when_running_bounded_then_still_runs_tasks() {
+        let rt = Runtime::new().unwrap();
+        let results = rt.block_on(async { run_bounded((1..=2).map(|i| async move { i }).collect(), 0).await });
+
+        assert_eq!(results, vec![1, 2]);
+    }
+}
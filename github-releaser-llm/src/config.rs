@@ -0,0 +1,473 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+use crate::openai_client::ResponseShape;
+
+/// Config file read from the working directory when `--config` isn't given.
+pub const DEFAULT_CONFIG_PATH: &str = "github-releaser.toml";
+
+/// Settings that can come from the top level of the config, a profile, or the command line.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+pub struct Settings {
+    /// Owner of repositories given as plain names
+    pub owner: Option<String>,
+    /// Repositories to release, as name or owner/name
+    pub repos: Option<Vec<String>>,
+    /// The GitHub token itself, usually as `${VAR}`; wins over `token_env`
+    pub token: Option<String>,
+    /// Name of the environment variable holding the GitHub token
+    pub token_env: Option<String>,
+    /// GitHub API base URL, e.g. for GitHub Enterprise Server
+    pub base_url: Option<String>,
+    /// OpenAI-compatible gateways to send models to, first match wins
+    pub routes: Option<Vec<ModelRoute>>,
+    /// File holding the OpenAI key, read instead of OPENAI_API_KEY
+    pub openai_key_file: Option<PathBuf>,
+    /// Prompt preset per model, first match wins
+    pub model_presets: Option<Vec<ModelPreset>>,
+}
+
+/// Formats the notes of the models matching a glob with a named prompt preset.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct ModelPreset {
+    /// Model name glob, as in `[[routes]]`
+    pub model: String,
+    pub preset: String,
+}
+
+/// The preset of the first entry whose glob matches `model`.
+pub fn preset_for<'a>(presets: &'a [ModelPreset], model: &str) -> Option<&'a str> {
+    presets
+        .iter()
+        .find(|preset| glob_matches(&preset.model, model))
+        .map(|preset| preset.preset.as_str())
+}
+
+/// Sends the models matching a glob such as `claude-*` to another OpenAI-compatible gateway.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct ModelRoute {
+    /// Model name glob; `*` matches any run of characters and `?` a single one
+    pub model: String,
+    pub base_url: String,
+    /// Name of the environment variable holding the gateway's API key (default OPENAI_API_KEY)
+    pub key_env: Option<String>,
+    /// Where the gateway puts the answer, e.g. `responses` (default `chat-completions`)
+    pub response_shape: Option<ResponseShape>,
+}
+
+/// The first route whose glob matches `model`.
+pub fn route_for<'a>(routes: &'a [ModelRoute], model: &str) -> Option<&'a ModelRoute> {
+    routes.iter().find(|route| glob_matches(&route.model, model))
+}
+
+fn glob_matches(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    // Classic wildcard matching, backtracking to the last `*` on a mismatch
+    let (mut p, mut t) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match star {
+                Some((star_p, star_t)) => {
+                    p = star_p + 1;
+                    t = star_t + 1;
+                    star = Some((star_p, star_t + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+impl Settings {
+    /// Layer `over` on top of these settings: every value it sets wins.
+    pub fn overlay(self, over: Settings) -> Settings {
+        Settings {
+            owner: over.owner.or(self.owner),
+            repos: over.repos.or(self.repos),
+            token: over.token.or(self.token),
+            token_env: over.token_env.or(self.token_env),
+            base_url: over.base_url.or(self.base_url),
+            routes: over.routes.or(self.routes),
+            openai_key_file: over.openai_key_file.or(self.openai_key_file),
+            model_presets: over.model_presets.or(self.model_presets),
+        }
+    }
+
+    /// The GitHub token: the configured `token`, else `env` of the `token_env` variable (default GITHUB_TOKEN).
+    pub fn github_token(&self, env: impl Fn(&str) -> Option<String>) -> Result<String, String> {
+        if let Some(token) = &self.token {
+            return Ok(token.clone());
+        }
+        let token_env = self.token_env.as_deref().unwrap_or("GITHUB_TOKEN");
+        env(token_env).ok_or_else(|| format!("{} is missing.", token_env))
+    }
+}
+
+/// The API key from `key_file` when one is configured, else the `key_env` variable's `env_value`.
+pub fn resolve_api_key(key_file: Option<&Path>, key_env: &str, env_value: Option<String>) -> Result<String, String> {
+    match key_file {
+        Some(path) => {
+            let contents = std::fs::read_to_string(path)
+                .map_err(|e| format!("Failed to read the OpenAI key file {}: {}", path.display(), e))?;
+            let key = contents.trim();
+            if key.is_empty() {
+                return Err(format!("The OpenAI key file {} is empty", path.display()));
+            }
+            Ok(key.to_string())
+        }
+        None => env_value.ok_or_else(|| format!("{} is missing", key_env)),
+    }
+}
+
+/// The TOML config: top-level defaults plus named `[profiles.<name>]` tables.
+#[derive(Debug, Default, Deserialize)]
+pub struct Config {
+    #[serde(flatten)]
+    pub defaults: Settings,
+    #[serde(default)]
+    pub profiles: HashMap<String, Settings>,
+}
+
+impl Config {
+    pub fn parse(contents: &str) -> Result<Self, Box<dyn Error>> {
+        Self::parse_with_env(contents, |name| std::env::var(name).ok())
+    }
+
+    /// Parse the config, replacing `${VAR}` in string values with `env("VAR")`.
+    pub fn parse_with_env(contents: &str, env: impl Fn(&str) -> Option<String>) -> Result<Self, Box<dyn Error>> {
+        let mut value: toml::Value = toml::from_str(contents)?;
+        expand_env(&mut value, &env)?;
+        Ok(value.try_into()?)
+    }
+
+    /// Read the config at `path`, or the default file if it exists.
+    ///
+    /// An explicitly given path must exist; a missing default file just means no config.
+    pub fn load(path: Option<&Path>) -> Result<Self, Box<dyn Error>> {
+        let (path, required) = match path {
+            Some(path) => (path, true),
+            None => (Path::new(DEFAULT_CONFIG_PATH), false),
+        };
+
+        match std::fs::read_to_string(path) {
+            Ok(contents) => Self::parse(&contents).map_err(|e| format!("Invalid config {}: {}", path.display(), e).into()),
+            Err(e) if !required && e.kind() == std::io::ErrorKind::NotFound => Ok(Config::default()),
+            Err(e) => Err(format!("Failed to read config {}: {}", path.display(), e).into()),
+        }
+    }
+
+    /// Resolve the settings for a run: command-line flags > the selected profile > top-level defaults.
+    pub fn resolve(&self, profile: Option<&str>, flags: Settings) -> Result<Settings, Box<dyn Error>> {
+        let mut settings = self.defaults.clone();
+
+        if let Some(name) = profile {
+            let selected = self
+                .profiles
+                .get(name)
+                .ok_or_else(|| format!("Profile '{}' is not defined in the config.", name))?;
+            settings = settings.overlay(selected.clone());
+        }
+
+        Ok(settings.overlay(flags))
+    }
+}
+
+/// Expand `${VAR}` in every string of the config, failing on the first variable that isn't set.
+///
+/// `$${` stands for a literal `${`.
+fn expand_env(value: &mut toml::Value, env: &impl Fn(&str) -> Option<String>) -> Result<(), String> {
+    match value {
+        toml::Value::String(text) => *text = expand_vars(text, env)?,
+        toml::Value::Array(items) => {
+            for item in items {
+                expand_env(item, env)?;
+            }
+        }
+        toml::Value::Table(table) => {
+            for (_, item) in table.iter_mut() {
+                expand_env(item, env)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn expand_vars(text: &str, env: &impl Fn(&str) -> Option<String>) -> Result<String, String> {
+    let mut expanded = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("${") {
+        if rest[..start].ends_with('$') {
+            expanded.push_str(&rest[..start - 1]);
+            expanded.push_str("${");
+            rest = &rest[start + 2..];
+            continue;
+        }
+        expanded.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let end = after
+            .find('}')
+            .ok_or_else(|| format!("Unterminated ${{ in \"{}\"", text))?;
+        let name = &after[..end];
+        let resolved = env(name).ok_or_else(|| format!("The config references ${{{}}}, but {} is not set", name, name))?;
+        expanded.push_str(&resolved);
+        rest = &after[end + 1..];
+    }
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"
+owner = "Human-Glitch"
+token_env = "GITHUB_TOKEN"
+
+[profiles.staging]
+repos = ["llm-playground-staging"]
+token_env = "STAGING_GITHUB_TOKEN"
+
+[profiles.production]
+owner = "prod-org"
+repos = ["api", "web"]
+base_url = "https://github.example.com/api/v3"
+"#;
+
+    #[test]
+    fn given_profile_when_resolving_then_overrides_top_level_defaults() {
+        let config = Config::parse(CONFIG).unwrap();
+
+        let staging = config.resolve(Some("staging"), Settings::default()).unwrap();
+        let production = config.resolve(Some("production"), Settings::default()).unwrap();
+
+        assert_eq!(staging.owner.as_deref(), Some("Human-Glitch"));
+        assert_eq!(staging.repos, Some(vec!["llm-playground-staging".to_string()]));
+        assert_eq!(staging.token_env.as_deref(), Some("STAGING_GITHUB_TOKEN"));
+        assert_eq!(production.owner.as_deref(), Some("prod-org"));
+        assert_eq!(production.token_env.as_deref(), Some("GITHUB_TOKEN"));
+        assert_eq!(production.base_url.as_deref(), Some("https://github.example.com/api/v3"));
+    }
+
+    #[test]
+    fn given_flags_when_resolving_then_flags_beat_profile_and_defaults() {
+        let config = Config::parse(CONFIG).unwrap();
+        let flags = Settings {
+            owner: Some("cli-org".to_string()),
+            ..Default::default()
+        };
+
+        let settings = config.resolve(Some("production"), flags).unwrap();
+
+        // Flag > profile
+        assert_eq!(settings.owner.as_deref(), Some("cli-org"));
+        // Profile > top-level
+        assert_eq!(settings.repos, Some(vec!["api".to_string(), "web".to_string()]));
+        // Top-level when neither sets it
+        assert_eq!(settings.token_env.as_deref(), Some("GITHUB_TOKEN"));
+    }
+
+    #[test]
+    fn given_no_profile_when_resolving_then_uses_top_level_defaults() {
+        let config = Config::parse(CONFIG).unwrap();
+
+        let settings = config.resolve(None, Settings::default()).unwrap();
+
+        assert_eq!(settings, config.defaults);
+    }
+
+    #[test]
+    fn given_unknown_profile_when_resolving_then_returns_error() {
+        let config = Config::parse(CONFIG).unwrap();
+
+        let result = config.resolve(Some("qa"), Settings::default());
+
+        assert_eq!(result.unwrap_err().to_string(), "Profile 'qa' is not defined in the config.");
+    }
+
+    #[test]
+    fn given_routes_when_resolving_a_model_then_first_matching_glob_wins() {
+        let config = Config::parse(
+            r#"
+[[routes]]
+model = "claude-*"
+base_url = "https://openrouter.ai/api"
+key_env = "OPENROUTER_API_KEY"
+
+[[routes]]
+model = "gpt-4?-internal"
+base_url = "https://llm.internal.example.com"
+response_shape = "responses"
+
+[[routes]]
+model = "*"
+base_url = "https://fallback.example.com"
+"#,
+        )
+        .unwrap();
+        let routes = config.defaults.routes.unwrap();
+
+        let claude = route_for(&routes, "claude-3-5-sonnet").unwrap();
+        assert_eq!(claude.base_url, "https://openrouter.ai/api");
+        assert_eq!(claude.key_env.as_deref(), Some("OPENROUTER_API_KEY"));
+        assert_eq!(claude.response_shape, None);
+        let internal = route_for(&routes, "gpt-4o-internal").unwrap();
+        assert_eq!(internal.base_url, "https://llm.internal.example.com");
+        assert_eq!(internal.response_shape, Some(ResponseShape::Responses));
+        assert_eq!(route_for(&routes, "gpt-4o").unwrap().base_url, "https://fallback.example.com");
+        assert!(route_for(&routes[..2], "gpt-4o").is_none());
+    }
+
+    #[test]
+    fn given_model_presets_when_resolving_a_model_then_first_matching_glob_wins() {
+        let config = Config::parse(
+            r#"
+[[model_presets]]
+model = "gpt-4o-mini*"
+preset = "concise"
+
+[[model_presets]]
+model = "gpt-4*"
+preset = "detailed"
+"#,
+        )
+        .unwrap();
+        let presets = config.defaults.model_presets.unwrap();
+
+        assert_eq!(preset_for(&presets, "gpt-4o-mini-2024-07-18"), Some("concise"));
+        assert_eq!(preset_for(&presets, "gpt-4o"), Some("detailed"));
+        assert_eq!(preset_for(&presets, "o3-mini"), None);
+    }
+
+    #[test]
+    fn given_globs_when_matching_then_supports_star_and_question_mark() {
+        assert!(glob_matches("gpt-*", "gpt-4o"));
+        assert!(glob_matches("*-mini", "gpt-4o-mini"));
+        assert!(glob_matches("gpt-?o", "gpt-4o"));
+        assert!(glob_matches("a*b*c", "aXbYbZc"));
+        assert!(!glob_matches("gpt-*", "claude-3"));
+        assert!(!glob_matches("gpt-?", "gpt-4o"));
+    }
+
+    #[test]
+    fn given_key_file_when_resolving_api_key_then_reads_and_trims_it_over_the_environment() {
+        let path = std::env::temp_dir().join(format!("releaser-openai-key-{}", std::process::id()));
+        std::fs::write(&path, "  sk-from-file\n").unwrap();
+
+        let from_file = resolve_api_key(Some(&path), "OPENAI_API_KEY", Some("sk-from-env".to_string()));
+        std::fs::write(&path, "\n").unwrap();
+        let empty = resolve_api_key(Some(&path), "OPENAI_API_KEY", None);
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(from_file.unwrap(), "sk-from-file");
+        assert!(empty.unwrap_err().contains("is empty"));
+        assert!(resolve_api_key(Some(&path), "OPENAI_API_KEY", None).unwrap_err().contains("Failed to read"));
+    }
+
+    #[test]
+    fn given_key_file_in_flag_and_config_when_resolving_then_flag_wins_and_env_is_the_fallback() {
+        let config = Config::parse("openai_key_file = \"/etc/releaser/openai.key\"").unwrap();
+        let flags = Settings {
+            openai_key_file: Some(PathBuf::from("/run/secrets/openai")),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            config.resolve(None, flags).unwrap().openai_key_file,
+            Some(PathBuf::from("/run/secrets/openai"))
+        );
+        assert_eq!(
+            config.resolve(None, Settings::default()).unwrap().openai_key_file,
+            Some(PathBuf::from("/etc/releaser/openai.key"))
+        );
+        assert_eq!(resolve_api_key(None, "OPENAI_API_KEY", Some("sk-from-env".to_string())).unwrap(), "sk-from-env");
+        assert_eq!(resolve_api_key(None, "OPENAI_API_KEY", None).unwrap_err(), "OPENAI_API_KEY is missing");
+    }
+
+    fn env(name: &str) -> Option<String> {
+        match name {
+            "GHE_HOST" => Some("github.example.com".to_string()),
+            "OPENAI_KEY_PATH" => Some("/run/secrets/openai".to_string()),
+            "GITHUB_TOKEN" => Some("ghp_from_env".to_string()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn given_env_references_when_parsing_then_expands_them_in_every_string() {
+        let config = Config::parse_with_env(
+            r#"
+openai_key_file = "${OPENAI_KEY_PATH}"
+
+[profiles.production]
+base_url = "https://${GHE_HOST}/api/v3"
+repos = ["${GHE_HOST}-mirror"]
+"#,
+            env,
+        )
+        .unwrap();
+
+        assert_eq!(config.defaults.openai_key_file, Some(PathBuf::from("/run/secrets/openai")));
+        let production = &config.profiles["production"];
+        assert_eq!(production.base_url.as_deref(), Some("https://github.example.com/api/v3"));
+        assert_eq!(production.repos, Some(vec!["github.example.com-mirror".to_string()]));
+    }
+
+    #[test]
+    fn given_doubled_dollar_when_parsing_then_keeps_a_literal_reference() {
+        let config = Config::parse_with_env(r#"base_url = "https://$${GHE_HOST}/${GHE_HOST}""#, env).unwrap();
+
+        assert_eq!(config.defaults.base_url.as_deref(), Some("https://${GHE_HOST}/github.example.com"));
+    }
+
+    #[test]
+    fn given_token_setting_when_resolving_github_token_then_it_wins_over_token_env() {
+        let config = Config::parse_with_env(
+            r#"
+token = "${GITHUB_TOKEN}"
+
+[profiles.staging]
+token_env = "STAGING_GITHUB_TOKEN"
+"#,
+            env,
+        )
+        .unwrap();
+
+        let settings = config.resolve(Some("staging"), Settings::default()).unwrap();
+        assert_eq!(settings.github_token(env).unwrap(), "ghp_from_env");
+        assert_eq!(Settings::default().github_token(env).unwrap(), "ghp_from_env");
+        let staging_only = Settings { token_env: Some("STAGING_GITHUB_TOKEN".to_string()), ..Default::default() };
+        assert_eq!(staging_only.github_token(env).unwrap_err(), "STAGING_GITHUB_TOKEN is missing.");
+    }
+
+    #[test]
+    fn given_unset_env_reference_when_parsing_then_names_the_variable() {
+        let error = Config::parse_with_env(r#"token_env = "${UNSET_TOKEN_VAR}""#, env).unwrap_err();
+
+        assert_eq!(error.to_string(), "The config references ${UNSET_TOKEN_VAR}, but UNSET_TOKEN_VAR is not set");
+        assert!(Config::parse_with_env(r#"owner = "${OOPS""#, env).is_err());
+    }
+
+    #[test]
+    fn given_missing_default_config_when_loading_then_returns_empty_config() {
+        let config = Config::load(None).unwrap();
+
+        assert!(config.profiles.is_empty());
+    }
+}
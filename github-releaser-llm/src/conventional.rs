@@ -0,0 +1,196 @@
+use regex::Regex;
+use std::sync::LazyLock;
+
+use crate::github_client::CommitSummary;
+
+/// Section for commits whose type has no `--commit-section`.
+pub const OTHER_SECTION: &str = "Other";
+
+/// A conventional-commit subject split into its parts, e.g. `feat(api)!: Add paging`.
+#[derive(Debug, PartialEq)]
+pub struct ConventionalSubject<'a> {
+    /// The type, lower-cased
+    pub kind: String,
+    pub scope: Option<&'a str>,
+    pub breaking: bool,
+    pub description: &'a str,
+}
+
+/// `type(scope)!: description`, with the scope and `!` optional.
+static SUBJECT_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^(\w+)(?:\(([^)]*)\))?(!)?:\s+(\S.*)$").unwrap());
+
+/// Parse a subject line as a conventional commit, or `None` when it doesn't follow the convention.
+pub fn parse_subject(subject: &str) -> Option<ConventionalSubject<'_>> {
+    let caps = SUBJECT_RE.captures(subject.trim())?;
+    Some(ConventionalSubject {
+        kind: caps[1].to_lowercase(),
+        scope: caps.get(2).map(|scope| scope.as_str().trim()).filter(|scope| !scope.is_empty()),
+        breaking: caps.get(3).is_some(),
+        description: caps.get(4).unwrap().as_str(),
+    })
+}
+
+/// Parse a `--commit-section` value, `<type>=<title>`.
+pub fn parse_commit_section(value: &str) -> Result<(String, String), String> {
+    match value.split_once('=') {
+        Some((kind, title)) if !kind.trim().is_empty() && !title.trim().is_empty() => {
+            Ok((kind.trim().to_lowercase(), title.trim().to_string()))
+        }
+        _ => Err(format!("expected <type>=<title>, got {:?}", value)),
+    }
+}
+
+/// Groups commit-based notes into a section per conventional-commit type.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CommitSections {
+    /// Type and section title, in the order the sections are rendered
+    pub sections: Vec<(String, String)>,
+    /// Where commits of other types, and ones that aren't conventional, go; `None` drops them
+    pub other: Option<String>,
+}
+
+impl CommitSections {
+    /// Resolve the `--commit-section` / `--other-commit-section` / `--drop-other-commits` flags; `None` without any sections.
+    pub fn from_flags(sections: Vec<(String, String)>, other: String, drop_other: bool) -> Option<Self> {
+        if sections.is_empty() {
+            return None;
+        }
+        Some(CommitSections { sections, other: (!drop_other).then_some(other) })
+    }
+
+    /// The section title a commit goes under, or `None` when it's dropped.
+    fn title_for(&self, subject: Option<&ConventionalSubject>) -> Option<&str> {
+        subject
+            .and_then(|subject| self.sections.iter().find(|(kind, _)| *kind == subject.kind))
+            .map(|(_, title)| title.as_str())
+            .or(self.other.as_deref())
+    }
+
+    /// A `##` section per title, in the configured order with the other section last, each listing its commits
+    /// the way `line` renders them, with the type prefix taken off the subject.
+    ///
+    /// Several types can share a title; their commits are listed together. Empty sections are left out.
+    pub fn render(&self, commits: &[CommitSummary], line: impl Fn(&CommitSummary) -> String) -> String {
+        let mut titles: Vec<&str> = Vec::new();
+        for (_, title) in &self.sections {
+            if !titles.contains(&title.as_str()) {
+                titles.push(title);
+            }
+        }
+        if let Some(other) = self.other.as_deref().filter(|other| !titles.contains(other)) {
+            titles.push(other);
+        }
+
+        let mut lines: Vec<Vec<String>> = vec![Vec::new(); titles.len()];
+        for commit in commits {
+            let subject_line = commit.commit.message.lines().next().unwrap_or("");
+            let subject = parse_subject(subject_line);
+            let Some(title) = self.title_for(subject.as_ref()) else {
+                continue;
+            };
+            let index = titles.iter().position(|known| *known == title).unwrap();
+            lines[index].push(line(&without_type(commit, subject.as_ref())));
+        }
+
+        titles
+            .iter()
+            .zip(lines)
+            .filter(|(_, lines)| !lines.is_empty())
+            .map(|(title, lines)| format!("## {}\n{}", title, lines.join("\n")))
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+}
+
+/// The commit with its subject reduced to the description, keeping the scope and a breaking-change marker.
+fn without_type(commit: &CommitSummary, subject: Option<&ConventionalSubject>) -> CommitSummary {
+    let Some(subject) = subject else {
+        return commit.clone();
+    };
+    let mut description = match subject.scope {
+        Some(scope) => format!("{}: {}", scope, subject.description),
+        None => subject.description.to_string(),
+    };
+    if subject.breaking {
+        description = format!("**Breaking:** {}", description);
+    }
+    let body = commit.commit.message.split_once('\n').map(|(_, body)| body);
+
+    let mut commit = commit.clone();
+    commit.commit.message = match body {
+        Some(body) => format!("{}\n{}", description, body),
+        None => description,
+    };
+    commit
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn commits(subjects: &[&str]) -> Vec<CommitSummary> {
+        subjects
+            .iter()
+            .map(|subject| serde_json::from_value(serde_json::json!({"commit": {"message": subject}})).unwrap())
+            .collect()
+    }
+
+    fn subject_line(commit: &CommitSummary) -> String {
+        format!("* {}", commit.commit.message.lines().next().unwrap())
+    }
+
+    const MIXED: [&str; 6] = [
+        "fix(api): Handle empty pages",
+        "feat!: Drop the v1 endpoints\n\nBREAKING CHANGE: v1 is gone",
+        "chore: Bump serde",
+        "Update README",
+        "perf: Cache the token",
+        "feat(web): Add dark mode",
+    ];
+
+    #[test]
+    fn given_mixed_commits_when_rendering_sections_then_follows_the_configured_order_with_other_last() {
+        let sections = CommitSections::from_flags(
+            vec![
+                ("feat".to_string(), "✨ Features".to_string()),
+                ("fix".to_string(), "🐛 Bug Fixes".to_string()),
+                ("perf".to_string(), "🐛 Bug Fixes".to_string()),
+            ],
+            OTHER_SECTION.to_string(),
+            false,
+        )
+        .unwrap();
+
+        assert_eq!(
+            sections.render(&commits(&MIXED), subject_line),
+            "## ✨ Features\n\
+            * **Breaking:** Drop the v1 endpoints\n\
+            * web: Add dark mode\n\
+            \n\
+            ## 🐛 Bug Fixes\n\
+            * api: Handle empty pages\n\
+            * Cache the token\n\
+            \n\
+            ## Other\n\
+            * Bump serde\n\
+            * Update README"
+        );
+    }
+
+    #[test]
+    fn given_drop_other_when_rendering_sections_then_leaves_unmapped_commits_out() {
+        let sections = CommitSections::from_flags(vec![("fix".to_string(), "Fixes".to_string())], OTHER_SECTION.to_string(), true).unwrap();
+
+        assert_eq!(sections.render(&commits(&MIXED), subject_line), "## Fixes\n* api: Handle empty pages");
+    }
+
+    #[test]
+    fn given_subjects_when_parsing_then_recognizes_only_conventional_ones() {
+        assert_eq!(
+            parse_subject("Feat(ui)!: Add themes"),
+            Some(ConventionalSubject { kind: "feat".to_string(), scope: Some("ui"), breaking: true, description: "Add themes" })
+        );
+        assert_eq!(parse_subject("PDE-1 Fix login"), None);
+        assert_eq!(parse_subject("fix:no space"), None);
+    }
+}
@@ -0,0 +1,195 @@
+use crate::github_client::GitHubClient;
+use crate::openai_client::OpenAIClient;
+
+/// Scopes a classic token needs to manage tags and releases.
+const REQUIRED_SCOPES: &[&str] = &["repo"];
+
+/// Outcome of a single preflight check.
+#[derive(Debug, PartialEq)]
+pub enum CheckStatus {
+    Pass,
+    /// Worth a look, but doesn't block a release
+    Warn,
+    Fail,
+}
+
+/// One line of the `doctor` checklist.
+#[derive(Debug)]
+pub struct Check {
+    pub name: String,
+    pub status: CheckStatus,
+    pub detail: String,
+}
+
+impl Check {
+    fn pass(name: impl Into<String>, detail: impl Into<String>) -> Self {
+        Check { name: name.into(), status: CheckStatus::Pass, detail: detail.into() }
+    }
+
+    fn warn(name: impl Into<String>, detail: impl Into<String>) -> Self {
+        Check { name: name.into(), status: CheckStatus::Warn, detail: detail.into() }
+    }
+
+    fn fail(name: impl Into<String>, detail: impl Into<String>) -> Self {
+        Check { name: name.into(), status: CheckStatus::Fail, detail: detail.into() }
+    }
+}
+
+/// Whether a run can go ahead: warnings are fine, any failed check is not.
+pub fn all_passed(checks: &[Check]) -> bool {
+    checks.iter().all(|check| check.status != CheckStatus::Fail)
+}
+
+/// Render the checklist, one check per line.
+pub fn render(checks: &[Check]) -> String {
+    checks
+        .iter()
+        .map(|check| {
+            let icon = match check.status {
+                CheckStatus::Pass => "✅",
+                CheckStatus::Warn => "⚠️ ",
+                CheckStatus::Fail => "❌",
+            };
+            format!("{} {}: {}", icon, check.name, check.detail)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Run every preflight check without changing anything.
+///
+/// `openai` is `None` when the run wouldn't call OpenAI at all, and an error when the key is missing.
+pub async fn run(
+    gh_clients: &[GitHubClient],
+    token_present: bool,
+    openai: Option<Result<OpenAIClient, String>>,
+    tag: Option<&str>,
+) -> Vec<Check> {
+    let mut checks = Vec::new();
+
+    if !token_present {
+        checks.push(Check::fail("GitHub token", "no token found in GITHUB_TOKEN (or the configured token_env)"));
+    } else if let Some(gh_client) = gh_clients.first() {
+        // Fine-grained tokens don't report scopes, so each repository is probed instead
+        let mut fine_grained = false;
+        match gh_client.authenticated_user().await {
+            Ok(user) => {
+                checks.push(Check::pass("GitHub token", format!("authenticated as {}", user.login)));
+                fine_grained = user.scopes.is_none();
+                checks.push(match user.missing_scopes(REQUIRED_SCOPES) {
+                    None => Check::warn("Token scopes", "not reported (fine-grained token); checking repository access instead"),
+                    Some(missing) if missing.is_empty() => Check::pass("Token scopes", REQUIRED_SCOPES.join(", ")),
+                    Some(missing) => Check::fail("Token scopes", format!("missing {}", missing.join(", "))),
+                });
+            }
+            Err(e) => checks.push(Check::fail("GitHub token", e.to_string())),
+        }
+
+        for gh_client in gh_clients {
+            checks.extend(repository_checks(gh_client, tag, fine_grained).await);
+        }
+    }
+
+    match openai {
+        None => {}
+        Some(Err(reason)) => checks.push(Check::fail("OpenAI key", reason)),
+        Some(Ok(client)) => checks.push(match client.list_models().await {
+            Ok(models) => Check::pass("OpenAI key", format!("{} models available", models.len())),
+            Err(e) => Check::fail("OpenAI key", e.to_string()),
+        }),
+    }
+
+    checks
+}
+
+/// Checks that the repository is reachable and the tag's release branch resolves.
+///
+/// With `check_access`, also confirms the token can write to it.
+async fn repository_checks(gh_client: &GitHubClient, tag: Option<&str>, check_access: bool) -> Vec<Check> {
+    let repository = gh_client.repository();
+    let mut checks = Vec::new();
+
+    // Probed first so a hidden repository reads as missing access rather than a bare 404
+    if check_access {
+        match gh_client.check_repo_access().await {
+            Ok(()) => checks.push(Check::pass(format!("{} access", repository), "token can write to it")),
+            Err(e) => {
+                checks.push(Check::fail(format!("{} access", repository), e.to_string()));
+                return checks;
+            }
+        }
+    }
+
+    match gh_client.default_branch().await {
+        Ok(branch) => checks.push(Check::pass(repository.clone(), format!("reachable (default branch {})", branch))),
+        Err(e) => {
+            checks.push(Check::fail(repository, e.to_string()));
+            return checks;
+        }
+    }
+
+    let Some(tag) = tag else {
+        return checks;
+    };
+
+    match gh_client.get_release_branch_name(tag) {
+        Ok(branch) => checks.push(match gh_client.branch_exists(&branch).await {
+            Ok(true) => Check::pass(format!("{} branch", repository), format!("{} exists", branch)),
+            Ok(false) => Check::warn(format!("{} branch", repository), format!("{} not found; the fallback branch will be used", branch)),
+            Err(e) => Check::fail(format!("{} branch", repository), e.to_string()),
+        }),
+        Err(e) => checks.push(Check::fail(format!("{} tag", repository), e.to_string())),
+    }
+
+    checks.push(match gh_client.tag_exists(tag).await {
+        Ok(true) => Check::pass(format!("{} tag", repository), format!("{} exists and will be recreated", tag)),
+        Ok(false) => Check::pass(format!("{} tag", repository), format!("{} will be created", tag)),
+        Err(e) => Check::fail(format!("{} tag", repository), e.to_string()),
+    });
+
+    checks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn given_only_passes_and_warnings_when_aggregating_then_passes() {
+        let checks = vec![
+            Check::pass("GitHub token", "authenticated as octocat"),
+            Check::warn("Token scopes", "not reported"),
+        ];
+
+        assert!(all_passed(&checks));
+    }
+
+    #[test]
+    fn given_any_failed_check_when_aggregating_then_fails() {
+        let checks = vec![
+            Check::pass("GitHub token", "authenticated as octocat"),
+            Check::fail("OpenAI key", "OPENAI_API_KEY is missing"),
+            Check::warn("Token scopes", "not reported"),
+        ];
+
+        assert!(!all_passed(&checks));
+    }
+
+    #[test]
+    fn given_no_checks_when_aggregating_then_passes() {
+        assert!(all_passed(&[]));
+    }
+
+    #[test]
+    fn given_checks_when_rendering_then_prints_one_line_per_check() {
+        let checks = vec![
+            Check::pass("GitHub token", "authenticated as octocat"),
+            Check::fail("OpenAI key", "OPENAI_API_KEY is missing"),
+        ];
+
+        assert_eq!(
+            render(&checks),
+            "✅ GitHub token: authenticated as octocat\n❌ OpenAI key: OPENAI_API_KEY is missing"
+        );
+    }
+}
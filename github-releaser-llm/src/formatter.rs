@@ -0,0 +1,769 @@
+use regex::Regex;
+use serde::Serialize;
+use similar::TextDiff;
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+
+use crate::openai_client::{OpenAIClient, TokenUsage};
+use crate::output;
+
+/// Which formatter turns the raw release notes into the final body.
+#[derive(Clone, Copy, Debug, Default, PartialEq, clap::ValueEnum)]
+pub enum FormatterKind {
+    /// Ask the OpenAI model to format the notes
+    #[default]
+    Openai,
+    /// Deterministic formatting without any LLM call
+    Local,
+}
+
+/// What to format the notes with when the LLM fails after its retries.
+#[derive(Clone, Copy, Debug, PartialEq, clap::ValueEnum)]
+pub enum LlmFallback {
+    /// The deterministic local formatter
+    Local,
+}
+
+/// What happens to line items that don't reference a known ticket.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum UnmatchedLines {
+    /// A plain list after the ticket sections
+    #[default]
+    List,
+    /// A list under a heading with this title
+    Heading(String),
+    /// Left out of the notes
+    Drop,
+}
+
+impl UnmatchedLines {
+    /// Resolve the `--other-heading` / `--drop-unmatched` flags.
+    pub fn from_flags(other_heading: Option<String>, drop_unmatched: bool) -> Self {
+        match (other_heading, drop_unmatched) {
+            (_, true) => UnmatchedLines::Drop,
+            (Some(title), false) => UnmatchedLines::Heading(title),
+            (None, false) => UnmatchedLines::List,
+        }
+    }
+}
+
+/// The character that starts each line item in the local formatter's output.
+#[derive(Clone, Copy, Debug, Default, PartialEq, clap::ValueEnum)]
+pub enum Bullet {
+    #[default]
+    #[value(name = "*")]
+    Asterisk,
+    #[value(name = "-")]
+    Dash,
+}
+
+impl Bullet {
+    fn as_str(self) -> &'static str {
+        match self {
+            Bullet::Asterisk => "*",
+            Bullet::Dash => "-",
+        }
+    }
+}
+
+/// Formats raw release notes with either the LLM or the local formatter.
+pub enum NotesFormatter {
+    /// Boxed, as the client's many settings dwarf the local formatter
+    OpenAI(Box<OpenAIClient>),
+    Local(LocalFormatter),
+}
+
+impl NotesFormatter {
+    /// Format the notes and clean up the output so both formatters produce tidy markdown.
+    pub async fn format(&self, unformatted: &str) -> Result<String, Box<dyn Error>> {
+        let formatted = match self {
+            NotesFormatter::OpenAI(client) => client.format_release_notes(unformatted).await?,
+            NotesFormatter::Local(formatter) => formatter.format(unformatted),
+        };
+
+        Ok(normalize_notes(&formatted))
+    }
+
+    /// Tokens the OpenAI formatter used so far; the local formatter uses none.
+    pub fn usage(&self) -> Option<TokenUsage> {
+        match self {
+            NotesFormatter::OpenAI(client) => Some(client.usage()),
+            NotesFormatter::Local(_) => None,
+        }
+    }
+
+    /// Format the notes, using `fallback` when the OpenAI formatter fails; `true` when it was used.
+    pub async fn format_or_fall_back(
+        &self,
+        unformatted: &str,
+        fallback: Option<&LocalFormatter>,
+    ) -> Result<(String, bool), Box<dyn Error>> {
+        match (self.format(unformatted).await, self, fallback) {
+            (Ok(formatted), _, _) => Ok((formatted, false)),
+            (Err(e), NotesFormatter::OpenAI(_), Some(fallback)) => {
+                println!("{}", output::warning(format!("OpenAI formatting failed ({}). Falling back to the local formatter.", e)));
+                Ok((normalize_notes(&fallback.format(unformatted)), true))
+            }
+            (Err(e), _, _) => Err(e),
+        }
+    }
+}
+
+/// Groups ticketed lines under a heading per ticket prefix, sorted by ticket number.
+pub struct LocalFormatter {
+    prefixes: Vec<String>,
+    ticket_base_url: String,
+    unmatched: UnmatchedLines,
+    bullet: Bullet,
+    /// Titles shown instead of the raw prefix in a section's heading
+    heading_titles: HashMap<String, String>,
+}
+
+impl Default for LocalFormatter {
+    fn default() -> Self {
+        LocalFormatter {
+            prefixes: vec!["PD".to_string(), "PDE".to_string(), "PRDY".to_string()],
+            ticket_base_url: "https://onezelis.atlassian.net/browse".to_string(),
+            unmatched: UnmatchedLines::default(),
+            bullet: Bullet::default(),
+            heading_titles: HashMap::new(),
+        }
+    }
+}
+
+/// A ticket a release's notes reference, for automation that updates the tracker.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Ticket {
+    pub prefix: String,
+    /// The number as written, so `PDE-007` keeps its zeros
+    pub number: String,
+    pub url: String,
+}
+
+/// A release notes line item that references a ticket.
+struct TicketLine {
+    prefix: String,
+    /// The ticket number as written, so an id like `PDE-007` keeps its zeros
+    digits: String,
+    /// What the lines are sorted by, so `PDE-9` comes before `PDE-10`
+    number: u64,
+    description: String,
+}
+
+impl LocalFormatter {
+    /// Choose what happens to line items without a ticket.
+    pub fn with_unmatched(mut self, unmatched: UnmatchedLines) -> Self {
+        self.unmatched = unmatched;
+        self
+    }
+
+    /// Choose the bullet for line items; headings stay `##`.
+    pub fn with_bullet(mut self, bullet: Bullet) -> Self {
+        self.bullet = bullet;
+        self
+    }
+
+    /// Title the sections of these prefixes instead of showing the prefix, e.g. "Platform Defects" for PDE.
+    pub fn with_heading_titles(mut self, titles: impl IntoIterator<Item = (String, String)>) -> Self {
+        self.heading_titles.extend(titles);
+        self
+    }
+
+    /// Regex matching a ticket ID (optionally wrapped in brackets and followed by a colon).
+    ///
+    /// A ticket link this formatter wrote, `[PDE-1](url)`, matches as a whole, so formatting
+    /// its own output again changes nothing.
+    fn ticket_regex(&self) -> Regex {
+        // Longest prefixes first so PDE isn't cut short by PD
+        let mut prefixes: Vec<String> = self.prefixes.iter().map(|p| regex::escape(p)).collect();
+        prefixes.sort_by_key(|p| std::cmp::Reverse(p.len()));
+
+        Regex::new(&format!(r"\[?\b({})-(\d+)\b(?:\]\([^)\s]*\))?\]?:?\s*", prefixes.join("|")))
+            .expect("ticket prefixes are escaped")
+    }
+
+    /// How many line items reference one of the known tickets.
+    pub fn count_ticket_lines(&self, notes: &str) -> usize {
+        let ticket_re = self.ticket_regex();
+        Self::line_items(notes).into_iter().filter(|item| ticket_re.is_match(item)).count()
+    }
+
+    /// Every distinct ticket the notes' line items reference, by prefix and then number.
+    pub fn tickets(&self, notes: &str) -> Vec<Ticket> {
+        let ticket_re = self.ticket_regex();
+        let mut found: Vec<(usize, u64, Ticket)> = Vec::new();
+        for item in Self::line_items(notes) {
+            for caps in ticket_re.captures_iter(item) {
+                let (prefix, number) = (caps[1].to_string(), caps[2].to_string());
+                if found.iter().any(|(_, _, t)| t.prefix == prefix && t.number == number) {
+                    continue;
+                }
+                let order = self.prefixes.iter().position(|p| *p == prefix).unwrap_or(usize::MAX);
+                let url = format!("{}/{}-{}", self.ticket_base_url, prefix, number);
+                found.push((order, number.parse().unwrap_or(u64::MAX), Ticket { prefix, number, url }));
+            }
+        }
+        found.sort_by_key(|(order, number, _)| (*order, *number));
+        found.into_iter().map(|(_, _, ticket)| ticket).collect()
+    }
+
+    /// Extract the line items from GitHub's generated notes, skipping headings and contributor notes.
+    fn line_items(unformatted: &str) -> Vec<&str> {
+        unformatted
+            .lines()
+            .map(str::trim)
+            .filter_map(|line| line.strip_prefix("* ").or_else(|| line.strip_prefix("- ")))
+            .map(str::trim)
+            .filter(|item| !item.is_empty() && !item.contains("made their first contribution"))
+            .collect()
+    }
+
+    pub fn format(&self, unformatted: &str) -> String {
+        let ticket_re = self.ticket_regex();
+        let mut tickets: Vec<TicketLine> = Vec::new();
+        let mut unmatched: Vec<&str> = Vec::new();
+
+        for item in Self::line_items(unformatted) {
+            match ticket_re.captures(item) {
+                Some(caps) => {
+                    let whole = caps.get(0).unwrap();
+                    let description = format!("{}{}", &item[..whole.start()], &item[whole.end()..]);
+                    tickets.push(TicketLine {
+                        prefix: caps[1].to_string(),
+                        digits: caps[2].to_string(),
+                        number: caps[2].parse().unwrap_or(u64::MAX),
+                        description: description.trim().to_string(),
+                    });
+                }
+                None => unmatched.push(item),
+            }
+        }
+
+        let bullet = self.bullet.as_str();
+        let mut sections: Vec<String> = Vec::new();
+
+        for prefix in &self.prefixes {
+            let mut lines: Vec<&TicketLine> = tickets.iter().filter(|t| &t.prefix == prefix).collect();
+            if lines.is_empty() {
+                continue;
+            }
+            lines.sort_by_key(|t| t.number);
+
+            let title = self.heading_titles.get(prefix).unwrap_or(prefix);
+            let mut section = format!("## {}\n", title);
+            for line in lines {
+                let id = format!("{}-{}", line.prefix, line.digits);
+                section.push_str(&format!(
+                    "{} [{}]({}/{}) {}\n",
+                    bullet, id, self.ticket_base_url, id, line.description
+                ));
+            }
+            sections.push(section);
+        }
+
+        // Lines without a ticket fall back to the default format: a plain list
+        if !unmatched.is_empty() {
+            let list: String = unmatched.iter().map(|item| format!("{} {}\n", bullet, item)).collect();
+            match &self.unmatched {
+                UnmatchedLines::List => sections.push(list),
+                UnmatchedLines::Heading(title) => sections.push(format!("## {}\n{}", title, list)),
+                UnmatchedLines::Drop => {}
+            }
+        }
+
+        sections.join("\n")
+    }
+}
+
+/// Parse a `--heading-title` value, `<prefix>=<title>`.
+pub fn parse_heading_title(value: &str) -> Result<(String, String), String> {
+    match value.split_once('=') {
+        Some((prefix, title)) if !prefix.trim().is_empty() && !title.trim().is_empty() => {
+            Ok((prefix.trim().to_string(), title.trim().to_string()))
+        }
+        _ => Err(format!("expected <prefix>=<title>, got {:?}", value)),
+    }
+}
+
+/// Fail when no line of the formatted notes references a ticket, which usually means the
+/// ticket prefixes don't match the repository's (`--require-tickets`).
+pub fn require_tickets(notes: &str) -> Result<(), String> {
+    match LocalFormatter::default().count_ticket_lines(notes) {
+        0 => Err("No ticket IDs were recognized in the formatted notes. Check the ticket prefixes or drop --require-tickets.".to_string()),
+        _ => Ok(()),
+    }
+}
+
+/// Clean up formatted notes: trim trailing whitespace, collapse runs of 3+ blank
+/// lines to one, and drop repeated lines for a ticket that was already listed.
+pub fn normalize_notes(notes: &str) -> String {
+    let ticket_re = Regex::new(r"^[*-]\s+\[?([A-Z][A-Z0-9]*-\d+)\b").unwrap();
+    let mut seen_tickets: HashSet<String> = HashSet::new();
+    let mut output: Vec<&str> = Vec::new();
+    let mut blank_run = 0;
+
+    for line in notes.lines().map(str::trim_end) {
+        if line.is_empty() {
+            blank_run += 1;
+            continue;
+        }
+
+        // Runs of 1-2 blank lines are kept as they are, longer runs become a single one
+        let blanks = if blank_run >= 3 { 1 } else { blank_run };
+        if !output.is_empty() {
+            output.extend(std::iter::repeat_n("", blanks));
+        }
+        blank_run = 0;
+
+        if let Some(caps) = ticket_re.captures(line) {
+            if !seen_tickets.insert(caps[1].to_string()) {
+                continue;
+            }
+        }
+
+        output.push(line);
+    }
+
+    output.join("\n")
+}
+
+/// Append GitHub's "Full Changelog" line linking to the compare page.
+pub fn append_compare_footer(notes: &str, compare_url: &str) -> String {
+    format!("{}\n\n**Full Changelog**: {}", notes.trim_end(), compare_url)
+}
+
+/// Opening line of the collapsible block `--append-raw` puts the raw notes in.
+pub const RAW_NOTES_OPEN: &str = "<details><summary>Raw notes</summary>";
+const RAW_NOTES_CLOSE: &str = "</details>";
+
+/// Split a release body into the notes before the raw notes block and the raw notes inside it.
+pub fn split_raw_notes(body: &str) -> (&str, Option<&str>) {
+    let Some(start) = body.find(RAW_NOTES_OPEN) else {
+        return (body, None);
+    };
+
+    let inner = &body[start + RAW_NOTES_OPEN.len()..];
+    let inner = inner.rfind(RAW_NOTES_CLOSE).map_or(inner, |end| &inner[..end]);
+    (&body[..start], Some(inner.trim()))
+}
+
+/// Put the raw notes below the formatted ones in a collapsible block, replacing any block
+/// left by an earlier run so it's only ever added once.
+pub fn append_raw_notes(notes: &str, raw_notes: &str) -> String {
+    let (notes, _) = split_raw_notes(notes);
+    let raw_notes = split_raw_notes(raw_notes).1.unwrap_or(raw_notes);
+
+    format!(
+        "{}\n\n{}\n\n{}\n\n{}",
+        notes.trim_end(),
+        RAW_NOTES_OPEN,
+        raw_notes.trim(),
+        RAW_NOTES_CLOSE
+    )
+}
+
+/// Maximum size of a GitHub release body, in characters.
+pub const GITHUB_RELEASE_BODY_LIMIT: usize = 125_000;
+
+/// Shorten notes to at most `max_len` characters, cutting at the last heading (or else line)
+/// boundary that fits and linking to the full changelog.
+///
+/// Cutting only between lines keeps every markdown link intact.
+pub fn truncate_notes(notes: &str, max_len: usize, changelog_url: &str) -> String {
+    if notes.chars().count() <= max_len {
+        return notes.to_string();
+    }
+
+    let footer = format!("\n\n[…see full changelog]({})", changelog_url);
+    let budget = max_len.saturating_sub(footer.chars().count());
+
+    // Byte offsets where each line within the first `budget` characters starts
+    let line_starts: Vec<usize> = std::iter::once(0)
+        .chain(notes.char_indices().take(budget).filter(|&(_, c)| c == '\n').map(|(i, _)| i + 1))
+        .collect();
+    let heading_cut = line_starts
+        .iter()
+        .rev()
+        .find(|&&start| start > 0 && notes[start..].starts_with('#'));
+    let cut = heading_cut.or(line_starts.last()).copied().unwrap_or(0);
+
+    format!("{}{}", notes[..cut].trim_end(), footer).trim_start().to_string()
+}
+
+/// Unified diff from the current release body to the newly formatted notes.
+pub fn notes_diff(current: &str, formatted: &str) -> String {
+    TextDiff::from_lines(current, formatted)
+        .unified_diff()
+        .header("current release body", "formatted notes")
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::runtime::Runtime;
+
+    #[test]
+    fn given_duplicate_ticket_lines_when_normalizing_then_keeps_first_occurrence() {
+        let notes = "## PDE\n* [PDE-1](https://t/PDE-1) Fixed bug\n* [PDE-2](https://t/PDE-2) Other fix\n* [PDE-1](https://t/PDE-1) Fixed bug";
+
+        let normalized = normalize_notes(notes);
+
+        assert_eq!(normalized, "## PDE\n* [PDE-1](https://t/PDE-1) Fixed bug\n* [PDE-2](https://t/PDE-2) Other fix");
+    }
+
+    #[test]
+    fn given_same_ticket_with_different_text_when_normalizing_then_drops_repeat() {
+        let notes = "* PDE-7 Fixed bug\n* PDE-7 Fixed bug again";
+
+        assert_eq!(normalize_notes(notes), "* PDE-7 Fixed bug");
+    }
+
+    #[test]
+    fn given_lines_without_tickets_when_normalizing_then_keeps_repeated_lines() {
+        let notes = "* Bump dependencies\n* Bump dependencies";
+
+        assert_eq!(normalize_notes(notes), notes);
+    }
+
+    #[test]
+    fn given_many_blank_lines_when_normalizing_then_collapses_to_one() {
+        let notes = "## PD\n* PD-1 Fix\n\n\n\n\n## PDE\n* PDE-2 Fix";
+
+        assert_eq!(normalize_notes(notes), "## PD\n* PD-1 Fix\n\n## PDE\n* PDE-2 Fix");
+    }
+
+    #[test]
+    fn given_single_blank_lines_between_sections_when_normalizing_then_preserves_them() {
+        let notes = "## PD\n* PD-1 Fix\n\n## PDE\n* PDE-2 Fix\n\n## PRDY\n* PRDY-3 Fix";
+
+        assert_eq!(normalize_notes(notes), notes);
+    }
+
+    #[test]
+    fn given_trailing_whitespace_when_normalizing_then_trims_each_line() {
+        let notes = "## PD   \n* PD-1 Fix\t\n\n\n\n";
+
+        assert_eq!(normalize_notes(notes), "## PD\n* PD-1 Fix");
+    }
+
+    #[test]
+    fn given_raw_notes_when_appending_then_wraps_them_in_a_details_block() {
+        let raw = "## What's Changed\n* PDE-1 Fix by @a in #1";
+
+        let body = append_raw_notes("## PDE\n* [PDE-1](https://t/PDE-1) Fix\n", raw);
+
+        assert_eq!(
+            body,
+            "## PDE\n* [PDE-1](https://t/PDE-1) Fix\n\n\
+            <details><summary>Raw notes</summary>\n\n\
+            ## What's Changed\n* PDE-1 Fix by @a in #1\n\n\
+            </details>"
+        );
+    }
+
+    #[test]
+    fn given_body_with_raw_block_when_appending_again_then_keeps_a_single_block() {
+        let raw = "* PDE-1 Fix by @a in #1";
+        let first_run = append_raw_notes("* [PDE-1](https://t/PDE-1) Fix", raw);
+
+        // A re-run reads the previous body back as its raw notes
+        let second_run = append_raw_notes(&first_run, &first_run);
+
+        assert_eq!(second_run, first_run);
+        assert_eq!(second_run.matches(RAW_NOTES_OPEN).count(), 1);
+        assert_eq!(split_raw_notes(&second_run).1, Some(raw));
+    }
+
+    #[test]
+    fn given_notes_when_appending_compare_footer_then_adds_full_changelog_line() {
+        let notes = "## PDE\n* [PDE-1](https://t/PDE-1) Fix\n";
+
+        let with_footer = append_compare_footer(
+            notes,
+            "https://github.com/Human-Glitch/llm-playground/compare/v1.0.0...v1.1.0",
+        );
+
+        assert_eq!(
+            with_footer,
+            "## PDE\n* [PDE-1](https://t/PDE-1) Fix\n\n**Full Changelog**: https://github.com/Human-Glitch/llm-playground/compare/v1.0.0...v1.1.0"
+        );
+    }
+
+    #[test]
+    fn given_notes_over_limit_when_truncating_then_cuts_at_heading_under_cap() {
+        let section = |prefix: &str| -> String {
+            (1..=20)
+                .map(|i| format!("* [{p}-{i}](https://onezelis.atlassian.net/browse/{p}-{i}) Change {i}\n", p = prefix, i = i))
+                .collect()
+        };
+        let notes = format!("## PD\n{}\n## PDE\n{}\n## PRDY\n{}", section("PD"), section("PDE"), section("PRDY"));
+        let url = "https://github.com/o/r/compare/v1.0.0...v1.1.0";
+        let max_len = notes.find("## PRDY").unwrap() + 100;
+
+        let truncated = truncate_notes(&notes, max_len, url);
+
+        assert!(truncated.chars().count() <= max_len, "{} > {}", truncated.chars().count(), max_len);
+        assert!(truncated.ends_with("* [PDE-20](https://onezelis.atlassian.net/browse/PDE-20) Change 20\n\n[…see full changelog](https://github.com/o/r/compare/v1.0.0...v1.1.0)"));
+        assert!(!truncated.contains("## PRDY"));
+    }
+
+    #[test]
+    fn given_single_long_section_when_truncating_then_cuts_between_lines() {
+        let notes: String = (1..=50).map(|i| format!("* [PDE-{i}](https://t/PDE-{i}) Fix\n", i = i)).collect();
+
+        let truncated = truncate_notes(&notes, 200, "https://x/compare");
+
+        assert!(truncated.chars().count() <= 200);
+        // Every remaining line is a complete link line
+        let (body, footer) = truncated.rsplit_once("\n\n").unwrap();
+        assert!(body.lines().all(|line| line.ends_with(") Fix")));
+        assert_eq!(footer, "[…see full changelog](https://x/compare)");
+    }
+
+    #[test]
+    fn given_notes_under_limit_when_truncating_then_leaves_them_unchanged() {
+        assert_eq!(truncate_notes("## PD\n* PD-1 Fix", 1000, "https://x"), "## PD\n* PD-1 Fix");
+    }
+
+    #[test]
+    fn given_multibyte_notes_when_truncating_then_the_limit_counts_characters() {
+        let notes: String = (1..=10).map(|i| format!("* PDE-{} Löst das Überlaufproblem ✓\n", i)).collect();
+        let max_len = notes.chars().count();
+        assert!(notes.len() > max_len);
+
+        assert_eq!(truncate_notes(&notes, max_len, "https://x"), notes);
+        let truncated = truncate_notes(&notes, max_len - 1, "https://x");
+        assert!(truncated.chars().count() < max_len);
+        assert!(truncated.ends_with("✓\n\n[…see full changelog](https://x)"), "{}", truncated);
+    }
+
+    #[test]
+    fn given_changed_notes_when_diffing_then_marks_changed_lines() {
+        let current = "## PDE\n* PDE-1 Fix\n* PDE-2 Other\n";
+        let formatted = "## PDE\n* [PDE-1](https://t/PDE-1) Fix\n* PDE-2 Other\n";
+
+        let diff = notes_diff(current, formatted);
+
+        assert_eq!(
+            diff,
+            "--- current release body\n\
+            +++ formatted notes\n\
+            @@ -1,3 +1,3 @@\n \
+            ## PDE\n\
+            -* PDE-1 Fix\n\
+            +* [PDE-1](https://t/PDE-1) Fix\n \
+            * PDE-2 Other\n"
+        );
+    }
+
+    #[test]
+    fn given_generated_notes_when_formatting_locally_then_groups_and_sorts_tickets() {
+        let raw = "## What's Changed\n\
+            * PDE-20 Second fix by @a in https://github.com/o/r/pull/2\n\
+            * PD-5: Small fix by @b in https://github.com/o/r/pull/3\n\
+            * PDE-3 First fix by @a in https://github.com/o/r/pull/1\n\
+            * Bump dependencies by @bot in https://github.com/o/r/pull/4\n\
+            \n\
+            ## New Contributors\n\
+            * @b made their first contribution in https://github.com/o/r/pull/3\n\
+            \n\
+            **Full Changelog**: https://github.com/o/r/compare/v1.0.0...v1.1.0";
+
+        let formatted = LocalFormatter::default().format(raw);
+
+        assert_eq!(
+            formatted,
+            "## PD\n\
+            * [PD-5](https://onezelis.atlassian.net/browse/PD-5) Small fix by @b in https://github.com/o/r/pull/3\n\
+            \n\
+            ## PDE\n\
+            * [PDE-3](https://onezelis.atlassian.net/browse/PDE-3) First fix by @a in https://github.com/o/r/pull/1\n\
+            * [PDE-20](https://onezelis.atlassian.net/browse/PDE-20) Second fix by @a in https://github.com/o/r/pull/2\n\
+            \n\
+            * Bump dependencies by @bot in https://github.com/o/r/pull/4\n"
+        );
+    }
+
+    #[test]
+    fn given_multi_digit_tickets_when_formatting_locally_then_sorts_numerically() {
+        let formatted = LocalFormatter::default().format("* PDE-9 Nine\n* PDE-10 Ten\n* PDE-2 Two\n* PDE-010 Padded ten");
+
+        let ids: Vec<&str> = formatted
+            .lines()
+            .filter_map(|line| line.strip_prefix("* ["))
+            .filter_map(|line| line.split(']').next())
+            .collect();
+        assert_eq!(ids, vec!["PDE-2", "PDE-9", "PDE-10", "PDE-010"]);
+    }
+
+    #[test]
+    fn given_other_heading_when_formatting_locally_then_collects_unmatched_lines_under_it() {
+        let raw = "* Bump dependencies by @bot\n* PDE-3 Fix by @a\n* Update CI by @b";
+
+        let formatted = LocalFormatter::default()
+            .with_unmatched(UnmatchedLines::Heading("Maintenance".to_string()))
+            .format(raw);
+
+        assert_eq!(
+            formatted,
+            "## PDE\n\
+            * [PDE-3](https://onezelis.atlassian.net/browse/PDE-3) Fix by @a\n\
+            \n\
+            ## Maintenance\n\
+            * Bump dependencies by @bot\n\
+            * Update CI by @b\n"
+        );
+    }
+
+    #[test]
+    fn given_dash_bullet_when_formatting_locally_then_every_line_item_uses_it() {
+        let raw = "* Bump dependencies by @bot\n* PDE-3 Fix by @a\n- PD-1 Add login by @b";
+
+        let formatted = LocalFormatter::default()
+            .with_unmatched(UnmatchedLines::Heading("Maintenance".to_string()))
+            .with_bullet(Bullet::Dash)
+            .format(raw);
+
+        assert_eq!(
+            formatted,
+            "## PD\n\
+            - [PD-1](https://onezelis.atlassian.net/browse/PD-1) Add login by @b\n\
+            \n\
+            ## PDE\n\
+            - [PDE-3](https://onezelis.atlassian.net/browse/PDE-3) Fix by @a\n\
+            \n\
+            ## Maintenance\n\
+            - Bump dependencies by @bot\n"
+        );
+    }
+
+    #[test]
+    fn given_locally_formatted_notes_when_formatting_again_then_nothing_changes() {
+        let formatter = LocalFormatter::default().with_unmatched(UnmatchedLines::Heading("Maintenance".to_string()));
+        let once = formatter.format("* PDE-3 Fix by @a\n* [PD-1] Add login by @b\n* Bump dependencies by @bot");
+
+        assert_eq!(normalize_notes(&formatter.format(&once)), normalize_notes(&once));
+    }
+
+    #[test]
+    fn given_heading_title_when_formatting_locally_then_titles_only_the_mapped_section() {
+        let notes = "* PDE-2 Fix login by @a\n* PD-1 Add SSO by @b\n";
+        let formatter = LocalFormatter::default().with_heading_titles([parse_heading_title("PDE=Platform Defects").unwrap()]);
+
+        let formatted = formatter.format(notes);
+
+        assert!(formatted.contains("## PD\n* [PD-1]"));
+        assert!(formatted.contains("## Platform Defects\n* [PDE-2](https://onezelis.atlassian.net/browse/PDE-2) Fix login by @a"));
+        assert!(!formatted.contains("## PDE"));
+        assert!(parse_heading_title("PDE").is_err());
+    }
+
+    #[test]
+    fn given_formatted_notes_when_extracting_tickets_then_lists_each_once_in_order() {
+        let notes = "## PDE\n\
+            * [PDE-9](https://onezelis.atlassian.net/browse/PDE-9) Fix login, see PD-3\n\
+            * [PDE-10](https://onezelis.atlassian.net/browse/PDE-10) Add SSO\n\
+            * [PDE-9](https://onezelis.atlassian.net/browse/PDE-9) Fix logout\n\
+            * Bump serde (ABC-1)\n";
+
+        let tickets = LocalFormatter::default().tickets(notes);
+
+        let ids: Vec<String> = tickets.iter().map(|t| format!("{}-{}", t.prefix, t.number)).collect();
+        assert_eq!(ids, vec!["PD-3", "PDE-9", "PDE-10"]);
+        assert_eq!(tickets[1].url, "https://onezelis.atlassian.net/browse/PDE-9");
+    }
+
+    #[test]
+    fn given_notes_without_tickets_when_requiring_tickets_then_errors() {
+        let notes = "## Changes\n* Bump dependencies by @bot\n* Fix the PDEX-1 typo";
+
+        assert!(require_tickets(notes).unwrap_err().contains("No ticket IDs were recognized"));
+        assert!(require_tickets("").is_err());
+    }
+
+    #[test]
+    fn given_notes_with_tickets_when_requiring_tickets_then_passes() {
+        let notes = "## PDE\n* [PDE-3](https://onezelis.atlassian.net/browse/PDE-3) Fix by @a\n\n* Bump dependencies by @bot";
+
+        assert_eq!(LocalFormatter::default().count_ticket_lines(notes), 1);
+        assert!(require_tickets(notes).is_ok());
+    }
+
+    #[test]
+    fn given_drop_unmatched_when_formatting_locally_then_discards_unmatched_lines() {
+        let raw = "* Bump dependencies by @bot\n* PDE-3 Fix by @a";
+
+        let formatted = LocalFormatter::default()
+            .with_unmatched(UnmatchedLines::Drop)
+            .format(raw);
+
+        assert_eq!(formatted, "## PDE\n* [PDE-3](https://onezelis.atlassian.net/browse/PDE-3) Fix by @a\n");
+    }
+
+    #[test]
+    fn given_both_flags_when_resolving_unmatched_lines_then_drop_wins() {
+        assert_eq!(UnmatchedLines::from_flags(None, false), UnmatchedLines::List);
+        assert_eq!(
+            UnmatchedLines::from_flags(Some("Other".to_string()), false),
+            UnmatchedLines::Heading("Other".to_string())
+        );
+        assert_eq!(UnmatchedLines::from_flags(Some("Other".to_string()), true), UnmatchedLines::Drop);
+    }
+
+    /// Golden-file check of the local formatter over `tests/fixtures/formatter`.
+    ///
+    /// Each `<name>.input.md` is formatted and compared with `<name>.expected.md`.
+    /// Run with `UPDATE_SNAPSHOTS=1` to rewrite the expected files after an intended change.
+    #[test]
+    fn given_fixture_notes_when_formatting_locally_then_matches_expected_snapshots() {
+        let fixtures = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/formatter");
+        let update = std::env::var("UPDATE_SNAPSHOTS").is_ok();
+
+        let mut inputs: Vec<_> = std::fs::read_dir(&fixtures)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.to_string_lossy().ends_with(".input.md"))
+            .collect();
+        inputs.sort();
+        assert!(inputs.len() >= 3, "expected at least three formatter fixtures");
+
+        let mut mismatches = Vec::new();
+        for input_path in inputs {
+            let expected_path = std::path::PathBuf::from(
+                input_path.to_string_lossy().replace(".input.md", ".expected.md"),
+            );
+            let input = std::fs::read_to_string(&input_path).unwrap();
+            let actual = format!("{}\n", normalize_notes(&LocalFormatter::default().format(&input)));
+
+            if update {
+                std::fs::write(&expected_path, &actual).unwrap();
+                continue;
+            }
+
+            let expected = std::fs::read_to_string(&expected_path)
+                .unwrap_or_else(|_| panic!("missing snapshot {}", expected_path.display()));
+            if actual != expected {
+                mismatches.push(format!(
+                    "{}\n--- expected\n{}\n--- actual\n{}",
+                    input_path.display(),
+                    expected,
+                    actual
+                ));
+            }
+        }
+
+        assert!(mismatches.is_empty(), "snapshot mismatches:\n{}", mismatches.join("\n"));
+    }
+
+    #[test]
+    fn given_local_formatter_when_formatting_then_output_is_normalized() {
+        let raw = "* PDE-1 Fix by @a\n* PDE-1 Fix by @a";
+        let formatter = NotesFormatter::Local(LocalFormatter::default());
+
+        let rt = Runtime::new().unwrap();
+        let formatted = rt.block_on(async { formatter.format(raw).await.unwrap() });
+
+        assert_eq!(formatted, "## PDE\n* [PDE-1](https://onezelis.atlassian.net/browse/PDE-1) Fix by @a");
+    }
+}
@@ -1,15 +1,33 @@
-use reqwest::{Client, StatusCode};
-use serde::Deserialize;
-use serde_json::json;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, AUTHORIZATION};
+use reqwest::{Client, Method, RequestBuilder, Response, StatusCode};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
 use std::error::Error;
 use regex::Regex;
 
+use crate::github_error::GitHubError;
+use crate::output;
+use crate::retry::{self, RetryPolicy};
+use crate::version::{self, Version};
+
 // Struct definitions needed by the GitHubClient
 #[derive(Deserialize)]
 pub struct GitHubRelease {
     pub id: u64,
+    #[serde(default)]
+    pub tag_name: String,
     pub body: Option<String>,
     pub prerelease: Option<bool>,
+    pub draft: Option<bool>,
+    /// The branch or commit the release was created from
+    #[serde(default)]
+    pub target_commitish: Option<String>,
+    /// The release page, e.g. `https://github.com/o/r/releases/tag/v1.0.0`
+    #[serde(default)]
+    pub html_url: Option<String>,
+    /// Templated uploads endpoint, e.g. `https://uploads.github.com/repos/o/r/releases/1/assets{?name,label}`
+    #[serde(default)]
+    pub upload_url: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -17,57 +35,394 @@ struct Commit {
     sha: String,
 }
 
+/// Author or committer details of a git commit.
+#[derive(Clone, Deserialize)]
+pub struct GitActor {
+    pub name: String,
+}
+
+#[derive(Clone, Deserialize)]
+pub struct GitCommitDetails {
+    pub message: String,
+    pub author: Option<GitActor>,
+}
+
+/// Who made a git object and when, as the git database API reports it.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct GitSignature {
+    pub name: String,
+    pub email: String,
+    /// ISO 8601 timestamp, e.g. `2024-03-05T14:30:00Z`
+    pub date: String,
+}
+
+#[derive(Deserialize)]
+struct GitCommitObject {
+    committer: GitSignature,
+}
+
+/// The token's user, as far as a tagger goes.
+#[derive(Deserialize)]
+struct TaggerUser {
+    login: String,
+    id: u64,
+    name: Option<String>,
+    email: Option<String>,
+}
+
+/// An issue or pull request, as far as its labels go.
+#[derive(Deserialize)]
+struct LabeledIssue {
+    #[serde(default)]
+    labels: Vec<Label>,
+}
+
+#[derive(Deserialize)]
+struct Label {
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct PullRequest {
+    user: Option<GitHubUser>,
+}
+
+#[derive(Deserialize)]
+struct Review {
+    user: Option<GitHubUser>,
+    state: String,
+}
+
+/// The GitHub account linked to a commit, when there is one.
+#[derive(Clone, Deserialize)]
+pub struct GitHubUser {
+    pub login: String,
+}
+
+/// A commit as returned by the compare and commit listing endpoints.
+#[derive(Clone, Deserialize)]
+pub struct CommitSummary {
+    #[serde(default)]
+    pub sha: String,
+    pub commit: GitCommitDetails,
+    pub author: Option<GitHubUser>,
+    /// Names from the `Co-authored-by:` trailers, filled in by `--credit-co-authors`
+    #[serde(skip)]
+    pub co_authors: Vec<String>,
+}
+
+/// Commits between two refs.
+#[derive(Deserialize)]
+pub struct Comparison {
+    /// How head relates to base: `ahead`, `behind`, `identical` or `diverged`
+    #[serde(default)]
+    pub status: Option<String>,
+    #[serde(default)]
+    pub commits: Vec<CommitSummary>,
+    /// Changed files; GitHub lists at most `COMPARE_FILE_LIMIT` of them
+    #[serde(default)]
+    pub files: Vec<FileChange>,
+}
+
+/// Most files the compare API lists before it silently truncates the list.
+pub const COMPARE_FILE_LIMIT: usize = 300;
+
+/// Line counts for one file changed between two refs.
+#[derive(Deserialize)]
+pub struct FileChange {
+    #[serde(default)]
+    pub additions: u64,
+    #[serde(default)]
+    pub deletions: u64,
+}
+
+#[derive(Deserialize)]
+struct GeneratedNotes {
+    body: String,
+}
+
 #[derive(Deserialize)]
 struct TagObjectResponse {
     sha: String,
 }
 
+/// An annotated tag object and what it points at.
+#[derive(Deserialize)]
+struct AnnotatedTag {
+    #[serde(default)]
+    message: String,
+    object: TaggedObject,
+}
+
+#[derive(Deserialize)]
+struct TaggedObject {
+    sha: String,
+    #[serde(rename = "type")]
+    kind: String,
+}
+
+/// Most tags-of-tags `resolve_to_commit` peels before giving up.
+const MAX_TAG_DEPTH: usize = 5;
+
+/// The account a token belongs to, with the scopes GitHub reported for it.
+pub struct TokenUser {
+    pub login: String,
+    /// `X-OAuth-Scopes`; `None` for fine-grained tokens, which don't report scopes
+    pub scopes: Option<Vec<String>>,
+}
+
+impl TokenUser {
+    /// The `required` scopes the token lacks, or `None` when its scopes are unknown.
+    pub fn missing_scopes<'a>(&self, required: &[&'a str]) -> Option<Vec<&'a str>> {
+        let scopes = self.scopes.as_ref()?;
+        Some(
+            required
+                .iter()
+                .filter(|scope| !scopes.iter().any(|s| s == *scope))
+                .copied()
+                .collect(),
+        )
+    }
+}
+
+#[derive(Deserialize)]
+struct Branch {
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct GitRef {
+    object: TagObjectResponse,
+}
+
+#[derive(Deserialize)]
+struct Repository {
+    default_branch: String,
+    /// What the authenticated user may do; only present on authorized reads
+    #[serde(default)]
+    permissions: Option<RepoPermissions>,
+}
+
+#[derive(Deserialize)]
+struct RepoPermissions {
+    #[serde(default)]
+    push: bool,
+}
+
+/// How new releases are published.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ReleaseKind {
+    #[default]
+    Prerelease,
+    Stable,
+    /// Stable and marked as the repository's latest release
+    Latest,
+}
+
+impl ReleaseKind {
+    /// Resolve the `--stable` / `--latest` flags.
+    ///
+    /// GitHub never marks a prerelease as latest, so `--latest` implies `--stable`.
+    pub fn from_flags(stable: bool, latest: bool) -> Self {
+        match (stable, latest) {
+            (_, true) => ReleaseKind::Latest,
+            (true, false) => ReleaseKind::Stable,
+            (false, false) => ReleaseKind::Prerelease,
+        }
+    }
+}
+
+/// Which branch to use when the release/v{major}.{minor}.x branch doesn't exist.
+#[derive(Clone, Copy, Debug, Default, PartialEq, clap::ValueEnum)]
+pub enum BranchFallback {
+    /// Create a new release/{tag} branch from the release
+    #[default]
+    TagBranch,
+    /// Use the repository's default branch
+    DefaultBranch,
+}
+
+/// How a published prerelease that's requested again gets its new tag.
+#[derive(Clone, Copy, Debug, Default, PartialEq, clap::ValueEnum)]
+pub enum PrereleaseBump {
+    /// Bump the patch version, v1.2.0-rc.1 -> v1.2.1-rc.1
+    #[default]
+    Patch,
+    /// Bump the trailing prerelease counter, v1.2.0-rc.1 -> v1.2.0-rc.2
+    Counter,
+}
+
 pub struct GitHubClient {
     client: Client,
     token: String,
     base_url: String,
+    owner: String,
+    repo: String,
+    retry: RetryPolicy,
+    branch_fallback: BranchFallback,
+    api_version: String,
+    strict_ordering: bool,
+    prerelease_bump: PrereleaseBump,
+    /// Width the bumped prerelease counter is zero-padded to
+    prerelease_pad: usize,
+    release_kind: ReleaseKind,
+    /// What comes before the version in a tag, `v` in `v1.2.3`
+    tag_prefix: String,
+    /// `--header` values sent on every request, taking precedence over the defaults
+    extra_headers: HeaderMap,
 }
 
+/// REST API version the client pins unless told otherwise.
+pub const DEFAULT_API_VERSION: &str = "2022-11-28";
+
+/// Fewest retries of the final release update, even with `--retries 0`.
+pub const UPDATE_RELEASE_MIN_RETRIES: u32 = 4;
+
 impl GitHubClient {
     pub fn new(client: Client, token: String) -> Self {
         GitHubClient {
             client,
             token,
             base_url: "https://api.github.com".to_string(),
+            owner: "Human-Glitch".to_string(),
+            repo: "llm-playground".to_string(),
+            retry: RetryPolicy::default(),
+            branch_fallback: BranchFallback::default(),
+            api_version: DEFAULT_API_VERSION.to_string(),
+            strict_ordering: false,
+            prerelease_bump: PrereleaseBump::default(),
+            prerelease_pad: 0,
+            release_kind: ReleaseKind::default(),
+            tag_prefix: version::DEFAULT_TAG_PREFIX.to_string(),
+            extra_headers: HeaderMap::new(),
         }
     }
 
     // Create a new client with a custom base URL (for testing)
+    #[cfg(test)]
     pub fn new_with_base_url(client: Client, token: String, base_url: String) -> Self {
         GitHubClient {
-            client,
-            token,
             base_url,
+            ..Self::new(client, token)
         }
     }
 
+    /// Talk to a different API host, e.g. GitHub Enterprise Server's `https://host/api/v3`.
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Target a repository other than the default Human-Glitch/llm-playground.
+    pub fn with_repository(mut self, owner: &str, repo: &str) -> Self {
+        self.owner = owner.to_string();
+        self.repo = repo.to_string();
+        self
+    }
+
+    /// The HTTP client requests go through; it carries no credentials of its own.
+    pub fn http_client(&self) -> &Client {
+        &self.client
+    }
+
+    /// The repository this client targets, as owner/repo.
+    pub fn repository(&self) -> String {
+        format!("{}/{}", self.owner, self.repo)
+    }
+
+    /// Retry transient failures (5xx, 429, connection errors) with exponential backoff.
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Choose the branch used when a release's minor version branch doesn't exist.
+    pub fn with_branch_fallback(mut self, branch_fallback: BranchFallback) -> Self {
+        self.branch_fallback = branch_fallback;
+        self
+    }
+
+    /// Fail instead of warning when a new tag isn't newer than the latest release.
+    pub fn with_strict_ordering(mut self, strict_ordering: bool) -> Self {
+        self.strict_ordering = strict_ordering;
+        self
+    }
+
+    /// Bump a re-released prerelease this way; a bumped counter is zero-padded to `pad` digits.
+    pub fn with_prerelease_bump(mut self, bump: PrereleaseBump, pad: usize) -> Self {
+        self.prerelease_bump = bump;
+        self.prerelease_pad = pad;
+        self
+    }
+
+    /// Publish new releases as stable, or as the latest release, instead of as prereleases.
+    pub fn with_release_kind(mut self, release_kind: ReleaseKind) -> Self {
+        self.release_kind = release_kind;
+        self
+    }
+
+    /// Expect tags like `release-1.2.3` instead of `v1.2.3`; release branches follow the same prefix.
+    pub fn with_tag_prefix(mut self, tag_prefix: &str) -> Self {
+        self.tag_prefix = tag_prefix.to_string();
+        self
+    }
+
+    pub fn tag_prefix(&self) -> &str {
+        &self.tag_prefix
+    }
+
+    pub fn release_kind(&self) -> ReleaseKind {
+        self.release_kind
+    }
+
+    /// Pin a different `X-GitHub-Api-Version` than the default.
+    pub fn with_api_version(mut self, api_version: &str) -> Self {
+        self.api_version = api_version.to_string();
+        self
+    }
+
+    /// Send these headers on every request too, e.g. a token an API gateway wants.
+    pub fn with_headers(mut self, headers: &[(HeaderName, HeaderValue)]) -> Self {
+        self.extra_headers = headers.iter().cloned().collect();
+        self
+    }
+
+    /// Helper to build the repository URL.
+    fn repo_url(&self) -> String {
+        format!("{}/repos/{}/{}", self.base_url, self.owner, self.repo)
+    }
+
     /// Helper to build the API URL.
-    fn api_url(&self, endpoint: &str) -> String {
-        format!(
-            "{}/repos/{}/{}/{}",
-            self.base_url,
-            "Human-Glitch",
-            "llm-playground",
-            endpoint
-        )
+    pub fn api_url(&self, endpoint: &str) -> String {
+        format!("{}/{}", self.repo_url(), endpoint)
+    }
+
+    /// URL of the token's user, `GET /user`.
+    pub fn user_url(&self) -> String {
+        format!("{}/user", self.base_url)
+    }
+
+    /// Build a request with the headers every GitHub API call needs.
+    fn request(&self, method: Method, url: &str) -> RequestBuilder {
+        self.client
+            .request(method, url)
+            .header("User-Agent", "release_updater")
+            .header("Authorization", format!("Bearer {}", self.token))
+            .header("Accept", "application/vnd.github+json")
+            .header("X-GitHub-Api-Version", &self.api_version)
+            .headers(self.extra_headers.clone())
+    }
+
+    /// Send a request, retrying transient failures according to the client's retry policy.
+    async fn send(&self, request: RequestBuilder) -> Result<Response, Box<dyn Error>> {
+        Ok(retry::send_with_retry(request, &self.retry).await?)
     }
 
     /// Get a release by tag.
     pub async fn get_release_by_tag(&self, tag: &str) -> Result<Option<GitHubRelease>, Box<dyn Error>> {
         let url = self.api_url(&format!("releases/tags/{}", tag));
 
-        let resp = self
-            .client
-            .get(&url)
-            .header("User-Agent", "release_updater")
-            .header("Authorization", format!("Bearer {}", self.token))
-            .send()
-            .await?;
+        let resp = self.send(self.request(Method::GET, &url)).await?;
         
         match resp.status() {
             StatusCode::OK => {
@@ -75,7 +430,104 @@ impl GitHubClient {
                 Ok(Some(release))
             }
             StatusCode::NOT_FOUND => Ok(None),
-            _ => Err(format!("Failed to get release: {}", resp.text().await?).into()),
+            _ => Err(GitHubError::from_response("Failed to get release", resp).await.into()),
+        }
+    }
+
+    /// Get a release by tag, falling back to a draft with that tag name.
+    ///
+    /// `releases/tags/{tag}` never returns drafts, since their tag isn't created until they're published.
+    pub async fn find_release_by_tag_including_drafts(&self, tag: &str) -> Result<Option<GitHubRelease>, Box<dyn Error>> {
+        if let Some(release) = self.get_release_by_tag(tag).await? {
+            return Ok(Some(release));
+        }
+        let releases = self.list_releases().await?;
+        Ok(releases
+            .into_iter()
+            .find(|release| release.draft == Some(true) && release.tag_name == tag))
+    }
+
+    /// Get the latest published (non-draft, non-prerelease) release, if there is one.
+    pub async fn get_latest_release(&self) -> Result<Option<GitHubRelease>, Box<dyn Error>> {
+        let url = self.api_url("releases/latest");
+
+        let resp = self.send(self.request(Method::GET, &url)).await?;
+
+        match resp.status() {
+            StatusCode::OK => {
+                let release: GitHubRelease = resp.json().await?;
+                Ok(Some(release))
+            }
+            StatusCode::NOT_FOUND => Ok(None),
+            _ => Err(GitHubError::from_response("Failed to get latest release", resp).await.into()),
+        }
+    }
+
+    /// Web URL comparing two tags, as used in "Full Changelog" links.
+    pub fn compare_url(&self, base: &str, head: &str) -> String {
+        format!("https://github.com/{}/{}/compare/{}...{}", self.owner, self.repo, base, head)
+    }
+
+    /// Identify the token's user via `GET /user`, which also proves the token is valid.
+    pub async fn authenticated_user(&self) -> Result<TokenUser, Box<dyn Error>> {
+        let url = self.user_url();
+
+        let resp = self.send(self.request(Method::GET, &url)).await?;
+
+        if resp.status().is_success() {
+            let scopes = resp
+                .headers()
+                .get("x-oauth-scopes")
+                .and_then(|value| value.to_str().ok())
+                .map(|value| {
+                    value
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|scope| !scope.is_empty())
+                        .map(str::to_string)
+                        .collect()
+                });
+            let user: GitHubUser = resp.json().await?;
+            Ok(TokenUser { login: user.login, scopes })
+        } else {
+            Err(GitHubError::from_response("Failed to authenticate", resp).await.into())
+        }
+    }
+
+    /// Web URL listing the history up to a tag, for releases without a previous one to compare with.
+    pub fn commits_url(&self, tag: &str) -> String {
+        format!("https://github.com/{}/{}/commits/{}", self.owner, self.repo, tag)
+    }
+
+    /// Web URL of a single commit.
+    pub fn commit_url(&self, sha: &str) -> String {
+        format!("https://github.com/{}/{}/commit/{}", self.owner, self.repo, sha)
+    }
+
+    /// Attach a file to a release as an asset named `name`.
+    pub async fn upload_asset(
+        &self,
+        release: &GitHubRelease,
+        name: &str,
+        content_type: &str,
+        data: Vec<u8>,
+    ) -> Result<(), Box<dyn Error>> {
+        let url = match &release.upload_url {
+            Some(upload_url) => upload_url.split('{').next().unwrap_or(upload_url).to_string(),
+            None => self.api_url(&format!("releases/{}/assets", release.id)),
+        };
+
+        let request = self
+            .request(Method::POST, &url)
+            .query(&[("name", name)])
+            .header("Content-Type", content_type)
+            .body(data);
+        let resp = self.send(request).await?;
+
+        if resp.status().is_success() {
+            Ok(())
+        } else {
+            Err(GitHubError::from_response(&format!("Failed to upload {}", name), resp).await.into())
         }
     }
 
@@ -83,19 +535,13 @@ impl GitHubClient {
     pub async fn delete_release(&self, release_id: u64) -> Result<(), Box<dyn Error>> {
         let url = self.api_url(&format!("releases/{}", release_id));
 
-        let resp = self
-            .client
-            .delete(&url)
-            .header("User-Agent", "release_updater")
-            .header("Authorization", format!("Bearer {}", self.token))
-            .send()
-            .await?;
+        let resp = self.send(self.request(Method::DELETE, &url)).await?;
 
         if resp.status().is_success() {
             println!("Deleted GitHub release id: {}", release_id);
             Ok(())
         } else {
-            Err(format!("Failed to delete release: {}", resp.text().await?).into())
+            Err(GitHubError::from_response("Failed to delete release", resp).await.into())
         }
     }
 
@@ -103,19 +549,13 @@ impl GitHubClient {
     pub async fn delete_tag(&self, tag: &str) -> Result<(), Box<dyn Error>> {
         let url = self.api_url(&format!("git/refs/tags/{}", tag));
 
-        let resp = self
-            .client
-            .delete(&url)
-            .header("User-Agent", "release_updater")
-            .header("Authorization", format!("Bearer {}", self.token))
-            .send()
-            .await?;
+        let resp = self.send(self.request(Method::DELETE, &url)).await?;
 
         if resp.status().is_success() || resp.status() == StatusCode::NOT_FOUND {
             println!("Deleted tag reference: {}", tag);
             Ok(())
         } else {
-            Err(format!("Failed to delete tag: {}", resp.text().await?).into())
+            Err(GitHubError::from_response("Failed to delete tag", resp).await.into())
         }
     }
 
@@ -123,59 +563,179 @@ impl GitHubClient {
     pub async fn get_latest_commit_sha(&self, branch: &str) -> Result<String, Box<dyn Error>> {
         let url = self.api_url(&format!("commits/{}", branch));
 
-        let resp = self
-            .client
-            .get(&url)
-            .header("User-Agent", "release_updater")
-            .header("Authorization", format!("Bearer {}", self.token))
-            .send()
-            .await?;
+        let resp = self.send(self.request(Method::GET, &url)).await?;
 
         if resp.status().is_success() {
             let commit: Commit = resp.json().await?;
             Ok(commit.sha)
+        } else if resp.status() == StatusCode::CONFLICT || resp.status() == StatusCode::NOT_FOUND {
+            // GitHub answers 409 "Git Repository is empty." when there is nothing to resolve, and some
+            // installs a 404 with the same message; any other 404 is a real lookup failure
+            let error = GitHubError::from_response("Failed to get latest commit", resp).await;
+            if error.status == StatusCode::CONFLICT || error.message.to_lowercase().contains("empty") {
+                return Err(format!("Branch '{}' has no commits to tag. Pass --sha <commit> to tag a specific commit.", branch).into());
+            }
+            Err(error.into())
         } else {
-            Err(format!(
-                "Failed to get latest commit: {}",
-                resp.text().await?
-            )
-            .into())
+            Err(GitHubError::from_response("Failed to get latest commit", resp).await.into())
+        }
+    }
+
+    /// Compare two refs, returning the commits reachable from `head` but not from `base`.
+    pub async fn compare(&self, base: &str, head: &str) -> Result<Comparison, Box<dyn Error>> {
+        let url = self.api_url(&format!("compare/{}...{}", base, head));
+
+        let resp = self.send(self.request(Method::GET, &url)).await?;
+
+        if resp.status().is_success() {
+            let comparison: Comparison = resp.json().await?;
+            Ok(comparison)
+        } else {
+            Err(GitHubError::from_response("Failed to compare refs", resp).await.into())
+        }
+    }
+
+    /// List every commit reachable from `head`, oldest first; with `since`, a UTC timestamp, only those
+    /// committed at or after it.
+    pub async fn list_commits(&self, head: &str, since: Option<&str>) -> Result<Vec<CommitSummary>, Box<dyn Error>> {
+        let mut commits = Vec::new();
+        let mut page = 1;
+        let since = since.map(|since| format!("&since={}", since)).unwrap_or_default();
+
+        loop {
+            let url = self.api_url(&format!("commits?sha={}{}&per_page=100&page={}", head, since, page));
+            let resp = self.send(self.request(Method::GET, &url)).await?;
+
+            if !resp.status().is_success() {
+                return Err(GitHubError::from_response("Failed to list commits", resp).await.into());
+            }
+
+            let batch: Vec<CommitSummary> = resp.json().await?;
+            let is_last_page = batch.len() < 100;
+            commits.extend(batch);
+
+            if is_last_page {
+                break;
+            }
+            page += 1;
+        }
+
+        // The API lists newest first, compare lists oldest first; keep both in the same order
+        commits.reverse();
+        Ok(commits)
+    }
+
+    /// The label names of a pull request, read through the issues API that pull requests share.
+    pub async fn pull_request_labels(&self, number: u64) -> Result<Vec<String>, Box<dyn Error>> {
+        let url = self.api_url(&format!("issues/{}", number));
+
+        let resp = self.send(self.request(Method::GET, &url)).await?;
+
+        if resp.status().is_success() {
+            let issue: LabeledIssue = resp.json().await?;
+            Ok(issue.labels.into_iter().map(|label| label.name).collect())
+        } else {
+            Err(GitHubError::from_response("Failed to get pull request labels", resp).await.into())
+        }
+    }
+
+    /// The accounts that approved a pull request, once each and without its author.
+    pub async fn pull_request_approvers(&self, number: u64) -> Result<Vec<String>, Box<dyn Error>> {
+        let url = self.api_url(&format!("pulls/{}", number));
+        let resp = self.send(self.request(Method::GET, &url)).await?;
+        if !resp.status().is_success() {
+            return Err(GitHubError::from_response("Failed to get pull request", resp).await.into());
+        }
+        let author = resp.json::<PullRequest>().await?.user.map(|user| user.login);
+
+        let url = self.api_url(&format!("pulls/{}/reviews?per_page=100", number));
+        let resp = self.send(self.request(Method::GET, &url)).await?;
+        if !resp.status().is_success() {
+            return Err(GitHubError::from_response("Failed to get pull request reviews", resp).await.into());
+        }
+        let reviews: Vec<Review> = resp.json().await?;
+
+        let mut approvers: Vec<String> = Vec::new();
+        for review in reviews.into_iter().filter(|review| review.state == "APPROVED") {
+            let Some(login) = review.user.map(|user| user.login) else { continue };
+            if Some(&login) != author.as_ref() && !approvers.contains(&login) {
+                approvers.push(login);
+            }
+        }
+        Ok(approvers)
+    }
+
+    /// The committer date of a commit, read from its git commit object.
+    pub async fn commit_date(&self, sha: &str) -> Result<String, Box<dyn Error>> {
+        let url = self.api_url(&format!("git/commits/{}", sha));
+
+        let resp = self.send(self.request(Method::GET, &url)).await?;
+
+        if resp.status().is_success() {
+            let commit: GitCommitObject = resp.json().await?;
+            Ok(commit.committer.date)
+        } else {
+            Err(GitHubError::from_response("Failed to get commit", resp).await.into())
+        }
+    }
+
+    /// The token's user as a tagger dated `date`, named and addressed the way GitHub would stamp the tag itself.
+    ///
+    /// Users without a public name or email get their login and their noreply address.
+    pub async fn tagger_at(&self, date: &str) -> Result<GitSignature, Box<dyn Error>> {
+        let url = self.user_url();
+
+        let resp = self.send(self.request(Method::GET, &url)).await?;
+
+        if !resp.status().is_success() {
+            return Err(GitHubError::from_response("Failed to get the token's user", resp).await.into());
         }
+        let user: TaggerUser = resp.json().await?;
+        Ok(GitSignature {
+            name: user.name.filter(|name| !name.trim().is_empty()).unwrap_or_else(|| user.login.clone()),
+            email: user
+                .email
+                .filter(|email| !email.trim().is_empty())
+                .unwrap_or_else(|| format!("{}+{}@users.noreply.github.com", user.id, user.login)),
+            date: date.to_string(),
+        })
     }
 
     /// Create an annotated tag object.
+    ///
+    /// Without a `tagger`, GitHub stamps the tag with the token's user and the current time.
     pub async fn create_tag_object(
         &self,
         tag: &str,
         message: &str,
         object: &str,
+        tagger: Option<&GitSignature>,
     ) -> Result<String, Box<dyn Error>> {
         let url = self.api_url("git/tags");
-        let body = json!({
+        let mut body = json!({
             "tag": tag,
             "message": message,
             "object": object,
             "type": "commit"
         });
+        if let Some(tagger) = tagger {
+            body["tagger"] = json!(tagger);
+        }
 
-        let resp = self
-            .client
-            .post(&url)
-            .header("User-Agent", "release_updater")
-            .header("Authorization", format!("Bearer {}", self.token))
-            .json(&body)
-            .send()
-            .await?;
+        let resp = self.send(self.request(Method::POST, &url).json(&body)).await?;
 
         if resp.status().is_success() {
             let tag_resp: TagObjectResponse = resp.json().await?;
             Ok(tag_resp.sha)
         } else {
-            Err(format!("Failed to create tag object: {}", resp.text().await?).into())
+            Err(GitHubError::from_response("Failed to create tag object", resp).await.into())
         }
     }
 
     /// Create a tag reference pointing to the tag object.
+    ///
+    /// A ref that already exists and points at `sha`, or at another tag of the same commit,
+    /// counts as success, so a retry after a partial success is safe.
     pub async fn create_tag_ref(&self, tag: &str, sha: &str) -> Result<(), Box<dyn Error>> {
         let url = self.api_url("git/refs");
         let body = json!({
@@ -183,78 +743,372 @@ impl GitHubClient {
             "sha": sha
         });
 
-        let resp = self
-            .client
-            .post(&url)
-            .header("User-Agent", "release_updater")
-            .header("Authorization", format!("Bearer {}", self.token))
-            .json(&body)
-            .send()
-            .await?;
+        let resp = self.send(self.request(Method::POST, &url).json(&body)).await?;
 
         if resp.status().is_success() {
             println!("Created tag reference for: {}", tag);
+            return Ok(());
+        }
+
+        let error = GitHubError::from_response("Failed to create tag ref", resp).await;
+        if error.status != StatusCode::UNPROCESSABLE_ENTITY || error.message != "Reference already exists" {
+            return Err(error.into());
+        }
+
+        let existing = match self.get_tag_ref_sha(tag).await? {
+            Some(existing) => existing,
+            None => return Err(error.into()),
+        };
+        // A retry creates a new tag object, so compare the commits behind them
+        if existing == sha || self.resolve_to_commit(&existing).await? == self.resolve_to_commit(sha).await? {
+            println!("Tag reference for {} already exists at {}", tag, existing);
             Ok(())
         } else {
-            Err(format!("Failed to create tag ref: {}", resp.text().await?).into())
+            Err(format!(
+                "Tag reference for {} already exists at {}, not {}",
+                tag, existing, sha
+            )
+            .into())
         }
     }
 
-    /// Create a GitHub release using auto-generated release notes.
-    pub async fn create_release(&self, tag: &str) -> Result<GitHubRelease, Box<dyn Error>> {
-        let url = self.api_url("releases");
-        
-        // Get the appropriate branch for this release
-        let branch = self.get_release_branch_for_tag(tag).await?;
-        
+    /// The commit a sha points at: annotated tag objects are dereferenced, anything else is returned as-is.
+    pub async fn resolve_to_commit(&self, sha: &str) -> Result<String, Box<dyn Error>> {
+        let mut sha = sha.to_string();
+        // A tag can point at another tag, so keep peeling until it doesn't
+        for _ in 0..MAX_TAG_DEPTH {
+            let url = self.api_url(&format!("git/tags/{}", sha));
+            let resp = self.send(self.request(Method::GET, &url)).await?;
+
+            if resp.status() == StatusCode::NOT_FOUND || resp.status() == StatusCode::UNPROCESSABLE_ENTITY {
+                // Not a tag object, so it's the commit
+                return Ok(sha);
+            }
+            if !resp.status().is_success() {
+                return Err(GitHubError::from_response("Failed to get tag object", resp).await.into());
+            }
+
+            let tag: AnnotatedTag = resp.json().await?;
+            if tag.object.kind != "tag" {
+                return Ok(tag.object.sha);
+            }
+            sha = tag.object.sha;
+        }
+        Err(format!("Tag object {} is nested more than {} tags deep", sha, MAX_TAG_DEPTH).into())
+    }
+
+    /// Move an existing tag ref to `sha`, even when that isn't a descendant of its current target.
+    pub async fn force_update_tag_ref(&self, tag: &str, sha: &str) -> Result<(), Box<dyn Error>> {
+        let url = self.api_url(&format!("git/refs/tags/{}", tag));
         let body = json!({
-            "tag_name": tag,
-            "target_commitish": branch,
-            "name": tag,
-            "draft": false,
-            "prerelease": true,
-            "generate_release_notes": true
+            "sha": sha,
+            "force": true
         });
 
-        let resp = self
-            .client
-            .post(&url)
-            .header("User-Agent", "release_updater")
-            .header("Authorization", format!("Bearer {}", self.token))
-            .json(&body)
-            .send()
-            .await?;
+        let resp = self.send(self.request(Method::PATCH, &url).json(&body)).await?;
 
         if resp.status().is_success() {
-            println!("Created GitHub release for tag: {}", tag);
-            let release: GitHubRelease = resp.json().await?;
-            Ok(release)
+            println!("Moved tag reference {} to {}", tag, sha);
+            Ok(())
         } else {
-            Err(format!("Failed to create release: {}", resp.text().await?).into())
+            Err(GitHubError::from_response("Failed to update tag ref", resp).await.into())
         }
     }
 
-    /// Update an existing GitHub release with new release notes.
-    pub async fn update_release(&self, release_id: u64, notes: &str) -> Result<(), Box<dyn Error>> {
-        let url = self.api_url(&format!("releases/{}", release_id));
-        let body = json!({
-            "body": notes
+    /// Whether a tag exists in the repository.
+    pub async fn tag_exists(&self, tag: &str) -> Result<bool, Box<dyn Error>> {
+        Ok(self.get_tag_ref_sha(tag).await?.is_some())
+    }
+
+    /// The commit a tag points at, or `None` when the tag doesn't exist.
+    pub async fn tag_commit(&self, tag: &str) -> Result<Option<String>, Box<dyn Error>> {
+        match self.get_tag_ref_sha(tag).await? {
+            Some(sha) => Ok(Some(self.resolve_to_commit(&sha).await?)),
+            None => Ok(None),
+        }
+    }
+
+    /// The message of a tag, or `None` when the tag doesn't exist or is a lightweight tag.
+    pub async fn tag_message(&self, tag: &str) -> Result<Option<String>, Box<dyn Error>> {
+        let Some(sha) = self.get_tag_ref_sha(tag).await? else {
+            return Ok(None);
+        };
+        let url = self.api_url(&format!("git/tags/{}", sha));
+        let resp = self.send(self.request(Method::GET, &url)).await?;
+
+        if resp.status() == StatusCode::NOT_FOUND || resp.status() == StatusCode::UNPROCESSABLE_ENTITY {
+            // The ref points straight at a commit
+            return Ok(None);
+        }
+        if !resp.status().is_success() {
+            return Err(GitHubError::from_response("Failed to get tag object", resp).await.into());
+        }
+
+        let tag: AnnotatedTag = resp.json().await?;
+        Ok(Some(tag.message).filter(|message| !message.trim().is_empty()))
+    }
+
+    /// The sha a tag ref points at, if the ref exists.
+    async fn get_tag_ref_sha(&self, tag: &str) -> Result<Option<String>, Box<dyn Error>> {
+        let url = self.api_url(&format!("git/ref/tags/{}", tag));
+
+        let resp = self.send(self.request(Method::GET, &url)).await?;
+
+        if resp.status().is_success() {
+            let git_ref: GitRef = resp.json().await?;
+            Ok(Some(git_ref.object.sha))
+        } else if resp.status() == StatusCode::NOT_FOUND {
+            Ok(None)
+        } else {
+            Err(GitHubError::from_response("Failed to get tag ref", resp).await.into())
+        }
+    }
+
+    /// Create a GitHub release using auto-generated release notes, or with `body` as its notes when given.
+    ///
+    /// The release is titled `name`, or the tag when no name is given.
+    pub async fn create_release(
+        &self,
+        tag: &str,
+        name: Option<&str>,
+        body: Option<&str>,
+    ) -> Result<GitHubRelease, Box<dyn Error>> {
+        let url = self.api_url("releases");
+        
+        // Get the appropriate branch for this release
+        let branch = self.get_release_branch_for_tag(tag).await?;
+        
+        let mut request = json!({
+            "tag_name": tag,
+            "target_commitish": branch,
+            "name": name.unwrap_or(tag),
+            "draft": false,
+            "prerelease": self.release_kind == ReleaseKind::Prerelease,
+            "generate_release_notes": body.is_none()
         });
+        if let Some(body) = body {
+            request["body"] = json!(body);
+        }
+        if self.release_kind == ReleaseKind::Latest {
+            request["make_latest"] = json!("true");
+        }
 
-        let resp = self
-            .client
-            .patch(&url)
-            .header("User-Agent", "release_updater")
-            .header("Authorization", format!("Bearer {}", self.token))
-            .json(&body)
-            .send()
-            .await?;
+        let resp = self.send(self.request(Method::POST, &url).json(&request)).await?;
+
+        if resp.status().is_success() {
+            println!("Created GitHub release for tag: {}", tag);
+            let release: GitHubRelease = resp.json().await?;
+            Ok(release)
+        } else {
+            Err(GitHubError::from_response("Failed to create release", resp).await.into())
+        }
+    }
+
+    /// Create the release for a tag, or return the existing one when another run created it first.
+    pub async fn get_or_create_release(
+        &self,
+        tag: &str,
+        name: Option<&str>,
+        body: Option<&str>,
+    ) -> Result<GitHubRelease, Box<dyn Error>> {
+        let err = match self.create_release(tag, name, body).await {
+            Ok(release) => return Ok(release),
+            Err(err) => err,
+        };
+
+        let already_exists = err.downcast_ref::<GitHubError>().is_some_and(GitHubError::is_already_exists);
+        if !already_exists {
+            return Err(err);
+        }
+
+        println!("Release for tag {} already exists, using it.", tag);
+        self.get_release_by_tag(tag).await?.ok_or(err)
+    }
+
+    /// Ask GitHub to generate fresh release notes for a tag, optionally since a specific previous tag.
+    pub async fn generate_notes(
+        &self,
+        tag: &str,
+        previous_tag: Option<&str>,
+        target_commitish: Option<&str>,
+    ) -> Result<String, Box<dyn Error>> {
+        let url = self.api_url("releases/generate-notes");
+        let mut body = json!({
+            "tag_name": tag
+        });
+        if let Some(previous_tag) = previous_tag {
+            body["previous_tag_name"] = json!(previous_tag);
+        }
+        // Only read when the tag doesn't exist yet, to know where its notes end
+        if let Some(target_commitish) = target_commitish {
+            body["target_commitish"] = json!(target_commitish);
+        }
+
+        let resp = self.send(self.request(Method::POST, &url).json(&body)).await?;
+
+        if resp.status().is_success() {
+            let notes: GeneratedNotes = resp.json().await?;
+            Ok(notes.body)
+        } else {
+            Err(GitHubError::from_response("Failed to generate release notes", resp).await.into())
+        }
+    }
+
+    /// Update an existing GitHub release with new release notes.
+    ///
+    /// It's the last call of a run and losing it leaves the unformatted notes behind, so it's retried
+    /// at least `UPDATE_RELEASE_MIN_RETRIES` times whatever `--retries` says.
+    pub async fn update_release(&self, release_id: u64, notes: &str, name: Option<&str>) -> Result<(), Box<dyn Error>> {
+        let url = self.api_url(&format!("releases/{}", release_id));
+        let mut body = json!({
+            "body": notes
+        });
+        if let Some(name) = name {
+            body["name"] = json!(name);
+        }
+
+        // Setting the body again is harmless, so the PATCH is retried like an idempotent call
+        let request = self.request(Method::PATCH, &url).json(&body);
+        let resp = retry::send_repeatable_with_retry(request, &self.retry.at_least(UPDATE_RELEASE_MIN_RETRIES), None).await?;
 
         if resp.status().is_success() {
             println!("Updated release notes for release id: {}", release_id);
             Ok(())
         } else {
-            Err(format!("Failed to update release: {}", resp.text().await?).into())
+            Err(GitHubError::from_response("Failed to update release", resp).await.into())
+        }
+    }
+
+    /// Set or clear a release's prerelease flag.
+    pub async fn set_prerelease(&self, release_id: u64, prerelease: bool) -> Result<(), Box<dyn Error>> {
+        let url = self.api_url(&format!("releases/{}", release_id));
+        let body = json!({
+            "prerelease": prerelease
+        });
+
+        let resp = self.send(self.request(Method::PATCH, &url).json(&body)).await?;
+
+        if resp.status().is_success() {
+            println!("Set prerelease to {} for release id: {}", prerelease, release_id);
+            Ok(())
+        } else {
+            Err(GitHubError::from_response("Failed to update prerelease flag", resp).await.into())
+        }
+    }
+
+    /// Set or clear a release's draft flag; `true` unpublishes it.
+    pub async fn set_draft(&self, release_id: u64, draft: bool) -> Result<(), Box<dyn Error>> {
+        let url = self.api_url(&format!("releases/{}", release_id));
+        let body = json!({
+            "draft": draft
+        });
+
+        let resp = self.send(self.request(Method::PATCH, &url).json(&body)).await?;
+
+        if resp.status().is_success() {
+            println!("Set draft to {} for release id: {}", draft, release_id);
+            Ok(())
+        } else {
+            Err(GitHubError::from_response("Failed to update draft flag", resp).await.into())
+        }
+    }
+
+    /// Get the repository's default branch (e.g. main).
+    pub async fn default_branch(&self) -> Result<String, Box<dyn Error>> {
+        let url = self.repo_url();
+
+        let resp = self.send(self.request(Method::GET, &url)).await?;
+
+        if resp.status().is_success() {
+            let repository: Repository = resp.json().await?;
+            Ok(repository.default_branch)
+        } else {
+            Err(GitHubError::from_response("Failed to get repository", resp).await.into())
+        }
+    }
+
+    /// The GraphQL endpoint next to the REST API, `https://host/api/graphql` on GitHub Enterprise Server.
+    fn graphql_url(&self) -> String {
+        match self.base_url.strip_suffix("/api/v3") {
+            Some(host) => format!("{}/api/graphql", host),
+            None => format!("{}/graphql", self.base_url),
+        }
+    }
+
+    /// Run a GraphQL query, failing on errors in the response as well as on a failed request.
+    async fn graphql(&self, query: &str, variables: Value) -> Result<Value, Box<dyn Error>> {
+        let body = json!({"query": query, "variables": variables});
+        let resp = self.send(self.request(Method::POST, &self.graphql_url()).json(&body)).await?;
+        if !resp.status().is_success() {
+            return Err(GitHubError::from_response("Failed to call the GraphQL API", resp).await.into());
+        }
+
+        let response: Value = resp.json().await?;
+        if let Some(errors) = response["errors"].as_array().filter(|errors| !errors.is_empty()) {
+            let messages: Vec<&str> = errors.iter().filter_map(|error| error["message"].as_str()).collect();
+            return Err(format!("GraphQL request failed: {}", messages.join("; ")).into());
+        }
+        Ok(response["data"].clone())
+    }
+
+    /// Start a discussion in the category with this name, returning its URL.
+    pub async fn create_discussion(&self, category: &str, title: &str, body: &str) -> Result<String, Box<dyn Error>> {
+        let data = self
+            .graphql(
+                "query($owner: String!, $name: String!) { repository(owner: $owner, name: $name) { id discussionCategories(first: 100) { nodes { id name } } } }",
+                json!({"owner": self.owner, "name": self.repo}),
+            )
+            .await?;
+        let repository = &data["repository"];
+        let categories = repository["discussionCategories"]["nodes"].as_array().cloned().unwrap_or_default();
+        let category_id = categories
+            .iter()
+            .find(|node| node["name"].as_str().is_some_and(|name| name.eq_ignore_ascii_case(category)))
+            .and_then(|node| node["id"].as_str())
+            .ok_or_else(|| {
+                let known: Vec<&str> = categories.iter().filter_map(|node| node["name"].as_str()).collect();
+                format!("No discussion category named {} in {}. Known categories: {}.", category, self.repository(), known.join(", "))
+            })?;
+
+        let data = self
+            .graphql(
+                "mutation($repositoryId: ID!, $categoryId: ID!, $title: String!, $body: String!) { createDiscussion(input: {repositoryId: $repositoryId, categoryId: $categoryId, title: $title, body: $body}) { discussion { url } } }",
+                json!({
+                    "repositoryId": repository["id"],
+                    "categoryId": category_id,
+                    "title": title,
+                    "body": body,
+                }),
+            )
+            .await?;
+        data["createDiscussion"]["discussion"]["url"]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| format!("GitHub created no discussion in {} for {}.", self.repository(), title).into())
+    }
+
+    /// Confirm the token can read the repository and plausibly write to it.
+    ///
+    /// Fine-grained tokens don't report scopes, so this authorized read stands in for the scope check.
+    pub async fn check_repo_access(&self) -> Result<(), Box<dyn Error>> {
+        let url = self.repo_url();
+
+        let resp = self.send(self.request(Method::GET, &url)).await?;
+
+        match resp.status() {
+            status if status.is_success() => {
+                let repository: Repository = resp.json().await?;
+                match repository.permissions {
+                    Some(permissions) if !permissions.push => {
+                        Err(format!("token lacks write access to {}", self.repository()).into())
+                    }
+                    _ => Ok(()),
+                }
+            }
+            StatusCode::FORBIDDEN | StatusCode::NOT_FOUND => {
+                Err(format!("token lacks access to {}", self.repository()).into())
+            }
+            _ => Err(GitHubError::from_response("Failed to get repository", resp).await.into()),
         }
     }
 
@@ -262,20 +1116,83 @@ impl GitHubClient {
     pub async fn branch_exists(&self, branch: &str) -> Result<bool, Box<dyn Error>> {
         let url = self.api_url(&format!("branches/{}", branch));
 
-        let resp = self
-            .client
-            .get(&url)
-            .header("User-Agent", "release_updater")
-            .header("Authorization", format!("Bearer {}", self.token))
-            .send()
-            .await?;
+        let resp = self.send(self.request(Method::GET, &url)).await?;
         
         Ok(resp.status().is_success())
     }
 
+    /// List every release in the repository, drafts included.
+    pub async fn list_releases(&self) -> Result<Vec<GitHubRelease>, Box<dyn Error>> {
+        let mut releases = Vec::new();
+        let mut page = 1;
+
+        loop {
+            let url = self.api_url(&format!("releases?per_page=100&page={}", page));
+            let resp = self.send(self.request(Method::GET, &url)).await?;
+
+            if !resp.status().is_success() {
+                return Err(GitHubError::from_response("Failed to list releases", resp).await.into());
+            }
+
+            let batch: Vec<GitHubRelease> = resp.json().await?;
+            let is_last_page = batch.len() < 100;
+            releases.extend(batch);
+
+            if is_last_page {
+                break;
+            }
+            page += 1;
+        }
+
+        Ok(releases)
+    }
+
+    /// List the names of every branch in the repository.
+    pub async fn list_branches(&self) -> Result<Vec<String>, Box<dyn Error>> {
+        let mut branches = Vec::new();
+        let mut page = 1;
+
+        loop {
+            let url = self.api_url(&format!("branches?per_page=100&page={}", page));
+            let resp = self.send(self.request(Method::GET, &url)).await?;
+
+            if !resp.status().is_success() {
+                return Err(GitHubError::from_response("Failed to list branches", resp).await.into());
+            }
+
+            let batch: Vec<Branch> = resp.json().await?;
+            let is_last_page = batch.len() < 100;
+            branches.extend(batch.into_iter().map(|branch| branch.name));
+
+            if is_last_page {
+                break;
+            }
+            page += 1;
+        }
+
+        Ok(branches)
+    }
+
+    /// A " (did you mean ...?)" hint naming the existing branch closest to a missing one.
+    ///
+    /// Listing failures only cost the hint, never the release.
+    async fn branch_suggestion(&self, missing: &str) -> String {
+        match self.list_branches().await {
+            Ok(branches) => closest_branch(missing, &branches)
+                .map(|branch| format!(" (did you mean {}?)", branch))
+                .unwrap_or_default(),
+            Err(_) => String::new(),
+        }
+    }
+
+    /// Matches `{prefix}{major}.{minor}.{patch}{suffix}`.
+    fn tag_regex(&self) -> Result<Regex, Box<dyn Error>> {
+        Ok(Regex::new(&format!(r"^{}(\d+)\.(\d+)\.(\d+)(.*)$", regex::escape(&self.tag_prefix)))?)
+    }
+
     /// Parse a semantic version tag (e.g., v1.2.3) and increment the patch version
     pub fn increment_patch_version(&self, tag: &str) -> Result<String, Box<dyn Error>> {
-        let re = Regex::new(r"^v(\d+)\.(\d+)\.(\d+)(.*)$")?;
+        let re = self.tag_regex()?;
         
         if let Some(caps) = re.captures(tag) {
             let major = caps.get(1).unwrap().as_str();
@@ -284,7 +1201,7 @@ impl GitHubClient {
             let suffix = caps.get(4).map_or("", |m| m.as_str());
             
             let new_patch = patch.parse::<u32>().unwrap() + 1;
-            Ok(format!("v{}.{}.{}{}", major, minor, new_patch, suffix))
+            Ok(format!("{}{}.{}.{}{}", self.tag_prefix, major, minor, new_patch, suffix))
         } else {
             Err(format!("Invalid semantic version tag format: {}", tag).into())
         }
@@ -292,7 +1209,7 @@ impl GitHubClient {
 
     /// Get the minor version part of a tag (e.g., v1.2.3 -> 1.2)
     pub fn get_minor_version(&self, tag: &str) -> Result<String, Box<dyn Error>> {
-        let re = Regex::new(r"^v(\d+)\.(\d+)\.(\d+)(.*)$")?;
+        let re = self.tag_regex()?;
         
         if let Some(caps) = re.captures(tag) {
             let major = caps.get(1).unwrap().as_str();
@@ -307,7 +1224,7 @@ impl GitHubClient {
     /// Get the release branch name for a tag following the convention release/v{major}.{minor}.x
     pub fn get_release_branch_name(&self, tag: &str) -> Result<String, Box<dyn Error>> {
         let minor_version = self.get_minor_version(tag)?;
-        Ok(format!("release/v{}.x", minor_version))
+        Ok(format!("release/{}{}.x", self.tag_prefix, minor_version))
     }
     
     /// Check if a release exists for a given tag and is in prerelease state
@@ -341,14 +1258,57 @@ impl GitHubClient {
 
     /// Determine if a tag should be incremented, and if so, return the new tag
     pub async fn determine_tag_version(&self, requested_tag: &str) -> Result<String, Box<dyn Error>> {
-        if self.should_increment_patch(requested_tag).await? {
-            let new_tag = self.increment_patch_version(requested_tag)?;
-            println!("ℹ️ The requested tag {} is in pre-release state with an existing minor version branch.", requested_tag);
-            println!("ℹ️ Creating a new patch version: {}", new_tag);
-            return Ok(new_tag);
+        let tag = if self.should_increment_patch(requested_tag).await? {
+            println!("{}", output::info(format!("The requested tag {} is in pre-release state with an existing minor version branch.", requested_tag)));
+            match self.prerelease_bump {
+                PrereleaseBump::Patch => {
+                    let new_tag = self.increment_patch_version(requested_tag)?;
+                    println!("{}", output::info(format!("Creating a new patch version: {}", new_tag)));
+                    new_tag
+                }
+                PrereleaseBump::Counter => {
+                    let new_tag = requested_tag
+                        .strip_prefix(&self.tag_prefix)
+                        .and_then(|version| version::increment_prerelease(version, self.prerelease_pad))
+                        .map(|version| format!("{}{}", self.tag_prefix, version))
+                        .ok_or_else(|| {
+                            format!("Tag {} has no numeric prerelease counter, such as -rc.1, for --prerelease-bump counter.", requested_tag)
+                        })?;
+                    println!("{}", output::info(format!("Creating the next prerelease: {}", new_tag)));
+                    new_tag
+                }
+            }
+        } else {
+            requested_tag.to_string()
+        };
+
+        self.check_tag_ordering(&tag).await?;
+        Ok(tag)
+    }
+
+    /// Warn, or fail with strict ordering, when `tag` isn't newer than the latest published release.
+    ///
+    /// Tags that aren't semver on either side are not compared.
+    pub async fn check_tag_ordering(&self, tag: &str) -> Result<(), Box<dyn Error>> {
+        let Some(latest) = self.get_latest_release().await? else {
+            return Ok(());
+        };
+        let (Some(new_version), Some(latest_version)) = (
+            Version::parse_with_prefix(tag, &self.tag_prefix),
+            Version::parse_with_prefix(&latest.tag_name, &self.tag_prefix),
+        ) else {
+            return Ok(());
+        };
+        if new_version > latest_version {
+            return Ok(());
         }
-        
-        Ok(requested_tag.to_string())
+
+        let message = format!("Tag {} is not newer than the latest release {}.", tag, latest.tag_name);
+        if self.strict_ordering {
+            return Err(format!("{} Request a higher version or drop --strict-ordering.", message).into());
+        }
+        println!("{}", output::warning(message));
+        Ok(())
     }
     
     /// Get the release branch corresponding to a tag following the convention release/v{major}.{minor}.x
@@ -361,17 +1321,52 @@ impl GitHubClient {
             return Ok(branch_name);
         }
         
+        let suggestion = self.branch_suggestion(&branch_name).await;
+
+        if self.branch_fallback == BranchFallback::DefaultBranch {
+            let default_branch = self.default_branch().await?;
+            println!("{}", output::warning(format!("Branch {} not found{}. Using default branch {}.", branch_name, suggestion, default_branch)));
+            return Ok(default_branch);
+        }
+
         // If the branch doesn't exist, use the direct tag-based branch name for new releases
         let fallback_branch = format!("release/{}", tag);
         
-        println!("⚠️  Branch {} not found. Creating a new branch {}.", branch_name, fallback_branch);
+        println!("{}", output::warning(format!("Branch {} not found{}. Creating a new branch {}.", branch_name, suggestion, fallback_branch)));
         Ok(fallback_branch)
     }
 }
 
+/// Parse a `--header` value, `<name>: <value>`. Authorization can't be set this way, since the client sends the token.
+pub fn parse_header(value: &str) -> Result<(HeaderName, HeaderValue), String> {
+    let (name, header_value) = value
+        .split_once(':')
+        .ok_or_else(|| format!("expected <name>: <value>, got {:?}", value))?;
+    let name = HeaderName::from_bytes(name.trim().as_bytes()).map_err(|e| format!("invalid header name {:?}: {}", name.trim(), e))?;
+    if name == AUTHORIZATION {
+        return Err("the Authorization header comes from the GitHub token and can't be overridden".to_string());
+    }
+    let header_value = HeaderValue::from_str(header_value.trim()).map_err(|e| format!("invalid value for header {}: {}", name, e))?;
+    Ok((name, header_value))
+}
+
+/// The branch closest to `expected` by edit distance, if any is close enough to be a likely typo.
+pub fn closest_branch<'a>(expected: &str, branches: &'a [String]) -> Option<&'a str> {
+    // Allow a couple of edits, fewer for short names so they don't match everything
+    let max_distance = (expected.len() / 4).clamp(1, 2);
+
+    branches
+        .iter()
+        .map(|branch| (strsim::levenshtein(expected, branch), branch))
+        .filter(|(distance, _)| *distance > 0 && *distance <= max_distance)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, branch)| branch.as_str())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::retry::RecordingSleeper;
     use mockito::Matcher;
     use tokio::runtime::Runtime;
 
@@ -427,6 +1422,82 @@ mod tests {
         assert_eq!(branch_name, "release/v3.4.x");
     }
 
+    #[test]
+    fn given_announcements_category_when_creating_discussion_then_posts_title_and_body() {
+        let mut server = mockito::Server::new();
+
+        let mock_categories = server.mock("POST", "/graphql")
+            .match_body(Matcher::Regex("discussionCategories".to_string()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"data": {"repository": {"id": "R_1", "discussionCategories": {"nodes": [
+                {"id": "DC_1", "name": "General"}, {"id": "DC_2", "name": "Announcements"}
+            ]}}}}"#)
+            .create();
+        let mock_create = server.mock("POST", "/graphql")
+            .match_body(Matcher::PartialJson(json!({"variables": {
+                "repositoryId": "R_1",
+                "categoryId": "DC_2",
+                "title": "v1.3.1",
+                "body": "## PDE\n* [PDE-1](https://onezelis.atlassian.net/browse/PDE-1) Fix by @a"
+            }})))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"data": {"createDiscussion": {"discussion": {"url": "https://github.com/o/r/discussions/7"}}}}"#)
+            .create();
+
+        let github_client = GitHubClient::new_with_base_url(Client::new(), "fake_token".to_string(), server.url());
+        let rt = Runtime::new().unwrap();
+        let url = rt.block_on(async {
+            github_client
+                .create_discussion("announcements", "v1.3.1", "## PDE\n* [PDE-1](https://onezelis.atlassian.net/browse/PDE-1) Fix by @a")
+                .await
+                .unwrap()
+        });
+
+        assert_eq!(url, "https://github.com/o/r/discussions/7");
+        mock_categories.assert();
+        mock_create.assert();
+    }
+
+    #[test]
+    fn given_create_discussion_response_without_url_when_creating_discussion_then_errors() {
+        let mut server = mockito::Server::new();
+
+        server.mock("POST", "/graphql")
+            .match_body(Matcher::Regex("discussionCategories".to_string()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"data": {"repository": {"id": "R_1", "discussionCategories": {"nodes": [{"id": "DC_2", "name": "Announcements"}]}}}}"#)
+            .create();
+        server.mock("POST", "/graphql")
+            .match_body(Matcher::Regex("createDiscussion".to_string()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"data": {"createDiscussion": null}}"#)
+            .create();
+
+        let github_client = GitHubClient::new_with_base_url(Client::new(), "fake_token".to_string(), server.url());
+        let rt = Runtime::new().unwrap();
+        let result = rt.block_on(async { github_client.create_discussion("Announcements", "v1.3.1", "Notes").await });
+
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "GitHub created no discussion in Human-Glitch/llm-playground for v1.3.1."
+        );
+    }
+
+    #[test]
+    fn given_release_prefix_when_bumping_and_naming_branches_then_keeps_the_prefix() {
+        let github_client = GitHubClient::new(Client::new(), "test_token".to_string()).with_tag_prefix("release-");
+
+        assert_eq!(github_client.get_minor_version("release-1.2.3").unwrap(), "1.2");
+        assert_eq!(github_client.increment_patch_version("release-1.2.3").unwrap(), "release-1.2.4");
+        assert_eq!(github_client.increment_patch_version("release-3.4.5-beta").unwrap(), "release-3.4.6-beta");
+        assert_eq!(github_client.get_release_branch_name("release-1.2.3").unwrap(), "release/release-1.2.x");
+        assert!(github_client.get_release_branch_name("v1.2.3").is_err());
+    }
+
     #[test]
     fn given_prerelease_tag_and_existing_branch_when_determining_tag_version_then_increments_patch_version() {
         let mut server = mockito::Server::new();
@@ -444,6 +1515,11 @@ mod tests {
             .with_header("content-type", "application/json")
             .with_body(r#"{"name": "release/v1.0.x"}"#)
             .create();
+
+        // No published release to compare the new tag with
+        let mock_latest = server.mock("GET", "/repos/Human-Glitch/llm-playground/releases/latest")
+            .with_status(404)
+            .create();
         
         let client = Client::new();
         let github_client = GitHubClient::new_with_base_url(
@@ -465,20 +1541,115 @@ mod tests {
         // Verify the mocks were called
         mock_release.assert();
         mock_branch.assert();
+        mock_latest.assert();
     }
 
-    // Tests for branch management
     #[test]
-    fn given_tag_when_branch_exists_then_returns_minor_version_branch() {
+    fn given_counter_bump_when_determining_tag_version_then_bumps_the_padded_counter_or_errors_without_one() {
+        let mut server = mockito::Server::new();
+
+        for tag in ["v1.0.0-rc.01", "v1.0.0-beta"] {
+            server.mock("GET", format!("/repos/Human-Glitch/llm-playground/releases/tags/{}", tag).as_str())
+                .with_status(200)
+                .with_header("content-type", "application/json")
+                .with_body(r#"{"id": 12345, "body": "Release notes", "prerelease": true}"#)
+                .create();
+        }
+        server.mock("GET", "/repos/Human-Glitch/llm-playground/branches/release/v1.0.x")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"name": "release/v1.0.x"}"#)
+            .create();
+        server.mock("GET", "/repos/Human-Glitch/llm-playground/releases/latest")
+            .with_status(404)
+            .create();
+
+        let github_client = GitHubClient::new_with_base_url(Client::new(), "fake_token".to_string(), server.url())
+            .with_prerelease_bump(PrereleaseBump::Counter, 2);
+
+        let rt = Runtime::new().unwrap();
+        let (bumped, uncounted) = rt.block_on(async {
+            (
+                github_client.determine_tag_version("v1.0.0-rc.01").await.unwrap(),
+                github_client.determine_tag_version("v1.0.0-beta").await,
+            )
+        });
+
+        assert_eq!(bumped, "v1.0.0-rc.02");
+        assert_eq!(
+            uncounted.unwrap_err().to_string(),
+            "Tag v1.0.0-beta has no numeric prerelease counter, such as -rc.1, for --prerelease-bump counter."
+        );
+    }
+
+    fn ordering_client(server: &mut mockito::Server, latest_tag: &str, strict_ordering: bool) -> GitHubClient {
+        server.mock("GET", "/repos/Human-Glitch/llm-playground/releases/latest")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(json!({"id": 1, "tag_name": latest_tag, "prerelease": false}).to_string())
+            .create();
+        GitHubClient::new_with_base_url(Client::new(), "fake_token".to_string(), server.url())
+            .with_strict_ordering(strict_ordering)
+    }
+
+    #[test]
+    fn given_newer_tag_when_checking_ordering_then_passes_even_when_strict() {
+        let mut server = mockito::Server::new();
+        let github_client = ordering_client(&mut server, "v1.3.0", true);
+
+        let rt = Runtime::new().unwrap();
+        assert!(rt.block_on(github_client.check_tag_ordering("v1.3.1")).is_ok());
+        assert!(rt.block_on(github_client.check_tag_ordering("v1.10.0")).is_ok());
+    }
+
+    #[test]
+    fn given_equal_tag_when_checking_ordering_then_warns_or_fails_when_strict() {
+        let mut server = mockito::Server::new();
+        let lenient = ordering_client(&mut server, "v1.3.0", false);
+        let strict = GitHubClient::new_with_base_url(Client::new(), "fake_token".to_string(), server.url())
+            .with_strict_ordering(true);
+
+        let rt = Runtime::new().unwrap();
+        assert!(rt.block_on(lenient.check_tag_ordering("v1.3.0")).is_ok());
+        assert_eq!(
+            rt.block_on(strict.check_tag_ordering("v1.3.0")).unwrap_err().to_string(),
+            "Tag v1.3.0 is not newer than the latest release v1.3.0. Request a higher version or drop --strict-ordering."
+        );
+    }
+
+    #[test]
+    fn given_older_tag_when_checking_ordering_then_warns_or_fails_when_strict() {
+        let mut server = mockito::Server::new();
+        let lenient = ordering_client(&mut server, "v1.3.0", false);
+        let strict = GitHubClient::new_with_base_url(Client::new(), "fake_token".to_string(), server.url())
+            .with_strict_ordering(true);
+
+        let rt = Runtime::new().unwrap();
+        assert!(rt.block_on(lenient.check_tag_ordering("v1.1.0")).is_ok());
+        assert!(rt
+            .block_on(strict.check_tag_ordering("v1.1.0"))
+            .unwrap_err()
+            .to_string()
+            .starts_with("Tag v1.1.0 is not newer than the latest release v1.3.0."));
+    }
+
+    #[test]
+    fn given_validation_error_when_creating_release_then_returns_readable_message() {
         let mut server = mockito::Server::new();
         
-        // Mock for checking existing branch
         let mock_branch = server.mock("GET", "/repos/Human-Glitch/llm-playground/branches/release/v1.0.x")
             .with_status(200)
             .with_header("content-type", "application/json")
             .with_body(r#"{"name": "release/v1.0.x"}"#)
             .create();
         
+        // GitHub's structured validation error
+        let mock = server.mock("POST", "/repos/Human-Glitch/llm-playground/releases")
+            .with_status(422)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"message": "Validation Failed", "errors": [{"resource": "Release", "code": "already_exists", "field": "tag_name"}]}"#)
+            .create();
+
         let client = Client::new();
         let github_client = GitHubClient::new_with_base_url(
             client, 
@@ -486,15 +1657,110 @@ mod tests {
             server.url()
         );
         
-        // Test the method with our mock
         let rt = Runtime::new().unwrap();
         let result = rt.block_on(async {
-            let branch = github_client.get_release_branch_for_tag("v1.0.0").await.unwrap();
-            branch
+            github_client.create_release("v1.0.0", None, None).await
         });
         
-        // Should return the minor version branch since it exists
-        assert_eq!(result, "release/v1.0.x");
+        let err = result.err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            r#"Failed to create release: GitHub 422: Validation Failed (field "tag_name": already_exists)"#
+        );
+        assert!(err.downcast_ref::<GitHubError>().is_some());
+        
+        mock_branch.assert();
+        mock.assert();
+    }
+
+    #[test]
+    fn given_no_release_when_getting_or_creating_release_then_creates_it() {
+        let mut server = mockito::Server::new();
+
+        let mock_branch = server.mock("GET", "/repos/Human-Glitch/llm-playground/branches/release/v1.0.x")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"name": "release/v1.0.x"}"#)
+            .create();
+        let mock_create = server.mock("POST", "/repos/Human-Glitch/llm-playground/releases")
+            .with_status(201)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"id": 42, "tag_name": "v1.0.0", "prerelease": true}"#)
+            .create();
+        let mock_get = server.mock("GET", "/repos/Human-Glitch/llm-playground/releases/tags/v1.0.0")
+            .expect(0)
+            .create();
+
+        let github_client = GitHubClient::new_with_base_url(Client::new(), "fake_token".to_string(), server.url());
+
+        let rt = Runtime::new().unwrap();
+        let release = rt.block_on(async { github_client.get_or_create_release("v1.0.0", None, None).await.unwrap() });
+
+        assert_eq!(release.id, 42);
+        mock_branch.assert();
+        mock_create.assert();
+        mock_get.assert();
+    }
+
+    #[test]
+    fn given_release_created_concurrently_when_getting_or_creating_release_then_returns_existing() {
+        let mut server = mockito::Server::new();
+
+        let mock_branch = server.mock("GET", "/repos/Human-Glitch/llm-playground/branches/release/v1.0.x")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"name": "release/v1.0.x"}"#)
+            .create();
+        let mock_create = server.mock("POST", "/repos/Human-Glitch/llm-playground/releases")
+            .with_status(422)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"message": "Validation Failed", "errors": [{"resource": "Release", "code": "already_exists", "field": "tag_name"}]}"#)
+            .create();
+        let mock_get = server.mock("GET", "/repos/Human-Glitch/llm-playground/releases/tags/v1.0.0")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"id": 7, "tag_name": "v1.0.0", "prerelease": true}"#)
+            .create();
+
+        let github_client = GitHubClient::new_with_base_url(Client::new(), "fake_token".to_string(), server.url());
+
+        let rt = Runtime::new().unwrap();
+        let release = rt.block_on(async { github_client.get_or_create_release("v1.0.0", None, None).await.unwrap() });
+
+        assert_eq!(release.id, 7);
+        mock_branch.assert();
+        mock_create.assert();
+        mock_get.assert();
+    }
+
+    // Tests for branch management
+    #[test]
+    fn given_tag_when_branch_exists_then_returns_minor_version_branch() {
+        let mut server = mockito::Server::new();
+        
+        // Mock for checking existing branch
+        let mock_branch = server.mock("GET", "/repos/Human-Glitch/llm-playground/branches/release/v1.0.x")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"name": "release/v1.0.x"}"#)
+            .create();
+        
+        let client = Client::new();
+        let github_client = GitHubClient::new_with_base_url(
+            client, 
+            "fake_token".to_string(),
+            server.url()
+        );
+        
+        // Test the method with our mock
+        let rt = Runtime::new().unwrap();
+        let result = rt.block_on(async {
+            let branch = github_client.get_release_branch_for_tag("v1.0.0").await.unwrap();
+            branch
+        });
+        
+        // Should return the minor version branch since it exists
+        assert_eq!(result, "release/v1.0.x");
         
         // Verify the mock was called
         mock_branch.assert();
@@ -508,9 +1774,707 @@ mod tests {
         let mock_branch = server.mock("GET", "/repos/Human-Glitch/llm-playground/branches/release/v1.0.x")
             .with_status(404)
             .with_header("content-type", "application/json")
-            .with_body(r#"{"message": "Not Found"}"#)
+            .with_body(r#"{"message": "Not Found"}"#)
+            .create();
+        
+        let client = Client::new();
+        let github_client = GitHubClient::new_with_base_url(
+            client, 
+            "fake_token".to_string(),
+            server.url()
+        );
+        
+        // Test the method with our mock
+        let rt = Runtime::new().unwrap();
+        let result = rt.block_on(async {
+            let branch = github_client.get_release_branch_for_tag("v1.0.0").await.unwrap();
+            branch
+        });
+        
+        // Should return the fallback branch name since the minor version branch doesn't exist
+        assert_eq!(result, "release/v1.0.0");
+        
+        // Verify the mock was called
+        mock_branch.assert();
+    }
+
+    #[test]
+    fn given_repository_when_getting_default_branch_then_returns_branch_name() {
+        let mut server = mockito::Server::new();
+        
+        // Mock for the repository metadata
+        let mock = server.mock("GET", "/repos/Human-Glitch/llm-playground")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"name": "llm-playground", "default_branch": "main"}"#)
+            .create();
+        
+        let client = Client::new();
+        let github_client = GitHubClient::new_with_base_url(
+            client, 
+            "fake_token".to_string(),
+            server.url()
+        );
+        
+        // Test the method with our mock
+        let rt = Runtime::new().unwrap();
+        let result = rt.block_on(async {
+            github_client.default_branch().await.unwrap()
+        });
+        
+        assert_eq!(result, "main");
+        
+        // Verify the mock was called
+        mock.assert();
+    }
+
+    #[test]
+    fn given_fine_grained_token_with_push_access_when_checking_repo_access_then_passes() {
+        let mut server = mockito::Server::new();
+        let mock = server.mock("GET", "/repos/Human-Glitch/llm-playground")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"default_branch": "main", "permissions": {"admin": false, "push": true, "pull": true}}"#)
+            .create();
+
+        let github_client = GitHubClient::new_with_base_url(Client::new(), "fake_token".to_string(), server.url());
+
+        let rt = Runtime::new().unwrap();
+        let result = rt.block_on(async { github_client.check_repo_access().await });
+
+        assert!(result.is_ok());
+        mock.assert();
+    }
+
+    #[test]
+    fn given_token_without_access_when_checking_repo_access_then_names_the_repository() {
+        let mut server = mockito::Server::new();
+        let mock_hidden = server.mock("GET", "/repos/Human-Glitch/llm-playground")
+            .with_status(404)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"message": "Not Found"}"#)
+            .create();
+        let mock_read_only = server.mock("GET", "/repos/Human-Glitch/other")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"default_branch": "main", "permissions": {"push": false, "pull": true}}"#)
+            .create();
+
+        let github_client = GitHubClient::new_with_base_url(Client::new(), "fake_token".to_string(), server.url());
+        let read_only = GitHubClient::new_with_base_url(Client::new(), "fake_token".to_string(), server.url())
+            .with_repository("Human-Glitch", "other");
+
+        let rt = Runtime::new().unwrap();
+        let (hidden, read_only) = rt.block_on(async {
+            (github_client.check_repo_access().await, read_only.check_repo_access().await)
+        });
+
+        assert_eq!(hidden.unwrap_err().to_string(), "token lacks access to Human-Glitch/llm-playground");
+        assert_eq!(read_only.unwrap_err().to_string(), "token lacks write access to Human-Glitch/other");
+        mock_hidden.assert();
+        mock_read_only.assert();
+    }
+
+    #[test]
+    fn given_default_branch_fallback_when_branch_does_not_exist_then_returns_default_branch() {
+        let mut server = mockito::Server::new();
+        
+        // Mock for checking non-existing branch
+        let mock_branch = server.mock("GET", "/repos/Human-Glitch/llm-playground/branches/release/v1.0.x")
+            .with_status(404)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"message": "Not Found"}"#)
+            .create();
+        
+        // Mock for the repository metadata
+        let mock_repo = server.mock("GET", "/repos/Human-Glitch/llm-playground")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"default_branch": "develop"}"#)
+            .create();
+        
+        let client = Client::new();
+        let github_client = GitHubClient::new_with_base_url(
+            client, 
+            "fake_token".to_string(),
+            server.url()
+        )
+        .with_branch_fallback(BranchFallback::DefaultBranch);
+        
+        // Test the method with our mock
+        let rt = Runtime::new().unwrap();
+        let result = rt.block_on(async {
+            github_client.get_release_branch_for_tag("v1.0.0").await.unwrap()
+        });
+        
+        // Should return the default branch instead of creating release/v1.0.0
+        assert_eq!(result, "develop");
+        
+        // Verify the mocks were called
+        mock_branch.assert();
+        mock_repo.assert();
+    }
+
+    // Tests for client creation and initialization
+    #[test]
+    fn given_valid_credentials_when_creating_client_then_succeeds() {
+        let client = Client::new();
+        let token = "test_token".to_string();
+        let github_client = GitHubClient::new(client, token);
+        
+        // Test passes if client is created successfully without panicking
+        assert_eq!(
+            github_client.api_url("test_endpoint"),
+            "https://api.github.com/repos/Human-Glitch/llm-playground/test_endpoint"
+        );
+    }
+    
+    #[test]
+    fn given_custom_repository_when_building_api_url_then_targets_that_repository() {
+        let github_client = GitHubClient::new(Client::new(), "test_token".to_string())
+            .with_repository("octo-org", "octo-repo");
+
+        assert_eq!(github_client.repository(), "octo-org/octo-repo");
+        assert_eq!(
+            github_client.api_url("releases"),
+            "https://api.github.com/repos/octo-org/octo-repo/releases"
+        );
+    }
+    
+    // Tests for release management
+    #[test]
+    fn given_valid_tag_when_getting_release_by_tag_then_returns_release() {
+        let mut server = mockito::Server::new();
+        
+        // Set up the mock response
+        let mock = server.mock("GET", "/repos/Human-Glitch/llm-playground/releases/tags/v1.0.0")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"id": 12345, "body": "Release notes"}"#)
+            .create();
+
+        // Create a client that will use our mock server instead of the real GitHub API
+        let client = Client::new();
+        let github_client = GitHubClient::new_with_base_url(
+            client, 
+            "fake_token".to_string(),
+            server.url()
+        );
+        
+        // Test the method with our mock
+        let rt = Runtime::new().unwrap();
+        let result = rt.block_on(async {
+            let release = github_client.get_release_by_tag("v1.0.0").await.unwrap();
+            release
+        });
+        
+        // Verify the result
+        assert!(result.is_some());
+        let release = result.unwrap();
+        assert_eq!(release.id, 12345);
+        assert_eq!(release.body.unwrap(), "Release notes");
+        
+        // Verify the mock was called
+        mock.assert();
+    }
+
+    #[test]
+    fn given_nonexistent_tag_when_getting_release_by_tag_then_returns_none() {
+        let mut server = mockito::Server::new();
+        
+        // Set up the mock response for a non-existent tag
+        let mock = server.mock("GET", "/repos/Human-Glitch/llm-playground/releases/tags/v9.9.9")
+            .with_status(404)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"message": "Not Found"}"#)
+            .create();
+
+        let client = Client::new();
+        let github_client = GitHubClient::new_with_base_url(
+            client, 
+            "fake_token".to_string(),
+            server.url()
+        );
+        
+        // Test the method with our mock
+        let rt = Runtime::new().unwrap();
+        let result = rt.block_on(async {
+            let release = github_client.get_release_by_tag("v9.9.9").await.unwrap();
+            release
+        });
+        
+        // Verify we got None for a non-existent tag
+        assert!(result.is_none());
+        
+        // Verify the mock was called
+        mock.assert();
+    }
+
+    #[test]
+    fn given_draft_only_in_the_list_when_finding_release_including_drafts_then_returns_the_draft() {
+        let mut server = mockito::Server::new();
+
+        let mock_tag = server.mock("GET", Matcher::Regex(r"^/repos/Human-Glitch/llm-playground/releases/tags/".to_string()))
+            .with_status(404)
+            .expect(2)
+            .create();
+        let mock_list = server.mock("GET", "/repos/Human-Glitch/llm-playground/releases")
+            .match_query(Matcher::UrlEncoded("page".into(), "1".into()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"[
+                {"id": 3, "tag_name": "v1.0.0", "draft": false},
+                {"id": 7, "tag_name": "v1.0.0", "draft": true},
+                {"id": 8, "tag_name": "v1.1.0", "draft": true}
+            ]"#)
+            .expect(2)
+            .create();
+
+        let github_client = GitHubClient::new_with_base_url(Client::new(), "fake_token".to_string(), server.url());
+
+        let rt = Runtime::new().unwrap();
+        let draft = rt.block_on(github_client.find_release_by_tag_including_drafts("v1.0.0")).unwrap();
+        let missing = rt.block_on(github_client.find_release_by_tag_including_drafts("v2.0.0")).unwrap();
+
+        assert_eq!(draft.map(|release| release.id), Some(7));
+        assert!(missing.is_none());
+        mock_tag.assert();
+        mock_list.assert();
+    }
+
+    #[test]
+    fn given_error_response_when_getting_release_by_tag_then_returns_error() {
+        let mut server = mockito::Server::new();
+        
+        // Set up the mock response for an error
+        let mock = server.mock("GET", "/repos/Human-Glitch/llm-playground/releases/tags/v1.0.0")
+            .with_status(500)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"message": "Internal Server Error"}"#)
+            .create();
+
+        let client = Client::new();
+        let github_client = GitHubClient::new_with_base_url(
+            client, 
+            "fake_token".to_string(),
+            server.url()
+        );
+        
+        // Test the method with our mock
+        let rt = Runtime::new().unwrap();
+        let result = rt.block_on(async {
+            github_client.get_release_by_tag("v1.0.0").await
+        });
+        
+        // Verify we got an error
+        assert!(result.is_err());
+        
+        // Verify the mock was called
+        mock.assert();
+    }
+
+    #[test]
+    fn given_published_release_when_getting_latest_release_then_returns_release() {
+        let mut server = mockito::Server::new();
+        
+        let mock = server.mock("GET", "/repos/Human-Glitch/llm-playground/releases/latest")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"id": 111, "tag_name": "v1.0.0", "body": "Notes", "prerelease": false}"#)
+            .create();
+
+        let client = Client::new();
+        let github_client = GitHubClient::new_with_base_url(
+            client, 
+            "fake_token".to_string(),
+            server.url()
+        );
+        
+        let rt = Runtime::new().unwrap();
+        let result = rt.block_on(async {
+            github_client.get_latest_release().await.unwrap()
+        });
+        
+        assert_eq!(result.unwrap().tag_name, "v1.0.0");
+        mock.assert();
+    }
+
+    #[test]
+    fn given_no_published_release_when_getting_latest_release_then_returns_none() {
+        let mut server = mockito::Server::new();
+        
+        let mock = server.mock("GET", "/repos/Human-Glitch/llm-playground/releases/latest")
+            .with_status(404)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"message": "Not Found"}"#)
+            .create();
+
+        let client = Client::new();
+        let github_client = GitHubClient::new_with_base_url(
+            client, 
+            "fake_token".to_string(),
+            server.url()
+        );
+        
+        let rt = Runtime::new().unwrap();
+        let result = rt.block_on(async {
+            github_client.get_latest_release().await.unwrap()
+        });
+        
+        assert!(result.is_none());
+        mock.assert();
+    }
+
+    #[test]
+    fn given_two_tags_when_building_compare_url_then_links_to_github_compare_page() {
+        let github_client = GitHubClient::new(Client::new(), "test_token".to_string());
+
+        assert_eq!(
+            github_client.compare_url("v1.0.0", "v1.1.0"),
+            "https://github.com/Human-Glitch/llm-playground/compare/v1.0.0...v1.1.0"
+        );
+    }
+
+    #[test]
+    fn given_classic_token_when_getting_authenticated_user_then_returns_login_and_scopes() {
+        let mut server = mockito::Server::new();
+        
+        let mock = server.mock("GET", "/user")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_header("x-oauth-scopes", "read:org, repo")
+            .with_body(r#"{"login": "octocat"}"#)
+            .create();
+
+        let client = Client::new();
+        let github_client = GitHubClient::new_with_base_url(
+            client, 
+            "fake_token".to_string(),
+            server.url()
+        );
+        
+        let rt = Runtime::new().unwrap();
+        let user = rt.block_on(async {
+            github_client.authenticated_user().await.unwrap()
+        });
+        
+        assert_eq!(user.login, "octocat");
+        assert_eq!(user.missing_scopes(&["repo"]), Some(vec![]));
+        assert_eq!(user.missing_scopes(&["repo", "workflow"]), Some(vec!["workflow"]));
+        mock.assert();
+    }
+
+    #[test]
+    fn given_fine_grained_token_when_checking_scopes_then_reports_unknown() {
+        let user = TokenUser { login: "octocat".to_string(), scopes: None };
+
+        assert_eq!(user.missing_scopes(&["repo"]), None);
+    }
+
+    #[test]
+    fn given_tag_when_building_commits_url_then_links_to_github_history() {
+        let github_client = GitHubClient::new(Client::new(), "test_token".to_string());
+
+        assert_eq!(
+            github_client.commits_url("v1.1.0"),
+            "https://github.com/Human-Glitch/llm-playground/commits/v1.1.0"
+        );
+    }
+
+    #[test]
+    fn given_failing_response_with_request_id_when_getting_release_then_error_includes_request_id() {
+        let mut server = mockito::Server::new();
+        
+        let mock = server.mock("GET", "/repos/Human-Glitch/llm-playground/releases/tags/v1.0.0")
+            .with_status(502)
+            .with_header("content-type", "application/json")
+            .with_header("x-github-request-id", "C0DE:1234:ABCD")
+            .with_body(r#"{"message": "Server Error"}"#)
+            .create();
+
+        let client = Client::new();
+        let github_client = GitHubClient::new_with_base_url(
+            client, 
+            "fake_token".to_string(),
+            server.url()
+        );
+        
+        let rt = Runtime::new().unwrap();
+        let result = rt.block_on(async {
+            github_client.get_release_by_tag("v1.0.0").await
+        });
+        
+        let err = result.err().unwrap();
+        let github_error = err.downcast_ref::<GitHubError>().unwrap();
+        assert_eq!(github_error.request_id.as_deref(), Some("C0DE:1234:ABCD"));
+        assert!(err.to_string().contains("[request id: C0DE:1234:ABCD]"));
+        
+        mock.assert();
+    }
+
+    #[test]
+    fn given_main_branch_when_getting_latest_commit_then_returns_sha() {
+        let mut server = mockito::Server::new();
+        
+        // Set up the mock response
+        let mock = server.mock("GET", "/repos/Human-Glitch/llm-playground/commits/main")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"sha": "abc123def456"}"#)
+            .create();
+
+        // Create a client that will use our mock server instead of the real GitHub API
+        let client = Client::new();
+        let github_client = GitHubClient::new_with_base_url(
+            client, 
+            "fake_token".to_string(),
+            server.url()
+        );
+        
+        // Test the method with our mock
+        let rt = Runtime::new().unwrap();
+        let result = rt.block_on(async {
+            let sha = github_client.get_latest_commit_sha("main").await.unwrap();
+            sha
+        });
+        
+        // Verify the result
+        assert_eq!(result, "abc123def456");
+        
+        // Verify the mock was called
+        mock.assert();
+    }
+
+    #[test]
+    fn given_error_response_when_getting_latest_commit_then_returns_error() {
+        let mut server = mockito::Server::new();
+        
+        // Set up the mock response for an error
+        let mock = server.mock("GET", "/repos/Human-Glitch/llm-playground/commits/error-branch")
+            .with_status(500)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"message": "Internal Server Error"}"#)
+            .create();
+
+        let client = Client::new();
+        let github_client = GitHubClient::new_with_base_url(
+            client, 
+            "fake_token".to_string(),
+            server.url()
+        );
+        
+        // Test the method with our mock
+        let rt = Runtime::new().unwrap();
+        let result = rt.block_on(async {
+            github_client.get_latest_commit_sha("error-branch").await
+        });
+        
+        // Verify we got an error
+        assert!(result.is_err());
+        
+        // Verify the mock was called
+        mock.assert();
+    }
+
+    #[test]
+    fn given_empty_branch_when_getting_latest_commit_then_returns_friendly_error() {
+        let mut server = mockito::Server::new();
+        
+        let mock = server.mock("GET", "/repos/Human-Glitch/llm-playground/commits/release/v1.3.x")
+            .with_status(409)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"message": "Git Repository is empty."}"#)
+            .create();
+
+        let client = Client::new();
+        let github_client = GitHubClient::new_with_base_url(
+            client, 
+            "fake_token".to_string(),
+            server.url()
+        );
+        
+        let rt = Runtime::new().unwrap();
+        let result = rt.block_on(async {
+            github_client.get_latest_commit_sha("release/v1.3.x").await
+        });
+        
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Branch 'release/v1.3.x' has no commits to tag. Pass --sha <commit> to tag a specific commit."
+        );
+        
+        // Verify the mock was called
+        mock.assert();
+    }
+
+    #[test]
+    fn given_empty_repository_404_when_getting_latest_commit_then_returns_friendly_error() {
+        let mut server = mockito::Server::new();
+        
+        let mock_empty = server.mock("GET", "/repos/Human-Glitch/llm-playground/commits/release/v1.3.x")
+            .with_status(404)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"message": "Git Repository is empty."}"#)
+            .create();
+        let mock_missing = server.mock("GET", "/repos/Human-Glitch/llm-playground/commits/release/v1.4.x")
+            .with_status(404)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"message": "Not Found"}"#)
+            .create();
+
+        let github_client = GitHubClient::new_with_base_url(Client::new(), "fake_token".to_string(), server.url());
+        
+        let rt = Runtime::new().unwrap();
+        let (empty, missing) = rt.block_on(async {
+            (
+                github_client.get_latest_commit_sha("release/v1.3.x").await,
+                github_client.get_latest_commit_sha("release/v1.4.x").await,
+            )
+        });
+        
+        assert_eq!(
+            empty.unwrap_err().to_string(),
+            "Branch 'release/v1.3.x' has no commits to tag. Pass --sha <commit> to tag a specific commit."
+        );
+        assert!(missing.unwrap_err().to_string().starts_with("Failed to get latest commit"));
+        mock_empty.assert();
+        mock_missing.assert();
+    }
+
+    #[test]
+    fn given_transient_server_error_when_getting_latest_commit_then_retries_and_returns_sha() {
+        let mut server = mockito::Server::new();
+
+        // First call fails with a transient error, the retry succeeds
+        let failing = server.mock("GET", "/repos/Human-Glitch/llm-playground/commits/main")
+            .with_status(503)
+            .expect(1)
+            .create();
+        let mock = server.mock("GET", "/repos/Human-Glitch/llm-playground/commits/main")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"sha": "abc123def456"}"#)
+            .create();
+
+        let client = Client::new();
+        let github_client = GitHubClient::new_with_base_url(
+            client,
+            "fake_token".to_string(),
+            server.url()
+        )
+        .with_retry_policy(RetryPolicy {
+            max_retries: 2,
+            base_delay: std::time::Duration::from_millis(1),
+            max_delay: std::time::Duration::from_millis(1),
+            jitter: false,
+            sleeper: std::sync::Arc::new(RecordingSleeper::default()),
+        });
+
+        let rt = Runtime::new().unwrap();
+        let result = rt.block_on(async {
+            github_client.get_latest_commit_sha("main").await.unwrap()
+        });
+
+        assert_eq!(result, "abc123def456");
+
+        // Verify both the failed attempt and the retry hit the server
+        failing.assert();
+        mock.assert();
+    }
+
+    #[test]
+    fn given_two_tags_when_comparing_then_returns_status_and_commits() {
+        let mut server = mockito::Server::new();
+        
+        let mock = server.mock("GET", "/repos/Human-Glitch/llm-playground/compare/v1.0.0...v1.1.0")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"status": "ahead", "commits": [{"sha": "abc123", "commit": {"message": "PDE-1 Fix bug\n\nDetails"}, "author": {"login": "alice"}}]}"#)
+            .create();
+
+        let client = Client::new();
+        let github_client = GitHubClient::new_with_base_url(
+            client, 
+            "fake_token".to_string(),
+            server.url()
+        );
+        
+        let rt = Runtime::new().unwrap();
+        let comparison = rt.block_on(async {
+            github_client.compare("v1.0.0", "v1.1.0").await.unwrap()
+        });
+        
+        assert_eq!(comparison.commits.len(), 1);
+        assert_eq!(comparison.commits[0].commit.message, "PDE-1 Fix bug\n\nDetails");
+        assert_eq!(comparison.commits[0].author.as_ref().unwrap().login, "alice");
+        mock.assert();
+    }
+
+    #[test]
+    fn given_paginated_history_when_listing_commits_then_returns_all_pages_oldest_first() {
+        let mut server = mockito::Server::new();
+
+        let first_page: Vec<String> = (0..100)
+            .map(|i| format!(r#"{{"sha": "sha{}", "commit": {{"message": "Commit {}"}}, "author": null}}"#, 101 - i, 101 - i))
+            .collect();
+        let page_one = server.mock("GET", "/repos/Human-Glitch/llm-playground/commits")
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("sha".into(), "v1.0.0".into()),
+                Matcher::UrlEncoded("page".into(), "1".into()),
+            ]))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(format!("[{}]", first_page.join(",")))
+            .create();
+        let page_two = server.mock("GET", "/repos/Human-Glitch/llm-playground/commits")
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("sha".into(), "v1.0.0".into()),
+                Matcher::UrlEncoded("page".into(), "2".into()),
+            ]))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"[{"sha": "sha1", "commit": {"message": "Initial commit"}, "author": null}]"#)
+            .create();
+
+        let client = Client::new();
+        let github_client = GitHubClient::new_with_base_url(
+            client, 
+            "fake_token".to_string(),
+            server.url()
+        );
+        
+        let rt = Runtime::new().unwrap();
+        let commits = rt.block_on(async {
+            github_client.list_commits("v1.0.0", None).await.unwrap()
+        });
+        
+        assert_eq!(commits.len(), 101);
+        assert_eq!(commits[0].commit.message, "Initial commit");
+        assert_eq!(commits[100].commit.message, "Commit 101");
+        page_one.assert();
+        page_two.assert();
+    }
+
+    #[test]
+    fn given_paginated_branches_when_listing_branches_then_returns_all_pages() {
+        let mut server = mockito::Server::new();
+
+        let first_page: Vec<String> = (0..100)
+            .map(|i| format!(r#"{{"name": "feature/{}", "commit": {{"sha": "sha{}"}}}}"#, i, i))
+            .collect();
+        let page_one = server.mock("GET", "/repos/Human-Glitch/llm-playground/branches")
+            .match_query(Matcher::UrlEncoded("page".into(), "1".into()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(format!("[{}]", first_page.join(",")))
+            .create();
+        let page_two = server.mock("GET", "/repos/Human-Glitch/llm-playground/branches")
+            .match_query(Matcher::UrlEncoded("page".into(), "2".into()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"[{"name": "release/v1.2.x", "commit": {"sha": "abc"}}]"#)
             .create();
-        
+
         let client = Client::new();
         let github_client = GitHubClient::new_with_base_url(
             client, 
@@ -518,47 +2482,51 @@ mod tests {
             server.url()
         );
         
-        // Test the method with our mock
         let rt = Runtime::new().unwrap();
-        let result = rt.block_on(async {
-            let branch = github_client.get_release_branch_for_tag("v1.0.0").await.unwrap();
-            branch
+        let branches = rt.block_on(async {
+            github_client.list_branches().await.unwrap()
         });
         
-        // Should return the fallback branch name since the minor version branch doesn't exist
-        assert_eq!(result, "release/v1.0.0");
-        
-        // Verify the mock was called
-        mock_branch.assert();
+        assert_eq!(branches.len(), 101);
+        assert_eq!(branches[0], "feature/0");
+        assert_eq!(branches[100], "release/v1.2.x");
+        page_one.assert();
+        page_two.assert();
     }
 
-    // Tests for client creation and initialization
     #[test]
-    fn given_valid_credentials_when_creating_client_then_succeeds() {
-        let client = Client::new();
-        let token = "test_token".to_string();
-        let github_client = GitHubClient::new(client, token);
-        
-        // Test passes if client is created successfully without panicking
-        assert_eq!(
-            github_client.api_url("test_endpoint"),
-            "https://api.github.com/repos/Human-Glitch/llm-playground/test_endpoint"
-        );
+    fn given_mistyped_branch_names_when_finding_closest_branch_then_suggests_nearest() {
+        let branches: Vec<String> = ["main", "release/v1.2.x", "hotfix-v1.4.x"]
+            .iter()
+            .map(|b| b.to_string())
+            .collect();
+
+        // One edit away from an existing branch
+        assert_eq!(closest_branch("release/v1.2.y", &branches), Some("release/v1.2.x"));
+        // A separator typo in the convention itself
+        assert_eq!(closest_branch("hotfix/v1.4.x", &branches), Some("hotfix-v1.4.x"));
+        // Nothing close enough
+        assert_eq!(closest_branch("hotfix/v2.0.x", &branches), None);
     }
-    
-    // Tests for release management
+
     #[test]
-    fn given_valid_tag_when_getting_release_by_tag_then_returns_release() {
+    fn given_valid_tag_info_when_creating_tag_object_then_returns_sha() {
         let mut server = mockito::Server::new();
         
         // Set up the mock response
-        let mock = server.mock("GET", "/repos/Human-Glitch/llm-playground/releases/tags/v1.0.0")
-            .with_status(200)
+        let mock = server.mock("POST", "/repos/Human-Glitch/llm-playground/git/tags")
+            .with_status(201)
             .with_header("content-type", "application/json")
-            .with_body(r#"{"id": 12345, "body": "Release notes"}"#)
+            .with_body(r#"{"sha": "tag_object_sha_123"}"#)
+            .match_body(Matcher::Json(json!({
+                "tag": "v1.0.0",
+                "message": "Version 1.0.0",
+                "object": "commit_sha_456",
+                "type": "commit"
+            })))
             .create();
 
-        // Create a client that will use our mock server instead of the real GitHub API
+        // Create a client that will use our mock server
         let client = Client::new();
         let github_client = GitHubClient::new_with_base_url(
             client, 
@@ -569,29 +2537,103 @@ mod tests {
         // Test the method with our mock
         let rt = Runtime::new().unwrap();
         let result = rt.block_on(async {
-            let release = github_client.get_release_by_tag("v1.0.0").await.unwrap();
-            release
+            let sha = github_client.create_tag_object("v1.0.0", "Version 1.0.0", "commit_sha_456", None).await.unwrap();
+            sha
         });
         
         // Verify the result
-        assert!(result.is_some());
-        let release = result.unwrap();
-        assert_eq!(release.id, 12345);
-        assert_eq!(release.body.unwrap(), "Release notes");
+        assert_eq!(result, "tag_object_sha_123");
         
         // Verify the mock was called
         mock.assert();
     }
 
     #[test]
-    fn given_nonexistent_tag_when_getting_release_by_tag_then_returns_none() {
+    fn given_commit_date_when_creating_tag_object_then_tagger_keeps_the_users_identity_with_the_commit_date() {
+        let mut server = mockito::Server::new();
+        let mock_commit = server.mock("GET", "/repos/Human-Glitch/llm-playground/git/commits/commit_sha_456")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{
+                "sha": "commit_sha_456",
+                "author": {"name": "Alice", "email": "alice@example.com", "date": "2024-03-01T09:00:00Z"},
+                "committer": {"name": "GitHub", "email": "noreply@github.com", "date": "2024-03-05T14:30:00Z"}
+            }"#)
+            .create();
+        let mock_user = server.mock("GET", "/user")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"login": "release-bot", "id": 4242, "name": null, "email": null}"#)
+            .create();
+        let mock_tag = server.mock("POST", "/repos/Human-Glitch/llm-playground/git/tags")
+            .match_body(Matcher::Json(json!({
+                "tag": "v1.0.0",
+                "message": "Release v1.0.0",
+                "object": "commit_sha_456",
+                "type": "commit",
+                "tagger": {
+                    "name": "release-bot",
+                    "email": "4242+release-bot@users.noreply.github.com",
+                    "date": "2024-03-05T14:30:00Z"
+                }
+            })))
+            .with_status(201)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"sha": "tag_object_sha_123"}"#)
+            .create();
+
+        let github_client = GitHubClient::new_with_base_url(Client::new(), "fake_token".to_string(), server.url());
+
+        let rt = Runtime::new().unwrap();
+        let sha = rt.block_on(async {
+            let date = github_client.commit_date("commit_sha_456").await.unwrap();
+            let tagger = github_client.tagger_at(&date).await.unwrap();
+            github_client.create_tag_object("v1.0.0", "Release v1.0.0", "commit_sha_456", Some(&tagger)).await.unwrap()
+        });
+
+        assert_eq!(sha, "tag_object_sha_123");
+        mock_commit.assert();
+        mock_user.assert();
+        mock_tag.assert();
+    }
+
+    #[test]
+    fn given_release_upload_url_when_uploading_asset_then_posts_bytes_with_name_and_content_type() {
+        let mut server = mockito::Server::new();
+        let mock = server.mock("POST", "/uploads/repos/Human-Glitch/llm-playground/releases/9/assets")
+            .match_query(Matcher::UrlEncoded("name".into(), "app.zip".into()))
+            .match_header("content-type", "application/zip")
+            .match_body("PK-bytes")
+            .with_status(201)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"id": 1, "name": "app.zip"}"#)
+            .create();
+
+        let github_client = GitHubClient::new_with_base_url(Client::new(), "fake_token".to_string(), server.url());
+        let release: GitHubRelease = serde_json::from_value(json!({
+            "id": 9,
+            "upload_url": format!("{}/uploads/repos/Human-Glitch/llm-playground/releases/9/assets{{?name,label}}", server.url())
+        }))
+        .unwrap();
+
+        let rt = Runtime::new().unwrap();
+        let result = rt.block_on(async {
+            github_client.upload_asset(&release, "app.zip", "application/zip", b"PK-bytes".to_vec()).await
+        });
+
+        assert!(result.is_ok());
+        mock.assert();
+    }
+
+    #[test]
+    fn given_error_response_when_creating_tag_object_then_returns_error() {
         let mut server = mockito::Server::new();
         
-        // Set up the mock response for a non-existent tag
-        let mock = server.mock("GET", "/repos/Human-Glitch/llm-playground/releases/tags/v9.9.9")
-            .with_status(404)
+        // Set up the mock response for an error
+        let mock = server.mock("POST", "/repos/Human-Glitch/llm-playground/git/tags")
+            .with_status(422)
             .with_header("content-type", "application/json")
-            .with_body(r#"{"message": "Not Found"}"#)
+            .with_body(r#"{"message": "Validation Failed"}"#)
             .create();
 
         let client = Client::new();
@@ -604,28 +2646,32 @@ mod tests {
         // Test the method with our mock
         let rt = Runtime::new().unwrap();
         let result = rt.block_on(async {
-            let release = github_client.get_release_by_tag("v9.9.9").await.unwrap();
-            release
+            github_client.create_tag_object("invalid-tag", "Invalid Tag", "invalid-sha", None).await
         });
         
-        // Verify we got None for a non-existent tag
-        assert!(result.is_none());
+        // Verify we got an error
+        assert!(result.is_err());
         
         // Verify the mock was called
         mock.assert();
     }
 
     #[test]
-    fn given_error_response_when_getting_release_by_tag_then_returns_error() {
+    fn given_valid_tag_when_creating_tag_ref_then_succeeds() {
         let mut server = mockito::Server::new();
         
-        // Set up the mock response for an error
-        let mock = server.mock("GET", "/repos/Human-Glitch/llm-playground/releases/tags/v1.0.0")
-            .with_status(500)
+        // Set up the mock response
+        let mock = server.mock("POST", "/repos/Human-Glitch/llm-playground/git/refs")
+            .with_status(201)
             .with_header("content-type", "application/json")
-            .with_body(r#"{"message": "Internal Server Error"}"#)
+            .with_body(r#"{}"#)
+            .match_body(Matcher::Json(json!({
+                "ref": "refs/tags/v1.0.0",
+                "sha": "tag_sha_123"
+            })))
             .create();
 
+        // Create a client that will use our mock server
         let client = Client::new();
         let github_client = GitHubClient::new_with_base_url(
             client, 
@@ -636,28 +2682,27 @@ mod tests {
         // Test the method with our mock
         let rt = Runtime::new().unwrap();
         let result = rt.block_on(async {
-            github_client.get_release_by_tag("v1.0.0").await
+            github_client.create_tag_ref("v1.0.0", "tag_sha_123").await
         });
         
-        // Verify we got an error
-        assert!(result.is_err());
+        // Verify the result
+        assert!(result.is_ok());
         
         // Verify the mock was called
         mock.assert();
     }
 
     #[test]
-    fn given_main_branch_when_getting_latest_commit_then_returns_sha() {
+    fn given_error_response_when_creating_tag_ref_then_returns_error() {
         let mut server = mockito::Server::new();
         
-        // Set up the mock response
-        let mock = server.mock("GET", "/repos/Human-Glitch/llm-playground/commits/main")
-            .with_status(200)
+        // Set up the mock response for an error
+        let mock = server.mock("POST", "/repos/Human-Glitch/llm-playground/git/refs")
+            .with_status(422)
             .with_header("content-type", "application/json")
-            .with_body(r#"{"sha": "abc123def456"}"#)
+            .with_body(r#"{"message": "Validation Failed"}"#)
             .create();
 
-        // Create a client that will use our mock server instead of the real GitHub API
         let client = Client::new();
         let github_client = GitHubClient::new_with_base_url(
             client, 
@@ -668,26 +2713,81 @@ mod tests {
         // Test the method with our mock
         let rt = Runtime::new().unwrap();
         let result = rt.block_on(async {
-            let sha = github_client.get_latest_commit_sha("main").await.unwrap();
-            sha
+            github_client.create_tag_ref("invalid-tag", "invalid-sha").await
         });
         
-        // Verify the result
-        assert_eq!(result, "abc123def456");
+        // Verify we got an error
+        assert!(result.is_err());
         
         // Verify the mock was called
         mock.assert();
     }
 
     #[test]
-    fn given_error_response_when_getting_latest_commit_then_returns_error() {
+    fn given_custom_headers_when_requesting_then_sends_them_alongside_the_token() {
+        let mut server = mockito::Server::new();
+
+        let mock = server.mock("GET", "/repos/Human-Glitch/llm-playground/git/ref/tags/v1.0.0")
+            .match_header("x-gateway-token", "gw-secret")
+            .match_header("user-agent", "gateway-client")
+            .match_header("authorization", "Bearer fake_token")
+            .with_status(404)
+            .create();
+
+        let headers = vec![
+            parse_header("X-Gateway-Token: gw-secret").unwrap(),
+            parse_header("User-Agent:gateway-client").unwrap(),
+        ];
+        let github_client = GitHubClient::new_with_base_url(Client::new(), "fake_token".to_string(), server.url())
+            .with_headers(&headers);
+
+        let rt = Runtime::new().unwrap();
+        assert!(!rt.block_on(github_client.tag_exists("v1.0.0")).unwrap());
+        mock.assert();
+    }
+
+    #[test]
+    fn given_authorization_or_malformed_header_when_parsing_then_rejects_it() {
+        assert!(parse_header("authorization: Bearer other").is_err());
+        assert!(parse_header("X-Gateway-Token").is_err());
+        assert!(parse_header("Bad Name: value").is_err());
+    }
+
+    #[test]
+    fn given_lightweight_tag_when_reading_tag_message_then_returns_none() {
+        let mut server = mockito::Server::new();
+
+        let _ref = server.mock("GET", "/repos/Human-Glitch/llm-playground/git/ref/tags/v1.0.0")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"ref": "refs/tags/v1.0.0", "object": {"sha": "abc123", "type": "commit"}}"#)
+            .create();
+        let mock_tag = server.mock("GET", "/repos/Human-Glitch/llm-playground/git/tags/abc123")
+            .with_status(404)
+            .create();
+
+        let github_client = GitHubClient::new_with_base_url(Client::new(), "fake_token".to_string(), server.url());
+
+        let rt = Runtime::new().unwrap();
+        let message = rt.block_on(github_client.tag_message("v1.0.0")).unwrap();
+
+        assert_eq!(message, None);
+        mock_tag.assert();
+    }
+
+    #[test]
+    fn given_existing_ref_at_same_sha_when_creating_tag_ref_then_succeeds() {
         let mut server = mockito::Server::new();
         
-        // Set up the mock response for an error
-        let mock = server.mock("GET", "/repos/Human-Glitch/llm-playground/commits/error-branch")
-            .with_status(500)
+        let mock_create = server.mock("POST", "/repos/Human-Glitch/llm-playground/git/refs")
+            .with_status(422)
             .with_header("content-type", "application/json")
-            .with_body(r#"{"message": "Internal Server Error"}"#)
+            .with_body(r#"{"message": "Reference already exists"}"#)
+            .create();
+        let mock_ref = server.mock("GET", "/repos/Human-Glitch/llm-playground/git/ref/tags/v1.0.0")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"ref": "refs/tags/v1.0.0", "object": {"sha": "tag_sha_123", "type": "tag"}}"#)
             .create();
 
         let client = Client::new();
@@ -697,37 +2797,43 @@ mod tests {
             server.url()
         );
         
-        // Test the method with our mock
         let rt = Runtime::new().unwrap();
         let result = rt.block_on(async {
-            github_client.get_latest_commit_sha("error-branch").await
+            github_client.create_tag_ref("v1.0.0", "tag_sha_123").await
         });
         
-        // Verify we got an error
-        assert!(result.is_err());
+        assert!(result.is_ok());
         
-        // Verify the mock was called
-        mock.assert();
+        // Verify the mocks were called
+        mock_create.assert();
+        mock_ref.assert();
     }
 
     #[test]
-    fn given_valid_tag_info_when_creating_tag_object_then_returns_sha() {
+    fn given_existing_ref_at_different_sha_when_creating_tag_ref_then_returns_error() {
         let mut server = mockito::Server::new();
         
-        // Set up the mock response
-        let mock = server.mock("POST", "/repos/Human-Glitch/llm-playground/git/tags")
-            .with_status(201)
+        let mock_create = server.mock("POST", "/repos/Human-Glitch/llm-playground/git/refs")
+            .with_status(422)
             .with_header("content-type", "application/json")
-            .with_body(r#"{"sha": "tag_object_sha_123"}"#)
-            .match_body(Matcher::Json(json!({
-                "tag": "v1.0.0",
-                "message": "Version 1.0.0",
-                "object": "commit_sha_456",
-                "type": "commit"
-            })))
+            .with_body(r#"{"message": "Reference already exists"}"#)
+            .create();
+        let mock_ref = server.mock("GET", "/repos/Human-Glitch/llm-playground/git/ref/tags/v1.0.0")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"ref": "refs/tags/v1.0.0", "object": {"sha": "other_sha", "type": "tag"}}"#)
+            .create();
+        let _existing_tag = server.mock("GET", "/repos/Human-Glitch/llm-playground/git/tags/other_sha")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"sha": "other_sha", "object": {"sha": "old_commit", "type": "commit"}}"#)
+            .create();
+        let _new_tag = server.mock("GET", "/repos/Human-Glitch/llm-playground/git/tags/tag_sha_123")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"sha": "tag_sha_123", "object": {"sha": "new_commit", "type": "commit"}}"#)
             .create();
 
-        // Create a client that will use our mock server
         let client = Client::new();
         let github_client = GitHubClient::new_with_base_url(
             client, 
@@ -735,67 +2841,143 @@ mod tests {
             server.url()
         );
         
-        // Test the method with our mock
         let rt = Runtime::new().unwrap();
         let result = rt.block_on(async {
-            let sha = github_client.create_tag_object("v1.0.0", "Version 1.0.0", "commit_sha_456").await.unwrap();
-            sha
+            github_client.create_tag_ref("v1.0.0", "tag_sha_123").await
         });
         
-        // Verify the result
-        assert_eq!(result, "tag_object_sha_123");
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Tag reference for v1.0.0 already exists at other_sha, not tag_sha_123"
+        );
         
-        // Verify the mock was called
+        // Verify the mocks were called
+        mock_create.assert();
+        mock_ref.assert();
+    }
+
+    #[test]
+    fn given_pull_request_when_getting_labels_then_returns_their_names() {
+        let mut server = mockito::Server::new();
+
+        let mock = server.mock("GET", "/repos/Human-Glitch/llm-playground/issues/42")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"number": 42, "labels": [{"id": 1, "name": "area/api"}, {"id": 2, "name": "bug"}]}"#)
+            .create();
+
+        let github_client = GitHubClient::new_with_base_url(Client::new(), "fake_token".to_string(), server.url());
+
+        let rt = Runtime::new().unwrap();
+        let labels = rt.block_on(async { github_client.pull_request_labels(42).await.unwrap() });
+
+        assert_eq!(labels, vec!["area/api", "bug"]);
+        mock.assert();
+    }
+
+    #[test]
+    fn given_approval_and_comment_reviews_when_getting_approvers_then_lists_only_the_approver() {
+        let mut server = mockito::Server::new();
+
+        let mock_pull = server.mock("GET", "/repos/Human-Glitch/llm-playground/pulls/42")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"number": 42, "user": {"login": "alice"}}"#)
+            .create();
+        let mock_reviews = server.mock("GET", "/repos/Human-Glitch/llm-playground/pulls/42/reviews")
+            .match_query(Matcher::UrlEncoded("per_page".into(), "100".into()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"[
+                {"id": 1, "user": {"login": "bob"}, "state": "COMMENTED"},
+                {"id": 2, "user": {"login": "carol"}, "state": "APPROVED"},
+                {"id": 3, "user": {"login": "alice"}, "state": "APPROVED"},
+                {"id": 4, "user": {"login": "carol"}, "state": "APPROVED"}
+            ]"#)
+            .create();
+
+        let github_client = GitHubClient::new_with_base_url(Client::new(), "fake_token".to_string(), server.url());
+
+        let rt = Runtime::new().unwrap();
+        let approvers = rt.block_on(async { github_client.pull_request_approvers(42).await.unwrap() });
+
+        assert_eq!(approvers, vec!["carol"]);
+        mock_pull.assert();
+        mock_reviews.assert();
+    }
+
+    #[test]
+    fn given_new_sha_when_force_updating_tag_ref_then_patches_the_ref_with_force() {
+        let mut server = mockito::Server::new();
+
+        let mock = server.mock("PATCH", "/repos/Human-Glitch/llm-playground/git/refs/tags/v1.0.0")
+            .match_body(Matcher::Json(json!({"sha": "tag_sha_123", "force": true})))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"ref": "refs/tags/v1.0.0", "object": {"sha": "tag_sha_123", "type": "tag"}}"#)
+            .create();
+
+        let github_client = GitHubClient::new_with_base_url(Client::new(), "fake_token".to_string(), server.url());
+
+        let rt = Runtime::new().unwrap();
+        rt.block_on(async { github_client.force_update_tag_ref("v1.0.0", "tag_sha_123").await.unwrap() });
+
         mock.assert();
     }
 
     #[test]
-    fn given_error_response_when_creating_tag_object_then_returns_error() {
+    fn given_commit_sha_when_resolving_to_commit_then_returns_it_as_is() {
         let mut server = mockito::Server::new();
-        
-        // Set up the mock response for an error
-        let mock = server.mock("POST", "/repos/Human-Glitch/llm-playground/git/tags")
-            .with_status(422)
+
+        let mock_tag = server.mock("GET", "/repos/Human-Glitch/llm-playground/git/tags/commit_sha_456")
+            .with_status(404)
             .with_header("content-type", "application/json")
-            .with_body(r#"{"message": "Validation Failed"}"#)
+            .with_body(r#"{"message": "Not Found"}"#)
             .create();
 
-        let client = Client::new();
-        let github_client = GitHubClient::new_with_base_url(
-            client, 
-            "fake_token".to_string(),
-            server.url()
-        );
-        
-        // Test the method with our mock
+        let github_client = GitHubClient::new_with_base_url(Client::new(), "fake_token".to_string(), server.url());
+
         let rt = Runtime::new().unwrap();
-        let result = rt.block_on(async {
-            github_client.create_tag_object("invalid-tag", "Invalid Tag", "invalid-sha").await
-        });
-        
-        // Verify we got an error
-        assert!(result.is_err());
-        
-        // Verify the mock was called
-        mock.assert();
+        let sha = rt.block_on(async { github_client.resolve_to_commit("commit_sha_456").await.unwrap() });
+
+        assert_eq!(sha, "commit_sha_456");
+        mock_tag.assert();
     }
 
     #[test]
-    fn given_valid_tag_when_creating_tag_ref_then_succeeds() {
+    fn given_annotated_tag_sha_when_resolving_to_commit_then_dereferences_it() {
+        let mut server = mockito::Server::new();
+
+        let _outer = server.mock("GET", "/repos/Human-Glitch/llm-playground/git/tags/outer_tag_sha")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"sha": "outer_tag_sha", "object": {"sha": "tag_sha_123", "type": "tag"}}"#)
+            .create();
+        let _inner = server.mock("GET", "/repos/Human-Glitch/llm-playground/git/tags/tag_sha_123")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"sha": "tag_sha_123", "object": {"sha": "commit_sha_456", "type": "commit"}}"#)
+            .create();
+
+        let github_client = GitHubClient::new_with_base_url(Client::new(), "fake_token".to_string(), server.url());
+
+        let rt = Runtime::new().unwrap();
+        let sha = rt.block_on(async { github_client.resolve_to_commit("outer_tag_sha").await.unwrap() });
+
+        assert_eq!(sha, "commit_sha_456");
+    }
+
+    #[test]
+    fn given_release_id_when_clearing_prerelease_then_sends_flag() {
         let mut server = mockito::Server::new();
         
-        // Set up the mock response
-        let mock = server.mock("POST", "/repos/Human-Glitch/llm-playground/git/refs")
-            .with_status(201)
+        let mock = server.mock("PATCH", "/repos/Human-Glitch/llm-playground/releases/123")
+            .match_body(Matcher::Json(json!({"prerelease": false})))
+            .with_status(200)
             .with_header("content-type", "application/json")
-            .with_body(r#"{}"#)
-            .match_body(Matcher::Json(json!({
-                "ref": "refs/tags/v1.0.0",
-                "sha": "tag_sha_123"
-            })))
+            .with_body(r#"{"id": 123, "prerelease": false}"#)
             .create();
 
-        // Create a client that will use our mock server
         let client = Client::new();
         let github_client = GitHubClient::new_with_base_url(
             client, 
@@ -803,13 +2985,11 @@ mod tests {
             server.url()
         );
         
-        // Test the method with our mock
         let rt = Runtime::new().unwrap();
         let result = rt.block_on(async {
-            github_client.create_tag_ref("v1.0.0", "tag_sha_123").await
+            github_client.set_prerelease(123, false).await
         });
         
-        // Verify the result
         assert!(result.is_ok());
         
         // Verify the mock was called
@@ -817,33 +2997,22 @@ mod tests {
     }
 
     #[test]
-    fn given_error_response_when_creating_tag_ref_then_returns_error() {
+    fn given_release_id_when_converting_to_draft_then_sends_flag() {
         let mut server = mockito::Server::new();
-        
-        // Set up the mock response for an error
-        let mock = server.mock("POST", "/repos/Human-Glitch/llm-playground/git/refs")
-            .with_status(422)
+
+        let mock = server.mock("PATCH", "/repos/Human-Glitch/llm-playground/releases/123")
+            .match_body(Matcher::Json(json!({"draft": true})))
+            .with_status(200)
             .with_header("content-type", "application/json")
-            .with_body(r#"{"message": "Validation Failed"}"#)
+            .with_body(r#"{"id": 123, "draft": true}"#)
             .create();
 
-        let client = Client::new();
-        let github_client = GitHubClient::new_with_base_url(
-            client, 
-            "fake_token".to_string(),
-            server.url()
-        );
-        
-        // Test the method with our mock
+        let github_client = GitHubClient::new_with_base_url(Client::new(), "fake_token".to_string(), server.url());
+
         let rt = Runtime::new().unwrap();
-        let result = rt.block_on(async {
-            github_client.create_tag_ref("invalid-tag", "invalid-sha").await
-        });
-        
-        // Verify we got an error
-        assert!(result.is_err());
-        
-        // Verify the mock was called
+        let result = rt.block_on(async { github_client.set_draft(123, true).await });
+
+        assert!(result.is_ok());
         mock.assert();
     }
 
@@ -884,7 +3053,7 @@ mod tests {
         // Test the method with our mock
         let rt = Runtime::new().unwrap();
         let result = rt.block_on(async {
-            let release = github_client.create_release("v1.0.0").await.unwrap();
+            let release = github_client.create_release("v1.0.0", None, None).await.unwrap();
             release
         });
         
@@ -925,7 +3094,7 @@ mod tests {
         // Test the method with our mock
         let rt = Runtime::new().unwrap();
         let result = rt.block_on(async {
-            github_client.create_release("v1.0.0").await
+            github_client.create_release("v1.0.0", None, None).await
         });
         
         // Verify we got an error
@@ -1029,6 +3198,117 @@ mod tests {
         mock.assert();
     }
 
+    #[test]
+    fn given_default_client_when_sending_request_then_pins_accept_and_api_version() {
+        let mut server = mockito::Server::new();
+        
+        let mock = server.mock("GET", "/repos/Human-Glitch/llm-playground/releases/tags/v1.0.0")
+            .match_header("accept", "application/vnd.github+json")
+            .match_header("x-github-api-version", "2022-11-28")
+            .with_status(404)
+            .create();
+
+        let client = Client::new();
+        let github_client = GitHubClient::new_with_base_url(
+            client, 
+            "fake_token".to_string(),
+            server.url()
+        );
+        
+        let rt = Runtime::new().unwrap();
+        let result = rt.block_on(async {
+            github_client.get_release_by_tag("v1.0.0").await.unwrap()
+        });
+        
+        assert!(result.is_none());
+        mock.assert();
+    }
+
+    #[test]
+    fn given_configured_api_version_when_sending_request_then_sends_it() {
+        let mut server = mockito::Server::new();
+        
+        let mock = server.mock("GET", "/repos/Human-Glitch/llm-playground/releases/tags/v1.0.0")
+            .match_header("accept", "application/vnd.github+json")
+            .match_header("x-github-api-version", "2026-03-10")
+            .with_status(404)
+            .create();
+
+        let client = Client::new();
+        let github_client = GitHubClient::new_with_base_url(
+            client, 
+            "fake_token".to_string(),
+            server.url()
+        ).with_api_version("2026-03-10");
+        
+        let rt = Runtime::new().unwrap();
+        let result = rt.block_on(async {
+            github_client.get_release_by_tag("v1.0.0").await.unwrap()
+        });
+        
+        assert!(result.is_none());
+        mock.assert();
+    }
+
+    #[test]
+    fn given_tag_and_previous_tag_when_generating_notes_then_returns_body() {
+        let mut server = mockito::Server::new();
+        
+        let mock = server.mock("POST", "/repos/Human-Glitch/llm-playground/releases/generate-notes")
+            .match_body(Matcher::Json(json!({
+                "tag_name": "v1.1.0",
+                "previous_tag_name": "v1.0.0"
+            })))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r###"{"name": "v1.1.0", "body": "## What's Changed\n* PDE-1 Fix by @alice"}"###)
+            .create();
+
+        let client = Client::new();
+        let github_client = GitHubClient::new_with_base_url(
+            client, 
+            "fake_token".to_string(),
+            server.url()
+        );
+        
+        let rt = Runtime::new().unwrap();
+        let result = rt.block_on(async {
+            github_client.generate_notes("v1.1.0", Some("v1.0.0"), None).await.unwrap()
+        });
+        
+        assert_eq!(result, "## What's Changed\n* PDE-1 Fix by @alice");
+        mock.assert();
+    }
+
+    #[test]
+    fn given_no_previous_tag_when_generating_notes_then_omits_previous_tag_name() {
+        let mut server = mockito::Server::new();
+        
+        let mock = server.mock("POST", "/repos/Human-Glitch/llm-playground/releases/generate-notes")
+            .match_body(Matcher::Json(json!({
+                "tag_name": "v1.1.0"
+            })))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"name": "v1.1.0", "body": "Notes"}"#)
+            .create();
+
+        let client = Client::new();
+        let github_client = GitHubClient::new_with_base_url(
+            client, 
+            "fake_token".to_string(),
+            server.url()
+        );
+        
+        let rt = Runtime::new().unwrap();
+        let result = rt.block_on(async {
+            github_client.generate_notes("v1.1.0", None, None).await.unwrap()
+        });
+        
+        assert_eq!(result, "Notes");
+        mock.assert();
+    }
+
     #[test]
     fn given_release_id_and_notes_when_updating_release_then_succeeds() {
         let mut server = mockito::Server::new();
@@ -1054,7 +3334,7 @@ mod tests {
         // Test the method with our mock
         let rt = Runtime::new().unwrap();
         let result = rt.block_on(async {
-            github_client.update_release(12345, "Updated release notes").await
+            github_client.update_release(12345, "Updated release notes", None).await
         });
         
         // Verify the result
@@ -1064,6 +3344,135 @@ mod tests {
         mock.assert();
     }
 
+    #[test]
+    fn given_latest_kind_when_creating_release_then_publishes_it_stable_and_latest() {
+        let mut server = mockito::Server::new();
+        let _branch = server.mock("GET", "/repos/Human-Glitch/llm-playground/branches/release/v1.0.x")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"name": "release/v1.0.x"}"#)
+            .create();
+        let mock_create = server.mock("POST", "/repos/Human-Glitch/llm-playground/releases")
+            .match_body(Matcher::PartialJson(json!({"prerelease": false, "make_latest": "true"})))
+            .with_status(201)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"id": 54321}"#)
+            .create();
+
+        let github_client = GitHubClient::new_with_base_url(Client::new(), "fake_token".to_string(), server.url())
+            .with_release_kind(ReleaseKind::Latest);
+
+        let rt = Runtime::new().unwrap();
+        rt.block_on(async { github_client.create_release("v1.0.0", None, None).await.unwrap() });
+
+        mock_create.assert();
+    }
+
+    #[test]
+    fn given_latest_without_stable_when_resolving_release_kind_then_it_is_latest() {
+        assert_eq!(ReleaseKind::from_flags(false, true), ReleaseKind::Latest);
+        assert_eq!(ReleaseKind::from_flags(true, true), ReleaseKind::Latest);
+        assert_eq!(ReleaseKind::from_flags(true, false), ReleaseKind::Stable);
+        assert_eq!(ReleaseKind::from_flags(false, false), ReleaseKind::Prerelease);
+    }
+
+    #[test]
+    fn given_custom_name_when_creating_and_updating_release_then_sends_it() {
+        let mut server = mockito::Server::new();
+
+        let mock_branch = server.mock("GET", "/repos/Human-Glitch/llm-playground/branches/release/v1.0.x")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"name": "release/v1.0.x"}"#)
+            .create();
+        let mock_create = server.mock("POST", "/repos/Human-Glitch/llm-playground/releases")
+            .match_body(Matcher::PartialJson(json!({"tag_name": "v1.0.0", "name": "March 2024 Release"})))
+            .with_status(201)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"id": 54321}"#)
+            .create();
+        let mock_update = server.mock("PATCH", "/repos/Human-Glitch/llm-playground/releases/54321")
+            .match_body(Matcher::Json(json!({"body": "Notes", "name": "March 2024 Release"})))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{}"#)
+            .create();
+
+        let github_client = GitHubClient::new_with_base_url(Client::new(), "fake_token".to_string(), server.url());
+
+        let rt = Runtime::new().unwrap();
+        rt.block_on(async {
+            let release = github_client.create_release("v1.0.0", Some("March 2024 Release"), None).await.unwrap();
+            github_client.update_release(release.id, "Notes", Some("March 2024 Release")).await.unwrap();
+        });
+
+        mock_branch.assert();
+        mock_create.assert();
+        mock_update.assert();
+    }
+
+    #[test]
+    fn given_seed_body_when_creating_release_then_turns_off_generated_notes() {
+        let mut server = mockito::Server::new();
+
+        let _branch = server.mock("GET", "/repos/Human-Glitch/llm-playground/branches/release/v1.0.x")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"name": "release/v1.0.x"}"#)
+            .create();
+        let mock_create = server.mock("POST", "/repos/Human-Glitch/llm-playground/releases")
+            .match_body(Matcher::Json(json!({
+                "tag_name": "v1.0.0",
+                "target_commitish": "release/v1.0.x",
+                "name": "v1.0.0",
+                "draft": false,
+                "prerelease": true,
+                "generate_release_notes": false,
+                "body": "* PDE-1 Fix login by @a"
+            })))
+            .with_status(201)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"id": 54321, "body": "* PDE-1 Fix login by @a"}"#)
+            .create();
+
+        let github_client = GitHubClient::new_with_base_url(Client::new(), "fake_token".to_string(), server.url());
+
+        let rt = Runtime::new().unwrap();
+        let release = rt.block_on(async {
+            github_client.create_release("v1.0.0", None, Some("* PDE-1 Fix login by @a")).await.unwrap()
+        });
+
+        mock_create.assert();
+        assert_eq!(release.body.as_deref(), Some("* PDE-1 Fix login by @a"));
+    }
+
+    #[test]
+    fn given_server_error_then_success_when_updating_release_then_retries_without_retries_configured() {
+        let mut server = mockito::Server::new();
+
+        let failing = server.mock("PATCH", "/repos/Human-Glitch/llm-playground/releases/12345")
+            .with_status(500)
+            .expect(1)
+            .create();
+        let succeeding = server.mock("PATCH", "/repos/Human-Glitch/llm-playground/releases/12345")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"id": 12345}"#)
+            .create();
+
+        let sleeper = std::sync::Arc::new(RecordingSleeper::default());
+        let policy = RetryPolicy { max_retries: 0, sleeper: sleeper.clone(), ..Default::default() };
+        let github_client = GitHubClient::new_with_base_url(Client::new(), "fake_token".to_string(), server.url())
+            .with_retry_policy(policy);
+
+        let rt = Runtime::new().unwrap();
+        rt.block_on(async { github_client.update_release(12345, "Updated release notes", None).await.unwrap() });
+
+        failing.assert();
+        succeeding.assert();
+        assert_eq!(sleeper.slept.lock().unwrap().len(), 1);
+    }
+
     #[test]
     fn given_error_response_when_updating_release_then_returns_error() {
         let mut server = mockito::Server::new();
@@ -1085,7 +3494,7 @@ mod tests {
         // Test the method with our mock
         let rt = Runtime::new().unwrap();
         let result = rt.block_on(async {
-            github_client.update_release(12345, "Updated release notes").await
+            github_client.update_release(12345, "Updated release notes", None).await
         });
         
         // Verify we got an error
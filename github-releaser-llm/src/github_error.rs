@@ -0,0 +1,181 @@
+use reqwest::{Response, StatusCode};
+use serde::Deserialize;
+use std::error::Error;
+use std::fmt;
+
+/// One entry of the `errors` array in a GitHub error body.
+#[derive(Debug, Default, Deserialize)]
+pub struct GitHubErrorDetail {
+    pub field: Option<String>,
+    pub code: Option<String>,
+    pub message: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawErrorDetail {
+    Detail(GitHubErrorDetail),
+    Message(String),
+}
+
+#[derive(Deserialize)]
+struct ErrorBody {
+    message: String,
+    #[serde(default)]
+    errors: Vec<RawErrorDetail>,
+}
+
+/// A failed GitHub API call, with GitHub's error message pulled out of the response body.
+#[derive(Debug)]
+pub struct GitHubError {
+    /// What the client was trying to do, e.g. "Failed to create release".
+    pub context: String,
+    pub status: StatusCode,
+    /// GitHub's `message`, or the raw body when it isn't a GitHub error document.
+    pub message: String,
+    pub errors: Vec<GitHubErrorDetail>,
+    /// The `X-GitHub-Request-Id` header, which GitHub support asks for.
+    pub request_id: Option<String>,
+}
+
+impl GitHubError {
+    /// Build the error from a failing response body.
+    pub fn from_body(context: &str, status: StatusCode, body: &str) -> Self {
+        let (message, errors) = match serde_json::from_str::<ErrorBody>(body) {
+            Ok(parsed) => {
+                let errors = parsed
+                    .errors
+                    .into_iter()
+                    .map(|detail| match detail {
+                        RawErrorDetail::Detail(detail) => detail,
+                        RawErrorDetail::Message(message) => GitHubErrorDetail {
+                            message: Some(message),
+                            ..Default::default()
+                        },
+                    })
+                    .collect();
+                (parsed.message, errors)
+            }
+            Err(_) => (body.trim().to_string(), Vec::new()),
+        };
+
+        GitHubError {
+            context: context.to_string(),
+            status,
+            message,
+            errors,
+            request_id: None,
+        }
+    }
+
+    /// Consume a failing response and build the error from it.
+    pub async fn from_response(context: &str, resp: Response) -> Self {
+        let status = resp.status();
+        let request_id = resp
+            .headers()
+            .get("x-github-request-id")
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+        let body = resp.text().await.unwrap_or_default();
+
+        GitHubError {
+            request_id,
+            ..Self::from_body(context, status, &body)
+        }
+    }
+
+    /// GitHub's 422 for a resource (e.g. a release for the tag) that already exists.
+    pub fn is_already_exists(&self) -> bool {
+        self.status == StatusCode::UNPROCESSABLE_ENTITY
+            && self.errors.iter().any(|detail| detail.code.as_deref() == Some("already_exists"))
+    }
+}
+
+impl fmt::Display for GitHubErrorDetail {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.field, &self.code, &self.message) {
+            (Some(field), Some(code), _) => write!(f, "field \"{}\": {}", field, code),
+            (_, _, Some(message)) => write!(f, "{}", message),
+            (None, Some(code), None) => write!(f, "{}", code),
+            (Some(field), None, None) => write!(f, "field \"{}\"", field),
+            (None, None, None) => write!(f, "unknown error"),
+        }
+    }
+}
+
+impl fmt::Display for GitHubError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: GitHub {}: {}", self.context, self.status.as_u16(), self.message)?;
+
+        if !self.errors.is_empty() {
+            let details: Vec<String> = self.errors.iter().map(|e| e.to_string()).collect();
+            write!(f, " ({})", details.join(", "))?;
+        }
+
+        if let Some(request_id) = &self.request_id {
+            write!(f, " [request id: {}]", request_id)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Error for GitHubError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn given_validation_errors_when_formatting_then_lists_field_and_code() {
+        let body = r#"{"message": "Validation Failed", "errors": [{"resource": "Release", "code": "already_exists", "field": "tag_name"}], "documentation_url": "https://docs.github.com"}"#;
+
+        let error = GitHubError::from_body("Failed to create release", StatusCode::UNPROCESSABLE_ENTITY, body);
+
+        assert_eq!(
+            error.to_string(),
+            r#"Failed to create release: GitHub 422: Validation Failed (field "tag_name": already_exists)"#
+        );
+        assert_eq!(error.errors[0].code.as_deref(), Some("already_exists"));
+    }
+
+    #[test]
+    fn given_message_only_body_when_formatting_then_shows_message() {
+        let body = r#"{"message": "Not Found", "documentation_url": "https://docs.github.com"}"#;
+
+        let error = GitHubError::from_body("Failed to delete release", StatusCode::NOT_FOUND, body);
+
+        assert_eq!(error.to_string(), "Failed to delete release: GitHub 404: Not Found");
+    }
+
+    #[test]
+    fn given_string_error_entries_when_formatting_then_shows_them() {
+        let body = r#"{"message": "Validation Failed", "errors": ["Tag name is invalid"]}"#;
+
+        let error = GitHubError::from_body("Failed to create tag ref", StatusCode::UNPROCESSABLE_ENTITY, body);
+
+        assert_eq!(
+            error.to_string(),
+            "Failed to create tag ref: GitHub 422: Validation Failed (Tag name is invalid)"
+        );
+    }
+
+    #[test]
+    fn given_request_id_when_formatting_then_appends_it() {
+        let mut error = GitHubError::from_body("Failed to get release", StatusCode::INTERNAL_SERVER_ERROR, r#"{"message": "Server Error"}"#);
+        error.request_id = Some("C0DE:1234:ABCD".to_string());
+
+        assert_eq!(
+            error.to_string(),
+            "Failed to get release: GitHub 500: Server Error [request id: C0DE:1234:ABCD]"
+        );
+    }
+
+    #[test]
+    fn given_unparseable_body_when_formatting_then_falls_back_to_raw_text() {
+        let error = GitHubError::from_body("Failed to get release", StatusCode::BAD_GATEWAY, "<html>Bad gateway</html>\n");
+
+        assert_eq!(error.to_string(), "Failed to get release: GitHub 502: <html>Bad gateway</html>");
+        assert!(error.errors.is_empty());
+    }
+}
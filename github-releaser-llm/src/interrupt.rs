@@ -0,0 +1,144 @@
+use std::error::Error;
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use crate::output;
+
+/// What the process should do in response to a Ctrl-C.
+#[derive(Debug, PartialEq)]
+pub enum InterruptAction {
+    /// First interrupt: let the in-flight step finish, then stop.
+    FinishCurrentStep,
+    /// Second interrupt: abort immediately.
+    Abort,
+}
+
+/// Tracks Ctrl-C presses so a destructive step is never cut off silently.
+#[derive(Default)]
+pub struct InterruptState {
+    signals: AtomicUsize,
+}
+
+impl InterruptState {
+    /// Record a received interrupt and decide how to react to it.
+    pub fn record_signal(&self) -> InterruptAction {
+        let previous = self.signals.fetch_add(1, Ordering::SeqCst);
+
+        if previous == 0 {
+            InterruptAction::FinishCurrentStep
+        } else {
+            InterruptAction::Abort
+        }
+    }
+
+    /// Whether at least one interrupt has been received.
+    pub fn is_interrupted(&self) -> bool {
+        self.signals.load(Ordering::SeqCst) > 0
+    }
+
+    /// Called between steps: stops the run if an interrupt arrived while `completed_step` was running.
+    pub fn checkpoint(&self, completed_step: &str) -> Result<(), Box<dyn Error>> {
+        if self.is_interrupted() {
+            return Err(format!(
+                "Interrupted after {}. The release may be in a partial state; re-run with the same tag to finish.",
+                completed_step
+            )
+            .into());
+        }
+
+        Ok(())
+    }
+}
+
+/// Wait for interrupts produced by `next_signal` until the second one arrives.
+///
+/// The first interrupt only prints a warning, so the current step can finish
+/// and the next `checkpoint` stops the run. Returns when the run should abort.
+pub async fn watch<S, F>(state: Arc<InterruptState>, mut next_signal: S)
+where
+    S: FnMut() -> F,
+    F: Future<Output = ()>,
+{
+    loop {
+        next_signal().await;
+
+        match state.record_signal() {
+            InterruptAction::FinishCurrentStep => {
+                eprintln!("{}", output::warning("Interrupt received. Finishing the current step before stopping; the release may be left in a partial state."));
+                eprintln!("{}", output::warning("Press Ctrl-C again to abort immediately."));
+            }
+            InterruptAction::Abort => {
+                eprintln!("⛔ Second interrupt received. Aborting immediately.");
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::runtime::Runtime;
+    use tokio::sync::mpsc;
+
+    #[test]
+    fn given_no_signal_when_checking_state_then_not_interrupted() {
+        let state = InterruptState::default();
+
+        assert!(!state.is_interrupted());
+        assert!(state.checkpoint("Step 1").is_ok());
+    }
+
+    #[test]
+    fn given_first_signal_when_recording_then_finishes_current_step() {
+        let state = InterruptState::default();
+
+        assert_eq!(state.record_signal(), InterruptAction::FinishCurrentStep);
+        assert!(state.is_interrupted());
+    }
+
+    #[test]
+    fn given_second_signal_when_recording_then_aborts() {
+        let state = InterruptState::default();
+        state.record_signal();
+
+        assert_eq!(state.record_signal(), InterruptAction::Abort);
+    }
+
+    #[test]
+    fn given_interrupted_state_when_checkpointing_then_returns_partial_state_error() {
+        let state = InterruptState::default();
+        state.record_signal();
+
+        let err = state.checkpoint("Step 1").unwrap_err();
+
+        assert!(err.to_string().contains("Interrupted after Step 1"));
+        assert!(err.to_string().contains("partial state"));
+    }
+
+    #[test]
+    fn given_injected_signals_when_watching_then_returns_after_second_signal() {
+        let state = Arc::new(InterruptState::default());
+        let (tx, rx) = mpsc::unbounded_channel::<()>();
+        let rx = Arc::new(tokio::sync::Mutex::new(rx));
+
+        // Inject two signals up front; the watcher should consume both and return
+        tx.send(()).unwrap();
+        tx.send(()).unwrap();
+
+        let rt = Runtime::new().unwrap();
+        rt.block_on(async {
+            watch(state.clone(), || {
+                let rx = rx.clone();
+                async move {
+                    rx.lock().await.recv().await;
+                }
+            })
+            .await;
+        });
+
+        assert!(state.is_interrupted());
+        assert_eq!(state.signals.load(Ordering::SeqCst), 2);
+    }
+}
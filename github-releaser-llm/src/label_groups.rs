@@ -0,0 +1,182 @@
+use regex::Regex;
+use std::collections::{BTreeSet, HashMap};
+use std::error::Error;
+use std::sync::LazyLock;
+
+use crate::github_client::GitHubClient;
+
+/// Subsection for line items whose pull request has no matching label.
+pub const GENERAL_SECTION: &str = "General";
+
+/// Where a pull request with several matching labels is listed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, clap::ValueEnum)]
+pub enum LabelPlacement {
+    /// Under every matching area
+    #[default]
+    Each,
+    /// Only under its first matching area, in alphabetical order
+    First,
+}
+
+/// Groups line items into a subsection per label under a prefix such as `area/`.
+#[derive(Clone, Debug)]
+pub struct LabelGrouping {
+    pub prefix: String,
+    pub placement: LabelPlacement,
+}
+
+impl LabelGrouping {
+    /// The areas a pull request's labels put it in, e.g. `api` for `area/api`.
+    fn areas(&self, labels: &[String]) -> Vec<String> {
+        let areas: BTreeSet<String> = labels
+            .iter()
+            .filter_map(|label| label.strip_prefix(&self.prefix))
+            .filter(|area| !area.is_empty())
+            .map(str::to_string)
+            .collect();
+
+        match self.placement {
+            LabelPlacement::Each => areas.into_iter().collect(),
+            LabelPlacement::First => areas.into_iter().take(1).collect(),
+        }
+    }
+
+    /// Split every run of line items into `###` subsections per area, with unlabeled items under "General".
+    ///
+    /// `labels` maps pull request numbers to their labels; runs without any matching label are left alone.
+    pub fn group(&self, notes: &str, labels: &HashMap<u64, Vec<String>>) -> String {
+        let mut output: Vec<String> = Vec::new();
+        let mut run: Vec<&str> = Vec::new();
+
+        for line in notes.lines() {
+            if is_line_item(line) {
+                run.push(line);
+            } else {
+                output.extend(self.group_run(&run, labels));
+                run.clear();
+                output.push(line.to_string());
+            }
+        }
+        output.extend(self.group_run(&run, labels));
+
+        let mut grouped = output.join("\n");
+        if notes.ends_with('\n') {
+            grouped.push('\n');
+        }
+        grouped
+    }
+
+    fn group_run(&self, run: &[&str], labels: &HashMap<u64, Vec<String>>) -> Vec<String> {
+        let mut sections: Vec<(String, Vec<&str>)> = Vec::new();
+        let mut general: Vec<&str> = Vec::new();
+
+        for line in run {
+            let line_labels = pull_request_number(line).and_then(|number| labels.get(&number));
+            let areas = line_labels.map(|line_labels| self.areas(line_labels)).unwrap_or_default();
+            if areas.is_empty() {
+                general.push(line);
+            }
+            for area in areas {
+                match sections.iter_mut().find(|(name, _)| *name == area) {
+                    Some((_, lines)) => lines.push(line),
+                    None => sections.push((area, vec![line])),
+                }
+            }
+        }
+
+        if sections.is_empty() {
+            return run.iter().map(|line| line.to_string()).collect();
+        }
+        sections.sort_by(|(a, _), (b, _)| a.cmp(b));
+        if !general.is_empty() {
+            sections.push((GENERAL_SECTION.to_string(), general));
+        }
+
+        sections
+            .into_iter()
+            .flat_map(|(name, lines)| {
+                std::iter::once(format!("### {}", name)).chain(lines.into_iter().map(str::to_string))
+            })
+            .collect()
+    }
+}
+
+fn is_line_item(line: &str) -> bool {
+    let line = line.trim_start();
+    line.starts_with("* ") || line.starts_with("- ")
+}
+
+/// The number in a pull request URL.
+static PULL_REQUEST_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"/pull/(\d+)").unwrap());
+
+/// The pull request a line item links to, e.g. 42 for `... in https://github.com/o/r/pull/42`.
+pub fn pull_request_number(line: &str) -> Option<u64> {
+    PULL_REQUEST_RE.captures(line)?[1].parse().ok()
+}
+
+/// Look up the labels of every pull request the notes link to, then group the notes by them.
+pub async fn group_notes(gh_client: &GitHubClient, notes: &str, grouping: &LabelGrouping) -> Result<String, Box<dyn Error>> {
+    let numbers: BTreeSet<u64> = notes.lines().filter_map(pull_request_number).collect();
+
+    let mut labels = HashMap::new();
+    for number in numbers {
+        labels.insert(number, gh_client.pull_request_labels(number).await?);
+    }
+    Ok(grouping.group(notes, &labels))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn labels() -> HashMap<u64, Vec<String>> {
+        HashMap::from([
+            (1, vec!["area/api".to_string(), "bug".to_string()]),
+            (2, vec!["area/web".to_string(), "area/api".to_string()]),
+            (3, vec!["dependencies".to_string()]),
+            (4, vec!["area/web".to_string()]),
+        ])
+    }
+
+    const NOTES: &str = "## PDE\n\
+        * [PDE-1](https://t/PDE-1) Fix login by @a in https://github.com/o/r/pull/1\n\
+        * [PDE-2](https://t/PDE-2) Share session by @b in https://github.com/o/r/pull/2\n\
+        * [PDE-3](https://t/PDE-3) Bump serde by @bot in https://github.com/o/r/pull/3\n\
+        * [PDE-4](https://t/PDE-4) New footer by @a in https://github.com/o/r/pull/4\n";
+
+    #[test]
+    fn given_area_labels_when_grouping_then_lists_multi_label_prs_under_each_area() {
+        let grouping = LabelGrouping { prefix: "area/".to_string(), placement: LabelPlacement::Each };
+
+        assert_eq!(
+            grouping.group(NOTES, &labels()),
+            "## PDE\n\
+            ### api\n\
+            * [PDE-1](https://t/PDE-1) Fix login by @a in https://github.com/o/r/pull/1\n\
+            * [PDE-2](https://t/PDE-2) Share session by @b in https://github.com/o/r/pull/2\n\
+            ### web\n\
+            * [PDE-2](https://t/PDE-2) Share session by @b in https://github.com/o/r/pull/2\n\
+            * [PDE-4](https://t/PDE-4) New footer by @a in https://github.com/o/r/pull/4\n\
+            ### General\n\
+            * [PDE-3](https://t/PDE-3) Bump serde by @bot in https://github.com/o/r/pull/3\n"
+        );
+    }
+
+    #[test]
+    fn given_first_placement_when_grouping_then_lists_each_pr_once() {
+        let grouping = LabelGrouping { prefix: "area/".to_string(), placement: LabelPlacement::First };
+
+        let grouped = grouping.group(NOTES, &labels());
+
+        assert_eq!(grouped.matches("pull/2").count(), 1);
+        assert!(grouped.contains("### api\n* [PDE-1](https://t/PDE-1) Fix login by @a in https://github.com/o/r/pull/1\n* [PDE-2]"));
+        assert!(grouped.contains("### web\n* [PDE-4]"));
+    }
+
+    #[test]
+    fn given_no_matching_labels_when_grouping_then_leaves_the_notes_alone() {
+        let grouping = LabelGrouping { prefix: "team/".to_string(), placement: LabelPlacement::Each };
+
+        assert_eq!(grouping.group(NOTES, &labels()), NOTES);
+    }
+}
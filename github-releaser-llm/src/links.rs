@@ -0,0 +1,138 @@
+use regex::Regex;
+use reqwest::{Client, Method, StatusCode};
+use std::sync::LazyLock;
+use std::time::Duration;
+
+use crate::concurrency;
+
+/// Most links checked at once.
+pub const LINK_CONCURRENCY: usize = 8;
+
+/// How long one link gets to answer before it counts as unreachable.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// An http(s) URL, ending before whitespace or the markdown around it.
+static URL_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"https?://[^\s)\]>"]+"#).unwrap());
+
+/// Every distinct URL in the notes, markdown link targets and bare URLs alike, in order of appearance.
+pub fn extract_links(notes: &str) -> Vec<String> {
+    let mut links: Vec<String> = Vec::new();
+    for found in URL_RE.find_iter(notes) {
+        // A sentence can end right after a bare URL
+        let link = found.as_str().trim_end_matches(['.', ',', ';', ':']);
+        if !links.iter().any(|known| known == link) {
+            links.push(link.to_string());
+        }
+    }
+    links
+}
+
+/// What checking one link found.
+#[derive(Debug, PartialEq)]
+pub enum LinkStatus {
+    Ok,
+    /// The host answered, but not with a 2xx
+    Broken(StatusCode),
+    /// The host wants credentials, so the link can't be checked
+    Skipped(StatusCode),
+    Unreachable(String),
+}
+
+/// Check a link with HEAD, falling back to GET for hosts that don't answer HEAD properly.
+pub async fn check_link(client: &Client, url: &str) -> LinkStatus {
+    let mut status = match send(client, Method::HEAD, url).await {
+        Ok(status) => status,
+        Err(e) => return LinkStatus::Unreachable(e),
+    };
+    if status == StatusCode::METHOD_NOT_ALLOWED || status == StatusCode::NOT_IMPLEMENTED || status == StatusCode::FORBIDDEN {
+        status = match send(client, Method::GET, url).await {
+            Ok(status) => status,
+            Err(e) => return LinkStatus::Unreachable(e),
+        };
+    }
+
+    if status.is_success() || status.is_redirection() {
+        LinkStatus::Ok
+    } else if status == StatusCode::UNAUTHORIZED || status == StatusCode::FORBIDDEN {
+        LinkStatus::Skipped(status)
+    } else {
+        LinkStatus::Broken(status)
+    }
+}
+
+async fn send(client: &Client, method: Method, url: &str) -> Result<StatusCode, String> {
+    client
+        .request(method, url)
+        .timeout(TIMEOUT)
+        .send()
+        .await
+        .map(|resp| resp.status())
+        .map_err(|e| e.to_string())
+}
+
+/// Check every link in the notes, returning a warning for each one that isn't reachable.
+pub async fn validate(client: &Client, notes: &str) -> Vec<String> {
+    let links = extract_links(notes);
+    let checks = links.iter().map(|link| check_link(client, link)).collect();
+    let statuses = concurrency::run_bounded(checks, LINK_CONCURRENCY).await;
+
+    links
+        .iter()
+        .zip(statuses)
+        .filter_map(|(link, status)| match status {
+            LinkStatus::Ok => None,
+            LinkStatus::Broken(status) => Some(format!("{} returned {}", link, status)),
+            LinkStatus::Skipped(status) => Some(format!("{} needs credentials ({}); not checked", link, status)),
+            LinkStatus::Unreachable(e) => Some(format!("{} is unreachable: {}", link, e)),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::runtime::Runtime;
+
+    #[test]
+    fn given_notes_when_extracting_links_then_finds_markdown_and_bare_urls_once() {
+        let notes = "* [PDE-1](https://t.example.com/browse/PDE-1) Fix by @a in https://github.com/o/r/pull/2\n\
+            * [PDE-1](https://t.example.com/browse/PDE-1) Again, see https://github.com/o/r/pull/3.";
+
+        assert_eq!(
+            extract_links(notes),
+            vec![
+                "https://t.example.com/browse/PDE-1",
+                "https://github.com/o/r/pull/2",
+                "https://github.com/o/r/pull/3",
+            ]
+        );
+    }
+
+    #[test]
+    fn given_reachable_and_missing_links_when_validating_then_warns_about_the_missing_ones() {
+        let mut server = mockito::Server::new();
+
+        let _ok = server.mock("HEAD", "/pull/2").with_status(200).create();
+        let _missing = server.mock("HEAD", "/browse/PDE-404").with_status(404).create();
+        let _head_rejected = server.mock("HEAD", "/browse/PDE-1").with_status(405).create();
+        let mock_get = server.mock("GET", "/browse/PDE-1").with_status(200).create();
+        let _private = server.mock("HEAD", "/private").with_status(401).create();
+
+        let notes = format!(
+            "* [PDE-1]({url}/browse/PDE-1) Fix in {url}/pull/2\n* [PDE-404]({url}/browse/PDE-404) Typo\n* See {url}/private",
+            url = server.url()
+        );
+
+        let rt = Runtime::new().unwrap();
+        let warnings = rt.block_on(validate(&Client::new(), &notes));
+
+        assert_eq!(
+            warnings,
+            vec![
+                format!("{}/browse/PDE-404 returned 404 Not Found", server.url()),
+                format!("{}/private needs credentials (401 Unauthorized); not checked", server.url()),
+            ]
+        );
+        mock_get.assert();
+    }
+}
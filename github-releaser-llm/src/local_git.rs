@@ -0,0 +1,392 @@
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Executes git commands against a repository; abstracted so command assembly can be stubbed in tests.
+pub trait GitRunner {
+    fn run(&self, repo_path: &Path, args: &[String]) -> Result<String, Box<dyn Error>>;
+}
+
+/// Runs the real `git` binary.
+pub struct SystemGit;
+
+impl GitRunner for SystemGit {
+    fn run(&self, repo_path: &Path, args: &[String]) -> Result<String, Box<dyn Error>> {
+        let output = Command::new("git").arg("-C").arg(repo_path).args(args).output()?;
+
+        if output.status.success() {
+            Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+        } else {
+            Err(format!(
+                "git {} failed: {}",
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            )
+            .into())
+        }
+    }
+}
+
+/// The checked-out branch and uncommitted changes of a working tree.
+#[derive(Debug, PartialEq)]
+pub struct TreeStatus {
+    /// `None` when HEAD is detached
+    pub branch: Option<String>,
+    /// Staged or unstaged changes to tracked files, as `git status` prints them
+    pub changes: Vec<String>,
+}
+
+/// Parse `git status --porcelain --branch`; untracked files don't count as changes.
+pub fn parse_status(output: &str) -> TreeStatus {
+    let mut branch = None;
+    let mut changes = Vec::new();
+    for line in output.lines() {
+        if let Some(header) = line.strip_prefix("## ") {
+            branch = parse_branch_header(header);
+        } else if !line.trim().is_empty() && !line.starts_with("??") {
+            changes.push(line.trim().to_string());
+        }
+    }
+    TreeStatus { branch, changes }
+}
+
+/// The branch in a status header such as `main...origin/main [ahead 1]` or `No commits yet on main`.
+fn parse_branch_header(header: &str) -> Option<String> {
+    if header.starts_with("HEAD (no branch)") {
+        return None;
+    }
+    let header = header
+        .strip_prefix("No commits yet on ")
+        .or_else(|| header.strip_prefix("Initial commit on "))
+        .unwrap_or(header);
+    let branch = header.split("...").next()?.split(' ').next()?;
+    Some(branch.to_string())
+}
+
+/// The host of a URL like `https://github.example.com/api/v3`.
+pub fn url_host(url: &str) -> Option<&str> {
+    let (_, rest) = url.split_once("://")?;
+    let authority = rest.split('/').next()?;
+    let host = authority.rsplit_once('@').map_or(authority, |(_, host)| host);
+    let host = host.split(':').next()?;
+    (!host.is_empty()).then_some(host)
+}
+
+/// `owner/name` from a GitHub remote URL in SSH (`git@github.com:owner/name.git`), `ssh://` or HTTPS form.
+///
+/// The remote must point at github.com or one of `extra_hosts`, e.g. a GitHub Enterprise Server host.
+pub fn parse_remote_url(url: &str, extra_hosts: &[&str]) -> Result<String, String> {
+    let url = url.trim();
+    let (host, path) = if url.contains("://") {
+        let host = url_host(url).unwrap_or("");
+        let rest = url.split_once("://").map_or("", |(_, rest)| rest);
+        (host, rest.split_once('/').map_or("", |(_, path)| path))
+    } else {
+        // scp-like `[user@]host:path`
+        let (authority, path) = url.split_once(':').unwrap_or((url, ""));
+        (authority.rsplit_once('@').map_or(authority, |(_, host)| host), path)
+    };
+
+    if !host.eq_ignore_ascii_case("github.com") && !extra_hosts.iter().any(|extra| extra.eq_ignore_ascii_case(host)) {
+        return Err(format!("The origin remote {} isn't a GitHub URL; pass --owner and --repo instead.", url));
+    }
+    let path = path.trim_matches('/');
+    let path = path.strip_suffix(".git").unwrap_or(path);
+    match path.split_once('/') {
+        Some((owner, name)) if !owner.is_empty() && !name.is_empty() && !name.contains('/') => Ok(format!("{}/{}", owner, name)),
+        _ => Err(format!("Can't read owner/name from the origin remote {}.", url)),
+    }
+}
+
+/// Creates and pushes release tags from a local clone instead of through the API.
+pub struct LocalGit {
+    runner: Box<dyn GitRunner>,
+    repo_path: PathBuf,
+    remote: String,
+}
+
+impl LocalGit {
+    pub fn new(runner: Box<dyn GitRunner>, repo_path: PathBuf) -> Self {
+        LocalGit {
+            runner,
+            repo_path,
+            remote: "origin".to_string(),
+        }
+    }
+
+    fn run(&self, args: Vec<String>) -> Result<String, Box<dyn Error>> {
+        self.runner.run(&self.repo_path, &args)
+    }
+
+    /// Fetch the branch from the remote so the tag targets its latest commit.
+    fn fetch_args(&self, branch: &str) -> Vec<String> {
+        vec!["fetch".to_string(), self.remote.clone(), branch.to_string()]
+    }
+
+    /// Resolve the fetched branch head to a commit SHA.
+    fn rev_parse_args(&self, branch: &str) -> Vec<String> {
+        vec![
+            "rev-parse".to_string(),
+            format!("{}/{}^{{commit}}", self.remote, branch),
+        ]
+    }
+
+    /// Create or move the annotated tag in place.
+    fn tag_args(tag: &str, message: &str, sha: &str) -> Vec<String> {
+        vec![
+            "tag".to_string(),
+            "--force".to_string(),
+            "--annotate".to_string(),
+            tag.to_string(),
+            "--message".to_string(),
+            message.to_string(),
+            sha.to_string(),
+        ]
+    }
+
+    /// Push the tag, replacing any existing remote tag of the same name.
+    fn push_tag_args(&self, tag: &str) -> Vec<String> {
+        vec![
+            "push".to_string(),
+            "--force".to_string(),
+            self.remote.clone(),
+            format!("refs/tags/{}", tag),
+        ]
+    }
+
+    /// Print the message of an annotated tag, and nothing for a lightweight or missing one.
+    fn tag_message_args(tag: &str) -> Vec<String> {
+        vec![
+            "tag".to_string(),
+            "--list".to_string(),
+            "--format=%(if:equals=tag)%(objecttype)%(then)%(contents)%(end)".to_string(),
+            tag.to_string(),
+        ]
+    }
+
+    /// Show the checked-out branch and changed files in a stable format.
+    fn status_args() -> Vec<String> {
+        vec!["status".to_string(), "--porcelain".to_string(), "--branch".to_string()]
+    }
+
+    /// The URL of the remote tags are pushed to.
+    pub fn remote_url(&self) -> Result<String, Box<dyn Error>> {
+        self.run(vec!["config".to_string(), "--get".to_string(), format!("remote.{}.url", self.remote)])
+    }
+
+    /// Fail unless the clone has `branch` checked out and no uncommitted changes.
+    pub fn ensure_clean(&self, branch: &str) -> Result<(), Box<dyn Error>> {
+        let status = parse_status(&self.run(Self::status_args())?);
+        let location = self.repo_path.display();
+
+        match &status.branch {
+            None => {
+                return Err(format!(
+                    "{} has a detached HEAD. Check out {} or pass --allow-dirty.",
+                    location, branch
+                )
+                .into())
+            }
+            Some(current) if current != branch => {
+                return Err(format!(
+                    "{} is on branch {}, not {}. Check out {} or pass --allow-dirty.",
+                    location, current, branch, branch
+                )
+                .into())
+            }
+            Some(_) => {}
+        }
+        if !status.changes.is_empty() {
+            return Err(format!(
+                "{} has {} uncommitted changes ({}). Commit or stash them, or pass --allow-dirty.",
+                location,
+                status.changes.len(),
+                status.changes.join(", ")
+            )
+            .into());
+        }
+        Ok(())
+    }
+
+    /// Get the latest commit SHA of the branch as known by the remote.
+    pub fn get_latest_commit_sha(&self, branch: &str) -> Result<String, Box<dyn Error>> {
+        self.run(self.fetch_args(branch))?;
+        self.run(self.rev_parse_args(branch))
+    }
+
+    /// The message of the clone's annotated tag, if it has one.
+    pub fn tag_message(&self, tag: &str) -> Result<Option<String>, Box<dyn Error>> {
+        let message = self.run(Self::tag_message_args(tag))?;
+        Ok((!message.is_empty()).then_some(message))
+    }
+
+    /// Tag the commit and push the tag to the remote.
+    pub fn tag_and_push(&self, tag: &str, message: &str, sha: &str) -> Result<(), Box<dyn Error>> {
+        self.run(Self::tag_args(tag, message, sha))?;
+        self.run(self.push_tag_args(tag))?;
+        println!("Pushed tag {} from local clone {}", tag, self.repo_path.display());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    /// Records every command and answers `rev-parse` with a canned SHA.
+    struct RecordingGit {
+        calls: Rc<RefCell<Vec<Vec<String>>>>,
+    }
+
+    impl GitRunner for RecordingGit {
+        fn run(&self, repo_path: &Path, args: &[String]) -> Result<String, Box<dyn Error>> {
+            assert_eq!(repo_path, Path::new("/tmp/checkout"));
+            self.calls.borrow_mut().push(args.to_vec());
+
+            if args[0] == "rev-parse" {
+                Ok("abc123def456".to_string())
+            } else if args[..2] == ["tag", "--list"] {
+                Ok("Hotfix for the login timeout".to_string())
+            } else {
+                Ok(String::new())
+            }
+        }
+    }
+
+    /// Answers every command with the same `git status` output.
+    struct StatusGit(&'static str);
+
+    impl GitRunner for StatusGit {
+        fn run(&self, _repo_path: &Path, _args: &[String]) -> Result<String, Box<dyn Error>> {
+            Ok(self.0.to_string())
+        }
+    }
+
+    struct FailingGit;
+
+    impl GitRunner for FailingGit {
+        fn run(&self, _repo_path: &Path, _args: &[String]) -> Result<String, Box<dyn Error>> {
+            Err("git push failed: rejected".into())
+        }
+    }
+
+    fn recording_client() -> (LocalGit, Rc<RefCell<Vec<Vec<String>>>>) {
+        let calls = Rc::new(RefCell::new(Vec::new()));
+        let runner = RecordingGit { calls: calls.clone() };
+        (LocalGit::new(Box::new(runner), PathBuf::from("/tmp/checkout")), calls)
+    }
+
+    #[test]
+    fn given_branch_when_getting_latest_commit_then_fetches_and_resolves_remote_head() {
+        let (local_git, calls) = recording_client();
+
+        let sha = local_git.get_latest_commit_sha("release/v1.0.x").unwrap();
+
+        assert_eq!(sha, "abc123def456");
+        assert_eq!(
+            *calls.borrow(),
+            vec![
+                vec!["fetch", "origin", "release/v1.0.x"],
+                vec!["rev-parse", "origin/release/v1.0.x^{commit}"],
+            ]
+        );
+    }
+
+    #[test]
+    fn given_tag_when_tagging_and_pushing_then_force_updates_annotated_tag() {
+        let (local_git, calls) = recording_client();
+
+        local_git.tag_and_push("v1.0.0", "Release v1.0.0", "abc123def456").unwrap();
+
+        assert_eq!(
+            *calls.borrow(),
+            vec![
+                vec!["tag", "--force", "--annotate", "v1.0.0", "--message", "Release v1.0.0", "abc123def456"],
+                vec!["push", "--force", "origin", "refs/tags/v1.0.0"],
+            ]
+        );
+    }
+
+    #[test]
+    fn given_existing_tag_message_when_retagging_then_passes_it_to_the_new_tag() {
+        let (local_git, calls) = recording_client();
+
+        let message = local_git.tag_message("v1.0.0").unwrap().unwrap();
+        local_git.tag_and_push("v1.0.0", &message, "abc123def456").unwrap();
+
+        assert_eq!(
+            *calls.borrow(),
+            vec![
+                vec!["tag", "--list", "--format=%(if:equals=tag)%(objecttype)%(then)%(contents)%(end)", "v1.0.0"],
+                vec!["tag", "--force", "--annotate", "v1.0.0", "--message", "Hotfix for the login timeout", "abc123def456"],
+                vec!["push", "--force", "origin", "refs/tags/v1.0.0"],
+            ]
+        );
+    }
+
+    #[test]
+    fn given_github_remotes_when_parsing_then_reads_owner_and_name() {
+        for url in [
+            "git@github.com:Human-Glitch/llm-playground.git",
+            "git@github.com:Human-Glitch/llm-playground",
+            "ssh://git@github.com:22/Human-Glitch/llm-playground.git",
+            "https://github.com/Human-Glitch/llm-playground.git",
+            "https://github.com/Human-Glitch/llm-playground/",
+            "https://x-access-token@github.com/Human-Glitch/llm-playground",
+        ] {
+            assert_eq!(parse_remote_url(url, &[]).unwrap(), "Human-Glitch/llm-playground", "{}", url);
+        }
+        assert_eq!(
+            parse_remote_url("git@github.example.com:prod-org/api.git", &["github.example.com"]).unwrap(),
+            "prod-org/api"
+        );
+    }
+
+    #[test]
+    fn given_non_github_or_malformed_remote_when_parsing_then_returns_error() {
+        assert!(parse_remote_url("git@gitlab.com:o/r.git", &[]).unwrap_err().contains("isn't a GitHub URL"));
+        assert!(parse_remote_url("https://github.com/just-owner", &[]).unwrap_err().contains("Can't read owner/name"));
+        assert!(parse_remote_url("https://github.com/o/r/tree/main", &[]).is_err());
+        assert_eq!(url_host("https://github.example.com/api/v3"), Some("github.example.com"));
+    }
+
+    #[test]
+    fn given_porcelain_status_when_parsing_then_reads_branch_and_tracked_changes() {
+        let status = parse_status("## release/v1.0.x...origin/release/v1.0.x [ahead 1]\n M src/main.rs\nA  notes.md\n?? target/");
+
+        assert_eq!(status.branch.as_deref(), Some("release/v1.0.x"));
+        assert_eq!(status.changes, vec!["M src/main.rs", "A  notes.md"]);
+    }
+
+    #[test]
+    fn given_detached_or_new_branch_when_parsing_status_then_reads_the_header() {
+        assert_eq!(parse_status("## HEAD (no branch)").branch, None);
+        assert_eq!(parse_status("## No commits yet on main").branch.as_deref(), Some("main"));
+        assert_eq!(parse_status("## main").branch.as_deref(), Some("main"));
+        assert!(parse_status("## main").changes.is_empty());
+    }
+
+    #[test]
+    fn given_working_tree_when_ensuring_clean_then_requires_the_branch_and_no_changes() {
+        let checkout = |status: &'static str| LocalGit::new(Box::new(StatusGit(status)), PathBuf::from("/tmp/checkout"));
+
+        assert!(checkout("## release/v1.0.x...origin/release/v1.0.x\n?? target/").ensure_clean("release/v1.0.x").is_ok());
+
+        let err = checkout("## main...origin/main").ensure_clean("release/v1.0.x").unwrap_err().to_string();
+        assert!(err.contains("on branch main, not release/v1.0.x"));
+        let err = checkout("## release/v1.0.x\n M src/main.rs").ensure_clean("release/v1.0.x").unwrap_err().to_string();
+        assert!(err.contains("1 uncommitted changes (M src/main.rs)"));
+        assert!(err.contains("--allow-dirty"));
+    }
+
+    #[test]
+    fn given_failing_git_when_tagging_and_pushing_then_returns_error() {
+        let local_git = LocalGit::new(Box::new(FailingGit), PathBuf::from("/tmp/checkout"));
+
+        let result = local_git.tag_and_push("v1.0.0", "Release v1.0.0", "abc123def456");
+
+        assert!(result.is_err());
+    }
+}
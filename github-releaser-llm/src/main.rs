@@ -1,158 +1,4055 @@
-use clap::Parser;
+use clap::{Parser, Subcommand};
+use reqwest::header::{HeaderName, HeaderValue};
 use reqwest::Client;
 use std::env;
 use std::error::Error;
-use dotenv;
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
 
+mod assets;
+mod browser;
+mod callback;
+mod changelog;
+mod concurrency;
+mod config;
+mod conventional;
+mod doctor;
+mod formatter;
 mod github_client;
+mod github_error;
+mod interrupt;
+mod label_groups;
+mod links;
+mod local_git;
+mod markup;
+mod mock_github;
 mod openai_client;
+mod output;
+mod plan;
+mod progress;
+mod release_list;
+mod release_name;
+mod retry;
+mod recap;
+mod reviewers;
+mod summarize;
+mod translate;
+mod version;
 
-use github_client::GitHubClient;
-use openai_client::OpenAIClient;
+use assets::{Asset, ReleaseUploader};
+use callback::Callback;
+use changelog::{CommitFilter, ExtraNotes, NoChanges, NotesBase, NotesSource};
+use config::{Config, Settings};
+use conventional::CommitSections;
+use release_list::ReleaseFilter;
+use summarize::SummaryOptions;
+use formatter::{Bullet, FormatterKind, LlmFallback, LocalFormatter, NotesFormatter, Ticket, UnmatchedLines};
+use github_client::{BranchFallback, CommitSummary, GitHubClient, GitHubRelease, PrereleaseBump, ReleaseKind};
+use interrupt::InterruptState;
+use label_groups::{LabelGrouping, LabelPlacement};
+use local_git::{LocalGit, SystemGit};
+use markup::NotesFormat;
+use mock_github::MockGitHub;
+use openai_client::{ModelSettings, OpenAIClient, OpenAiApi, Provider, ResponseShape, TokenPrices, TokenUsage};
+use plan::{PlannedAction, ReleasePlan};
+use progress::{JsonLinesProgress, NoProgress, ProgressReporter, StepStatus, StepTimer, StepTracker};
+use recap::{ReleaseSummary, StepTiming};
+use retry::RetryPolicy;
+use translate::Translator;
 
 #[derive(Parser)]
+#[command(subcommand_negates_reqs = true)]
 struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// Release tag (e.g. v1.2.3)
-    #[arg(short, long)]
-    tag: String,
+    #[arg(short, long, required = true)]
+    tag: Option<String>,
+
+    /// TOML config with defaults and named profiles (defaults to ./github-releaser.toml when present)
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
+
+    /// Config profile to use, e.g. staging or production
+    #[arg(long, global = true)]
+    profile: Option<String>,
+
+    /// Read the OpenAI key from this file instead of OPENAI_API_KEY
+    #[arg(long, global = true)]
+    openai_key_file: Option<PathBuf>,
+
+    /// Owner of the repositories given as plain names [default: Human-Glitch]
+    #[arg(long, global = true)]
+    owner: Option<String>,
+
+    /// Repository to release, as name or owner/name (repeat to release several repositories)
+    /// [default: llm-playground]
+    #[arg(long = "repo", global = true)]
+    repos: Vec<String>,
+
+    /// Release the repository the origin remote of the checkout (--repo-path or the working
+    /// directory) points at
+    #[arg(long, global = true, conflicts_with = "repos")]
+    repo_from_git: bool,
+
+    /// Maximum number of repositories released (or, with reformat-all, releases reformatted) at the same time
+    #[arg(long, global = true, default_value_t = 2)]
+    concurrency: usize,
+
+    /// Local clone used to create and push the tag with git instead of the GitHub API
+    #[arg(long)]
+    repo_path: Option<PathBuf>,
+
+    /// File to attach to the release, as <path>[#<content-type>]; repeat for several
+    #[arg(long = "asset", value_parser = assets::parse_asset)]
+    assets: Vec<Asset>,
+
+    /// Most asset uploads in flight at once
+    #[arg(long, default_value_t = 3)]
+    asset_concurrency: usize,
+
+    /// Date the annotated tag with the target commit's committer date instead of now
+    #[arg(long, conflicts_with = "repo_path")]
+    use_commit_date: bool,
+
+    /// Number of times a failed GitHub or OpenAI request is retried with exponential backoff
+    #[arg(long, global = true, default_value_t = 2)]
+    retries: u32,
+
+    /// Branch to release from when release/v{major}.{minor}.x doesn't exist
+    #[arg(long, value_enum, default_value_t = BranchFallback::TagBranch)]
+    fallback: BranchFallback,
+
+    /// Fail instead of warning when the new tag isn't newer than the latest published release
+    #[arg(long)]
+    strict_ordering: bool,
+
+    /// Fail instead of warning when the release branch's latest commit doesn't contain the previous release
+    #[arg(long)]
+    strict_branch: bool,
+
+    /// Commit to tag instead of the release branch's latest commit
+    #[arg(long)]
+    sha: Option<String>,
+
+    /// Give up on the releases still running this many seconds after the run started
+    #[arg(long)]
+    deadline_secs: Option<u64>,
+
+    /// Fail instead of succeeding when compare-based notes find no commits since the previous release
+    #[arg(long)]
+    fail_on_empty: bool,
+
+    /// Release against an in-process fake GitHub with canned data and the local formatter, so a run
+    /// needs no credentials or network; also enabled by RELEASER_MOCK=1
+    #[arg(long, hide = true)]
+    mock: bool,
+
+    /// Delete and recreate a release even when it's already finalized (not a prerelease, with notes)
+    #[arg(long)]
+    force: bool,
+
+    /// How a re-released prerelease gets its new tag: bump the patch version (-rc.1 on v1.2.1) or
+    /// the prerelease counter (v1.2.0-rc.2)
+    #[arg(long, value_enum, default_value_t = PrereleaseBump::Patch)]
+    prerelease_bump: PrereleaseBump,
+
+    /// Zero-pad the counter bumped by --prerelease-bump counter to this width (e.g. -rc.01 -> -rc.02)
+    #[arg(long, default_value_t = 0)]
+    prerelease_pad: usize,
+
+    /// What comes before the version in tags, e.g. release- for release-1.2.3; release branches
+    /// become release/{prefix}{major}.{minor}.x
+    #[arg(long, global = true, default_value = version::DEFAULT_TAG_PREFIX)]
+    tag_prefix: String,
+
+    /// Tag from --repo-path even when it isn't on the release branch or has uncommitted changes
+    #[arg(long, requires = "repo_path")]
+    allow_dirty: bool,
+
+    /// Publish new releases as stable instead of as prereleases
+    #[arg(long)]
+    stable: bool,
+
+    /// Mark the new release as the repository's latest; implies --stable
+    #[arg(long)]
+    latest: bool,
+
+    /// GitHub REST API version sent as X-GitHub-Api-Version
+    #[arg(long, global = true, default_value = github_client::DEFAULT_API_VERSION)]
+    github_api_version: String,
+
+    /// Extra header for every GitHub request, as "Name: Value"; repeat for several. Authorization can't be overridden
+    #[arg(long = "header", global = true, value_parser = github_client::parse_header)]
+    headers: Vec<(HeaderName, HeaderValue)>,
+
+    /// Formatter used for the release notes
+    #[arg(long, global = true, value_enum, default_value_t = FormatterKind::Openai)]
+    formatter: FormatterKind,
+
+    /// Format the notes with this instead of failing the release when OpenAI fails after its retries
+    #[arg(long, value_enum)]
+    llm_fallback: Option<LlmFallback>,
+
+    /// Whose models format the notes: OpenAI, or Anthropic or Ollama through their OpenAI-compatible APIs
+    #[arg(long, global = true, value_enum, default_value_t = Provider::Openai)]
+    provider: Provider,
+
+    /// Model used to format the notes [default: gpt-4o, claude-3-5-sonnet-latest or llama3.1, per --provider]
+    #[arg(long, global = true)]
+    model: Option<String>,
+
+    /// Stable end-user identifier sent to OpenAI for abuse monitoring
+    #[arg(long, global = true)]
+    openai_user: Option<String>,
+
+    /// Don't check that the model exists before formatting (e.g. for OpenAI-compatible APIs without /v1/models)
+    #[arg(long, global = true)]
+    skip_model_check: bool,
+
+    /// Context window in tokens for the prompt size check, overriding the built-in model list
+    #[arg(long, global = true)]
+    context_window: Option<usize>,
+
+    /// Most independent OpenAI calls, such as --summarize chunks and translations, in flight at once
+    #[arg(long, global = true, default_value_t = openai_client::DEFAULT_MAX_PARALLEL)]
+    max_parallel_openai: usize,
+
+    /// Raw notes longer than this many characters are formatted in chunks, each under it, instead of in one call
+    #[arg(long, global = true, default_value_t = openai_client::DEFAULT_CHUNK_THRESHOLD)]
+    chunk_threshold: usize,
+
+    /// OpenAI endpoint the prompts go to: chat completions, or the Responses API (no --seed or penalties)
+    #[arg(long, global = true, value_enum, default_value_t = OpenAiApi::Chat)]
+    openai_api: OpenAiApi,
+
+    /// Where the OpenAI-compatible endpoint puts the answer, overriding the matching route's
+    /// response_shape [default: the one --openai-api returns]
+    #[arg(long, global = true, value_enum)]
+    response_shape: Option<ResponseShape>,
+
+    /// Extra formatting instructions: standard, concise or detailed (default: the config's
+    /// model_presets entry for --model, else standard)
+    #[arg(long, global = true)]
+    prompt_preset: Option<String>,
+
+    /// Comma-separated models to try in order if the model is unavailable
+    #[arg(long, global = true, value_delimiter = ',')]
+    model_fallbacks: Vec<String>,
+
+    /// Seed sent to OpenAI for more reproducible output
+    #[arg(long, global = true)]
+    seed: Option<u64>,
+
+    /// OpenAI presence penalty (-2.0 to 2.0)
+    #[arg(long, global = true, value_parser = openai_client::parse_penalty, allow_negative_numbers = true)]
+    presence_penalty: Option<f64>,
+
+    /// OpenAI frequency penalty (-2.0 to 2.0)
+    #[arg(long, global = true, value_parser = openai_client::parse_penalty, allow_negative_numbers = true)]
+    frequency_penalty: Option<f64>,
+
+    /// Comma-separated language codes to translate the notes into, e.g. es,fr
+    #[arg(long, global = true, value_delimiter = ',')]
+    translate: Vec<String>,
+
+    /// Write translations to files in this directory instead of appending them to the release
+    #[arg(long, global = true, requires = "translate")]
+    translate_dir: Option<PathBuf>,
+
+    /// Heading that collects lines without a ticket (default: a plain list at the end)
+    #[arg(long, global = true)]
+    other_heading: Option<String>,
+
+    /// Leave lines without a ticket out of the notes
+    #[arg(long, global = true, conflicts_with = "other_heading")]
+    drop_unmatched: bool,
+
+    /// Bullet that starts each line item in the local formatter's output
+    #[arg(long, global = true, value_enum, default_value_t = Bullet::Asterisk)]
+    bullet: Bullet,
+
+    /// Heading title for a ticket prefix in the local formatter's output, as <prefix>=<title>; repeat for several
+    #[arg(long = "heading-title", global = true, value_parser = formatter::parse_heading_title)]
+    heading_titles: Vec<(String, String)>,
+
+    /// Also write the final notes to this file, in --format
+    #[arg(long, global = true)]
+    output: Option<PathBuf>,
+
+    /// Markup of the --output file; the release body stays markdown
+    #[arg(long, global = true, value_enum, default_value_t = NotesFormat::Markdown, requires = "output")]
+    format: NotesFormat,
+
+    /// Append a "Full Changelog" link comparing the previous release with this one
+    #[arg(long, global = true)]
+    footer_compare: bool,
+
+    /// Previous tag used as the baseline for changelogs, compare links and regenerated notes
+    /// (defaults to the latest published release)
+    #[arg(long, global = true, visible_aliases = ["prev", "since"])]
+    prev_tag: Option<String>,
+
+    /// Release GitHub's regenerated notes start from without --prev-tag: github lets GitHub pick,
+    /// same-line takes the previous release of the tag's major.minor line
+    #[arg(long, global = true, value_enum, default_value_t = NotesBase::Github)]
+    notes_base: NotesBase,
+
+    /// Where the raw release notes come from
+    #[arg(long, value_enum, default_value_t = NotesSource::Github)]
+    notes_source: NotesSource,
+
+    /// Create the release with the --notes-source compare notes as its body instead of GitHub's
+    /// generated notes
+    #[arg(long)]
+    no_auto_notes: bool,
+
+    /// End each line of --notes-source compare with the commit's short SHA, linked to the commit
+    #[arg(long, global = true)]
+    include_sha: bool,
+
+    /// Regex for commit subjects to leave out of --notes-source compare, on top of merges and
+    /// chore(release) bumps (repeatable)
+    #[arg(long = "exclude-commit-pattern", global = true, value_parser = changelog::parse_pattern)]
+    exclude_commit_patterns: Vec<regex::Regex>,
+
+    /// Remove Co-authored-by:, Signed-off-by: and similar trailers from the commits of
+    /// --notes-source compare, so they stay out of --commit-bodies
+    #[arg(long, global = true)]
+    strip_trailers: bool,
+
+    /// Credit the co-authors of each commit's Co-authored-by: trailers on its line of
+    /// --notes-source compare
+    #[arg(long, global = true)]
+    credit_co_authors: bool,
+
+    /// Put each commit's message body, indented, under its line of --notes-source compare
+    #[arg(long, global = true)]
+    commit_bodies: bool,
+
+    /// Only put commits committed within this long before the run into --notes-source compare, such as
+    /// 14d for a two-week digest (h, d or w)
+    #[arg(long, global = true, value_parser = changelog::parse_since_duration)]
+    since_duration: Option<Duration>,
+
+    /// Hand-written notes file, such as known issues, formatted together with the raw notes under a
+    /// separator naming it; repeat for several, in order
+    #[arg(long = "extra-notes", global = true, value_parser = changelog::read_extra_notes)]
+    extra_notes: Vec<ExtraNotes>,
+
+    /// Section for a conventional-commit type in --notes-source compare, as <type>=<title>; repeat
+    /// for several, in the order the sections should appear
+    #[arg(long = "commit-section", global = true, value_parser = conventional::parse_commit_section)]
+    commit_sections: Vec<(String, String)>,
+
+    /// Title of the section for commits of other types, after the --commit-section ones
+    #[arg(long, global = true, default_value = conventional::OTHER_SECTION)]
+    other_commit_section: String,
+
+    /// Leave commits of other types out instead of listing them under --other-commit-section
+    #[arg(long, global = true)]
+    drop_other_commits: bool,
+
+    /// Split the formatted line items into a subsection per pull request label with this prefix
+    /// (e.g. area/), with unlabeled ones under "General"
+    #[arg(long, global = true)]
+    group_by_label_prefix: Option<String>,
+
+    /// Whether a pull request with several matching labels is listed under each area or only the first
+    #[arg(long, global = true, value_enum, default_value_t = LabelPlacement::Each, requires = "group_by_label_prefix")]
+    label_placement: LabelPlacement,
+
+    /// Check every link in the formatted notes and warn about the ones that don't resolve
+    #[arg(long, global = true)]
+    validate_links: bool,
+
+    /// End each line linking a pull request with the accounts that approved it
+    #[arg(long, global = true)]
+    include_reviewers: bool,
+
+    /// Regenerate GitHub's notes before formatting instead of reusing the release's current body
+    #[arg(long)]
+    regenerate_notes: bool,
+
+    /// Fail when the release body is empty instead of generating the notes another way
+    #[arg(long)]
+    no_empty_body_fallback: bool,
+
+    /// Never delete or recreate an existing prerelease; update its notes in place instead
+    #[arg(long)]
+    immutable_prereleases: bool,
+
+    /// Keep GitHub's raw notes below the formatted ones in a collapsible block
+    #[arg(long, global = true)]
+    append_raw: bool,
+
+    /// Longest release body in characters; longer notes are cut at a heading with a link to the full changelog
+    #[arg(long, global = true, default_value_t = formatter::GITHUB_RELEASE_BODY_LIMIT)]
+    max_notes_length: usize,
+
+    /// Release title, with {tag} and {date} placeholders (defaults to the tag)
+    #[arg(long, global = true)]
+    name: Option<String>,
+
+    /// Format the notes in chunks and merge them into a concise categorized summary (OpenAI only)
+    #[arg(long, global = true)]
+    summarize: bool,
+
+    /// Line items per chunk with --summarize
+    #[arg(long, global = true, default_value_t = 100, requires = "summarize")]
+    summary_chunk_lines: usize,
+
+    /// Most OpenAI calls one summary may use, including the final merge, retries and model fallbacks;
+    /// chunks grow to stay under it
+    #[arg(long, global = true, default_value_t = 10, value_parser = clap::value_parser!(u64).range(2..), requires = "summarize")]
+    max_llm_calls: u64,
+
+    /// File with the instructions for the final merge, replacing the built-in prompt
+    #[arg(long, global = true, requires = "summarize")]
+    reduce_prompt_file: Option<PathBuf>,
+
+    /// Print plain status lines without ANSI colors (also set by NO_COLOR, and when stdout isn't a terminal)
+    #[arg(long, global = true)]
+    no_color: bool,
+
+    /// Format the notes and print a diff against the current release body without changing anything
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Fail when the formatted notes don't reference a single ticket
+    #[arg(long, global = true)]
+    require_tickets: bool,
+
+    /// Append a "files changed, insertions, deletions" line for the range since the previous release
+    #[arg(long, global = true)]
+    diff_stats: bool,
+
+    /// Also print one JSON object per completed release step to stderr, for CI wrappers
+    #[arg(long)]
+    progress_json: bool,
+
+    /// Also write the end-of-run summary as one JSON object to this file, for CI
+    #[arg(long, global = true)]
+    summary_json: Option<PathBuf>,
+
+    /// USD per million prompt tokens, to put a cost on the token usage in the summary
+    #[arg(long, global = true, requires = "completion_token_price")]
+    prompt_token_price: Option<f64>,
+
+    /// USD per million completion tokens, to put a cost on the token usage in the summary
+    #[arg(long, global = true, requires = "prompt_token_price")]
+    completion_token_price: Option<f64>,
+
+    /// Print how long planning and each release step took, per repository, with the summary
+    #[arg(long)]
+    timings: bool,
+
+    /// Open each release page in the default browser when the run finishes; only prints the URL in CI
+    /// or without a display
+    #[arg(long)]
+    open: bool,
+
+    /// Convert the tag's published release back to a draft instead of releasing
+    #[arg(long, conflicts_with = "dry_run")]
+    to_draft: bool,
+
+    /// Only reformat the existing release's notes; no tags, branches or releases are created or deleted
+    #[arg(long, conflicts_with_all = ["dry_run", "to_draft"])]
+    update_only: bool,
+
+    /// Post the formatted notes as a new discussion instead of creating a release
+    #[arg(long, conflicts_with_all = ["dry_run", "to_draft", "update_only"])]
+    announce_only: bool,
+
+    /// Discussion category --announce-only posts in
+    #[arg(long, default_value = "Announcements", requires = "announce_only")]
+    discussion_category: String,
+
+    /// Keep an existing stable release and move its tag to the new commit instead of deleting and
+    /// recreating both
+    #[arg(long, conflicts_with = "update_only")]
+    update_in_place: bool,
+
+    /// POST a JSON completion event with each repository's outcome or error to this URL
+    #[arg(long)]
+    callback_url: Option<String>,
+
+    /// Sign the completion event with HMAC-SHA256 of this secret, in the X-Releaser-Signature-256 header
+    #[arg(long, requires = "callback_url")]
+    callback_secret: Option<String>,
+}
+
+impl Cli {
+    /// `--model`, or the provider's default when it isn't given.
+    fn model(&self) -> &str {
+        self.model.as_deref().unwrap_or(self.provider.default_model())
+    }
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Promote a prerelease to stable and regenerate its notes since the previous stable release
+    Finalize {
+        /// Release tag to finalize (e.g. v1.2.0)
+        #[arg(short, long)]
+        tag: String,
+    },
+    /// Check tokens, scopes, repositories and branches without changing anything
+    Doctor {
+        /// Release tag whose branch and tag should be checked
+        #[arg(short, long)]
+        tag: Option<String>,
+    },
+    /// Print every release with its id, prerelease and draft flags, newest version first
+    ListReleases {
+        /// Only list prereleases
+        #[arg(long, conflicts_with = "stable_only")]
+        prereleases_only: bool,
+        /// Only list stable releases
+        #[arg(long)]
+        stable_only: bool,
+    },
+    /// Reformat the notes of every existing release, skipping those that wouldn't change
+    ReformatAll {
+        /// Only reformat prereleases
+        #[arg(long)]
+        prereleases_only: bool,
+        /// Print which releases would change without updating them
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Print the API calls a release would make, in order, without making any
+    Plan {
+        /// Release tag to plan (e.g. v1.2.3)
+        #[arg(short, long)]
+        tag: String,
+    },
+}
+
+/// Optional behavior of a release run, resolved from the CLI.
+#[derive(Default)]
+struct ReleaseOptions {
+    /// Append a "Full Changelog" compare link to the notes
+    footer_compare: bool,
+    /// Previous tag to compare against instead of the latest published release
+    previous_tag: Option<String>,
+    /// Which release regenerated notes start from without `previous_tag`
+    notes_base: NotesBase,
+    /// Where the raw notes come from
+    notes_source: NotesSource,
+    /// Seed new releases with the compare notes instead of GitHub's generated ones
+    no_auto_notes: bool,
+    /// Commits left out of compare-based notes
+    commit_filter: CommitFilter,
+    /// Drop commit trailers
+    strip_trailers: bool,
+    /// Credit co-authors after each commit's author
+    credit_co_authors: bool,
+    /// List each commit's message body under its line
+    commit_bodies: bool,
+    /// Sections per conventional-commit type for compare-based notes
+    commit_sections: Option<CommitSections>,
+    /// Only commits committed at or after this UTC timestamp go into compare-based notes
+    since: Option<String>,
+    /// Files formatted together with the raw notes
+    extra_notes: Vec<ExtraNotes>,
+    /// Link each compare-based line to its commit
+    include_sha: bool,
+    /// Refresh GitHub's generated notes instead of reusing a possibly stale release body
+    regenerate_notes: bool,
+    /// Fail on an empty release body instead of falling back to generate-notes, then the compare notes
+    no_empty_body_fallback: bool,
+    /// Translate the formatted notes into additional languages
+    translator: Option<Translator>,
+    /// Keep existing prereleases (and their tags) instead of recreating them
+    immutable_prereleases: bool,
+    /// Pin the tagger date to the target commit's committer date
+    use_commit_date: bool,
+    /// Skip the clean working tree check of the local clone
+    allow_dirty: bool,
+    /// Update an existing stable release and retarget its tag instead of recreating them
+    update_in_place: bool,
+    /// Fail when the commit to tag doesn't contain the previous release
+    strict_branch: bool,
+    /// Commit to tag instead of the branch's latest one
+    sha: Option<String>,
+    /// Fail when there's nothing to release instead of skipping the repository
+    fail_on_empty: bool,
+    /// Recreate a finalized release instead of refusing to
+    force: bool,
+    /// Files uploaded to the release once its notes are updated
+    assets: Vec<Asset>,
+    /// Most asset uploads in flight at once
+    asset_concurrency: usize,
+    /// Fail when the formatted notes reference no ticket
+    require_tickets: bool,
+    /// Subsections per pull request label
+    label_grouping: Option<LabelGrouping>,
+    /// Credit each pull request's approvers on its line
+    include_reviewers: bool,
+    /// Warn about links in the notes that don't resolve
+    validate_links: bool,
+    /// Append the diff stats since the previous release
+    diff_stats: bool,
+    /// Keep the raw notes in a collapsible block below the formatted ones
+    append_raw: bool,
+    /// Truncate notes longer than this
+    max_notes_length: Option<usize>,
+    /// `--name` template for the release title; the tag is used when unset
+    release_name: Option<String>,
+    /// Formats the notes when OpenAI fails, instead of failing the release
+    llm_fallback: Option<LocalFormatter>,
+    /// Finds the tickets the final notes reference, for the summary and the callback
+    ticket_finder: LocalFormatter,
+    /// File that also gets the final notes
+    output: Option<PathBuf>,
+    /// Markup of the output file
+    output_format: NotesFormat,
 }
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
 
     dotenv::dotenv().ok();
-    let github_token = env::var("GITHUB_TOKEN").expect("GITHUB_TOKEN is missing.");
-    let openai_api_key = env::var("OPENAI_API_KEY").expect("OPENAI_API_KEY is missing.");
-
     let args = Cli::parse();
-    let tag = args.tag;
+    output::init(args.no_color);
     let http_client = Client::new();
+    let retry_policy = RetryPolicy::new(args.retries);
 
-    let gh_client = GitHubClient::new(http_client.clone(), github_token);
+    let tag = match &args.command {
+        Some(Command::Doctor { tag }) => {
+            let settings = load_settings(&args)?;
+            return run_doctor(&args, &settings, tag.as_deref(), &http_client, &retry_policy).await
+        }
+        Some(Command::ListReleases { prereleases_only, stable_only }) => {
+            let filter = ReleaseFilter::from_flags(*prereleases_only, *stable_only);
+            return run_list_releases(&args, &load_settings(&args)?, filter, &http_client, &retry_policy).await;
+        }
+        Some(Command::ReformatAll { prereleases_only, dry_run }) => {
+            let filter = ReleaseFilter::from_flags(*prereleases_only, false);
+            return run_reformat_all(&args, &load_settings(&args)?, filter, *dry_run, &http_client, &retry_policy).await;
+        }
+        Some(Command::Finalize { tag }) | Some(Command::Plan { tag }) => tag.clone(),
+        None => args.tag.clone().expect("clap requires --tag without a subcommand"),
+    };
 
-    // Execute the release process
-    process_release(&gh_client, &tag, http_client, openai_api_key).await?;
+    // Every way a release run ends goes through finish_run, so the callback hears about failures too
+    let run = release(&args, &http_client, &retry_policy, &tag).await;
+    finish_run(&args, &http_client, &tag, run).await
+}
 
-    println!("Release update process for '{}' completed successfully.", tag);
-    Ok(())
+/// The config's settings for this run, with the flags layered on top.
+fn load_settings(args: &Cli) -> Result<Settings, Box<dyn Error>> {
+    let mut settings = Config::load(args.config.as_deref())?.resolve(
+        args.profile.as_deref(),
+        Settings {
+            owner: args.owner.clone(),
+            repos: (!args.repos.is_empty()).then(|| args.repos.clone()),
+            openai_key_file: args.openai_key_file.clone(),
+            ..Default::default()
+        },
+    )?;
+    if args.repo_from_git {
+        settings.repos = Some(vec![repository_from_git(args, &settings)?]);
+    }
+    Ok(settings)
 }
 
-/// Process the GitHub release including checking for existing pre-releases,
-/// incrementing the version if needed, and creating or updating releases.
-async fn process_release(
-    gh_client: &GitHubClient,
-    requested_tag: &str,
-    http_client: Client,
-    openai_api_key: String,
-) -> Result<(), Box<dyn Error>> {
-    // Display the branch naming format for improved logging
-    println!("🚀 Starting release process for '{}' using branch format release/v{}.{}.x...", 
-        requested_tag,
-        // Use placeholder values since we're just showing the format
-        "major", "minor"
-    );
-    
-    // Determine if we need to increment the version based on criteria
-    let tag = gh_client.determine_tag_version(requested_tag).await?;
-    
-    // If the tag is different, we're creating a new incremented version
-    let is_incremented_version = tag != requested_tag;
-    
-    if is_incremented_version {
-        println!("⬆️ Using incremented version {} instead of {}", tag, requested_tag);
+/// What a release run did: the repositories and how each one went, and the tokens it used.
+struct Run {
+    gh_clients: Vec<GitHubClient>,
+    results: Vec<Result<Outcome, Box<dyn Error>>>,
+    usage: Option<TokenUsage>,
+}
+
+/// The flags whose GitHub calls mock mode has no answers for, or that reach past it to the network.
+fn mock_gaps(args: &Cli) -> Vec<&'static str> {
+    [
+        (matches!(args.command, Some(Command::Finalize { .. })), "finalize"),
+        (args.regenerate_notes, "--regenerate-notes"),
+        (args.include_reviewers, "--include-reviewers"),
+        (args.group_by_label_prefix.is_some(), "--group-by-label-prefix"),
+        (args.announce_only, "--announce-only"),
+        (args.validate_links, "--validate-links"),
+    ]
+    .into_iter()
+    .filter_map(|(used, flag)| used.then_some(flag))
+    .collect()
+}
+
+/// Release, plan, preview, announce or update `tag` in every repository.
+///
+/// An error means the run stopped before it got to the repositories.
+async fn release(args: &Cli, http_client: &Client, retry_policy: &RetryPolicy, tag: &str) -> Result<Run, Box<dyn Error>> {
+    let deadline = args.deadline_secs.map(|secs| Deadline::starting_now(Duration::from_secs(secs)));
+    let mut settings = load_settings(args)?;
+    let tag = tag.to_string();
+    let mock = args.mock || env::var("RELEASER_MOCK").is_ok_and(|value| value == "1");
+    let gaps = if mock { mock_gaps(args) } else { Vec::new() };
+    if !gaps.is_empty() {
+        return Err(format!("{} can't run in mock mode, which only fakes the GitHub calls of a release run.", gaps.join(", ")).into());
     }
-    
-    // 1. Check for existing GitHub release for the new tag.
-    println!("Step 1: Checking for existing GitHub release...");
-    if let Some(release) = gh_client.get_release_by_tag(&tag).await? {
-        if is_incremented_version {
-            // For incremented versions, update the existing release instead of deleting it
-            println!("  Found existing release for incremented version (ID: {}). Will update instead of recreate.", release.id);
-        } else {
-            // Only delete if not an incremented version, preserving immutability of existing releases
-            println!("  Found existing release (ID: {}). Deleting...", release.id);
-            gh_client.delete_release(release.id).await?;
-            println!("  ✅ Existing release deleted successfully.");
+    // Kept alive for the whole run, since the clients talk to it
+    let mock_github = if mock { Some(MockGitHub::start(&tag).await) } else { None };
+    let github_token = match &mock_github {
+        Some(mock_github) => {
+            println!("{}", output::info("Mock mode: using a fake GitHub and the local formatter."));
+            settings.base_url = Some(mock_github.url());
+            mock_github::MOCK_TOKEN.to_string()
         }
-    } else {
-        println!("  ✅ No existing release found. Proceeding with creation.");
+        None => settings.github_token(|name| env::var(name).ok())?,
+    };
+    let gh_clients = github_clients(args, &settings, http_client, &github_token, retry_policy)?;
+
+    if args.to_draft {
+        // Nothing is formatted, so there's no need for a formatter or OpenAI
+        let conversions = gh_clients.iter().map(|gh_client| convert_to_draft(gh_client, &tag)).collect();
+        let results = concurrency::run_bounded(conversions, args.concurrency).await;
+        return Ok(Run { gh_clients, results, usage: None });
     }
 
-    // 2. For non-incremented versions, we might need to delete the tag
-    if !is_incremented_version {
-        println!("Step 2: Checking existing Git tag...");
-        match gh_client.delete_tag(&tag).await {
-            Ok(_) => println!("  ✅ Successfully deleted tag {}", tag),
-            Err(e) => {
-                println!("  ℹ️ Tag {} doesn't exist or was already deleted ({})", tag, e);
-                // Not returning error as this is an acceptable condition
+    if args.no_auto_notes && args.notes_source != NotesSource::Compare {
+        return Err("--no-auto-notes needs --notes-source compare to seed the release body.".into());
+    }
+    if args.prerelease_pad > 0 && args.prerelease_bump != PrereleaseBump::Counter {
+        return Err("--prerelease-pad needs --prerelease-bump counter.".into());
+    }
+    if args.since_duration.is_some() && args.notes_source != NotesSource::Compare {
+        return Err("--since-duration only applies to --notes-source compare.".into());
+    }
+    if args.repo_path.is_some() && gh_clients.len() > 1 {
+        return Err("--repo-path can only be used when releasing a single repository.".into());
+    }
+    if args.output.is_some() && gh_clients.len() > 1 {
+        return Err("--output can only be used when releasing a single repository.".into());
+    }
+    let local_git = args
+        .repo_path
+        .clone()
+        .map(|path| LocalGit::new(Box::new(SystemGit), path));
+
+    if let Some(Command::Plan { .. }) = args.command {
+        // Planning only reads, so it needs neither a formatter nor OpenAI
+        let options = ReleaseOptions {
+            previous_tag: args.prev_tag.clone(),
+            immutable_prereleases: args.immutable_prereleases,
+            use_commit_date: args.use_commit_date,
+            allow_dirty: args.allow_dirty,
+            update_in_place: args.update_in_place,
+            strict_branch: args.strict_branch,
+            sha: args.sha.clone(),
+            fail_on_empty: args.fail_on_empty,
+            notes_source: args.notes_source,
+            force: args.force,
+            assets: args.assets.clone(),
+            ..Default::default()
+        };
+        let plans = gh_clients
+            .iter()
+            .map(|gh_client| print_plan(gh_client, &tag, local_git.as_ref(), &options))
+            .collect();
+        let results = concurrency::run_bounded(plans, args.concurrency).await;
+        return Ok(Run { gh_clients, results, usage: None });
+    }
+
+    let formatter = if mock_github.is_some() {
+        NotesFormatter::Local(local_formatter(args))
+    } else {
+        notes_formatter(args, &settings, http_client, retry_policy).await?
+    };
+    let translator = if args.translate.is_empty() {
+        None
+    } else if mock_github.is_some() {
+        return Err("--translate needs OpenAI, which mock mode doesn't fake.".into());
+    } else {
+        let client = openai_client(args, &settings, http_client.clone(), retry_policy.clone())?;
+        Some(Translator::new(client, args.translate.clone()).with_output_dir(args.translate_dir.clone()))
+    };
+
+    // First Ctrl-C lets the in-flight step finish, the second one aborts immediately
+    let interrupt = Arc::new(InterruptState::default());
+    let watcher = interrupt.clone();
+    tokio::spawn(async move {
+        interrupt::watch(watcher, || async {
+            if tokio::signal::ctrl_c().await.is_err() {
+                // Without a signal handler there's nothing to wait for
+                std::future::pending::<()>().await;
             }
+        })
+        .await;
+        std::process::exit(130);
+    });
+
+    let options = ReleaseOptions {
+        footer_compare: args.footer_compare,
+        previous_tag: args.prev_tag.clone(),
+        notes_base: args.notes_base,
+        notes_source: args.notes_source,
+        no_auto_notes: args.no_auto_notes,
+        commit_filter: CommitFilter::new(args.exclude_commit_patterns.clone()),
+        commit_sections: CommitSections::from_flags(
+            args.commit_sections.clone(),
+            args.other_commit_section.clone(),
+            args.drop_other_commits,
+        ),
+        since: args.since_duration.map(release_name::timestamp_before),
+        extra_notes: args.extra_notes.clone(),
+        strip_trailers: args.strip_trailers,
+        credit_co_authors: args.credit_co_authors,
+        commit_bodies: args.commit_bodies,
+        include_sha: args.include_sha,
+        regenerate_notes: args.regenerate_notes,
+        no_empty_body_fallback: args.no_empty_body_fallback,
+        translator,
+        immutable_prereleases: args.immutable_prereleases,
+        use_commit_date: args.use_commit_date,
+        allow_dirty: args.allow_dirty,
+        update_in_place: args.update_in_place,
+        strict_branch: args.strict_branch,
+        sha: args.sha.clone(),
+        fail_on_empty: args.fail_on_empty,
+        force: args.force,
+        assets: args.assets.clone(),
+        asset_concurrency: args.asset_concurrency,
+        require_tickets: args.require_tickets,
+        validate_links: args.validate_links,
+        include_reviewers: args.include_reviewers,
+        label_grouping: args
+            .group_by_label_prefix
+            .clone()
+            .map(|prefix| LabelGrouping { prefix, placement: args.label_placement }),
+        diff_stats: args.diff_stats,
+        append_raw: args.append_raw,
+        max_notes_length: Some(args.max_notes_length),
+        release_name: args.name.clone(),
+        llm_fallback: args.llm_fallback.map(|LlmFallback::Local| local_formatter(args)),
+        ticket_finder: local_formatter(args),
+        output: args.output.clone(),
+        output_format: args.format,
+    };
+
+    // Execute the release process for each repository, a few at a time
+    let outcomes = match args.command {
+        Some(Command::Finalize { .. }) => {
+            let finalizations = gh_clients
+                .iter()
+                .map(|gh_client| finalize_release(gh_client, &tag, &formatter, &options))
+                .collect();
+            concurrency::run_bounded(finalizations, args.concurrency).await
+        }
+        _ if args.dry_run => {
+            let previews = gh_clients
+                .iter()
+                .map(|gh_client| preview_release(gh_client, &tag, &formatter, &options))
+                .collect();
+            concurrency::run_bounded(previews, args.concurrency).await
+        }
+        _ if args.announce_only => {
+            let announcements = gh_clients
+                .iter()
+                .map(|gh_client| announce_release(gh_client, &tag, &formatter, &args.discussion_category, &options))
+                .collect();
+            concurrency::run_bounded(announcements, args.concurrency).await
+        }
+        _ if args.update_only => {
+            let updates = gh_clients
+                .iter()
+                .map(|gh_client| update_notes(gh_client, &tag, &formatter, &options))
+                .collect();
+            concurrency::run_bounded(updates, args.concurrency).await
+        }
+        _ => {
+            let progress: &dyn ProgressReporter = if args.progress_json { &JsonLinesProgress } else { &NoProgress };
+            let timer = StepTimer::new(progress);
+            let tracker = StepTracker::new(&timer);
+            let releases = gh_clients
+                .iter()
+                .map(|gh_client| async {
+                    let release = process_release(gh_client, &tag, &formatter, &interrupt, local_git.as_ref(), &tracker, &options);
+                    let mut outcome = within_deadline(deadline, gh_client, &tracker, release).await;
+                    if let Some(summary) = outcome.as_mut().ok().filter(|_| args.timings).and_then(Outcome::summary_mut) {
+                        summary.timings = StepTiming::from_durations(timer.timings(&gh_client.repository()));
+                    }
+                    outcome
+                })
+                .collect();
+            concurrency::run_bounded(releases, args.concurrency).await
+        }
+    };
+
+    let usage = [formatter.usage(), options.translator.as_ref().map(Translator::usage)]
+        .into_iter()
+        .flatten()
+        .reduce(TokenUsage::add);
+    Ok(Run { gh_clients, results: outcomes, usage })
+}
+
+/// The local formatter, set up like the flags ask.
+fn local_formatter(args: &Cli) -> LocalFormatter {
+    LocalFormatter::default()
+        .with_unmatched(UnmatchedLines::from_flags(args.other_heading.clone(), args.drop_unmatched))
+        .with_bullet(args.bullet)
+        .with_heading_titles(args.heading_titles.clone())
+}
+
+/// Build the formatter the flags ask for, checking that the OpenAI model exists.
+async fn notes_formatter(
+    args: &Cli,
+    settings: &Settings,
+    http_client: &Client,
+    retry_policy: &RetryPolicy,
+) -> Result<NotesFormatter, Box<dyn Error>> {
+    let unmatched = UnmatchedLines::from_flags(args.other_heading.clone(), args.drop_unmatched);
+    let summary = summary_options(args)?;
+    let formatter = match args.formatter {
+        FormatterKind::Openai => NotesFormatter::OpenAI(Box::new(
+            openai_client(args, settings, http_client.clone(), retry_policy.clone())?
+                .with_unmatched(unmatched)
+                .with_summary(summary),
+        )),
+        FormatterKind::Local if summary.is_some() => return Err("--summarize needs the OpenAI formatter.".into()),
+        FormatterKind::Local => NotesFormatter::Local(local_formatter(args)),
+    };
+    if let NotesFormatter::OpenAI(client) = &formatter {
+        // Catch a mistyped --model before anything is deleted or recreated
+        if !args.skip_model_check && args.provider.lists_models() {
+            check_model(client, args.llm_fallback.is_some()).await?;
+        }
+    }
+    Ok(formatter)
+}
+
+/// Fail when the model isn't available, or only warn when `--llm-fallback` can format the notes without it.
+async fn check_model(client: &OpenAIClient, has_fallback: bool) -> Result<(), Box<dyn Error>> {
+    match client.ensure_model_available().await {
+        Err(e) if has_fallback => {
+            println!("{}", output::warning(format!("Couldn't check the model ({}). Continuing, as --llm-fallback can format the notes.", e)));
+            Ok(())
         }
+        result => Ok(result?),
+    }
+}
+
+/// Build the notes from the commits since the previous release, failing when there are none.
+async fn compare_notes(gh_client: &GitHubClient, tag: &str, options: &ReleaseOptions) -> Result<String, Box<dyn Error>> {
+    let commits = note_commits(compare_commits(gh_client, tag, options).await?, options);
+    if commits.is_empty() {
+        return Err(format!("No commits found for {}.", tag).into());
+    }
+    println!("  {}", output::success(format!("Built release notes from {} commits.", commits.len())));
+    Ok(changelog::commits_to_notes(&commits, options.include_sha.then_some(gh_client), options.commit_sections.as_ref(), options.commit_bodies))
+}
+
+/// The commits since the previous release or, with `--since-duration`, those committed within the window.
+async fn compare_commits(gh_client: &GitHubClient, tag: &str, options: &ReleaseOptions) -> Result<Vec<CommitSummary>, Box<dyn Error>> {
+    match &options.since {
+        Some(since) => changelog::collect_commits_since(gh_client, since, tag).await,
+        None => changelog::collect_commits(gh_client, options.previous_tag.as_deref(), tag).await,
+    }
+}
+
+/// The commits compare-based notes list: noise filtered out, co-authors read with `--credit-co-authors`
+/// and, with `--strip-trailers`, trailers removed.
+fn note_commits(commits: Vec<CommitSummary>, options: &ReleaseOptions) -> Vec<CommitSummary> {
+    let mut commits = options.commit_filter.apply(commits);
+    if options.credit_co_authors {
+        commits = changelog::credit_co_authors(commits);
+    }
+    if options.strip_trailers {
+        changelog::strip_commit_trailers(commits)
     } else {
-        println!("Step 2: Skipping tag deletion for incremented version to maintain immutability.");
+        commits
     }
+}
 
-    // Determine which branch to use for the release
-    let branch = gh_client.get_release_branch_for_tag(&tag).await?;
-    println!("Step 3: Using release branch: {}", branch);
-    
-    // 3. Retrieve the latest commit SHA from the release branch.
-    println!("Step 4: Retrieving latest commit from branch {}...", branch);
-    let commit_sha = match gh_client.get_latest_commit_sha(&branch).await {
-        Ok(sha) => {
-            println!("  ✅ Found commit: {}", sha);
-            sha
-        },
+/// Plan the release and print the calls it would make.
+async fn print_plan(
+    gh_client: &GitHubClient,
+    tag: &str,
+    local_git: Option<&LocalGit>,
+    options: &ReleaseOptions,
+) -> Result<Outcome, Box<dyn Error>> {
+    let plan = match plan_release(gh_client, tag, local_git, options).await {
+        Ok(plan) => plan,
+        Err(e) => return nothing_to_release(e, options),
+    };
+    println!("{}", plan.render(gh_client));
+    Ok(Outcome::Planned)
+}
+
+/// What a run did to one repository's release, for the summary at the end.
+#[derive(Debug, PartialEq)]
+enum Outcome {
+    Released(Box<ReleaseSummary>),
+    /// Released with the local formatter's notes after OpenAI failed
+    ReleasedWithLocalFallback(Box<ReleaseSummary>),
+    Planned,
+    /// Posted as a discussion at this URL instead of released
+    Announced(String, Box<ReleaseSummary>),
+    /// Announced with the local formatter's notes after OpenAI failed
+    AnnouncedWithLocalFallback(String, Box<ReleaseSummary>),
+    /// Nothing released, as the branch has no commits since this previous release
+    NoChanges(String),
+    ConvertedToDraft,
+    AlreadyDraft,
+}
+
+impl Outcome {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Outcome::Released(_) => "released",
+            Outcome::ReleasedWithLocalFallback(_) => "released_with_local_fallback",
+            Outcome::Planned => "planned",
+            Outcome::Announced(..) => "announced",
+            Outcome::AnnouncedWithLocalFallback(..) => "announced_with_local_fallback",
+            Outcome::NoChanges(_) => "no_changes",
+            Outcome::ConvertedToDraft => "converted_to_draft",
+            Outcome::AlreadyDraft => "already_draft",
+        }
+    }
+
+    fn summary(&self) -> Option<&ReleaseSummary> {
+        match self {
+            Outcome::Released(summary) | Outcome::ReleasedWithLocalFallback(summary) => Some(summary),
+            Outcome::Announced(_, summary) | Outcome::AnnouncedWithLocalFallback(_, summary) => Some(summary),
+            _ => None,
+        }
+    }
+
+    fn summary_mut(&mut self) -> Option<&mut ReleaseSummary> {
+        match self {
+            Outcome::Released(summary) | Outcome::ReleasedWithLocalFallback(summary) => Some(summary),
+            Outcome::Announced(_, summary) | Outcome::AnnouncedWithLocalFallback(_, summary) => Some(summary),
+            _ => None,
+        }
+    }
+
+    /// Released with `summary`, noting when the local fallback formatted the notes.
+    fn released(summary: ReleaseSummary, fell_back: bool) -> Self {
+        if fell_back {
+            Outcome::ReleasedWithLocalFallback(Box::new(summary))
+        } else {
+            Outcome::Released(Box::new(summary))
+        }
+    }
+
+    /// Announced at `url` with `summary`, noting when the local fallback formatted the notes.
+    fn announced(url: String, summary: ReleaseSummary, fell_back: bool) -> Self {
+        if fell_back {
+            Outcome::AnnouncedWithLocalFallback(url, Box::new(summary))
+        } else {
+            Outcome::Announced(url, Box::new(summary))
+        }
+    }
+
+    fn tickets(&self) -> &[Ticket] {
+        self.summary().map_or(&[], |summary| summary.tickets.as_slice())
+    }
+}
+
+/// Send the completion event to `--callback-url`, when set, print the recap, then report the outcomes.
+///
+/// A run that stopped before the repositories still sends the callback, with its error.
+async fn finish_run(args: &Cli, http_client: &Client, tag: &str, run: Result<Run, Box<dyn Error>>) -> Result<(), Box<dyn Error>> {
+    let Run { gh_clients, results, usage } = match run {
+        Ok(run) => run,
         Err(e) => {
-            return Err(format!("Failed to get latest commit from branch '{}': {}", branch, e).into());
+            notify(args, http_client, &callback::failed_run_event(tag, &e.to_string())).await;
+            return Err(e);
         }
     };
+    let gh_clients = gh_clients.as_slice();
 
-    // 4. Create an annotated tag object and then its reference if it doesn't exist
-    let existing_release = gh_client.get_release_by_tag(&tag).await?;
-    
-    if existing_release.is_none() || !is_incremented_version {
-        println!("Step 5: Creating annotated tag...");
-        let tag_message = format!("Release {}", tag);
-        let tag_object_sha = gh_client.create_tag_object(&tag, &tag_message, &commit_sha).await?;
-        gh_client.create_tag_ref(&tag, &tag_object_sha).await?;
-        println!("  ✅ Tag created and pushed successfully.");
-    } else {
-        println!("Step 5: Skipping tag creation as it already exists for incremented version.");
+    let outcomes: Vec<callback::RepositoryResult> = gh_clients
+        .iter()
+        .zip(&results)
+        .map(|(gh_client, result)| {
+            let outcome = result.as_ref().map(|outcome| (outcome.as_str(), outcome.tickets()));
+            (gh_client.repository(), outcome.map_err(|e| e.to_string()))
+        })
+        .collect();
+    notify(args, http_client, &callback::event(tag, &outcomes)).await;
+
+    let entries: Vec<recap::RecapEntry> = gh_clients
+        .iter()
+        .zip(&results)
+        .map(|(gh_client, result)| {
+            let entry = result.as_ref().map(|outcome| (outcome.as_str(), outcome.summary()));
+            (gh_client.repository(), entry.map_err(|e| e.to_string()))
+        })
+        .collect();
+    let prices = args
+        .prompt_token_price
+        .zip(args.completion_token_price)
+        .map(|(prompt, completion)| TokenPrices { prompt, completion });
+    println!("{}", recap::to_text(tag, &entries, usage, prices));
+    // A file of its own, so CI doesn't have to pick the JSON out of the progress lines on stdout
+    if let Some(path) = &args.summary_json {
+        if let Err(e) = std::fs::write(path, recap::to_json(tag, &entries, usage, prices).to_string()) {
+            println!("{}", output::warning(format!("Failed to write the summary to {}: {}", path.display(), e)));
+        }
     }
+    if args.open {
+        let env = browser::Environment::current();
+        for url in results.iter().flatten().filter_map(|outcome| outcome.summary()?.release_url.as_deref()) {
+            browser::open(url, &browser::SystemLauncher, &env);
+        }
+    }
+    report_outcomes(tag, gh_clients, results)
+}
 
-    // 5. Create or update GitHub release
-    let release = if let Some(existing) = existing_release {
-        println!("Step 6: Using existing GitHub release...");
-        existing
-    } else {
-        println!("Step 6: Creating new GitHub release...");
-        gh_client.create_release(&tag).await?
+/// Post `event` to `--callback-url`, when set.
+async fn notify(args: &Cli, http_client: &Client, event: &serde_json::Value) {
+    let Some(url) = &args.callback_url else {
+        return;
     };
-    
-    println!("  ✅ Release ready (ID: {}).", release.id);
+    let callback = Callback::new(http_client.clone(), url).with_secret(args.callback_secret.clone());
+    // The run is over either way, so a dashboard that's down only gets a warning
+    if let Err(e) = callback.send(event).await {
+        println!("{}", output::warning(format!("Callback to {} failed: {}", url, e)));
+    }
+}
 
-    // 6. Retrieve the release notes
-    println!("Step 7: Getting release notes...");
-    let auto_notes = match &release.body {
-        Some(notes) if !notes.trim().is_empty() => {
-            println!("  ✅ Release notes retrieved.");
-            notes.clone()
-        },
-        _ => {
-            return Err("No release notes found or notes are empty.".into());
+/// Print one summary line per repository and fail with every error collected.
+fn report_outcomes(
+    tag: &str,
+    gh_clients: &[GitHubClient],
+    results: Vec<Result<Outcome, Box<dyn Error>>>,
+) -> Result<(), Box<dyn Error>> {
+    let mut failures = Vec::new();
+    for (gh_client, result) in gh_clients.iter().zip(results) {
+        match result {
+            Ok(Outcome::Released(_)) => println!("Release update process for '{}' in {} completed successfully.", tag, gh_client.repository()),
+            Ok(Outcome::ReleasedWithLocalFallback(_)) => println!(
+                "Release update process for '{}' in {} completed with the local formatter after OpenAI failed.",
+                tag,
+                gh_client.repository()
+            ),
+            Ok(Outcome::Planned) => println!("Planned release '{}' in {}; nothing changed.", tag, gh_client.repository()),
+            Ok(Outcome::NoChanges(since)) => {
+                println!("No changes since {} in {}, nothing to release.", since, gh_client.repository())
+            }
+            Ok(Outcome::Announced(url, _)) => println!("Announced '{}' in {} at {}.", tag, gh_client.repository(), url),
+            Ok(Outcome::AnnouncedWithLocalFallback(url, _)) => println!(
+                "Announced '{}' in {} at {} with the local formatter after OpenAI failed.",
+                tag,
+                gh_client.repository(),
+                url
+            ),
+            Ok(Outcome::ConvertedToDraft) => println!("Release '{}' in {} converted to draft.", tag, gh_client.repository()),
+            Ok(Outcome::AlreadyDraft) => println!("Release '{}' in {} was already a draft; nothing changed.", tag, gh_client.repository()),
+            Err(e) => failures.push(format!("{}: {}", gh_client.repository(), e)),
         }
-    };
-    
-    // 7. Send the notes to OpenAI for formatting.
-    let openai_client = OpenAIClient::new(http_client, openai_api_key, "gpt-4o");
+    }
 
-    let formatted_notes = openai_client.format_release_notes(&auto_notes).await?;
-    println!("Formatted Release Notes:\n{}", formatted_notes);
+    match failures.as_slice() {
+        [] => Ok(()),
+        [failure] => Err(failure.clone().into()),
+        _ => Err(format!("Release failed for {} repositories:\n{}", failures.len(), failures.join("\n")).into()),
+    }
+}
 
-    // 8. Update the GitHub release with the formatted release notes.
-    gh_client.update_release(release.id, &formatted_notes).await?;
-    println!("  ✅ Release notes updated successfully.");
+/// Run the preflight checks and fail if any critical one fails.
+async fn run_doctor(
+    args: &Cli,
+    settings: &Settings,
+    tag: Option<&str>,
+    http_client: &Client,
+    retry_policy: &RetryPolicy,
+) -> Result<(), Box<dyn Error>> {
+    let github_token = settings.github_token(|name| env::var(name).ok()).ok();
+    let gh_clients = github_clients(args, settings, http_client, github_token.as_deref().unwrap_or_default(), retry_policy)?;
+    let openai = (args.formatter == FormatterKind::Openai || !args.translate.is_empty())
+        .then(|| openai_client(args, settings, http_client.clone(), retry_policy.clone()));
 
-    Ok(())
+    let checks = doctor::run(&gh_clients, github_token.is_some(), openai, tag).await;
+    println!("{}", doctor::render(&checks));
+
+    if doctor::all_passed(&checks) {
+        Ok(())
+    } else {
+        Err("Preflight checks failed.".into())
+    }
+}
+
+/// Print the releases of every repository.
+async fn run_list_releases(
+    args: &Cli,
+    settings: &Settings,
+    filter: ReleaseFilter,
+    http_client: &Client,
+    retry_policy: &RetryPolicy,
+) -> Result<(), Box<dyn Error>> {
+    let github_token = settings.github_token(|name| env::var(name).ok())?;
+
+    for gh_client in github_clients(args, settings, http_client, &github_token, retry_policy)? {
+        println!("{}:\n{}\n", gh_client.repository(), list_releases(&gh_client, filter).await?);
+    }
+    Ok(())
+}
+
+/// Reformat every matching release in every repository, a few releases at a time.
+async fn run_reformat_all(
+    args: &Cli,
+    settings: &Settings,
+    filter: ReleaseFilter,
+    dry_run: bool,
+    http_client: &Client,
+    retry_policy: &RetryPolicy,
+) -> Result<(), Box<dyn Error>> {
+    let github_token = settings.github_token(|name| env::var(name).ok())?;
+    let formatter = notes_formatter(args, settings, http_client, retry_policy).await?;
+
+    let mut failures = Vec::new();
+    for gh_client in github_clients(args, settings, http_client, &github_token, retry_policy)? {
+        let results = reformat_all(&gh_client, &formatter, filter, dry_run, args.concurrency).await?;
+        for (tag, result) in results {
+            match result {
+                Ok(Reformat::Updated) => println!("  {}", output::success(format!("{} {}: notes updated.", gh_client.repository(), tag))),
+                Ok(Reformat::WouldUpdate) => println!("  {} {}: notes would change.", gh_client.repository(), tag),
+                Ok(Reformat::Unchanged) => println!("  {} {}: already formatted.", gh_client.repository(), tag),
+                Err(e) => failures.push(format!("{} {}: {}", gh_client.repository(), tag, e)),
+            }
+        }
+    }
+
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("Reformatting failed for {} releases:\n{}", failures.len(), failures.join("\n")).into())
+    }
+}
+
+/// What `reformat-all` did to one release.
+#[derive(Debug, PartialEq)]
+enum Reformat {
+    Updated,
+    /// The notes differ, but `--dry-run` left them alone
+    WouldUpdate,
+    Unchanged,
+}
+
+/// Reformat the notes of every release that has any, returning each release's tag and outcome.
+async fn reformat_all(
+    gh_client: &GitHubClient,
+    formatter: &NotesFormatter,
+    filter: ReleaseFilter,
+    dry_run: bool,
+    concurrency: usize,
+) -> Result<Vec<(String, Result<Reformat, Box<dyn Error>>)>, Box<dyn Error>> {
+    let releases: Vec<GitHubRelease> = release_list::select(gh_client.list_releases().await?, filter, gh_client.tag_prefix())
+        .into_iter()
+        .filter(|release| release.body.as_deref().is_some_and(|body| !body.trim().is_empty()))
+        .collect();
+
+    let reformats = releases.iter().map(|release| reformat_release(gh_client, release, formatter, dry_run)).collect();
+    let results = concurrency::run_bounded(reformats, concurrency).await;
+    Ok(releases.iter().map(|release| release.tag_name.clone()).zip(results).collect())
+}
+
+/// Format a release's body again and write it back only when the result differs.
+async fn reformat_release(
+    gh_client: &GitHubClient,
+    release: &GitHubRelease,
+    formatter: &NotesFormatter,
+    dry_run: bool,
+) -> Result<Reformat, Box<dyn Error>> {
+    let body = release.body.as_deref().unwrap_or_default();
+    // Bodies from --append-raw runs are formatted again from the raw notes they kept
+    let (_, raw_notes) = formatter::split_raw_notes(body);
+    let mut formatted = formatter.format(raw_notes.unwrap_or(body)).await?;
+    if let Some(raw_notes) = raw_notes {
+        formatted = formatter::append_raw_notes(&formatted, raw_notes);
+    }
+
+    if formatted.trim() == body.trim() {
+        Ok(Reformat::Unchanged)
+    } else if dry_run {
+        Ok(Reformat::WouldUpdate)
+    } else {
+        gh_client.update_release(release.id, &formatted, None).await?;
+        Ok(Reformat::Updated)
+    }
+}
+
+/// The filtered, sorted release table for one repository.
+async fn list_releases(gh_client: &GitHubClient, filter: ReleaseFilter) -> Result<String, Box<dyn Error>> {
+    let releases = release_list::select(gh_client.list_releases().await?, filter, gh_client.tag_prefix());
+    Ok(release_list::render(&releases))
+}
+
+/// Build one GitHub client per repository in the resolved settings.
+fn github_clients(
+    args: &Cli,
+    settings: &Settings,
+    http_client: &Client,
+    github_token: &str,
+    retry_policy: &RetryPolicy,
+) -> Result<Vec<GitHubClient>, Box<dyn Error>> {
+    let release_kind = ReleaseKind::from_flags(args.stable, args.latest);
+    let default_owner = settings.owner.as_deref().unwrap_or("Human-Glitch");
+    let repos = settings.repos.clone().unwrap_or_else(|| vec!["llm-playground".to_string()]);
+
+    let mut gh_clients = Vec::new();
+    for spec in &repos {
+        let (owner, repo) = parse_repository(spec, default_owner)?;
+        let mut gh_client = GitHubClient::new(http_client.clone(), github_token.to_string())
+            .with_repository(&owner, &repo)
+            .with_retry_policy(retry_policy.clone())
+            .with_branch_fallback(args.fallback)
+            .with_strict_ordering(args.strict_ordering)
+            .with_prerelease_bump(args.prerelease_bump, args.prerelease_pad)
+            .with_tag_prefix(&args.tag_prefix)
+            .with_release_kind(release_kind)
+            .with_api_version(&args.github_api_version)
+            .with_headers(&args.headers);
+        if let Some(base_url) = &settings.base_url {
+            gh_client = gh_client.with_base_url(base_url);
+        }
+        gh_clients.push(gh_client);
+    }
+    Ok(gh_clients)
+}
+
+/// Build the OpenAI client for `--model`, sent to the gateway of the first matching route.
+///
+/// Without a matching route requests go to the `--provider`'s API, or for OpenAI to `OPENAI_BASE_URL` when set.
+fn openai_client(
+    args: &Cli,
+    settings: &Settings,
+    http_client: Client,
+    retry_policy: RetryPolicy,
+) -> Result<OpenAIClient, String> {
+    let chat_only = args.seed.is_some() || args.presence_penalty.is_some() || args.frequency_penalty.is_some();
+    if args.openai_api == OpenAiApi::Responses && chat_only {
+        return Err("--seed, --presence-penalty and --frequency-penalty need --openai-api chat.".to_string());
+    }
+
+    let model = args.model();
+    let ModelSettings { base_url, api_key, preset_instructions, response_shape } = model_settings(args, settings, model)?;
+    let mut client = OpenAIClient::new(http_client, api_key, model)
+        .with_base_url(&base_url)
+        .with_prompt_preset(preset_instructions)
+        .with_response_shape(response_shape)
+        .with_model_fallbacks(args.model_fallbacks.clone());
+    // Each fallback goes where its own route says, with its own preset
+    for fallback in &args.model_fallbacks {
+        client = client.with_fallback_settings(fallback, model_settings(args, settings, fallback)?);
+    }
+
+    Ok(client
+        .with_retry_policy(retry_policy)
+        .with_seed(args.seed)
+        .with_penalties(args.presence_penalty, args.frequency_penalty)
+        .with_context_window(args.context_window)
+        .with_max_parallel(args.max_parallel_openai)
+        .with_chunk_threshold(args.chunk_threshold)
+        .with_api(args.openai_api)
+        .with_user(args.openai_user.clone()))
+}
+
+/// The gateway, key, prompt preset and response shape `model` gets from its route, its preset and the flags.
+fn model_settings(args: &Cli, settings: &Settings, model: &str) -> Result<ModelSettings, String> {
+    let route = settings.routes.as_deref().and_then(|routes| config::route_for(routes, model));
+    let key_env = route.and_then(|route| route.key_env.as_deref());
+    // A route with its own key variable talks to another gateway, so the OpenAI key file doesn't apply
+    let key_file = settings
+        .openai_key_file
+        .as_deref()
+        .filter(|_| key_env.is_none() && args.provider == Provider::Openai);
+    let api_key = match key_env.or(args.provider.key_env()) {
+        Some(key_env) => config::resolve_api_key(key_file, key_env, env::var(key_env).ok())?,
+        // Ollama accepts any key
+        None => "ollama".to_string(),
+    };
+
+    let preset = args
+        .prompt_preset
+        .as_deref()
+        .or_else(|| settings.model_presets.as_deref().and_then(|presets| config::preset_for(presets, model)))
+        .unwrap_or(openai_client::DEFAULT_PROMPT_PRESET);
+
+    let base_url = match route {
+        Some(route) => route.base_url.clone(),
+        None if args.provider != Provider::Openai => args.provider.base_url().to_string(),
+        None => env::var("OPENAI_BASE_URL").unwrap_or_else(|_| openai_client::OPENAI_BASE_URL.to_string()),
+    };
+
+    Ok(ModelSettings {
+        base_url,
+        api_key,
+        preset_instructions: openai_client::prompt_preset(preset)?,
+        response_shape: args
+            .response_shape
+            .or_else(|| route.and_then(|route| route.response_shape))
+            .unwrap_or(args.openai_api.response_shape()),
+    })
+}
+
+/// The `--summarize` settings, reading the reduce prompt from its file when one is given.
+fn summary_options(args: &Cli) -> Result<Option<SummaryOptions>, Box<dyn Error>> {
+    if !args.summarize {
+        return Ok(None);
+    }
+
+    let reduce_prompt = match &args.reduce_prompt_file {
+        Some(path) => std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read reduce prompt {}: {}", path.display(), e))?,
+        None => summarize::DEFAULT_REDUCE_PROMPT.to_string(),
+    };
+
+    Ok(Some(SummaryOptions {
+        chunk_lines: args.summary_chunk_lines,
+        max_calls: args.max_llm_calls as usize,
+        reduce_prompt,
+    }))
+}
+
+/// `owner/name` of the checkout's origin remote, accepting the configured GitHub Enterprise host.
+fn repository_from_git(args: &Cli, settings: &Settings) -> Result<String, Box<dyn Error>> {
+    let path = args.repo_path.clone().unwrap_or_else(|| PathBuf::from("."));
+    let url = LocalGit::new(Box::new(SystemGit), path)
+        .remote_url()
+        .map_err(|e| format!("--repo-from-git couldn't read the origin remote: {}", e))?;
+    let extra_hosts: Vec<&str> = settings.base_url.as_deref().and_then(local_git::url_host).into_iter().collect();
+    Ok(local_git::parse_remote_url(&url, &extra_hosts)?)
+}
+
+/// Split a repository given as `name` or `owner/name` into its owner and name.
+fn parse_repository(spec: &str, default_owner: &str) -> Result<(String, String), Box<dyn Error>> {
+    match spec.split_once('/') {
+        None if !spec.is_empty() => Ok((default_owner.to_string(), spec.to_string())),
+        Some((owner, repo)) if !owner.is_empty() && !repo.is_empty() && !repo.contains('/') => {
+            Ok((owner.to_string(), repo.to_string()))
+        }
+        _ => Err(format!("Invalid repository '{}', expected name or owner/name.", spec).into()),
+    }
+}
+
+/// Process the GitHub release including checking for existing pre-releases,
+/// incrementing the version if needed, and creating or updating releases.
+async fn process_release(
+    gh_client: &GitHubClient,
+    requested_tag: &str,
+    formatter: &NotesFormatter,
+    interrupt: &InterruptState,
+    local_git: Option<&LocalGit>,
+    progress: &dyn ProgressReporter,
+    options: &ReleaseOptions,
+) -> Result<Outcome, Box<dyn Error>> {
+    let repository = gh_client.repository();
+    progress.started(&repository);
+    let tracker = StepTracker::new(progress);
+    let result = match plan_release(gh_client, requested_tag, local_git, options).await {
+        Ok(plan) => {
+            tracker.planned(&repository);
+            apply_plan(gh_client, plan, formatter, interrupt, local_git, &tracker, options).await
+        }
+        Err(e) => nothing_to_release(e, options),
+    };
+    if let Err(e) = &result {
+        // So --progress-json shows which step the run stopped in
+        tracker.step(&repository, tracker.current_step(&repository), StepStatus::Failed, &e.to_string());
+    }
+    result
+}
+
+/// Make sure the tag about to be deleted is the one this release made: its commit must be the commit
+/// about to be tagged, or in the history of what the deleted release was created from.
+///
+/// A tag that doesn't exist has nothing to check.
+async fn verify_tag_before_delete(
+    gh_client: &GitHubClient,
+    tag: &str,
+    release_target: Option<&str>,
+    commit_sha: &str,
+) -> Result<(), Box<dyn Error>> {
+    let Some(tag_commit) = gh_client.tag_commit(tag).await? else {
+        return Ok(());
+    };
+    if tag_commit == commit_sha {
+        return Ok(());
+    }
+    for target in release_target.into_iter().chain([commit_sha]) {
+        // A target that can't be compared, e.g. a deleted branch, just doesn't vouch for the tag
+        if let Ok(comparison) = gh_client.compare(&tag_commit, target).await {
+            if matches!(comparison.status.as_deref(), Some("ahead" | "identical")) {
+                return Ok(());
+            }
+        }
+    }
+
+    let expected = match release_target {
+        Some(target) => format!("{} or {}", target, commit_sha),
+        None => commit_sha.to_string(),
+    };
+    Err(format!(
+        "Tag {} points at {}, which isn't in the history of {}. Refusing to delete it; check the tag, or pass --force to delete it anyway.",
+        tag, tag_commit, expected
+    )
+    .into())
+}
+
+/// The `--deadline-secs` budget of the whole run.
+#[derive(Clone, Copy)]
+struct Deadline {
+    budget: Duration,
+    at: tokio::time::Instant,
+}
+
+impl Deadline {
+    fn starting_now(budget: Duration) -> Self {
+        Deadline { budget, at: tokio::time::Instant::now() + budget }
+    }
+}
+
+/// Cancel `release` when it's still running at the deadline, failing with the step it was in.
+async fn within_deadline(
+    deadline: Option<Deadline>,
+    gh_client: &GitHubClient,
+    tracker: &StepTracker<'_>,
+    release: impl Future<Output = Result<Outcome, Box<dyn Error>>>,
+) -> Result<Outcome, Box<dyn Error>> {
+    let Some(deadline) = deadline else {
+        return release.await;
+    };
+    match tokio::time::timeout_at(deadline.at, release).await {
+        Ok(result) => result,
+        Err(_) => {
+            let repository = gh_client.repository();
+            let step = tracker.current_step(&repository);
+            let message = format!(
+                "Deadline of {:?} exceeded during {}. The release may be in a partial state; re-run with the same tag to finish.",
+                deadline.budget, step
+            );
+            tracker.step(&repository, step, StepStatus::Failed, &message);
+            Err(message.into())
+        }
+    }
+}
+
+/// A run that found no changes to release succeeds, unless `--fail-on-empty` says otherwise; other errors pass through.
+fn nothing_to_release(e: Box<dyn Error>, options: &ReleaseOptions) -> Result<Outcome, Box<dyn Error>> {
+    match e.downcast::<NoChanges>() {
+        Ok(no_changes) if !options.fail_on_empty => {
+            println!("{}", output::info(no_changes.to_string()));
+            Ok(Outcome::NoChanges(no_changes.since))
+        }
+        Ok(no_changes) => Err(no_changes),
+        Err(e) => Err(e),
+    }
+}
+
+/// Look up everything the release depends on and decide what to change, without changing anything.
+async fn plan_release(
+    gh_client: &GitHubClient,
+    requested_tag: &str,
+    local_git: Option<&LocalGit>,
+    options: &ReleaseOptions,
+) -> Result<ReleasePlan, Box<dyn Error>> {
+    // Display the branch naming format for improved logging
+    println!("🚀 Starting release process for '{}' using branch format release/{}{{major}}.{{minor}}.x...", 
+        requested_tag,
+        gh_client.tag_prefix()
+    );
+    
+    if let Some(previous_tag) = &options.previous_tag {
+        changelog::validate_previous_tag(gh_client, previous_tag).await?;
+    }
+
+    // Determine if we need to increment the version based on criteria
+    let tag = gh_client.determine_tag_version(requested_tag).await?;
+    
+    // If the tag is different, we're creating a new incremented version
+    let is_incremented_version = tag != requested_tag;
+    
+    if is_incremented_version {
+        println!("⬆️ Using incremented version {} instead of {}", tag, requested_tag);
+    }
+
+    let mut actions = Vec::new();
+    let mut skipped = Vec::new();
+    
+    // 1. Check for existing GitHub release for the new tag.
+    println!("Step 1: Checking for existing GitHub release...");
+    // Existing releases are kept (with their tags) for incremented versions and, when asked, for prereleases
+    let mut keep_existing = is_incremented_version;
+    // With --update-in-place a stable release keeps existing while its tag moves to the new commit
+    let mut in_place = false;
+    let mut existing_release = None;
+    // What the release about to be deleted was created from, to check its tag against
+    let mut deleted_release_target = None;
+    // A draft left by an earlier run is picked up too, instead of being duplicated
+    if let Some(release) = gh_client.find_release_by_tag_including_drafts(&tag).await? {
+        if is_incremented_version {
+            // For incremented versions, update the existing release instead of deleting it
+            println!("  Found existing release for incremented version (ID: {}). Will update instead of recreate.", release.id);
+            skipped.push(("delete_release", format!("keeping release {}", release.id)));
+            existing_release = Some(release);
+        } else if options.immutable_prereleases && release.prerelease == Some(true) {
+            println!("  Found existing prerelease (ID: {}). Keeping it as --immutable-prereleases is set.", release.id);
+            keep_existing = true;
+            skipped.push(("delete_release", format!("keeping prerelease {}", release.id)));
+            existing_release = Some(release);
+        } else if options.update_in_place && release.prerelease != Some(true) {
+            println!("  Found existing release (ID: {}). Updating it in place as --update-in-place is set.", release.id);
+            in_place = true;
+            skipped.push(("delete_release", format!("updating release {} in place", release.id)));
+            existing_release = Some(release);
+        } else {
+            // A published release with notes is finished; recreating it is almost always a mistake
+            let finalized = release.prerelease != Some(true)
+                && release.draft != Some(true)
+                && release.body.as_deref().is_some_and(|body| !body.trim().is_empty());
+            if finalized && !options.force {
+                return Err(format!(
+                    "Release {} (ID: {}) is already finalized: it's not a prerelease and it has notes. Re-running the release would delete and recreate it. Pass --force to do that anyway, or --update-only to only reformat its notes.",
+                    tag, release.id
+                )
+                .into());
+            }
+            // Only delete if not an incremented version, preserving immutability of existing releases
+            println!("  Found existing release (ID: {}). It will be deleted and recreated.", release.id);
+            actions.push(PlannedAction::DeleteRelease { id: release.id });
+            deleted_release_target = release.target_commitish.filter(|target| !target.is_empty());
+        }
+    } else {
+        println!("  {}", output::success("No existing release found. Proceeding with creation."));
+        skipped.push(("delete_release", "no existing release".to_string()));
+    }
+    // Only a new release is marked as latest, and GitHub never marks a prerelease as latest anyway
+    if gh_client.release_kind() == ReleaseKind::Latest {
+        if let Some(release) = existing_release.as_ref().filter(|release| release.prerelease == Some(true)) {
+            return Err(format!(
+                "--latest can't mark {} as the latest release: its existing prerelease (ID: {}) is kept and stays a prerelease. Run `finalize` on it instead.",
+                tag, release.id
+            )
+            .into());
+        }
+    }
+
+    // 2. For non-incremented versions, we might need to delete the tag
+    if local_git.is_some() {
+        skipped.push(("delete_tag", "the local clone force-updates the tag".to_string()));
+    } else if in_place {
+        skipped.push(("delete_tag", "the tag is moved to the new commit instead".to_string()));
+    } else if !keep_existing {
+        actions.push(PlannedAction::DeleteTag);
+    } else {
+        skipped.push(("delete_tag", "keeping the tag of the existing release".to_string()));
+    }
+
+    // Determine which branch to use for the release
+    let branch = gh_client.get_release_branch_for_tag(&tag).await?;
+    println!("Step 3: Using release branch: {}", branch);
+    if let Some(git) = local_git.filter(|_| !options.allow_dirty) {
+        // Tagging from a clone that's elsewhere, or mid-change, is rarely what was meant
+        git.ensure_clean(&branch)?;
+    }
+    
+    // 3. Retrieve the latest commit SHA from the release branch, unless a commit was given.
+    let latest_commit = match (&options.sha, local_git) {
+        (Some(sha), _) => {
+            println!("Step 4: Using commit {} instead of the latest on branch {}...", sha, branch);
+            Ok(sha.clone())
+        }
+        (None, Some(git)) => {
+            println!("Step 4: Retrieving latest commit from branch {}...", branch);
+            git.get_latest_commit_sha(&branch)
+        }
+        (None, None) => {
+            println!("Step 4: Retrieving latest commit from branch {}...", branch);
+            gh_client.get_latest_commit_sha(&branch).await
+        }
+    };
+    let commit_sha = match latest_commit {
+        Ok(sha) => {
+            println!("  {}", output::success(format!("Found commit: {}", sha)));
+            sha
+        },
+        Err(e) => {
+            return Err(format!("Failed to get latest commit from branch '{}': {}", branch, e).into());
+        }
+    };
+
+    // The commit should build on the previous release rather than be some unrelated branch head
+    if let Some(base) = changelog::history_base(gh_client, options.previous_tag.as_deref(), &tag).await? {
+        let status = changelog::check_branch_history(gh_client, &base, &branch, &commit_sha, options.strict_branch).await?;
+        // Compare-based notes would be empty, so stop before anything is deleted or created
+        if options.notes_source == NotesSource::Compare && status.as_deref() == Some("identical") {
+            return Err(NoChanges { since: base }.into());
+        }
+    }
+
+    if actions.contains(&PlannedAction::DeleteTag) && !options.force {
+        verify_tag_before_delete(gh_client, &tag, deleted_release_target.as_deref(), &commit_sha).await?;
+    }
+
+    // 4. Create an annotated tag object and then its reference if it doesn't exist
+    if in_place {
+        actions.push(match local_git {
+            Some(_) => PlannedAction::PushTag,
+            None => PlannedAction::RetargetTag { commit_date: options.use_commit_date },
+        });
+    } else if existing_release.is_none() {
+        actions.push(match local_git {
+            Some(_) => PlannedAction::PushTag,
+            None => PlannedAction::CreateTag { commit_date: options.use_commit_date },
+        });
+        // 5. Create the GitHub release
+        actions.push(PlannedAction::CreateRelease);
+    } else {
+        skipped.push(("create_tag", "the existing release keeps its tag".to_string()));
+    }
+
+    // 6-8. Fetch and format the notes, then write them to the release
+    actions.push(PlannedAction::UpdateNotes);
+
+    // 9. Upload the assets
+    if !options.assets.is_empty() {
+        actions.push(PlannedAction::UploadAssets { names: options.assets.iter().map(Asset::name).collect() });
+    }
+
+    Ok(ReleasePlan {
+        requested_tag: requested_tag.to_string(),
+        tag,
+        branch,
+        commit_sha,
+        existing_release,
+        actions,
+        skipped,
+    })
+}
+
+/// Carry out a release plan, step by step.
+async fn apply_plan(
+    gh_client: &GitHubClient,
+    plan: ReleasePlan,
+    formatter: &NotesFormatter,
+    interrupt: &InterruptState,
+    local_git: Option<&LocalGit>,
+    progress: &dyn ProgressReporter,
+    options: &ReleaseOptions,
+) -> Result<Outcome, Box<dyn Error>> {
+    let repository = gh_client.repository();
+    let report = |step: &str, status: StepStatus, detail: String| progress.step(&repository, step, status, &detail);
+    let tag = plan.tag.as_str();
+    // Once the release is deleted, the run always gets as far as recreating it
+    interrupt.checkpoint("planning")?;
+
+    // 1. Delete the existing release unless it's kept
+    match plan.actions.iter().find_map(|action| match action {
+        PlannedAction::DeleteRelease { id } => Some(*id),
+        _ => None,
+    }) {
+        Some(id) => {
+            println!("  Deleting existing release (ID: {})...", id);
+            gh_client.delete_release(id).await?;
+            println!("  {}", output::success("Existing release deleted successfully."));
+            report("delete_release", StepStatus::Ok, format!("deleted release {}", id));
+        }
+        None => report("delete_release", StepStatus::Skipped, plan.skip_reason("delete_release")),
+    }
+
+    let tag_action = plan
+        .actions
+        .iter()
+        .find(|action| {
+            matches!(action, PlannedAction::CreateTag { .. } | PlannedAction::RetargetTag { .. } | PlannedAction::PushTag)
+        });
+    // Read before the tag is deleted, so a recreated or moved tag keeps its original message
+    let existing_tag_message = match tag_action {
+        Some(PlannedAction::CreateTag { .. } | PlannedAction::RetargetTag { .. }) => gh_client.tag_message(tag).await?,
+        Some(PlannedAction::PushTag) => local_git
+            .ok_or("the plan pushes the tag but there's no local clone")?
+            .tag_message(tag)?,
+        _ => None,
+    };
+
+    // 2. Delete the tag so it can point at the latest commit
+    if plan.has(&PlannedAction::DeleteTag) {
+        println!("Step 2: Checking existing Git tag...");
+        match gh_client.delete_tag(tag).await {
+            Ok(_) => {
+                println!("  {}", output::success(format!("Successfully deleted tag {}", tag)));
+                report("delete_tag", StepStatus::Ok, format!("deleted tag {}", tag));
+            }
+            Err(e) => {
+                println!("  {}", output::info(format!("Tag {} doesn't exist or was already deleted ({})", tag, e)));
+                // Not returning error as this is an acceptable condition
+                report("delete_tag", StepStatus::Skipped, format!("tag {} not found", tag));
+            }
+        }
+    } else {
+        println!("Step 2: Skipping tag deletion; {}.", plan.skip_reason("delete_tag"));
+        report("delete_tag", StepStatus::Skipped, plan.skip_reason("delete_tag"));
+    }
+
+    report("resolve_branch", StepStatus::Ok, plan.branch.clone());
+    report("latest_commit", StepStatus::Ok, plan.commit_sha.clone());
+
+    // 3. Create an annotated tag object and then its reference
+    let tag_message = existing_tag_message.unwrap_or_else(|| format!("Release {}", tag));
+    match tag_action {
+        Some(PlannedAction::PushTag) => {
+            println!("Step 5: Creating annotated tag...");
+            local_git
+                .ok_or("the plan pushes the tag but there's no local clone")?
+                .tag_and_push(tag, &tag_message, &plan.commit_sha)?;
+        }
+        Some(action @ (PlannedAction::CreateTag { commit_date } | PlannedAction::RetargetTag { commit_date })) => {
+            println!("Step 5: Creating annotated tag...");
+            // Only the date comes from the commit; the tag is still attributed to the token's user
+            let tagger = if *commit_date {
+                let date = gh_client.commit_date(&plan.commit_sha).await?;
+                Some(gh_client.tagger_at(&date).await?)
+            } else {
+                None
+            };
+            let tag_object_sha = gh_client.create_tag_object(tag, &tag_message, &plan.commit_sha, tagger.as_ref()).await?;
+            if matches!(action, PlannedAction::RetargetTag { .. }) {
+                gh_client.force_update_tag_ref(tag, &tag_object_sha).await?;
+            } else {
+                gh_client.create_tag_ref(tag, &tag_object_sha).await?;
+            }
+        }
+        _ => {
+            println!("Step 5: Skipping tag creation as it already exists for the existing release.");
+            report("create_tag", StepStatus::Skipped, plan.skip_reason("create_tag"));
+        }
+    }
+    if tag_action.is_some() {
+        println!("  {}", output::success("Tag created and pushed successfully."));
+        report("create_tag", StepStatus::Ok, tag.to_string());
+    }
+
+    // With --no-auto-notes a new release starts out with the compare notes instead of GitHub's
+    let seed_notes = match options.notes_source {
+        NotesSource::Compare if options.no_auto_notes && plan.existing_release.is_none() => {
+            Some(compare_notes(gh_client, tag, options).await?)
+        }
+        _ => None,
+    };
+
+    // 4. Create or reuse the GitHub release
+    let release = match plan.existing_release {
+        Some(existing) => {
+            println!("Step 6: Using existing GitHub release...");
+            existing
+        }
+        None => {
+            println!("Step 6: Creating new GitHub release...");
+            // A concurrent run may have created it since the lookup
+            gh_client.get_or_create_release(tag, release_title(tag, options).as_deref(), seed_notes.as_deref()).await?
+        }
+    };
+    
+    println!("  {}", output::success(format!("Release ready (ID: {}).", release.id)));
+    report("create_release", StepStatus::Ok, format!("release {}", release.id));
+    interrupt.checkpoint("Step 6 (release creation)")?;
+
+    // 5. Retrieve the release notes
+    println!("Step 7: Getting release notes...");
+    let mut notes_source = "release_body";
+    let auto_notes = match options.notes_source {
+        NotesSource::Compare => {
+            notes_source = "compare";
+            match seed_notes {
+                Some(notes) => notes,
+                None => compare_notes(gh_client, tag, options).await?,
+            }
+        }
+        NotesSource::Github if options.regenerate_notes => {
+            let base = changelog::generate_notes_base(gh_client, options.previous_tag.as_deref(), tag, options.notes_base).await?;
+            let notes = gh_client.generate_notes(tag, base.as_deref(), None).await?;
+            if notes.trim().is_empty() {
+                return Err("GitHub generated empty release notes.".into());
+            }
+            println!("  {}", output::success("Release notes regenerated."));
+            notes_source = "regenerated";
+            notes
+        }
+        NotesSource::Github => match &release.body {
+            Some(notes) if !notes.trim().is_empty() => {
+                println!("  {}", output::success("Release notes retrieved."));
+                // A body from an --append-raw run is formatted again from the raw notes it kept
+                formatter::split_raw_notes(notes).1.unwrap_or(notes).to_string()
+            },
+            _ if options.no_empty_body_fallback => {
+                return Err("No release notes found or notes are empty.".into());
+            }
+            _ => {
+                notes_source = "generated";
+                empty_body_notes(gh_client, tag, options).await?
+            }
+        },
+    };
+    
+    report("fetch_notes", StepStatus::Ok, format!("{} lines", auto_notes.lines().count()));
+
+    // 6. Format the notes (with OpenAI unless the local formatter was chosen).
+    let formatter_input = changelog::with_extra_notes(&auto_notes, &options.extra_notes);
+    let (formatted_notes, fell_back) = formatter.format_or_fall_back(&formatter_input, options.llm_fallback.as_ref()).await?;
+
+    let previous_tag = if options.footer_compare {
+        changelog::resolve_previous_tag(gh_client, options.previous_tag.as_deref(), tag).await?
+    } else {
+        None
+    };
+    let formatted_notes = finish_notes(gh_client, tag, formatted_notes, &auto_notes, previous_tag.as_deref(), options).await?;
+    println!("Formatted Release Notes:\n{}", formatted_notes);
+    let fallback_note = if fell_back { ", local fallback" } else { "" };
+    report("format_notes", StepStatus::Ok, format!("{} characters{}", formatted_notes.len(), fallback_note));
+    if options.validate_links {
+        warn_about_broken_links(gh_client, &formatted_notes).await;
+    }
+
+    // 7. Update the GitHub release with the formatted release notes.
+    save_notes(gh_client, release.id, tag, &formatted_notes, release_title(tag, options).as_deref()).await?;
+    println!("  {}", output::success("Release notes updated successfully."));
+    report("update_release", StepStatus::Ok, format!("release {}", release.id));
+
+    // 8. Upload the assets, a few at a time.
+    if !options.assets.is_empty() {
+        println!("Step 9: Uploading {} assets...", options.assets.len());
+        upload_assets(gh_client, &release, options).await?;
+        report("upload_assets", StepStatus::Ok, format!("{} assets", options.assets.len()));
+    }
+
+    let summary = ReleaseSummary {
+        requested_tag: plan.requested_tag.clone(),
+        tag: tag.to_string(),
+        incremented: plan.requested_tag != tag,
+        branch: Some(plan.branch.clone()),
+        commit_sha: Some(plan.commit_sha.clone()),
+        release_id: Some(release.id),
+        release_url: release.html_url.clone(),
+        notes_source: Some(notes_source),
+        formatter: Some(formatter_name(formatter, fell_back)),
+        tickets: options.ticket_finder.tickets(&formatted_notes),
+        timings: Vec::new(),
+    };
+    Ok(Outcome::released(summary, fell_back))
+}
+
+/// `openai`, `local` or, when OpenAI failed, `local_fallback`.
+fn formatter_name(formatter: &NotesFormatter, fell_back: bool) -> &'static str {
+    match formatter {
+        _ if fell_back => "local_fallback",
+        NotesFormatter::OpenAI(_) => "openai",
+        NotesFormatter::Local(_) => "local",
+    }
+}
+
+/// The summary of a run that formatted `notes` for an existing release, or for a preview with none.
+fn notes_summary(
+    tag: &str,
+    release: Option<&GitHubRelease>,
+    notes_source: &'static str,
+    notes: &str,
+    formatter: &NotesFormatter,
+    fell_back: bool,
+    options: &ReleaseOptions,
+) -> ReleaseSummary {
+    ReleaseSummary {
+        release_id: release.map(|release| release.id),
+        release_url: release.and_then(|release| release.html_url.clone()),
+        notes_source: Some(notes_source),
+        formatter: Some(formatter_name(formatter, fell_back)),
+        tickets: options.ticket_finder.tickets(notes),
+        ..ReleaseSummary::for_tag(tag)
+    }
+}
+
+/// Notes for a release whose body is empty, as on GitHub Enterprise versions that ignore
+/// `generate_release_notes`: the generate-notes endpoint's, else the compare notes.
+async fn empty_body_notes(gh_client: &GitHubClient, tag: &str, options: &ReleaseOptions) -> Result<String, Box<dyn Error>> {
+    println!("{}", output::warning("The release body is empty. Generating the notes instead."));
+    let base = changelog::generate_notes_base(gh_client, options.previous_tag.as_deref(), tag, options.notes_base).await?;
+    match gh_client.generate_notes(tag, base.as_deref(), None).await {
+        Ok(notes) if !notes.trim().is_empty() => {
+            println!("  {}", output::success("Release notes generated."));
+            return Ok(notes);
+        }
+        Ok(_) => println!("{}", output::warning("GitHub generated empty notes. Building them from the commits instead.")),
+        Err(e) => println!("{}", output::warning(format!("Generating the notes failed ({}). Building them from the commits instead.", e))),
+    }
+    compare_notes(gh_client, tag, options)
+        .await
+        .map_err(|e| format!("No release notes found or notes are empty, and building them from the commits failed: {}", e).into())
+}
+
+/// Upload every asset, reporting each one, and fail once all have been tried if any upload failed.
+async fn upload_assets(gh_client: &GitHubClient, release: &GitHubRelease, options: &ReleaseOptions) -> Result<(), Box<dyn Error>> {
+    let uploader = ReleaseUploader { client: gh_client, release };
+    let results = assets::upload_all(&uploader, &options.assets, options.asset_concurrency).await;
+
+    let mut failures = Vec::new();
+    for (asset, result) in options.assets.iter().zip(results) {
+        match result {
+            Ok(()) => println!("  {}", output::success(format!("Uploaded {}.", asset.name()))),
+            Err(e) => failures.push(format!("{}: {}", asset.name(), e)),
+        }
+    }
+
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("Failed to upload {} of {} assets:\n{}", failures.len(), options.assets.len(), failures.join("\n")).into())
+    }
+}
+
+/// Reformat an existing release's current body and write it back, for releases another job created.
+async fn update_notes(
+    gh_client: &GitHubClient,
+    tag: &str,
+    formatter: &NotesFormatter,
+    options: &ReleaseOptions,
+) -> Result<Outcome, Box<dyn Error>> {
+    println!("📝 Updating the notes of release '{}'...", tag);
+
+    let release = gh_client
+        .get_release_by_tag(tag)
+        .await?
+        .ok_or_else(|| format!("No release found for tag {}.", tag))?;
+    let notes = match &release.body {
+        // A body from an --append-raw run is formatted again from the raw notes it kept
+        Some(body) if !body.trim().is_empty() => formatter::split_raw_notes(body).1.unwrap_or(body).to_string(),
+        _ => return Err(format!("Release {} has no notes to format.", tag).into()),
+    };
+
+    let formatter_input = changelog::with_extra_notes(&notes, &options.extra_notes);
+    let (formatted_notes, fell_back) = formatter.format_or_fall_back(&formatter_input, options.llm_fallback.as_ref()).await?;
+    let previous_tag = if options.footer_compare {
+        changelog::resolve_previous_tag(gh_client, options.previous_tag.as_deref(), tag).await?
+    } else {
+        None
+    };
+    let formatted_notes = finish_notes(gh_client, tag, formatted_notes, &notes, previous_tag.as_deref(), options).await?;
+    println!("Formatted Release Notes:\n{}", formatted_notes);
+
+    save_notes(gh_client, release.id, tag, &formatted_notes, release_title(tag, options).as_deref()).await?;
+    println!("  {}", output::success("Release notes updated successfully."));
+
+    let summary = notes_summary(tag, Some(&release), "release_body", &formatted_notes, formatter, fell_back, options);
+    Ok(Outcome::released(summary, fell_back))
+}
+
+/// Format the tag's notes and post them as a discussion in `category`; no tag or release is touched.
+async fn announce_release(
+    gh_client: &GitHubClient,
+    tag: &str,
+    formatter: &NotesFormatter,
+    category: &str,
+    options: &ReleaseOptions,
+) -> Result<Outcome, Box<dyn Error>> {
+    println!("📣 Announcing '{}' as a discussion in {}...", tag, category);
+
+    let (notes_source, notes) = match options.notes_source {
+        NotesSource::Compare => ("compare", compare_notes(gh_client, tag, options).await?),
+        NotesSource::Github => {
+            let base = changelog::generate_notes_base(gh_client, options.previous_tag.as_deref(), tag, options.notes_base).await?;
+            // Without the tag, GitHub only knows where the notes end from the release branch
+            let target = match gh_client.tag_exists(tag).await? {
+                true => None,
+                false => Some(gh_client.get_release_branch_for_tag(tag).await?),
+            };
+            ("generated", gh_client.generate_notes(tag, base.as_deref(), target.as_deref()).await?)
+        }
+    };
+    if notes.trim().is_empty() {
+        return Err(format!("No release notes found for {}.", tag).into());
+    }
+
+    let formatter_input = changelog::with_extra_notes(&notes, &options.extra_notes);
+    let (formatted_notes, fell_back) = formatter.format_or_fall_back(&formatter_input, options.llm_fallback.as_ref()).await?;
+    let previous_tag = if options.footer_compare {
+        changelog::resolve_previous_tag(gh_client, options.previous_tag.as_deref(), tag).await?
+    } else {
+        None
+    };
+    let formatted_notes = finish_notes(gh_client, tag, formatted_notes, &notes, previous_tag.as_deref(), options).await?;
+    println!("Formatted Release Notes:\n{}", formatted_notes);
+
+    let title = release_title(tag, options).unwrap_or_else(|| tag.to_string());
+    let url = gh_client.create_discussion(category, &title, &formatted_notes).await?;
+    println!("  {}", output::success(format!("Discussion created: {}", url)));
+    let summary = notes_summary(tag, None, notes_source, &formatted_notes, formatter, fell_back, options);
+    Ok(Outcome::announced(url, summary, fell_back))
+}
+
+/// Save the notes to the release; when the update fails for good, the error carries them for a manual paste.
+async fn save_notes(
+    gh_client: &GitHubClient,
+    release_id: u64,
+    tag: &str,
+    notes: &str,
+    title: Option<&str>,
+) -> Result<(), Box<dyn Error>> {
+    if let Err(e) = gh_client.update_release(release_id, notes, title).await {
+        return Err(format!("{} Paste the formatted notes into the release by hand:\n{}", e, unsaved_notes(tag, notes)).into());
+    }
+    Ok(())
+}
+
+/// The notes between markers, so they're easy to copy out of a CI log.
+fn unsaved_notes(tag: &str, notes: &str) -> String {
+    format!(
+        "{}\n----- BEGIN NOTES {} -----\n{}\n----- END NOTES {} -----",
+        output::warning(format!("The notes of {} couldn't be saved.", tag)),
+        tag,
+        notes.trim_end(),
+        tag
+    )
+}
+
+/// Pull a published release back to a draft so it can be edited before republishing.
+async fn convert_to_draft(gh_client: &GitHubClient, tag: &str) -> Result<Outcome, Box<dyn Error>> {
+    let release = gh_client
+        .get_release_by_tag(tag)
+        .await?
+        .ok_or_else(|| format!("No release found for tag {}.", tag))?;
+
+    if release.draft == Some(true) {
+        return Ok(Outcome::AlreadyDraft);
+    }
+
+    gh_client.set_draft(release.id, true).await?;
+    Ok(Outcome::ConvertedToDraft)
+}
+
+/// Promote a prerelease to stable and rebuild its notes from the range since the previous stable release.
+async fn finalize_release(
+    gh_client: &GitHubClient,
+    tag: &str,
+    formatter: &NotesFormatter,
+    options: &ReleaseOptions,
+) -> Result<Outcome, Box<dyn Error>> {
+    println!("🚀 Finalizing release '{}'...", tag);
+
+    let release = gh_client
+        .get_release_by_tag(tag)
+        .await?
+        .ok_or_else(|| format!("No release found for tag {}.", tag))?;
+    if let Some(previous_tag) = &options.previous_tag {
+        changelog::validate_previous_tag(gh_client, previous_tag).await?;
+    }
+
+    // Resolve the baseline first: once this release is stable it becomes the latest release itself
+    let previous_tag = changelog::resolve_previous_tag(gh_client, options.previous_tag.as_deref(), tag).await?;
+    match &previous_tag {
+        Some(previous_tag) => println!("  Regenerating notes since {}...", previous_tag),
+        None => println!("  {}", output::info("No previous stable release found. Regenerating notes for the whole history...")),
+    }
+
+    // 1. Clear the prerelease flag.
+    gh_client.set_prerelease(release.id, false).await?;
+    println!("  {}", output::success("Release marked as stable."));
+
+    // 2. Regenerate the notes against the previous stable release.
+    let generated = gh_client.generate_notes(tag, previous_tag.as_deref(), None).await?;
+    if generated.trim().is_empty() {
+        return Err("GitHub generated empty release notes.".into());
+    }
+
+    // 3. Reformat them and update the release body.
+    let formatter_input = changelog::with_extra_notes(&generated, &options.extra_notes);
+    let (formatted_notes, fell_back) = formatter.format_or_fall_back(&formatter_input, options.llm_fallback.as_ref()).await?;
+    let formatted_notes = finish_notes(gh_client, tag, formatted_notes, &generated, previous_tag.as_deref(), options).await?;
+    println!("Formatted Release Notes:\n{}", formatted_notes);
+
+    save_notes(gh_client, release.id, tag, &formatted_notes, release_title(tag, options).as_deref()).await?;
+    println!("  {}", output::success("Release notes updated successfully."));
+
+    let summary = notes_summary(tag, Some(&release), "regenerated", &formatted_notes, formatter, fell_back, options);
+    Ok(Outcome::released(summary, fell_back))
+}
+
+/// The release title from `--name`, if one was given.
+fn release_title(tag: &str, options: &ReleaseOptions) -> Option<String> {
+    options
+        .release_name
+        .as_deref()
+        .map(|template| release_name::render(template, tag, &release_name::today()))
+}
+
+/// Format the notes a release would get and print how they differ from its current body.
+///
+/// Nothing is created, deleted or updated on GitHub.
+async fn preview_release(
+    gh_client: &GitHubClient,
+    tag: &str,
+    formatter: &NotesFormatter,
+    options: &ReleaseOptions,
+) -> Result<Outcome, Box<dyn Error>> {
+    println!("🔍 Previewing release notes for '{}' (dry run)...", tag);
+
+    if let Some(previous_tag) = &options.previous_tag {
+        changelog::validate_previous_tag(gh_client, previous_tag).await?;
+    }
+    // The preview is of the tag a real run would release, bumped or not
+    let requested_tag = tag;
+    let tag = gh_client.determine_tag_version(tag).await?;
+    let tag = tag.as_str();
+    let release = gh_client.get_release_by_tag(tag).await?;
+    let current_body = release.as_ref().and_then(|release| release.body.clone()).unwrap_or_default();
+
+    let (notes_source, raw_notes) = match options.notes_source {
+        NotesSource::Compare => {
+            let commits = compare_commits(gh_client, tag, options).await?;
+            ("compare", changelog::commits_to_notes(&note_commits(commits, options), options.include_sha.then_some(gh_client), options.commit_sections.as_ref(), options.commit_bodies))
+        }
+        NotesSource::Github => {
+            let base = changelog::generate_notes_base(gh_client, options.previous_tag.as_deref(), tag, options.notes_base).await?;
+            ("generated", gh_client.generate_notes(tag, base.as_deref(), None).await?)
+        }
+    };
+
+    let formatter_input = changelog::with_extra_notes(&raw_notes, &options.extra_notes);
+    let (formatted_notes, fell_back) = formatter.format_or_fall_back(&formatter_input, options.llm_fallback.as_ref()).await?;
+    let previous_tag = if options.footer_compare {
+        changelog::resolve_previous_tag(gh_client, options.previous_tag.as_deref(), tag).await?
+    } else {
+        None
+    };
+    let formatted_notes = finish_notes(gh_client, tag, formatted_notes, &raw_notes, previous_tag.as_deref(), options).await?;
+
+    if options.validate_links {
+        warn_about_broken_links(gh_client, &formatted_notes).await;
+    }
+
+    if current_body.trim() == formatted_notes.trim() {
+        println!("  {}", output::success("The formatted notes match the current release body."));
+    } else {
+        println!("{}", formatter::notes_diff(&current_body, &formatted_notes));
+    }
+
+    let summary = ReleaseSummary {
+        requested_tag: requested_tag.to_string(),
+        incremented: requested_tag != tag,
+        ..notes_summary(tag, release.as_ref(), notes_source, &formatted_notes, formatter, fell_back, options)
+    };
+    Ok(Outcome::released(summary, fell_back))
+}
+
+/// Append the "Full Changelog" footer when it was asked for and there is a previous release.
+fn with_compare_footer(
+    gh_client: &GitHubClient,
+    tag: &str,
+    notes: String,
+    previous_tag: Option<&str>,
+    options: &ReleaseOptions,
+) -> String {
+    if !options.footer_compare {
+        return notes;
+    }
+
+    match previous_tag {
+        Some(previous_tag) => formatter::append_compare_footer(&notes, &gh_client.compare_url(previous_tag, tag)),
+        None => {
+            println!("  {}", output::info("No previous release found. Skipping the compare footer."));
+            notes
+        }
+    }
+}
+
+/// Append the diff stats for the range since the previous release, when there is one.
+async fn with_diff_stats(
+    gh_client: &GitHubClient,
+    tag: &str,
+    notes: String,
+    previous_tag: Option<&str>,
+    options: &ReleaseOptions,
+) -> Result<String, Box<dyn Error>> {
+    let previous_tag = match previous_tag {
+        Some(previous_tag) => Some(previous_tag.to_string()),
+        None => changelog::resolve_previous_tag(gh_client, options.previous_tag.as_deref(), tag).await?,
+    };
+
+    match previous_tag {
+        Some(previous_tag) => {
+            let stats = changelog::diff_stats(gh_client, &previous_tag, tag).await?;
+            Ok(format!("{}\n\n{}", notes.trim_end(), stats.summary_line()))
+        }
+        None => {
+            println!("  {}", output::info("No previous release found. Skipping the diff stats."));
+            Ok(notes)
+        }
+    }
+}
+
+/// Warn about every link in the notes that doesn't resolve; broken links never fail the release.
+async fn warn_about_broken_links(gh_client: &GitHubClient, notes: &str) {
+    println!("Checking links...");
+    let warnings = links::validate(gh_client.http_client(), notes).await;
+    if warnings.is_empty() {
+        println!("  {}", output::success("Every link resolves."));
+    }
+    for warning in warnings {
+        println!("  {}", output::warning(warning));
+    }
+}
+
+/// Check for tickets if required, group by label, credit reviewers, then add the optional diff stats, compare footer,
+/// translations and raw notes, and write the result to `--output`.
+async fn finish_notes(
+    gh_client: &GitHubClient,
+    tag: &str,
+    notes: String,
+    raw_notes: &str,
+    previous_tag: Option<&str>,
+    options: &ReleaseOptions,
+) -> Result<String, Box<dyn Error>> {
+    if options.require_tickets {
+        formatter::require_tickets(&notes)?;
+    }
+    let notes = match &options.label_grouping {
+        Some(grouping) => label_groups::group_notes(gh_client, &notes, grouping).await?,
+        None => notes,
+    };
+    let notes = if options.include_reviewers {
+        reviewers::annotate_notes(gh_client, &notes).await?
+    } else {
+        notes
+    };
+    let notes = if options.diff_stats {
+        with_diff_stats(gh_client, tag, notes, previous_tag, options).await?
+    } else {
+        notes
+    };
+    let mut notes = with_compare_footer(gh_client, tag, notes, previous_tag, options);
+    if let Some(translator) = &options.translator {
+        let file_stem = format!("{}-{}", gh_client.repository().replace('/', "-"), tag);
+        notes = translator.apply(&notes, &file_stem).await?;
+    }
+
+    if let Some(max_len) = options.max_notes_length.filter(|max_len| notes.chars().count() > *max_len) {
+        let previous_tag = match previous_tag {
+            Some(previous_tag) => Some(previous_tag.to_string()),
+            None => changelog::resolve_previous_tag(gh_client, options.previous_tag.as_deref(), tag).await?,
+        };
+        let changelog_url = match previous_tag {
+            Some(previous_tag) => gh_client.compare_url(&previous_tag, tag),
+            None => gh_client.commits_url(tag),
+        };
+        println!("  {}", output::warning(format!("The notes are {} characters long. Truncating them to {}.", notes.chars().count(), max_len)));
+        notes = formatter::truncate_notes(&notes, max_len, &changelog_url);
+    }
+    if options.append_raw {
+        // The raw notes block is kept whole or left out, so a cut never leaves an unclosed <details>
+        let with_raw = formatter::append_raw_notes(&notes, raw_notes);
+        match options.max_notes_length.filter(|max_len| with_raw.chars().count() > *max_len) {
+            Some(max_len) => println!(
+                "  {}",
+                output::warning(format!("The raw notes would take the notes over {} characters. Leaving them out.", max_len))
+            ),
+            None => notes = with_raw,
+        }
+    }
+
+    if let Some(path) = &options.output {
+        std::fs::write(path, markup::render(&notes, options.output_format))
+            .map_err(|e| format!("Failed to write the notes to {}: {}", path.display(), e))?;
+        println!("  {}", output::success(format!("Wrote {}", path.display())));
+    }
+
+    Ok(notes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::Matcher;
+    use serde_json::json;
+    use tokio::runtime::Runtime;
+
+    #[test]
+    fn given_prerelease_when_finalizing_then_promotes_and_regenerates_notes_since_last_stable() {
+        let mut server = mockito::Server::new();
+
+        let mock_release = server.mock("GET", "/repos/Human-Glitch/llm-playground/releases/tags/v1.2.0")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"id": 7, "tag_name": "v1.2.0", "body": "stale", "prerelease": true}"#)
+            .create();
+        let mock_latest = server.mock("GET", "/repos/Human-Glitch/llm-playground/releases/latest")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"id": 5, "tag_name": "v1.1.0", "prerelease": false}"#)
+            .create();
+        let mock_promote = server.mock("PATCH", "/repos/Human-Glitch/llm-playground/releases/7")
+            .match_body(Matcher::Json(json!({"prerelease": false})))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"id": 7}"#)
+            .create();
+        let mock_generate = server.mock("POST", "/repos/Human-Glitch/llm-playground/releases/generate-notes")
+            .match_body(Matcher::Json(json!({"tag_name": "v1.2.0", "previous_tag_name": "v1.1.0"})))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"name": "v1.2.0", "body": "* PDE-2 Second by @b\n* PDE-1 First by @a"}"#)
+            .create();
+        let mock_update = server.mock("PATCH", "/repos/Human-Glitch/llm-playground/releases/7")
+            .match_body(Matcher::Json(json!({
+                "body": "## PDE\n* [PDE-1](https://onezelis.atlassian.net/browse/PDE-1) First by @a\n* [PDE-2](https://onezelis.atlassian.net/browse/PDE-2) Second by @b"
+            })))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"id": 7}"#)
+            .create();
+
+        let gh_client = GitHubClient::new_with_base_url(Client::new(), "fake_token".to_string(), server.url());
+        let formatter = NotesFormatter::Local(LocalFormatter::default());
+
+        let rt = Runtime::new().unwrap();
+        rt.block_on(async {
+            finalize_release(&gh_client, "v1.2.0", &formatter, &ReleaseOptions::default()).await.unwrap()
+        });
+
+        // Verify every step of the sequence was called
+        mock_release.assert();
+        mock_latest.assert();
+        mock_promote.assert();
+        mock_generate.assert();
+        mock_update.assert();
+    }
+
+    #[test]
+    fn given_latest_with_a_kept_prerelease_when_releasing_then_fails_before_any_change() {
+        let mut server = mockito::Server::new();
+
+        let _release = server.mock("GET", "/repos/Human-Glitch/llm-playground/releases/tags/v1.3.0")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"id": 9, "tag_name": "v1.3.0", "body": "* PDE-1 Fix by @a", "prerelease": true}"#)
+            .create();
+        let _branch = server.mock("GET", "/repos/Human-Glitch/llm-playground/branches/release/v1.3.x")
+            .with_status(404)
+            .create();
+        let _latest = server.mock("GET", "/repos/Human-Glitch/llm-playground/releases/latest")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"id": 5, "tag_name": "v1.2.0", "prerelease": false}"#)
+            .create();
+        let _releases = server.mock("GET", "/repos/Human-Glitch/llm-playground/releases")
+            .match_query(Matcher::Any)
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"[{"id": 5, "tag_name": "v1.2.0", "prerelease": false}]"#)
+            .create();
+        let writes: Vec<_> = ["POST", "PATCH", "DELETE"]
+            .into_iter()
+            .map(|method| server.mock(method, Matcher::Any).expect(0).create())
+            .collect();
+
+        let args = Cli::try_parse_from(["github-releaser-llm", "--tag", "v1.3.0", "--latest"]).unwrap();
+        let gh_client = GitHubClient::new_with_base_url(Client::new(), "fake_token".to_string(), server.url())
+            .with_release_kind(ReleaseKind::from_flags(args.stable, args.latest));
+        let formatter = NotesFormatter::Local(LocalFormatter::default());
+        let options = ReleaseOptions { immutable_prereleases: true, ..Default::default() };
+
+        let rt = Runtime::new().unwrap();
+        let result = rt.block_on(async {
+            process_release(&gh_client, "v1.3.0", &formatter, &InterruptState::default(), None, &NoProgress, &options).await
+        });
+
+        assert!(result.unwrap_err().to_string().starts_with("--latest can't mark v1.3.0 as the latest release"));
+        for write in writes {
+            write.assert();
+        }
+    }
+
+    #[test]
+    fn given_immutable_prereleases_when_prerelease_exists_then_never_deletes_it() {
+        let mut server = mockito::Server::new();
+
+        let mock_release = server.mock("GET", "/repos/Human-Glitch/llm-playground/releases/tags/v1.3.0")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"id": 9, "tag_name": "v1.3.0", "body": "* PDE-1 Fix by @a", "prerelease": true}"#)
+            .expect_at_least(1)
+            .create();
+        let mock_branch = server.mock("GET", "/repos/Human-Glitch/llm-playground/branches/release/v1.3.x")
+            .with_status(404)
+            .expect_at_least(1)
+            .create();
+        let mock_commit = server.mock("GET", "/repos/Human-Glitch/llm-playground/commits/release/v1.3.0")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"sha": "abc123"}"#)
+            .create();
+        let mock_latest = server.mock("GET", "/repos/Human-Glitch/llm-playground/releases/latest")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"id": 5, "tag_name": "v1.2.0", "prerelease": false}"#)
+            .create();
+        let _releases = server.mock("GET", "/repos/Human-Glitch/llm-playground/releases")
+            .match_query(Matcher::Any)
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"[{"id": 5, "tag_name": "v1.2.0", "prerelease": false}]"#)
+            .create();
+        let _history = server.mock("GET", "/repos/Human-Glitch/llm-playground/compare/v1.2.0...abc123")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"status": "ahead", "commits": []}"#)
+            .create();
+        let mock_delete_release = server.mock("DELETE", "/repos/Human-Glitch/llm-playground/releases/9")
+            .expect(0)
+            .create();
+        let mock_delete_tag = server.mock("DELETE", "/repos/Human-Glitch/llm-playground/git/refs/tags/v1.3.0")
+            .expect(0)
+            .create();
+        let mock_create_tag = server.mock("POST", "/repos/Human-Glitch/llm-playground/git/tags")
+            .expect(0)
+            .create();
+        let mock_update = server.mock("PATCH", "/repos/Human-Glitch/llm-playground/releases/9")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"id": 9}"#)
+            .create();
+
+        let gh_client = GitHubClient::new_with_base_url(Client::new(), "fake_token".to_string(), server.url());
+        let formatter = NotesFormatter::Local(LocalFormatter::default());
+        let options = ReleaseOptions {
+            immutable_prereleases: true,
+            ..Default::default()
+        };
+
+        let rt = Runtime::new().unwrap();
+        rt.block_on(async {
+            process_release(&gh_client, "v1.3.0", &formatter, &InterruptState::default(), None, &NoProgress, &options).await.unwrap()
+        });
+
+        // Verify the prerelease was updated in place and never deleted
+        mock_release.assert();
+        mock_branch.assert();
+        mock_commit.assert();
+        mock_latest.assert();
+        mock_delete_release.assert();
+        mock_delete_tag.assert();
+        mock_create_tag.assert();
+        mock_update.assert();
+    }
+
+    #[test]
+    fn given_openai_failing_with_local_fallback_when_releasing_then_updates_with_local_notes() {
+        let mut server = mockito::Server::new();
+
+        let _release = server.mock("GET", "/repos/Human-Glitch/llm-playground/releases/tags/v1.3.0")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"id": 9, "tag_name": "v1.3.0", "body": "* PDE-1 Fix by @a", "prerelease": true}"#)
+            .expect_at_least(1)
+            .create();
+        let _branch = server.mock("GET", "/repos/Human-Glitch/llm-playground/branches/release/v1.3.x")
+            .with_status(404)
+            .expect_at_least(1)
+            .create();
+        let _commit = server.mock("GET", "/repos/Human-Glitch/llm-playground/commits/release/v1.3.0")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"sha": "abc123"}"#)
+            .create();
+        let _latest = server.mock("GET", "/repos/Human-Glitch/llm-playground/releases/latest")
+            .with_status(404)
+            .expect_at_least(1)
+            .create();
+        let _releases = server.mock("GET", "/repos/Human-Glitch/llm-playground/releases")
+            .match_query(Matcher::Any)
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body("[]")
+            .create();
+        let mock_openai = server.mock("POST", "/v1/chat/completions")
+            .with_status(500)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"error": {"message": "The server had an error"}}"#)
+            .create();
+        let mock_update = server.mock("PATCH", "/repos/Human-Glitch/llm-playground/releases/9")
+            .match_body(Matcher::PartialJson(json!({
+                "body": "## PDE\n* [PDE-1](https://onezelis.atlassian.net/browse/PDE-1) Fix by @a"
+            })))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"id": 9}"#)
+            .create();
+
+        let gh_client = GitHubClient::new_with_base_url(Client::new(), "fake_token".to_string(), server.url());
+        let formatter = NotesFormatter::OpenAI(Box::new(OpenAIClient::new_with_base_url(
+            Client::new(),
+            "fake_key".to_string(),
+            "gpt-4o",
+            server.url(),
+        )));
+        let options = ReleaseOptions {
+            immutable_prereleases: true,
+            llm_fallback: Some(LocalFormatter::default()),
+            ..Default::default()
+        };
+
+        let rt = Runtime::new().unwrap();
+        let outcome = rt.block_on(async {
+            process_release(&gh_client, "v1.3.0", &formatter, &InterruptState::default(), None, &NoProgress, &options).await.unwrap()
+        });
+
+        assert_eq!(outcome.as_str(), "released_with_local_fallback");
+        assert_eq!(
+            outcome.tickets(),
+            [Ticket {
+                prefix: "PDE".to_string(),
+                number: "1".to_string(),
+                url: "https://onezelis.atlassian.net/browse/PDE-1".to_string(),
+            }]
+        );
+        mock_openai.assert();
+        mock_update.assert();
+    }
+
+    #[test]
+    fn given_update_failing_for_good_when_releasing_then_prints_the_notes_for_a_manual_paste() {
+        let mut server = mockito::Server::new();
+
+        let _release = server.mock("GET", "/repos/Human-Glitch/llm-playground/releases/tags/v1.3.0")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"id": 9, "tag_name": "v1.3.0", "body": "* PDE-1 Fix by @a", "prerelease": true}"#)
+            .expect_at_least(1)
+            .create();
+        let _branch = server.mock("GET", "/repos/Human-Glitch/llm-playground/branches/release/v1.3.x")
+            .with_status(404)
+            .expect_at_least(1)
+            .create();
+        let _commit = server.mock("GET", "/repos/Human-Glitch/llm-playground/commits/release/v1.3.0")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"sha": "abc123"}"#)
+            .create();
+        let _latest = server.mock("GET", "/repos/Human-Glitch/llm-playground/releases/latest")
+            .with_status(404)
+            .expect_at_least(1)
+            .create();
+        let _releases = server.mock("GET", "/repos/Human-Glitch/llm-playground/releases")
+            .match_query(Matcher::Any)
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body("[]")
+            .create();
+        let mock_update = server.mock("PATCH", "/repos/Human-Glitch/llm-playground/releases/9")
+            .with_status(502)
+            .with_body(r#"{"message": "Bad Gateway"}"#)
+            .expect(1 + github_client::UPDATE_RELEASE_MIN_RETRIES as usize)
+            .create();
+
+        let policy = RetryPolicy { sleeper: Arc::new(retry::RecordingSleeper::default()), ..Default::default() };
+        let gh_client = GitHubClient::new_with_base_url(Client::new(), "fake_token".to_string(), server.url())
+            .with_retry_policy(policy);
+        let formatter = NotesFormatter::Local(LocalFormatter::default());
+        let options = ReleaseOptions {
+            immutable_prereleases: true,
+            ..Default::default()
+        };
+
+        let rt = Runtime::new().unwrap();
+        let result = rt.block_on(async {
+            process_release(&gh_client, "v1.3.0", &formatter, &InterruptState::default(), None, &NoProgress, &options).await
+        });
+
+        let error = result.err().unwrap().to_string();
+        assert!(error.contains("Paste the formatted notes into the release by hand:"), "{}", error);
+        assert!(
+            error.ends_with("----- BEGIN NOTES v1.3.0 -----\n## PDE\n* [PDE-1](https://onezelis.atlassian.net/browse/PDE-1) Fix by @a\n----- END NOTES v1.3.0 -----"),
+            "{}",
+            error
+        );
+        mock_update.assert();
+    }
+
+    /// Collects the steps `process_release` reports, in order.
+    #[derive(Default)]
+    struct RecordingProgress(std::sync::Mutex<Vec<(String, StepStatus)>>);
+
+    impl ProgressReporter for RecordingProgress {
+        fn step(&self, _repository: &str, step: &str, status: StepStatus, _detail: &str) {
+            self.0.lock().unwrap().push((step.to_string(), status));
+        }
+    }
+
+    #[test]
+    fn given_progress_reporter_when_releasing_then_reports_each_step_in_order() {
+        let mut server = mockito::Server::new();
+
+        let _release = server.mock("GET", "/repos/Human-Glitch/llm-playground/releases/tags/v1.3.0")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"id": 9, "tag_name": "v1.3.0", "body": "* PDE-1 Fix by @a", "prerelease": true}"#)
+            .create();
+        let _branch = server.mock("GET", "/repos/Human-Glitch/llm-playground/branches/release/v1.3.x")
+            .with_status(404)
+            .create();
+        let _commit = server.mock("GET", "/repos/Human-Glitch/llm-playground/commits/release/v1.3.0")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"sha": "abc123"}"#)
+            .create();
+        let _latest = server.mock("GET", "/repos/Human-Glitch/llm-playground/releases/latest")
+            .with_status(404)
+            .create();
+        let _releases = server.mock("GET", "/repos/Human-Glitch/llm-playground/releases")
+            .match_query(Matcher::Any)
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body("[]")
+            .create();
+        let _update = server.mock("PATCH", "/repos/Human-Glitch/llm-playground/releases/9")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"id": 9}"#)
+            .create();
+
+        let gh_client = GitHubClient::new_with_base_url(Client::new(), "fake_token".to_string(), server.url());
+        let formatter = NotesFormatter::Local(LocalFormatter::default());
+        let options = ReleaseOptions {
+            immutable_prereleases: true,
+            ..Default::default()
+        };
+        let progress = RecordingProgress::default();
+
+        let rt = Runtime::new().unwrap();
+        rt.block_on(async {
+            process_release(&gh_client, "v1.3.0", &formatter, &InterruptState::default(), None, &progress, &options).await.unwrap()
+        });
+
+        let steps = progress.0.into_inner().unwrap();
+        assert_eq!(
+            steps,
+            vec![
+                ("delete_release".to_string(), StepStatus::Skipped),
+                ("delete_tag".to_string(), StepStatus::Skipped),
+                ("resolve_branch".to_string(), StepStatus::Ok),
+                ("latest_commit".to_string(), StepStatus::Ok),
+                ("create_tag".to_string(), StepStatus::Skipped),
+                ("create_release".to_string(), StepStatus::Ok),
+                ("fetch_notes".to_string(), StepStatus::Ok),
+                ("format_notes".to_string(), StepStatus::Ok),
+                ("update_release".to_string(), StepStatus::Ok),
+            ]
+        );
+    }
+
+    #[test]
+    fn given_failing_step_when_releasing_then_reports_it_as_failed() {
+        let mut server = mockito::Server::new();
+
+        let _release = server.mock("GET", "/repos/Human-Glitch/llm-playground/releases/tags/v1.3.0")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"id": 9, "tag_name": "v1.3.0", "body": "* PDE-1 Fix by @a", "prerelease": true}"#)
+            .create();
+        let _branch = server.mock("GET", "/repos/Human-Glitch/llm-playground/branches/release/v1.3.x")
+            .with_status(404)
+            .create();
+        let _commit = server.mock("GET", "/repos/Human-Glitch/llm-playground/commits/release/v1.3.0")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"sha": "abc123"}"#)
+            .create();
+        let _latest = server.mock("GET", "/repos/Human-Glitch/llm-playground/releases/latest")
+            .with_status(404)
+            .create();
+        let _releases = server.mock("GET", "/repos/Human-Glitch/llm-playground/releases")
+            .match_query(Matcher::Any)
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body("[]")
+            .create();
+        let _update = server.mock("PATCH", "/repos/Human-Glitch/llm-playground/releases/9")
+            .with_status(422)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"message": "Validation Failed"}"#)
+            .create();
+
+        let gh_client = GitHubClient::new_with_base_url(Client::new(), "fake_token".to_string(), server.url());
+        let formatter = NotesFormatter::Local(LocalFormatter::default());
+        let options = ReleaseOptions {
+            immutable_prereleases: true,
+            ..Default::default()
+        };
+        let progress = RecordingProgress::default();
+
+        let rt = Runtime::new().unwrap();
+        rt.block_on(async {
+            process_release(&gh_client, "v1.3.0", &formatter, &InterruptState::default(), None, &progress, &options).await.unwrap_err()
+        });
+
+        let steps = progress.0.into_inner().unwrap();
+        assert_eq!(steps.last(), Some(&("update_release".to_string(), StepStatus::Failed)));
+        assert_eq!(steps.iter().filter(|(_, status)| *status == StepStatus::Failed).count(), 1);
+    }
+
+    #[test]
+    fn given_update_in_place_when_releasing_over_stable_release_then_retargets_tag_instead_of_deleting() {
+        let mut server = mockito::Server::new();
+
+        let _release = server.mock("GET", "/repos/Human-Glitch/llm-playground/releases/tags/v1.3.0")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"id": 9, "tag_name": "v1.3.0", "body": "* PDE-1 Fix by @a", "prerelease": false}"#)
+            .create();
+        let _branch = server.mock("GET", "/repos/Human-Glitch/llm-playground/branches/release/v1.3.x")
+            .with_status(404)
+            .create();
+        let _commit = server.mock("GET", "/repos/Human-Glitch/llm-playground/commits/release/v1.3.0")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"sha": "abc123"}"#)
+            .create();
+        let _latest = server.mock("GET", "/repos/Human-Glitch/llm-playground/releases/latest")
+            .with_status(404)
+            .create();
+        let _releases = server.mock("GET", "/repos/Human-Glitch/llm-playground/releases")
+            .match_query(Matcher::Any)
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body("[]")
+            .create();
+        let mock_delete = server.mock("DELETE", Matcher::Any).expect(0).create();
+        let _ref = server.mock("GET", "/repos/Human-Glitch/llm-playground/git/ref/tags/v1.3.0")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"ref": "refs/tags/v1.3.0", "object": {"sha": "tag_sha_1", "type": "tag"}}"#)
+            .create();
+        let _existing_tag = server.mock("GET", "/repos/Human-Glitch/llm-playground/git/tags/tag_sha_1")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"sha": "tag_sha_1", "message": "Spring release\n", "object": {"sha": "old123", "type": "commit"}}"#)
+            .create();
+        // The moved tag keeps the message it was created with
+        let mock_tag = server.mock("POST", "/repos/Human-Glitch/llm-playground/git/tags")
+            .match_body(Matcher::PartialJson(json!({"tag": "v1.3.0", "message": "Spring release\n", "object": "abc123"})))
+            .with_status(201)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"sha": "tag_sha_2"}"#)
+            .create();
+        let mock_retarget = server.mock("PATCH", "/repos/Human-Glitch/llm-playground/git/refs/tags/v1.3.0")
+            .match_body(Matcher::Json(json!({"sha": "tag_sha_2", "force": true})))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{}"#)
+            .create();
+        let mock_create = server.mock("POST", "/repos/Human-Glitch/llm-playground/releases").expect(0).create();
+        let mock_update = server.mock("PATCH", "/repos/Human-Glitch/llm-playground/releases/9")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"id": 9}"#)
+            .create();
+
+        let gh_client = GitHubClient::new_with_base_url(Client::new(), "fake_token".to_string(), server.url());
+        let formatter = NotesFormatter::Local(LocalFormatter::default());
+        let options = ReleaseOptions {
+            update_in_place: true,
+            ..Default::default()
+        };
+
+        let rt = Runtime::new().unwrap();
+        rt.block_on(async {
+            process_release(&gh_client, "v1.3.0", &formatter, &InterruptState::default(), None, &NoProgress, &options).await.unwrap()
+        });
+
+        mock_delete.assert();
+        mock_tag.assert();
+        mock_retarget.assert();
+        mock_create.assert();
+        mock_update.assert();
+    }
+
+    #[test]
+    fn given_created_release_with_empty_body_when_releasing_then_falls_back_to_generated_notes() {
+        let mut server = mockito::Server::new();
+
+        let _release = server.mock("GET", "/repos/Human-Glitch/llm-playground/releases/tags/v1.3.0")
+            .with_status(404)
+            .create();
+        let _no_drafts = server.mock("GET", "/repos/Human-Glitch/llm-playground/releases")
+            .match_query(Matcher::Any)
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body("[]")
+            .create();
+        let _branch = server.mock("GET", "/repos/Human-Glitch/llm-playground/branches/release/v1.3.x")
+            .with_status(404)
+            .create();
+        let _commit = server.mock("GET", "/repos/Human-Glitch/llm-playground/commits/release/v1.3.0")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"sha": "abc123"}"#)
+            .create();
+        let _latest = server.mock("GET", "/repos/Human-Glitch/llm-playground/releases/latest")
+            .with_status(404)
+            .create();
+        let _delete_tag = server.mock("DELETE", "/repos/Human-Glitch/llm-playground/git/refs/tags/v1.3.0")
+            .with_status(404)
+            .create();
+        let _no_ref = server.mock("GET", "/repos/Human-Glitch/llm-playground/git/ref/tags/v1.3.0")
+            .with_status(404)
+            .create();
+        // A tag that didn't exist gets the default message
+        let _tag = server.mock("POST", "/repos/Human-Glitch/llm-playground/git/tags")
+            .match_body(Matcher::PartialJson(json!({"message": "Release v1.3.0"})))
+            .with_status(201)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"sha": "tag_sha"}"#)
+            .create();
+        let _ref = server.mock("POST", "/repos/Human-Glitch/llm-playground/git/refs")
+            .with_status(201)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{}"#)
+            .create();
+        // An Enterprise server that ignores generate_release_notes
+        let mock_create = server.mock("POST", "/repos/Human-Glitch/llm-playground/releases")
+            .with_status(201)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"id": 9, "tag_name": "v1.3.0", "body": ""}"#)
+            .create();
+        let mock_generate = server.mock("POST", "/repos/Human-Glitch/llm-playground/releases/generate-notes")
+            .match_body(Matcher::Json(json!({"tag_name": "v1.3.0"})))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"name": "v1.3.0", "body": "* PDE-1 Fix by @a"}"#)
+            .create();
+        let mock_update = server.mock("PATCH", "/repos/Human-Glitch/llm-playground/releases/9")
+            .match_body(Matcher::PartialJson(json!({
+                "body": "## PDE\n* [PDE-1](https://onezelis.atlassian.net/browse/PDE-1) Fix by @a"
+            })))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"id": 9}"#)
+            .create();
+
+        let gh_client = GitHubClient::new_with_base_url(Client::new(), "fake_token".to_string(), server.url());
+        let formatter = NotesFormatter::Local(LocalFormatter::default());
+
+        let rt = Runtime::new().unwrap();
+        rt.block_on(async {
+            process_release(&gh_client, "v1.3.0", &formatter, &InterruptState::default(), None, &NoProgress, &ReleaseOptions::default())
+                .await
+                .unwrap()
+        });
+
+        mock_create.assert();
+        mock_generate.assert();
+        mock_update.assert();
+    }
+
+    #[test]
+    fn given_empty_body_without_the_fallback_when_releasing_then_fails_without_generating_or_comparing() {
+        let mut server = mockito::Server::new();
+
+        let _release = server.mock("GET", "/repos/Human-Glitch/llm-playground/releases/tags/v1.3.0")
+            .with_status(404)
+            .create();
+        let _no_drafts = server.mock("GET", "/repos/Human-Glitch/llm-playground/releases")
+            .match_query(Matcher::Any)
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body("[]")
+            .create();
+        let _branch = server.mock("GET", "/repos/Human-Glitch/llm-playground/branches/release/v1.3.x")
+            .with_status(404)
+            .create();
+        let _commit = server.mock("GET", "/repos/Human-Glitch/llm-playground/commits/release/v1.3.0")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"sha": "abc123"}"#)
+            .create();
+        let _latest = server.mock("GET", "/repos/Human-Glitch/llm-playground/releases/latest")
+            .with_status(404)
+            .create();
+        let _delete_tag = server.mock("DELETE", "/repos/Human-Glitch/llm-playground/git/refs/tags/v1.3.0")
+            .with_status(404)
+            .create();
+        let _no_ref = server.mock("GET", "/repos/Human-Glitch/llm-playground/git/ref/tags/v1.3.0")
+            .with_status(404)
+            .create();
+        let _tag = server.mock("POST", "/repos/Human-Glitch/llm-playground/git/tags")
+            .with_status(201)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"sha": "tag_sha"}"#)
+            .create();
+        let _ref = server.mock("POST", "/repos/Human-Glitch/llm-playground/git/refs")
+            .with_status(201)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{}"#)
+            .create();
+        let mock_create = server.mock("POST", "/repos/Human-Glitch/llm-playground/releases")
+            .with_status(201)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"id": 9, "tag_name": "v1.3.0", "body": ""}"#)
+            .create();
+        let mock_generate = server.mock("POST", "/repos/Human-Glitch/llm-playground/releases/generate-notes")
+            .expect(0)
+            .create();
+        let mock_compare = server.mock("GET", Matcher::Regex(r"^/repos/Human-Glitch/llm-playground/compare/".to_string()))
+            .expect(0)
+            .create();
+        let mock_update = server.mock("PATCH", "/repos/Human-Glitch/llm-playground/releases/9")
+            .expect(0)
+            .create();
+
+        let gh_client = GitHubClient::new_with_base_url(Client::new(), "fake_token".to_string(), server.url());
+        let formatter = NotesFormatter::Local(LocalFormatter::default());
+        let options = ReleaseOptions { no_empty_body_fallback: true, ..Default::default() };
+
+        let rt = Runtime::new().unwrap();
+        let result = rt.block_on(async {
+            process_release(&gh_client, "v1.3.0", &formatter, &InterruptState::default(), None, &NoProgress, &options).await
+        });
+
+        assert_eq!(result.unwrap_err().to_string(), "No release notes found or notes are empty.");
+        mock_create.assert();
+        mock_generate.assert();
+        mock_compare.assert();
+        mock_update.assert();
+    }
+
+    #[test]
+    fn given_interrupt_during_the_delete_when_releasing_then_recreates_the_release_before_stopping() {
+        let mut server = mockito::Server::new();
+        let interrupt = Arc::new(InterruptState::default());
+
+        let _release = server.mock("GET", "/repos/Human-Glitch/llm-playground/releases/tags/v1.3.0")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"id": 9, "tag_name": "v1.3.0", "body": "", "prerelease": true}"#)
+            .create();
+        let _releases = server.mock("GET", "/repos/Human-Glitch/llm-playground/releases")
+            .match_query(Matcher::Any)
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body("[]")
+            .create();
+        let _branch = server.mock("GET", "/repos/Human-Glitch/llm-playground/branches/release/v1.3.x")
+            .with_status(404)
+            .create();
+        let _commit = server.mock("GET", "/repos/Human-Glitch/llm-playground/commits/release/v1.3.0")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"sha": "abc123"}"#)
+            .create();
+        let _latest = server.mock("GET", "/repos/Human-Glitch/llm-playground/releases/latest")
+            .with_status(404)
+            .create();
+        let _no_ref = server.mock("GET", "/repos/Human-Glitch/llm-playground/git/ref/tags/v1.3.0")
+            .with_status(404)
+            .create();
+        // Ctrl-C arrives while the release is being deleted
+        let signal = interrupt.clone();
+        let mock_delete_release = server.mock("DELETE", "/repos/Human-Glitch/llm-playground/releases/9")
+            .with_status(200)
+            .with_chunked_body(move |_| {
+                signal.record_signal();
+                Ok(())
+            })
+            .create();
+        let mock_delete_tag = server.mock("DELETE", "/repos/Human-Glitch/llm-playground/git/refs/tags/v1.3.0")
+            .with_status(204)
+            .create();
+        let mock_tag = server.mock("POST", "/repos/Human-Glitch/llm-playground/git/tags")
+            .with_status(201)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"sha": "tag_sha"}"#)
+            .create();
+        let mock_ref = server.mock("POST", "/repos/Human-Glitch/llm-playground/git/refs")
+            .with_status(201)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{}"#)
+            .create();
+        let mock_create = server.mock("POST", "/repos/Human-Glitch/llm-playground/releases")
+            .with_status(201)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"id": 10, "tag_name": "v1.3.0", "body": "* PDE-1 Fix by @a"}"#)
+            .create();
+        let mock_update = server.mock("PATCH", Matcher::Any).expect(0).create();
+
+        let gh_client = GitHubClient::new_with_base_url(Client::new(), "fake_token".to_string(), server.url());
+        let formatter = NotesFormatter::Local(LocalFormatter::default());
+        let options = ReleaseOptions { force: true, ..Default::default() };
+
+        let rt = Runtime::new().unwrap();
+        let result = rt.block_on(async {
+            process_release(&gh_client, "v1.3.0", &formatter, &interrupt, None, &NoProgress, &options).await
+        });
+
+        assert!(result.unwrap_err().to_string().starts_with("Interrupted after Step 6 (release creation)."));
+        mock_delete_release.assert();
+        mock_delete_tag.assert();
+        mock_tag.assert();
+        mock_ref.assert();
+        mock_create.assert();
+        mock_update.assert();
+    }
+
+    #[test]
+    fn given_slow_github_when_the_deadline_passes_then_fails_naming_the_step() {
+        let mut server = mockito::Server::new();
+
+        let _release = server.mock("GET", "/repos/Human-Glitch/llm-playground/releases/tags/v1.3.0")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_chunked_body(|w| {
+                std::thread::sleep(Duration::from_millis(500));
+                std::io::Write::write_all(w, br#"{"id": 9, "tag_name": "v1.3.0", "prerelease": true}"#)
+            })
+            .create();
+
+        let gh_client = GitHubClient::new_with_base_url(Client::new(), "fake_token".to_string(), server.url());
+        let formatter = NotesFormatter::Local(LocalFormatter::default());
+        let options = ReleaseOptions::default();
+        let tracker = StepTracker::new(&NoProgress);
+
+        let rt = Runtime::new().unwrap();
+        let error = rt.block_on(async {
+            let interrupt = InterruptState::default();
+            let release = process_release(&gh_client, "v1.3.0", &formatter, &interrupt, None, &tracker, &options);
+            within_deadline(Some(Deadline::starting_now(Duration::from_millis(100))), &gh_client, &tracker, release)
+                .await
+                .unwrap_err()
+        });
+
+        assert!(error.to_string().starts_with("Deadline of 100ms exceeded during planning."));
+    }
+
+    #[test]
+    fn given_identical_compare_in_compare_mode_when_releasing_then_stops_before_changing_anything() {
+        let mut server = mockito::Server::new();
+
+        let _release = server.mock("GET", "/repos/Human-Glitch/llm-playground/releases/tags/v1.3.0")
+            .with_status(404)
+            .create();
+        let _no_drafts = server.mock("GET", "/repos/Human-Glitch/llm-playground/releases")
+            .match_query(Matcher::Any)
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"[{"id": 5, "tag_name": "v1.2.0", "prerelease": false}]"#)
+            .create();
+        let _branch = server.mock("GET", "/repos/Human-Glitch/llm-playground/branches/release/v1.3.x")
+            .with_status(404)
+            .create();
+        let _commit = server.mock("GET", "/repos/Human-Glitch/llm-playground/commits/release/v1.3.0")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"sha": "abc123"}"#)
+            .create();
+        let _latest = server.mock("GET", "/repos/Human-Glitch/llm-playground/releases/latest")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"id": 5, "tag_name": "v1.2.0", "prerelease": false}"#)
+            .create();
+        let mock_compare = server.mock("GET", "/repos/Human-Glitch/llm-playground/compare/v1.2.0...abc123")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"status": "identical", "commits": []}"#)
+            .expect(2)
+            .create();
+        let mock_delete = server.mock("DELETE", Matcher::Any).expect(0).create();
+        let mock_create = server.mock("POST", Matcher::Any).expect(0).create();
+
+        let gh_client = GitHubClient::new_with_base_url(Client::new(), "fake_token".to_string(), server.url());
+        let formatter = NotesFormatter::Local(LocalFormatter::default());
+        let options = ReleaseOptions {
+            notes_source: NotesSource::Compare,
+            ..Default::default()
+        };
+        let strict = ReleaseOptions {
+            notes_source: NotesSource::Compare,
+            fail_on_empty: true,
+            ..Default::default()
+        };
+
+        let rt = Runtime::new().unwrap();
+        let (outcome, failure) = rt.block_on(async {
+            let interrupt = InterruptState::default();
+            (
+                process_release(&gh_client, "v1.3.0", &formatter, &interrupt, None, &NoProgress, &options).await.unwrap(),
+                process_release(&gh_client, "v1.3.0", &formatter, &interrupt, None, &NoProgress, &strict).await.unwrap_err(),
+            )
+        });
+
+        assert_eq!(outcome, Outcome::NoChanges("v1.2.0".to_string()));
+        assert_eq!(failure.to_string(), "no changes since v1.2.0, nothing to release");
+        mock_compare.assert();
+        mock_delete.assert();
+        mock_create.assert();
+    }
+
+    /// Plan a re-release of v1.3.0 whose tag points at `old123`, with that commit's history against the
+    /// deleted release's branch answered by `status`.
+    fn plan_with_existing_tag(status: &str, force: bool) -> Result<ReleasePlan, String> {
+        let mut server = mockito::Server::new();
+
+        let _release = server.mock("GET", "/repos/Human-Glitch/llm-playground/releases/tags/v1.3.0")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"id": 9, "tag_name": "v1.3.0", "prerelease": false, "body": "", "target_commitish": "release/v1.3.x"}"#)
+            .create();
+        let _branch = server.mock("GET", "/repos/Human-Glitch/llm-playground/branches/release/v1.3.x")
+            .with_status(404)
+            .create();
+        let _commit = server.mock("GET", "/repos/Human-Glitch/llm-playground/commits/release/v1.3.0")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"sha": "abc123"}"#)
+            .create();
+        let _latest = server.mock("GET", "/repos/Human-Glitch/llm-playground/releases/latest")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"id": 5, "tag_name": "v1.2.0", "prerelease": false}"#)
+            .create();
+        let _releases = server.mock("GET", "/repos/Human-Glitch/llm-playground/releases")
+            .match_query(Matcher::Any)
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"[{"id": 5, "tag_name": "v1.2.0", "prerelease": false}]"#)
+            .create();
+        let _history = server.mock("GET", "/repos/Human-Glitch/llm-playground/compare/v1.2.0...abc123")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"status": "ahead", "commits": []}"#)
+            .create();
+        let _tag_ref = server.mock("GET", "/repos/Human-Glitch/llm-playground/git/ref/tags/v1.3.0")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"object": {"sha": "old123", "type": "commit"}}"#)
+            .create();
+        let _tag_object = server.mock("GET", "/repos/Human-Glitch/llm-playground/git/tags/old123")
+            .with_status(404)
+            .create();
+        let _against_release = server.mock("GET", "/repos/Human-Glitch/llm-playground/compare/old123...release/v1.3.x")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(format!(r#"{{"status": "{}", "commits": []}}"#, status))
+            .create();
+        let _against_commit = server.mock("GET", "/repos/Human-Glitch/llm-playground/compare/old123...abc123")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"status": "diverged", "commits": []}"#)
+            .create();
+
+        let gh_client = GitHubClient::new_with_base_url(Client::new(), "fake_token".to_string(), server.url());
+        let options = ReleaseOptions { force, ..Default::default() };
+
+        let rt = Runtime::new().unwrap();
+        rt.block_on(async { plan_release(&gh_client, "v1.3.0", None, &options).await.map_err(|e| e.to_string()) })
+    }
+
+    #[test]
+    fn given_tag_in_history_of_deleted_release_when_planning_then_deletes_it() {
+        let plan = plan_with_existing_tag("ahead", false).unwrap();
+
+        assert!(plan.has(&PlannedAction::DeleteRelease { id: 9 }));
+        assert!(plan.has(&PlannedAction::DeleteTag));
+    }
+
+    #[test]
+    fn given_tag_outside_history_when_planning_then_refuses_to_delete_it_unless_forced() {
+        let error = plan_with_existing_tag("diverged", false).err().unwrap();
+
+        assert_eq!(
+            error,
+            "Tag v1.3.0 points at old123, which isn't in the history of release/v1.3.x or abc123. Refusing to delete it; check the tag, or pass --force to delete it anyway."
+        );
+        assert!(plan_with_existing_tag("diverged", true).unwrap().has(&PlannedAction::DeleteTag));
+    }
+
+    #[test]
+    fn given_finalized_release_without_force_when_releasing_then_aborts_before_deleting() {
+        let mut server = mockito::Server::new();
+
+        let _release = server.mock("GET", "/repos/Human-Glitch/llm-playground/releases/tags/v1.3.0")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"id": 9, "tag_name": "v1.3.0", "body": "* PDE-1 Fix by @a", "prerelease": false}"#)
+            .create();
+        let _branch = server.mock("GET", "/repos/Human-Glitch/llm-playground/branches/release/v1.3.x")
+            .with_status(404)
+            .create();
+        let _latest = server.mock("GET", "/repos/Human-Glitch/llm-playground/releases/latest")
+            .with_status(404)
+            .create();
+        let mock_delete = server.mock("DELETE", Matcher::Any).expect(0).create();
+
+        let gh_client = GitHubClient::new_with_base_url(Client::new(), "fake_token".to_string(), server.url());
+        let formatter = NotesFormatter::Local(LocalFormatter::default());
+
+        let rt = Runtime::new().unwrap();
+        let result = rt.block_on(async {
+            process_release(&gh_client, "v1.3.0", &formatter, &InterruptState::default(), None, &NoProgress, &ReleaseOptions::default()).await
+        });
+
+        let error = result.err().unwrap().to_string();
+        assert!(error.contains("v1.3.0 (ID: 9) is already finalized"), "{}", error);
+        assert!(error.contains("--force"));
+        mock_delete.assert();
+    }
+
+    #[test]
+    fn given_existing_release_when_planning_then_lists_the_calls_in_order_without_making_them() {
+        let mut server = mockito::Server::new();
+
+        let _release = server.mock("GET", "/repos/Human-Glitch/llm-playground/releases/tags/v1.3.0")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"id": 9, "tag_name": "v1.3.0", "body": "* PDE-1 Fix by @a", "prerelease": false}"#)
+            .create();
+        let _branch = server.mock("GET", "/repos/Human-Glitch/llm-playground/branches/release/v1.3.x")
+            .with_status(404)
+            .create();
+        let _commit = server.mock("GET", "/repos/Human-Glitch/llm-playground/commits/release/v1.3.0")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"sha": "abc123"}"#)
+            .create();
+        let _latest = server.mock("GET", "/repos/Human-Glitch/llm-playground/releases/latest")
+            .with_status(404)
+            .create();
+        let _releases = server.mock("GET", "/repos/Human-Glitch/llm-playground/releases")
+            .match_query(Matcher::Any)
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body("[]")
+            .create();
+        let writes: Vec<_> = ["POST", "PATCH", "DELETE"]
+            .into_iter()
+            .map(|method| server.mock(method, Matcher::Any).expect(0).create())
+            .collect();
+
+        let gh_client = GitHubClient::new_with_base_url(Client::new(), "fake_token".to_string(), server.url());
+        let options = ReleaseOptions {
+            assets: vec![Asset { path: PathBuf::from("dist/app.zip"), content_type: "application/zip".to_string() }],
+            // The release is finalized, so recreating it needs --force
+            force: true,
+            ..Default::default()
+        };
+
+        let rt = Runtime::new().unwrap();
+        let plan = rt.block_on(async { plan_release(&gh_client, "v1.3.0", None, &options).await.unwrap() });
+
+        let api = format!("{}/repos/Human-Glitch/llm-playground", server.url());
+        assert_eq!(plan.branch, "release/v1.3.0");
+        assert_eq!(
+            plan.calls(&gh_client),
+            vec![
+                format!("DELETE {}/releases/9", api),
+                format!("DELETE {}/git/refs/tags/v1.3.0", api),
+                format!("POST {}/git/tags", api),
+                format!("POST {}/git/refs", api),
+                format!("POST {}/releases", api),
+                format!("PATCH {}/releases/{{new}}", api),
+                format!("POST {}/releases/{{new}}/assets?name=app.zip", api),
+            ]
+        );
+        for write in writes {
+            write.assert();
+        }
+    }
+
+    /// Plan a first release of v1.3.0 from a branch whose commits lookup answers `status` and `body`,
+    /// and whether that lookup was made.
+    fn plan_from_branch(status: usize, body: &str, options: &ReleaseOptions) -> (Result<ReleasePlan, String>, bool) {
+        let mut server = mockito::Server::new();
+
+        let _release = server.mock("GET", "/repos/Human-Glitch/llm-playground/releases/tags/v1.3.0")
+            .with_status(404)
+            .create();
+        let _no_drafts = server.mock("GET", "/repos/Human-Glitch/llm-playground/releases")
+            .match_query(Matcher::Any)
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body("[]")
+            .create();
+        let _branch = server.mock("GET", "/repos/Human-Glitch/llm-playground/branches/release/v1.3.x")
+            .with_status(404)
+            .create();
+        let _latest = server.mock("GET", "/repos/Human-Glitch/llm-playground/releases/latest")
+            .with_status(404)
+            .create();
+        let mock_commit = server.mock("GET", "/repos/Human-Glitch/llm-playground/commits/release/v1.3.0")
+            .with_status(status)
+            .with_header("content-type", "application/json")
+            .with_body(body)
+            .create();
+        let _tag_ref = server.mock("GET", "/repos/Human-Glitch/llm-playground/git/ref/tags/v1.3.0")
+            .with_status(404)
+            .create();
+
+        let gh_client = GitHubClient::new_with_base_url(Client::new(), "fake_token".to_string(), server.url());
+        let rt = Runtime::new().unwrap();
+        let plan = rt.block_on(async { plan_release(&gh_client, "v1.3.0", None, options).await.map_err(|e| e.to_string()) });
+        (plan, mock_commit.matched())
+    }
+
+    #[test]
+    fn given_empty_branch_when_planning_then_step_4_suggests_passing_a_sha() {
+        let (plan, looked_up) = plan_from_branch(409, r#"{"message": "Git Repository is empty."}"#, &ReleaseOptions::default());
+
+        assert_eq!(
+            plan.err().unwrap(),
+            "Failed to get latest commit from branch 'release/v1.3.0': Branch 'release/v1.3.0' has no commits to tag. Pass --sha <commit> to tag a specific commit."
+        );
+        assert!(looked_up);
+    }
+
+    #[test]
+    fn given_sha_when_planning_then_tags_it_without_looking_up_the_branch_head() {
+        let options = ReleaseOptions {
+            sha: Some("def456".to_string()),
+            ..Default::default()
+        };
+        let (plan, looked_up) = plan_from_branch(409, r#"{"message": "Git Repository is empty."}"#, &options);
+
+        assert_eq!(plan.unwrap().commit_sha, "def456");
+        assert!(!looked_up);
+    }
+
+    #[test]
+    fn given_prev_tag_when_adding_compare_footer_then_uses_it_as_the_base() {
+        let mut server = mockito::Server::new();
+
+        let mock_latest = server.mock("GET", "/repos/Human-Glitch/llm-playground/releases/latest")
+            .expect(0)
+            .create();
+
+        let gh_client = GitHubClient::new_with_base_url(Client::new(), "fake_token".to_string(), server.url());
+        let args = Cli::try_parse_from(["github-releaser-llm", "--tag", "v1.2.0", "--prev-tag", "v1.0.5", "--footer-compare"]).unwrap();
+        let options = ReleaseOptions {
+            footer_compare: args.footer_compare,
+            previous_tag: args.prev_tag,
+            ..Default::default()
+        };
+
+        let rt = Runtime::new().unwrap();
+        let notes = rt.block_on(async {
+            let previous_tag = changelog::resolve_previous_tag(&gh_client, options.previous_tag.as_deref(), "v1.2.0").await.unwrap();
+            finish_notes(&gh_client, "v1.2.0", "* Notes".to_string(), "* Raw", previous_tag.as_deref(), &options).await.unwrap()
+        });
+
+        assert_eq!(
+            notes,
+            "* Notes\n\n**Full Changelog**: https://github.com/Human-Glitch/llm-playground/compare/v1.0.5...v1.2.0"
+        );
+        mock_latest.assert();
+    }
+
+    #[test]
+    fn given_append_raw_over_the_limit_when_finishing_notes_then_keeps_the_raw_block_whole_or_drops_it() {
+        let gh_client = GitHubClient::new_with_base_url(Client::new(), "fake_token".to_string(), "http://127.0.0.1:9".to_string());
+        let formatted = "## PDE\n* PDE-1 Fix login\n\n## OPS\n* OPS-2 Rotate keys".to_string();
+        let raw = "* PDE-1 Fix login by @alice\n* OPS-2 Rotate keys by @bob";
+        let finish = |max_len: usize| {
+            let options = ReleaseOptions { append_raw: true, max_notes_length: Some(max_len), ..Default::default() };
+            let rt = Runtime::new().unwrap();
+            rt.block_on(finish_notes(&gh_client, "v1.2.0", formatted.clone(), raw, Some("v1.1.0"), &options)).unwrap()
+        };
+
+        let roomy = finish(1_000);
+        assert!(roomy.starts_with(&formatted));
+        assert!(roomy.trim_end().ends_with("</details>"));
+
+        let tight = finish(formatted.len() + 10);
+        assert_eq!(tight, formatted);
+
+        let truncated = finish(100);
+        assert!(!truncated.contains("<details>"));
+        assert!(truncated.starts_with("## PDE\n* PDE-1 Fix login"));
+        assert!(truncated.chars().count() <= 100);
+    }
+
+    #[test]
+    fn given_update_only_when_release_exists_then_only_patches_its_notes() {
+        let mut server = mockito::Server::new();
+
+        let mock_release = server.mock("GET", "/repos/Human-Glitch/llm-playground/releases/tags/v1.2.0")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"id": 9, "tag_name": "v1.2.0", "body": "* PDE-1 Fix by @a"}"#)
+            .create();
+        let mock_update = server.mock("PATCH", "/repos/Human-Glitch/llm-playground/releases/9")
+            .match_body(Matcher::Json(json!({
+                "body": "## PDE\n* [PDE-1](https://onezelis.atlassian.net/browse/PDE-1) Fix by @a"
+            })))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"id": 9}"#)
+            .create();
+        let mock_create = server.mock("POST", Matcher::Any).expect(0).create();
+        let mock_delete = server.mock("DELETE", Matcher::Any).expect(0).create();
+
+        let gh_client = GitHubClient::new_with_base_url(Client::new(), "fake_token".to_string(), server.url());
+        let formatter = NotesFormatter::Local(LocalFormatter::default());
+
+        let rt = Runtime::new().unwrap();
+        rt.block_on(async { update_notes(&gh_client, "v1.2.0", &formatter, &ReleaseOptions::default()).await.unwrap() });
+
+        mock_release.assert();
+        mock_update.assert();
+        mock_create.assert();
+        mock_delete.assert();
+    }
+
+    #[test]
+    fn given_tag_not_created_yet_when_announcing_then_generates_the_notes_up_to_the_release_branch() {
+        let mut server = mockito::Server::new();
+
+        server.mock("GET", "/repos/Human-Glitch/llm-playground/git/ref/tags/v1.3.0")
+            .with_status(404)
+            .create();
+        server.mock("GET", "/repos/Human-Glitch/llm-playground/branches/release/v1.3.x")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"name": "release/v1.3.x"}"#)
+            .create();
+        let mock_generate = server.mock("POST", "/repos/Human-Glitch/llm-playground/releases/generate-notes")
+            .match_body(Matcher::Json(json!({"tag_name": "v1.3.0", "target_commitish": "release/v1.3.x"})))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"name": "v1.3.0", "body": "* PDE-1 Fix by @a"}"#)
+            .create();
+        server.mock("POST", "/graphql")
+            .match_body(Matcher::Regex("discussionCategories".to_string()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"data": {"repository": {"id": "R_1", "discussionCategories": {"nodes": [{"id": "DC_2", "name": "Announcements"}]}}}}"#)
+            .create();
+        server.mock("POST", "/graphql")
+            .match_body(Matcher::Regex("createDiscussion".to_string()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"data": {"createDiscussion": {"discussion": {"url": "https://github.com/o/r/discussions/7"}}}}"#)
+            .create();
+
+        let gh_client = GitHubClient::new_with_base_url(Client::new(), "fake_token".to_string(), server.url());
+        let formatter = NotesFormatter::Local(LocalFormatter::default());
+
+        let rt = Runtime::new().unwrap();
+        let outcome = rt.block_on(async {
+            announce_release(&gh_client, "v1.3.0", &formatter, "Announcements", &ReleaseOptions::default()).await.unwrap()
+        });
+
+        assert!(matches!(&outcome, Outcome::Announced(url, _) if url == "https://github.com/o/r/discussions/7"));
+        assert_eq!(outcome.summary().unwrap().notes_source, Some("generated"));
+        mock_generate.assert();
+    }
+
+    #[test]
+    fn given_openai_failing_with_local_fallback_when_announcing_then_the_outcome_records_the_fallback() {
+        let mut server = mockito::Server::new();
+
+        server.mock("GET", "/repos/Human-Glitch/llm-playground/git/ref/tags/v1.3.0")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"ref": "refs/tags/v1.3.0", "object": {"sha": "abc123", "type": "commit"}}"#)
+            .create();
+        server.mock("POST", "/repos/Human-Glitch/llm-playground/releases/generate-notes")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"name": "v1.3.0", "body": "* PDE-1 Fix by @a"}"#)
+            .create();
+        let mock_openai = server.mock("POST", "/v1/chat/completions")
+            .with_status(500)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"error": {"message": "The server had an error"}}"#)
+            .create();
+        server.mock("POST", "/graphql")
+            .match_body(Matcher::Regex("discussionCategories".to_string()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"data": {"repository": {"id": "R_1", "discussionCategories": {"nodes": [{"id": "DC_2", "name": "Announcements"}]}}}}"#)
+            .create();
+        server.mock("POST", "/graphql")
+            .match_body(Matcher::Regex("createDiscussion".to_string()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"data": {"createDiscussion": {"discussion": {"url": "https://github.com/o/r/discussions/7"}}}}"#)
+            .create();
+
+        let gh_client = GitHubClient::new_with_base_url(Client::new(), "fake_token".to_string(), server.url());
+        let formatter = NotesFormatter::OpenAI(Box::new(OpenAIClient::new_with_base_url(
+            Client::new(),
+            "fake_key".to_string(),
+            "gpt-4o",
+            server.url(),
+        )));
+        let options = ReleaseOptions { llm_fallback: Some(LocalFormatter::default()), ..Default::default() };
+
+        let rt = Runtime::new().unwrap();
+        let outcome = rt.block_on(async {
+            announce_release(&gh_client, "v1.3.0", &formatter, "Announcements", &options).await.unwrap()
+        });
+
+        assert_eq!(outcome.as_str(), "announced_with_local_fallback");
+        assert_eq!(outcome.summary().unwrap().formatter, Some("local_fallback"));
+        let tickets: Vec<&str> = outcome.tickets().iter().map(|ticket| ticket.number.as_str()).collect();
+        assert_eq!(tickets, vec!["1"]);
+        mock_openai.assert();
+    }
+
+    #[test]
+    fn given_update_only_when_notes_are_saved_then_summary_has_the_release_and_its_tickets() {
+        let mut server = mockito::Server::new();
+
+        server.mock("GET", "/repos/Human-Glitch/llm-playground/releases/tags/v1.2.0")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"id": 9, "tag_name": "v1.2.0", "html_url": "https://github.com/Human-Glitch/llm-playground/releases/tag/v1.2.0", "body": "* PDE-1 Fix by @a\n* PD-2 Add export"}"#)
+            .create();
+        server.mock("PATCH", "/repos/Human-Glitch/llm-playground/releases/9")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"id": 9}"#)
+            .create();
+
+        let gh_client = GitHubClient::new_with_base_url(Client::new(), "fake_token".to_string(), server.url());
+        let formatter = NotesFormatter::Local(LocalFormatter::default());
+
+        let rt = Runtime::new().unwrap();
+        let outcome = rt.block_on(async { update_notes(&gh_client, "v1.2.0", &formatter, &ReleaseOptions::default()).await.unwrap() });
+
+        let summary = outcome.summary().unwrap();
+        assert_eq!(summary.release_id, Some(9));
+        assert_eq!(summary.release_url.as_deref(), Some("https://github.com/Human-Glitch/llm-playground/releases/tag/v1.2.0"));
+        assert_eq!(summary.notes_source, Some("release_body"));
+        assert_eq!(summary.formatter, Some("local"));
+        let ids: Vec<String> = summary.tickets.iter().map(|t| format!("{}-{}", t.prefix, t.number)).collect();
+        assert_eq!(ids, vec!["PD-2", "PDE-1"]);
+    }
+
+    #[test]
+    fn given_openai_failing_with_local_fallback_when_updating_only_then_saves_the_local_notes() {
+        let mut server = mockito::Server::new();
+
+        let _release = server.mock("GET", "/repos/Human-Glitch/llm-playground/releases/tags/v1.2.0")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"id": 9, "tag_name": "v1.2.0", "body": "* PDE-1 Fix by @a"}"#)
+            .create();
+        let mock_openai = server.mock("POST", "/v1/chat/completions")
+            .with_status(500)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"error": {"message": "The server had an error"}}"#)
+            .create();
+        let mock_update = server.mock("PATCH", "/repos/Human-Glitch/llm-playground/releases/9")
+            .match_body(Matcher::Json(json!({
+                "body": "## PDE\n* [PDE-1](https://onezelis.atlassian.net/browse/PDE-1) Fix by @a"
+            })))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"id": 9}"#)
+            .create();
+
+        let gh_client = GitHubClient::new_with_base_url(Client::new(), "fake_token".to_string(), server.url());
+        let formatter = NotesFormatter::OpenAI(Box::new(OpenAIClient::new_with_base_url(
+            Client::new(),
+            "fake_key".to_string(),
+            "gpt-4o",
+            server.url(),
+        )));
+        let options = ReleaseOptions { llm_fallback: Some(LocalFormatter::default()), ..Default::default() };
+
+        let rt = Runtime::new().unwrap();
+        let outcome = rt.block_on(async { update_notes(&gh_client, "v1.2.0", &formatter, &options).await.unwrap() });
+
+        assert_eq!(outcome.as_str(), "released_with_local_fallback");
+        mock_openai.assert();
+        mock_update.assert();
+    }
+
+    #[test]
+    fn given_unreachable_model_list_when_checking_model_then_only_fails_without_a_fallback() {
+        let mut server = mockito::Server::new();
+        let _models = server.mock("GET", "/v1/models")
+            .with_status(503)
+            .with_body(r#"{"error": {"message": "Service unavailable"}}"#)
+            .create();
+        let client = OpenAIClient::new_with_base_url(Client::new(), "fake_key".to_string(), "gpt-4o", server.url());
+
+        let rt = Runtime::new().unwrap();
+        rt.block_on(async {
+            assert!(check_model(&client, false).await.is_err());
+            assert!(check_model(&client, true).await.is_ok());
+        });
+    }
+
+    #[test]
+    fn given_update_only_when_release_is_missing_then_errors_without_creating_it() {
+        let mut server = mockito::Server::new();
+
+        let mock_release = server.mock("GET", "/repos/Human-Glitch/llm-playground/releases/tags/v1.2.0")
+            .with_status(404)
+            .create();
+        let mock_create = server.mock("POST", Matcher::Any).expect(0).create();
+
+        let gh_client = GitHubClient::new_with_base_url(Client::new(), "fake_token".to_string(), server.url());
+        let formatter = NotesFormatter::Local(LocalFormatter::default());
+
+        let rt = Runtime::new().unwrap();
+        let result = rt.block_on(async { update_notes(&gh_client, "v1.2.0", &formatter, &ReleaseOptions::default()).await });
+
+        assert_eq!(result.unwrap_err().to_string(), "No release found for tag v1.2.0.");
+        mock_release.assert();
+        mock_create.assert();
+    }
+
+    #[test]
+    fn given_published_release_when_converting_to_draft_then_patches_draft_flag() {
+        let mut server = mockito::Server::new();
+
+        let mock_release = server.mock("GET", "/repos/Human-Glitch/llm-playground/releases/tags/v1.2.0")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"id": 7, "tag_name": "v1.2.0", "draft": false}"#)
+            .create();
+        let mock_draft = server.mock("PATCH", "/repos/Human-Glitch/llm-playground/releases/7")
+            .match_body(Matcher::Json(json!({"draft": true})))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"id": 7, "draft": true}"#)
+            .create();
+
+        let gh_client = GitHubClient::new_with_base_url(Client::new(), "fake_token".to_string(), server.url());
+
+        let rt = Runtime::new().unwrap();
+        let outcome = rt.block_on(async { convert_to_draft(&gh_client, "v1.2.0").await.unwrap() });
+
+        assert_eq!(outcome, Outcome::ConvertedToDraft);
+        mock_release.assert();
+        mock_draft.assert();
+    }
+
+    #[test]
+    fn given_draft_release_when_converting_to_draft_then_leaves_it_unchanged() {
+        let mut server = mockito::Server::new();
+
+        let mock_release = server.mock("GET", "/repos/Human-Glitch/llm-playground/releases/tags/v1.2.0")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"id": 7, "tag_name": "v1.2.0", "draft": true}"#)
+            .create();
+        let mock_draft = server.mock("PATCH", "/repos/Human-Glitch/llm-playground/releases/7")
+            .expect(0)
+            .create();
+
+        let gh_client = GitHubClient::new_with_base_url(Client::new(), "fake_token".to_string(), server.url());
+
+        let rt = Runtime::new().unwrap();
+        let outcome = rt.block_on(async { convert_to_draft(&gh_client, "v1.2.0").await.unwrap() });
+
+        assert_eq!(outcome, Outcome::AlreadyDraft);
+        mock_release.assert();
+        mock_draft.assert();
+    }
+
+    #[test]
+    fn given_mixed_releases_when_listing_prereleases_then_prints_them_newest_first() {
+        let mut server = mockito::Server::new();
+
+        let mock = server.mock("GET", "/repos/Human-Glitch/llm-playground/releases")
+            .match_query(Matcher::UrlEncoded("page".into(), "1".into()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"[
+                {"id": 1, "tag_name": "v1.2.0-rc.2", "prerelease": true, "draft": false},
+                {"id": 2, "tag_name": "v1.10.0", "prerelease": false, "draft": false},
+                {"id": 3, "tag_name": "v1.2.0-rc.10", "prerelease": true, "draft": true},
+                {"id": 4, "tag_name": "v1.9.1", "prerelease": false, "draft": false},
+                {"id": 5, "tag_name": "v1.11.0-beta.1", "prerelease": true, "draft": false}
+            ]"#)
+            .expect(2)
+            .create();
+
+        let gh_client = GitHubClient::new_with_base_url(Client::new(), "fake_token".to_string(), server.url());
+
+        let rt = Runtime::new().unwrap();
+        let (prereleases, stable) = rt.block_on(async {
+            (
+                list_releases(&gh_client, ReleaseFilter::PrereleasesOnly).await.unwrap(),
+                list_releases(&gh_client, ReleaseFilter::StableOnly).await.unwrap(),
+            )
+        });
+
+        assert_eq!(
+            prereleases,
+            [
+                "TAG                                ID  PRERELEASE  DRAFT",
+                "v1.11.0-beta.1                      5  true        false",
+                "v1.2.0-rc.10                        3  true        true",
+                "v1.2.0-rc.2                         1  true        false",
+            ]
+            .join("\n")
+        );
+        let stable_tags: Vec<&str> = stable.lines().skip(1).map(|line| line.split_whitespace().next().unwrap()).collect();
+        assert_eq!(stable_tags, vec!["v1.10.0", "v1.9.1"]);
+        mock.assert();
+    }
+
+    #[test]
+    fn given_reformat_all_when_some_notes_already_match_then_only_updates_the_others() {
+        let mut server = mockito::Server::new();
+
+        let formatted = "## PDE\n* [PDE-1](https://onezelis.atlassian.net/browse/PDE-1) Fix by @a";
+        let mock_list = server.mock("GET", "/repos/Human-Glitch/llm-playground/releases")
+            .match_query(Matcher::UrlEncoded("page".into(), "1".into()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(json!([
+                {"id": 1, "tag_name": "v1.1.0", "body": formatted, "prerelease": false},
+                {"id": 2, "tag_name": "v1.2.0", "body": "* PDE-2 Add login by @b", "prerelease": false},
+                {"id": 3, "tag_name": "v1.3.0", "body": "", "prerelease": false}
+            ]).to_string())
+            .create();
+        let mock_unchanged = server.mock("PATCH", "/repos/Human-Glitch/llm-playground/releases/1")
+            .expect(0)
+            .create();
+        let mock_update = server.mock("PATCH", "/repos/Human-Glitch/llm-playground/releases/2")
+            .match_body(Matcher::Json(json!({
+                "body": "## PDE\n* [PDE-2](https://onezelis.atlassian.net/browse/PDE-2) Add login by @b"
+            })))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"id": 2}"#)
+            .create();
+        let mock_empty = server.mock("PATCH", "/repos/Human-Glitch/llm-playground/releases/3")
+            .expect(0)
+            .create();
+
+        let gh_client = GitHubClient::new_with_base_url(Client::new(), "fake_token".to_string(), server.url());
+        let formatter = NotesFormatter::Local(LocalFormatter::default());
+
+        let rt = Runtime::new().unwrap();
+        let results = rt.block_on(async { reformat_all(&gh_client, &formatter, ReleaseFilter::All, false, 2).await.unwrap() });
+
+        let outcomes: Vec<(String, Reformat)> = results.into_iter().map(|(tag, result)| (tag, result.unwrap())).collect();
+        assert_eq!(
+            outcomes,
+            vec![("v1.2.0".to_string(), Reformat::Updated), ("v1.1.0".to_string(), Reformat::Unchanged)]
+        );
+        mock_list.assert();
+        mock_unchanged.assert();
+        mock_update.assert();
+        mock_empty.assert();
+    }
+
+    #[test]
+    fn given_finalize_subcommand_when_parsing_args_then_tag_is_not_required_globally() {
+        let args = Cli::try_parse_from(["github-releaser-llm", "finalize", "--tag", "v1.2.0", "--formatter", "local"]).unwrap();
+
+        assert!(matches!(args.command, Some(Command::Finalize { ref tag }) if tag == "v1.2.0"));
+        assert_eq!(args.formatter, FormatterKind::Local);
+        assert!(Cli::try_parse_from(["github-releaser-llm"]).is_err());
+    }
+
+    #[test]
+    fn given_repository_specs_when_parsing_then_splits_owner_and_name() {
+        assert_eq!(
+            parse_repository("llm-playground", "Human-Glitch").unwrap(),
+            ("Human-Glitch".to_string(), "llm-playground".to_string())
+        );
+        assert_eq!(
+            parse_repository("octo-org/octo-repo", "Human-Glitch").unwrap(),
+            ("octo-org".to_string(), "octo-repo".to_string())
+        );
+
+        assert!(parse_repository("", "Human-Glitch").is_err());
+        assert!(parse_repository("octo-org/", "Human-Glitch").is_err());
+        assert!(parse_repository("a/b/c", "Human-Glitch").is_err());
+    }
+
+    #[test]
+    fn given_provider_without_model_when_parsing_args_then_resolves_its_default_model() {
+        let model_for = |flags: &[&str]| {
+            let args = Cli::try_parse_from(["github-releaser-llm", "--tag", "v1.0.0"].iter().chain(flags)).unwrap();
+            args.model().to_string()
+        };
+
+        assert_eq!(model_for(&[]), "gpt-4o");
+        assert_eq!(model_for(&["--provider", "openai"]), "gpt-4o");
+        assert_eq!(model_for(&["--provider", "anthropic"]), "claude-3-5-sonnet-latest");
+        assert_eq!(model_for(&["--provider", "ollama"]), "llama3.1");
+        assert_eq!(model_for(&["--provider", "anthropic", "--model", "claude-3-opus-latest"]), "claude-3-opus-latest");
+    }
+
+    #[test]
+    fn given_out_of_range_penalty_when_parsing_args_then_fails() {
+        let result = Cli::try_parse_from(["github-releaser-llm", "--tag", "v1.0.0", "--presence-penalty", "2.5"]);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn given_negative_penalty_when_parsing_args_then_succeeds() {
+        let args = Cli::try_parse_from(["github-releaser-llm", "--tag", "v1.0.0", "--frequency-penalty", "-1.5"]).unwrap();
+
+        assert_eq!(args.frequency_penalty, Some(-1.5));
+        assert_eq!(args.presence_penalty, None);
+    }
+
+    #[test]
+    fn given_mock_mode_when_releasing_then_completes_the_run_without_the_network() {
+        let rt = Runtime::new().unwrap();
+        let outcome = rt.block_on(async {
+            let mock_github = MockGitHub::start("v1.0.0").await;
+            // Without retries, any request the fake doesn't answer fails the run
+            let gh_client = GitHubClient::new(Client::new(), mock_github::MOCK_TOKEN.to_string())
+                .with_base_url(&mock_github.url())
+                .with_retry_policy(RetryPolicy::new(0));
+            let formatter = NotesFormatter::Local(LocalFormatter::default());
+            let options = ReleaseOptions::default();
+
+            process_release(&gh_client, "v1.0.0", &formatter, &InterruptState::default(), None, &NoProgress, &options).await.unwrap()
+        });
+
+        let summary = outcome.summary().unwrap();
+        assert_eq!(summary.release_id, Some(1));
+        assert_eq!(summary.branch.as_deref(), Some("release/v1.0.x"));
+        assert_eq!(summary.formatter, Some("local"));
+        let tickets: Vec<&str> = outcome.tickets().iter().map(|ticket| ticket.number.as_str()).collect();
+        assert_eq!(tickets, vec!["101", "102"]);
+    }
+
+    #[test]
+    fn given_flags_the_fake_does_not_answer_when_mocking_then_they_are_the_gaps() {
+        let args = Cli::try_parse_from(["github-releaser-llm", "--tag", "v1.0.0", "--mock", "--regenerate-notes", "--include-reviewers"]).unwrap();
+        let plain = Cli::try_parse_from(["github-releaser-llm", "--tag", "v1.0.0", "--mock"]).unwrap();
+
+        assert_eq!(mock_gaps(&args), vec!["--regenerate-notes", "--include-reviewers"]);
+        assert!(mock_gaps(&plain).is_empty());
+    }
+
+    #[test]
+    fn given_timed_release_when_it_completes_then_has_a_timing_for_planning_and_every_executed_step() {
+        let timer = StepTimer::new(&NoProgress);
+        let rt = Runtime::new().unwrap();
+        rt.block_on(async {
+            let mock_github = MockGitHub::start("v1.0.0").await;
+            let gh_client = GitHubClient::new(Client::new(), mock_github::MOCK_TOKEN.to_string())
+                .with_base_url(&mock_github.url())
+                .with_retry_policy(RetryPolicy::new(0));
+            let formatter = NotesFormatter::Local(LocalFormatter::default());
+
+            process_release(&gh_client, "v1.0.0", &formatter, &InterruptState::default(), None, &timer, &ReleaseOptions::default())
+                .await
+                .unwrap()
+        });
+
+        let phases: Vec<String> = timer
+            .timings("Human-Glitch/llm-playground")
+            .into_iter()
+            .map(|(phase, _)| phase)
+            .collect();
+        // No --asset files, so nothing is uploaded
+        let mut expected = vec!["planning"];
+        expected.extend(&progress::STEPS[..9]);
+        assert_eq!(phases, expected);
+    }
+
+    #[test]
+    fn given_bumped_prerelease_when_previewing_then_finishes_the_notes_of_the_new_tag_like_a_release() {
+        let mut server = mockito::Server::new();
+
+        let _release = server.mock("GET", "/repos/Human-Glitch/llm-playground/releases/tags/v1.3.0")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"id": 9, "tag_name": "v1.3.0", "body": "* PDE-1 Fix by @a", "prerelease": true}"#)
+            .create();
+        let _branch = server.mock("GET", "/repos/Human-Glitch/llm-playground/branches/release/v1.3.x")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"name": "release/v1.3.x"}"#)
+            .create();
+        let _latest = server.mock("GET", "/repos/Human-Glitch/llm-playground/releases/latest")
+            .with_status(404)
+            .create();
+        let _new_release = server.mock("GET", "/repos/Human-Glitch/llm-playground/releases/tags/v1.3.1")
+            .with_status(404)
+            .create();
+        let mock_generate = server.mock("POST", "/repos/Human-Glitch/llm-playground/releases/generate-notes")
+            .match_body(Matcher::PartialJson(json!({"tag_name": "v1.3.1"})))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(json!({"body": (1..=20).map(|n| format!("* PDE-{} Fix a long-standing bug by @a\n", n)).collect::<String>()}).to_string())
+            .create();
+        let writes: Vec<_> = ["PATCH", "DELETE"]
+            .into_iter()
+            .map(|method| server.mock(method, Matcher::Any).expect(0).create())
+            .collect();
+
+        let output = std::env::temp_dir().join(format!("releaser-preview-{}.md", std::process::id()));
+        let gh_client = GitHubClient::new_with_base_url(Client::new(), "fake_token".to_string(), server.url());
+        let formatter = NotesFormatter::Local(LocalFormatter::default());
+        let options = ReleaseOptions {
+            max_notes_length: Some(300),
+            output: Some(output.clone()),
+            ..Default::default()
+        };
+
+        let rt = Runtime::new().unwrap();
+        rt.block_on(async { preview_release(&gh_client, "v1.3.0", &formatter, &options).await.unwrap() });
+
+        let previewed = std::fs::read_to_string(&output).unwrap();
+        std::fs::remove_file(&output).unwrap();
+        assert!(previewed.len() <= 300, "{}", previewed);
+        assert!(previewed.ends_with("[…see full changelog](https://github.com/Human-Glitch/llm-playground/commits/v1.3.1)"), "{}", previewed);
+        mock_generate.assert();
+        for write in writes {
+            write.assert();
+        }
+    }
+
+    #[test]
+    fn given_extra_notes_when_updating_notes_then_the_formatter_sees_them_after_the_raw_notes() {
+        let mut server = mockito::Server::new();
+        let expected_input = "* PDE-1 Fix by @a\n\n<!-- extra notes: known-issues.md -->\n* PDE-200 Exports time out on large files";
+        // The prompt puts the notes between its template and its rules, so the stub only answers the exact input
+        let in_prompt = serde_json::to_string(&format!("\\n\\n{}\n\n", expected_input)).unwrap();
+
+        let _release = server.mock("GET", "/repos/Human-Glitch/llm-playground/releases/tags/v1.2.0")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"id": 9, "tag_name": "v1.2.0", "body": "* PDE-1 Fix by @a\n"}"#)
+            .create();
+        let mock_openai = server.mock("POST", "/v1/chat/completions")
+            .match_body(Matcher::Regex(regex::escape(&in_prompt[1..in_prompt.len() - 1])))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(json!({"choices": [{"message": {"role": "assistant", "content": "## PDE\n* PDE-1 Fix by @a"}}]}).to_string())
+            .create();
+        let mock_update = server.mock("PATCH", "/repos/Human-Glitch/llm-playground/releases/9")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"id": 9}"#)
+            .create();
+
+        let gh_client = GitHubClient::new_with_base_url(Client::new(), "fake_token".to_string(), server.url());
+        let formatter = NotesFormatter::OpenAI(Box::new(OpenAIClient::new_with_base_url(
+            Client::new(),
+            "fake_key".to_string(),
+            "gpt-4o",
+            server.url(),
+        )));
+        let options = ReleaseOptions {
+            extra_notes: vec![ExtraNotes {
+                label: "known-issues.md".to_string(),
+                text: "* PDE-200 Exports time out on large files\n".to_string(),
+            }],
+            ..Default::default()
+        };
+
+        let rt = Runtime::new().unwrap();
+        rt.block_on(async { update_notes(&gh_client, "v1.2.0", &formatter, &options).await.unwrap() });
+
+        mock_openai.assert();
+        mock_update.assert();
+    }
 }
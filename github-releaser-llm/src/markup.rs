@@ -0,0 +1,135 @@
+use regex::{Captures, Regex};
+use std::sync::LazyLock;
+
+/// Markup the formatted notes are written to `--output` in; the release body is always markdown.
+#[derive(Clone, Copy, Debug, Default, PartialEq, clap::ValueEnum)]
+pub enum NotesFormat {
+    #[default]
+    Markdown,
+    Asciidoc,
+    Rst,
+}
+
+/// Render markdown notes (`##` headings, `*`/`-` bullets, `[text](url)` links) in another markup.
+pub fn render(notes: &str, format: NotesFormat) -> String {
+    if format == NotesFormat::Markdown {
+        return notes.to_string();
+    }
+
+    let mut lines: Vec<String> = Vec::new();
+    for line in notes.lines() {
+        match heading(line) {
+            Some((level, title)) => render_heading(&mut lines, level, &links(title, format), format),
+            None => match bullet_item(line) {
+                // Both markups start items with `* `
+                Some(item) => {
+                    // reStructuredText wants a blank line between a paragraph and a list
+                    if format == NotesFormat::Rst && lines.last().is_some_and(|last| !last.is_empty() && !last.starts_with("* ")) {
+                        lines.push(String::new());
+                    }
+                    lines.push(format!("* {}", links(item, format)));
+                }
+                None => lines.push(links(line, format)),
+            },
+        }
+    }
+
+    let mut rendered = lines.join("\n");
+    if notes.ends_with('\n') {
+        rendered.push('\n');
+    }
+    rendered
+}
+
+fn heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|c| *c == '#').count();
+    let title = line[level..].strip_prefix(' ')?;
+    (level > 0).then_some((level, title.trim()))
+}
+
+fn bullet_item(line: &str) -> Option<&str> {
+    let line = line.trim_start();
+    line.strip_prefix("* ").or_else(|| line.strip_prefix("- "))
+}
+
+fn render_heading(lines: &mut Vec<String>, level: usize, title: &str, format: NotesFormat) {
+    match format {
+        NotesFormat::Asciidoc => lines.push(format!("{} {}", "=".repeat(level), title)),
+        NotesFormat::Rst => {
+            // Underline characters by level; `#` for the rare top-level heading
+            let underline = match level {
+                1 => '#',
+                2 => '=',
+                3 => '-',
+                _ => '~',
+            };
+            if lines.last().is_some_and(|last| !last.is_empty()) {
+                lines.push(String::new());
+            }
+            lines.push(title.to_string());
+            lines.push(underline.to_string().repeat(title.chars().count()));
+            lines.push(String::new());
+        }
+        NotesFormat::Markdown => lines.push(format!("{} {}", "#".repeat(level), title)),
+    }
+}
+
+/// A markdown `[text](url)` link.
+static LINK_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\[([^\]]+)\]\(([^)\s]+)\)").unwrap());
+
+/// Rewrite the `[text](url)` links of a line.
+fn links(text: &str, format: NotesFormat) -> String {
+    LINK_RE
+        .replace_all(text, |caps: &Captures| match format {
+            NotesFormat::Asciidoc => format!("link:{}[{}]", &caps[2], &caps[1]),
+            // Anonymous hyperlinks, so two links with the same text don't clash
+            NotesFormat::Rst => format!("`{} <{}>`__", &caps[1], &caps[2]),
+            NotesFormat::Markdown => caps[0].to_string(),
+        })
+        .into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOTES: &str = "## PDE\n\
+        * [PDE-1](https://t.example.com/browse/PDE-1) Fix login by @a\n\
+        - [PDE-2](https://t.example.com/browse/PDE-2) Add SSO\n\
+        ### General\n\
+        * Bump serde\n";
+
+    #[test]
+    fn given_structured_notes_when_rendering_asciidoc_then_uses_asciidoc_syntax() {
+        assert_eq!(
+            render(NOTES, NotesFormat::Asciidoc),
+            "== PDE\n\
+            * link:https://t.example.com/browse/PDE-1[PDE-1] Fix login by @a\n\
+            * link:https://t.example.com/browse/PDE-2[PDE-2] Add SSO\n\
+            === General\n\
+            * Bump serde\n"
+        );
+    }
+
+    #[test]
+    fn given_structured_notes_when_rendering_rst_then_underlines_headings_and_uses_anonymous_links() {
+        assert_eq!(
+            render(NOTES, NotesFormat::Rst),
+            "PDE\n\
+            ===\n\
+            \n\
+            * `PDE-1 <https://t.example.com/browse/PDE-1>`__ Fix login by @a\n\
+            * `PDE-2 <https://t.example.com/browse/PDE-2>`__ Add SSO\n\
+            \n\
+            General\n\
+            -------\n\
+            \n\
+            * Bump serde\n"
+        );
+    }
+
+    #[test]
+    fn given_markdown_format_when_rendering_then_leaves_the_notes_alone() {
+        assert_eq!(render(NOTES, NotesFormat::Markdown), NOTES);
+    }
+}
@@ -0,0 +1,80 @@
+use mockito::{Matcher, Mock, ServerGuard};
+use serde_json::json;
+
+/// Token handed to the clients in mock mode; the fake server doesn't check it.
+pub const MOCK_TOKEN: &str = "mock-token";
+
+/// The release the canned data treats as the previous one.
+const PREVIOUS_TAG: &str = "v0.9.0";
+
+/// An in-process GitHub API with canned answers for a release run, for `--mock`.
+///
+/// Every repository gets the same answers: no existing release or tag, a release branch with one
+/// commit ahead of the previous release, and a few line items as the generated notes.
+///
+/// Only the calls of a release run are answered: there are no generate-notes, pull request, review,
+/// label or discussion endpoints, so `finalize`, `--regenerate-notes`, `--include-reviewers`,
+/// `--group-by-label-prefix` and `--announce-only` are refused in mock mode, as is `--validate-links`,
+/// which checks the links over the network.
+pub struct MockGitHub {
+    server: ServerGuard,
+    // Mocks are removed from the server when they're dropped
+    _mocks: Vec<Mock>,
+}
+
+impl MockGitHub {
+    pub async fn start(tag: &str) -> Self {
+        let mut server = mockito::Server::new_async().await;
+        let repo = r"/repos/[^/]+/[^/]+";
+        let path = |rest: &str| Matcher::Regex(format!("^{}/{}", repo, rest));
+
+        let notes = format!(
+            "## What's Changed\n\
+            * PDE-101 Fix login timeout by @alice in https://github.com/Human-Glitch/llm-playground/pull/12\n\
+            * PDE-102 Add SSO for enterprise accounts by @bob in https://github.com/Human-Glitch/llm-playground/pull/13\n\
+            * Bump serde to 1.0.200 by @dependabot in https://github.com/Human-Glitch/llm-playground/pull/14\n\n\
+            **Full Changelog**: https://github.com/Human-Glitch/llm-playground/compare/{}...{}",
+            PREVIOUS_TAG, tag
+        );
+        let release = json!({
+            "id": 1,
+            "tag_name": tag,
+            "body": notes,
+            "prerelease": false,
+            "draft": false,
+            "html_url": format!("https://github.com/Human-Glitch/llm-playground/releases/tag/{}", tag),
+        });
+        let answers = [
+            ("GET", path(r"releases/tags/"), 404, json!({"message": "Not Found"})),
+            ("GET", path(r"releases/latest$"), 200, json!({"id": 0, "tag_name": PREVIOUS_TAG, "prerelease": false})),
+            ("GET", path(r"releases(\?.*)?$"), 200, json!([{"id": 0, "tag_name": PREVIOUS_TAG, "prerelease": false}])),
+            ("GET", path(r"branches/"), 200, json!({"name": "release"})),
+            ("GET", path(r"commits/"), 200, json!({"sha": "0a1b2c3d4e5f"})),
+            ("GET", path(r"compare/"), 200, json!({"status": "ahead", "commits": []})),
+            ("GET", path(r"git/ref/tags/"), 404, json!({"message": "Not Found"})),
+            ("DELETE", path(r"git/refs/tags/"), 404, json!({"message": "Not Found"})),
+            ("POST", path(r"git/tags$"), 201, json!({"sha": "9f8e7d6c5b4a"})),
+            ("POST", path(r"git/refs$"), 201, json!({})),
+            ("POST", path(r"releases$"), 201, release.clone()),
+            ("PATCH", path(r"releases/\d+$"), 200, release),
+        ];
+
+        let mut mocks = Vec::new();
+        for (method, matcher, status, body) in answers {
+            let mock = server
+                .mock(method, matcher)
+                .with_status(status)
+                .with_header("content-type", "application/json")
+                .with_body(body.to_string())
+                .create_async()
+                .await;
+            mocks.push(mock);
+        }
+        MockGitHub { server, _mocks: mocks }
+    }
+
+    /// The base URL to point the GitHub clients at.
+    pub fn url(&self) -> String {
+        self.server.url()
+    }
+}
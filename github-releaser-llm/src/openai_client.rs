@@ -1,112 +1,1516 @@
-use std::error::Error;
 use reqwest::Client;
-use serde_json::json;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::sync::Mutex;
+
+use crate::formatter::UnmatchedLines;
+use crate::output;
+use crate::retry::{self, CallBudget, RetryPolicy};
+use crate::summarize::{self, SummaryOptions};
 
 pub struct OpenAIClient {
     http_client: Client,
     api_key: String,
     model: String,
     base_url: String,
+    retry: RetryPolicy,
+    seed: Option<u64>,
+    presence_penalty: Option<f64>,
+    frequency_penalty: Option<f64>,
+    model_fallbacks: Vec<String>,
+    /// Fallback models whose route or preset differs from the configured model's
+    fallback_settings: Vec<(String, ModelSettings)>,
+    unmatched: UnmatchedLines,
+    context_window: Option<usize>,
+    user: Option<String>,
+    summary: Option<Box<SummaryOptions>>,
+    /// Prompt preset instructions added to the formatting prompt
+    preset_instructions: &'static str,
+    response_shape: ResponseShape,
+    api: OpenAiApi,
+    /// Most independent calls (summary chunks, translations) in flight at once
+    max_parallel: usize,
+    /// Tokens every completion so far used
+    usage: Mutex<TokenUsage>,
+    /// Raw notes longer than this many characters are formatted in chunks
+    chunk_threshold: usize,
+}
+
+/// Where requests go unless a gateway is configured.
+pub const OPENAI_BASE_URL: &str = "https://api.openai.com";
+
+/// The gateway, key, prompt preset and response shape one model of the fallback chain is called with.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ModelSettings {
+    pub base_url: String,
+    pub api_key: String,
+    pub preset_instructions: &'static str,
+    pub response_shape: ResponseShape,
+}
+
+/// Characters of raw notes formatted in a single call unless `--chunk-threshold` says otherwise.
+pub const DEFAULT_CHUNK_THRESHOLD: usize = 24_000;
+
+/// Tokens OpenAI reported using, for the end-of-run summary.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize)]
+pub struct TokenUsage {
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+}
+
+impl TokenUsage {
+    /// The `usage` of a response, named as by chat completions (`prompt_tokens`) or the Responses API (`input_tokens`).
+    pub fn from_response(response: &Value) -> Option<TokenUsage> {
+        let usage = &response["usage"];
+        let tokens = |names: [&str; 2]| names.iter().find_map(|name| usage[name].as_u64());
+        let prompt_tokens = tokens(["prompt_tokens", "input_tokens"]);
+        let completion_tokens = tokens(["completion_tokens", "output_tokens"]);
+        (prompt_tokens.is_some() || completion_tokens.is_some()).then(|| TokenUsage {
+            prompt_tokens: prompt_tokens.unwrap_or(0),
+            completion_tokens: completion_tokens.unwrap_or(0),
+        })
+    }
+
+    pub fn add(self, other: TokenUsage) -> TokenUsage {
+        TokenUsage {
+            prompt_tokens: self.prompt_tokens + other.prompt_tokens,
+            completion_tokens: self.completion_tokens + other.completion_tokens,
+        }
+    }
+
+    pub fn total(&self) -> u64 {
+        self.prompt_tokens + self.completion_tokens
+    }
+
+    /// What the tokens cost in USD at `prices`.
+    pub fn cost(&self, prices: TokenPrices) -> f64 {
+        (self.prompt_tokens as f64 * prices.prompt + self.completion_tokens as f64 * prices.completion) / 1_000_000.0
+    }
+}
+
+/// USD per million prompt and completion tokens, which differ by model and plan.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TokenPrices {
+    pub prompt: f64,
+    pub completion: f64,
+}
+
+/// Independent calls in flight at once unless `--max-parallel-openai` says otherwise.
+pub const DEFAULT_MAX_PARALLEL: usize = 2;
+
+/// Tokens kept free for the model's answer when checking the prompt against the context window.
+pub const RESPONSE_TOKEN_RESERVE: usize = 4096;
+
+/// Known context windows, most specific prefix first so dated snapshots match their family.
+const CONTEXT_WINDOWS: &[(&str, usize)] = &[
+    ("gpt-4o-mini", 128_000),
+    ("gpt-4o", 128_000),
+    ("gpt-4.1", 1_047_576),
+    ("gpt-4-turbo", 128_000),
+    ("gpt-4", 8_192),
+    ("gpt-3.5-turbo", 16_385),
+    ("o1", 200_000),
+    ("o3", 200_000),
+];
+
+/// Why an OpenAI call failed, so callers can tell rate limits from refusals and bad keys.
+#[derive(Debug, thiserror::Error)]
+pub enum OpenAiError {
+    /// The request couldn't be sent, or came back with an unexpected status
+    #[error("{0}")]
+    Http(String),
+    #[error("OpenAI rejected the API key ({status}): {message}")]
+    Auth { status: u16, message: String },
+    #[error("OpenAI rate limit reached: {0}")]
+    RateLimited(String),
+    /// The model declined to answer
+    #[error("OpenAI refused the request: {0}")]
+    Refused(String),
+    /// Every attempt came back with blank content
+    #[error("OpenAI returned empty content {0} times in a row.")]
+    EmptyContent(u32),
+    #[error("{0}")]
+    BadResponse(String),
+    /// The model doesn't exist or isn't available to this account
+    #[error("{0}")]
+    ModelUnavailable(String),
+    /// The prompt wouldn't fit the model's context window
+    #[error("{0}")]
+    PromptTooLarge(String),
+    /// The summary used up its `--max-llm-calls`, counting retries and model fallbacks
+    #[error("The summary used all of its {0} OpenAI calls. Raise --max-llm-calls or --summary-chunk-lines.")]
+    CallCapReached(usize),
+}
+
+impl From<reqwest::Error> for OpenAiError {
+    fn from(err: reqwest::Error) -> Self {
+        OpenAiError::Http(format!("OpenAI request failed: {}", err))
+    }
+}
+
+impl OpenAiError {
+    /// Classify a failed response by its status code.
+    fn from_status(context: &str, status: reqwest::StatusCode, response: &serde_json::Value) -> Self {
+        let message = response["error"]["message"].as_str().unwrap_or("unknown error").to_string();
+
+        match status.as_u16() {
+            401 | 403 => OpenAiError::Auth { status: status.as_u16(), message },
+            429 => OpenAiError::RateLimited(message),
+            code => OpenAiError::Http(format!("{}: {} {}", context, code, message)),
+        }
+    }
+}
+
+/// Whose models format the notes, through the provider's OpenAI-compatible chat completions endpoint.
+#[derive(Clone, Copy, Debug, Default, PartialEq, clap::ValueEnum)]
+pub enum Provider {
+    #[default]
+    Openai,
+    Anthropic,
+    /// A local Ollama server
+    Ollama,
+}
+
+impl Provider {
+    /// The model used when `--model` isn't given.
+    pub fn default_model(&self) -> &'static str {
+        match self {
+            Provider::Openai => "gpt-4o",
+            Provider::Anthropic => "claude-3-5-sonnet-latest",
+            Provider::Ollama => "llama3.1",
+        }
+    }
+
+    pub fn base_url(&self) -> &'static str {
+        match self {
+            Provider::Openai => "https://api.openai.com",
+            Provider::Anthropic => "https://api.anthropic.com",
+            Provider::Ollama => "http://localhost:11434",
+        }
+    }
+
+    /// The variable holding the API key, or `None` for a local server that doesn't check one.
+    pub fn key_env(&self) -> Option<&'static str> {
+        match self {
+            Provider::Openai => Some("OPENAI_API_KEY"),
+            Provider::Anthropic => Some("ANTHROPIC_API_KEY"),
+            Provider::Ollama => None,
+        }
+    }
+
+    /// Whether `/v1/models` takes the same bearer token as the prompts; Anthropic's wants its own headers.
+    pub fn lists_models(&self) -> bool {
+        *self != Provider::Anthropic
+    }
+}
+
+/// Which OpenAI endpoint the prompts are sent to.
+#[derive(Clone, Copy, Debug, Default, PartialEq, clap::ValueEnum)]
+pub enum OpenAiApi {
+    /// `/v1/chat/completions`, with the prompt as a user message
+    #[default]
+    Chat,
+    /// `/v1/responses`, with the prompt as `input`
+    Responses,
+}
+
+impl OpenAiApi {
+    fn path(&self) -> &'static str {
+        match self {
+            OpenAiApi::Chat => "/v1/chat/completions",
+            OpenAiApi::Responses => "/v1/responses",
+        }
+    }
+
+    /// Where this API puts the answer, unless a gateway says otherwise.
+    pub fn response_shape(&self) -> ResponseShape {
+        match self {
+            OpenAiApi::Chat => ResponseShape::ChatCompletions,
+            OpenAiApi::Responses => ResponseShape::Responses,
+        }
+    }
+}
+
+/// Where a gateway puts the model's answer in its response.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum ResponseShape {
+    /// `choices[0].message.content`, as OpenAI's chat completions return it
+    #[default]
+    ChatCompletions,
+    /// `output_text`, or the `output_text` parts of `output[].content`, as the Responses API returns it
+    Responses,
+    /// `choices[0].text`, as legacy completion gateways return it
+    Completions,
+}
+
+impl ResponseShape {
+    fn path(&self) -> &'static str {
+        match self {
+            ResponseShape::ChatCompletions => "choices[0].message.content",
+            ResponseShape::Responses => "output_text",
+            ResponseShape::Completions => "choices[0].text",
+        }
+    }
+
+    /// The answer in a response of this shape; a refusal comes back as `Refused`.
+    pub fn extract(&self, response: &Value) -> Result<String, OpenAiError> {
+        let content = match self {
+            ResponseShape::ChatCompletions => {
+                let message = &response["choices"][0]["message"];
+                if let Some(refusal) = message["refusal"].as_str() {
+                    return Err(OpenAiError::Refused(refusal.to_string()));
+                }
+                message["content"].as_str().map(str::to_string)
+            }
+            ResponseShape::Responses => match response["output_text"].as_str() {
+                Some(text) => Some(text.to_string()),
+                None => Self::responses_output_text(response)?,
+            },
+            ResponseShape::Completions => response["choices"][0]["text"].as_str().map(str::to_string),
+        };
+
+        content.ok_or_else(|| {
+            OpenAiError::BadResponse(format!(
+                "Failed to extract formatted release notes from OpenAI response (expected {}).",
+                self.path()
+            ))
+        })
+    }
+
+    /// The `output_text` parts of the message items in a Responses API `output`, joined.
+    fn responses_output_text(response: &Value) -> Result<Option<String>, OpenAiError> {
+        let parts: Vec<&Value> = response["output"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|item| item["content"].as_array())
+            .flatten()
+            .collect();
+
+        if let Some(refusal) = parts.iter().find(|part| part["type"] == "refusal") {
+            return Err(OpenAiError::Refused(refusal["refusal"].as_str().unwrap_or("no reason given").to_string()));
+        }
+        let texts: Vec<&str> = parts
+            .iter()
+            .filter(|part| part["type"] == "output_text")
+            .filter_map(|part| part["text"].as_str())
+            .collect();
+        Ok((!texts.is_empty()).then(|| texts.concat()))
+    }
+}
+
+/// The context window of a known model, if there is one.
+pub fn context_window(model: &str) -> Option<usize> {
+    CONTEXT_WINDOWS
+        .iter()
+        .find(|(prefix, _)| model.starts_with(prefix))
+        .map(|(_, tokens)| *tokens)
+}
+
+/// Rough token count of a prompt, using OpenAI's rule of thumb of ~4 characters per token.
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(4)
+}
+
+/// Check that a prompt leaves `max_tokens` free in the context window, returning its estimated size.
+pub fn check_token_budget(prompt: &str, context_window: usize, max_tokens: usize) -> Result<usize, String> {
+    let estimate = estimate_tokens(prompt);
+    let budget = context_window.saturating_sub(max_tokens);
+
+    if estimate <= budget {
+        Ok(estimate)
+    } else {
+        Err(format!(
+            "The prompt is about {} tokens, which exceeds the {} tokens available ({} token context window minus {} for the response). Trim the release range or use a model with a larger context window.",
+            estimate, budget, context_window, max_tokens
+        ))
+    }
+}
+
+/// Parse a presence/frequency penalty, which OpenAI only accepts within -2.0..=2.0.
+pub fn parse_penalty(value: &str) -> Result<f64, String> {
+    let penalty: f64 = value
+        .parse()
+        .map_err(|_| format!("'{}' is not a number", value))?;
+
+    if (-2.0..=2.0).contains(&penalty) {
+        Ok(penalty)
+    } else {
+        Err(format!("{} is outside the allowed range -2.0..=2.0", penalty))
+    }
+}
+
+/// Prompt preset used unless `--prompt-preset` or the config picks another.
+pub const DEFAULT_PROMPT_PRESET: &str = "standard";
+
+/// Named extra instructions for the formatting prompt; smaller models tend to do better with `concise`.
+const PROMPT_PRESETS: &[(&str, &str)] = &[
+    ("standard", ""),
+    (
+        "concise",
+        concat!(
+            "- Keep each line item to one short sentence and drop filler words.\n",
+            "- Never add introductions, summaries or closing remarks.",
+        ),
+    ),
+    (
+        "detailed",
+        concat!(
+            "- Keep the full description of each line item, including the component or area it touches.\n",
+            "- Keep every @mention and pull request link of a line item.",
+        ),
+    ),
+];
+
+/// The instructions of a prompt preset, or an error listing the known presets.
+pub fn prompt_preset(name: &str) -> Result<&'static str, String> {
+    PROMPT_PRESETS
+        .iter()
+        .find(|(preset, _)| *preset == name)
+        .map(|(_, instructions)| *instructions)
+        .ok_or_else(|| {
+            let known: Vec<&str> = PROMPT_PRESETS.iter().map(|(preset, _)| *preset).collect();
+            format!("Unknown prompt preset '{}'. Known presets: {}.", name, known.join(", "))
+        })
+}
+
+impl OpenAIClient {
+    pub fn new(http_client: Client, api_key: String, model: &str) -> Self {
+        OpenAIClient {
+            http_client,
+            api_key,
+            model: model.to_string(),
+            base_url: OPENAI_BASE_URL.to_string(),
+            retry: RetryPolicy::default(),
+            seed: None,
+            presence_penalty: None,
+            frequency_penalty: None,
+            model_fallbacks: Vec::new(),
+            fallback_settings: Vec::new(),
+            unmatched: UnmatchedLines::default(),
+            context_window: None,
+            user: None,
+            summary: None,
+            preset_instructions: "",
+            response_shape: ResponseShape::default(),
+            api: OpenAiApi::default(),
+            max_parallel: DEFAULT_MAX_PARALLEL,
+            usage: Mutex::new(TokenUsage::default()),
+            chunk_threshold: DEFAULT_CHUNK_THRESHOLD,
+        }
+    }
+
+    /// Send requests to an OpenAI-compatible gateway instead of api.openai.com.
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    // Create a new client with a custom base URL (for testing)
+    #[cfg(test)]
+    pub fn new_with_base_url(http_client: Client, api_key: String, model: &str, base_url: String) -> Self {
+        OpenAIClient {
+            base_url,
+            ..Self::new(http_client, api_key, model)
+        }
+    }
+
+    /// Retry transient failures (5xx, 429, connection errors) with exponential backoff.
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Ask the model for reproducible output by sending a fixed seed.
+    pub fn with_seed(mut self, seed: Option<u64>) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Discourage repetitive phrasing; unset penalties keep OpenAI's defaults.
+    pub fn with_penalties(mut self, presence_penalty: Option<f64>, frequency_penalty: Option<f64>) -> Self {
+        self.presence_penalty = presence_penalty;
+        self.frequency_penalty = frequency_penalty;
+        self
+    }
+
+    /// Models tried in order when the configured model is unavailable.
+    pub fn with_model_fallbacks(mut self, model_fallbacks: Vec<String>) -> Self {
+        self.model_fallbacks = model_fallbacks;
+        self
+    }
+
+    /// Call fallback `model` with its own settings instead of the configured model's.
+    pub fn with_fallback_settings(mut self, model: &str, mut settings: ModelSettings) -> Self {
+        settings.base_url = settings.base_url.trim_end_matches('/').to_string();
+        self.fallback_settings.push((model.to_string(), settings));
+        self
+    }
+
+    /// The settings `model` is called with: its own when it has them, else the client's.
+    fn settings_for(&self, model: &str) -> ModelSettings {
+        self.fallback_settings
+            .iter()
+            .find(|(name, _)| name == model)
+            .map(|(_, settings)| settings.clone())
+            .unwrap_or_else(|| ModelSettings {
+                base_url: self.base_url.clone(),
+                api_key: self.api_key.clone(),
+                preset_instructions: self.preset_instructions,
+                response_shape: self.response_shape,
+            })
+    }
+
+    /// Tell the model what to do with line items that don't reference a ticket.
+    pub fn with_unmatched(mut self, unmatched: UnmatchedLines) -> Self {
+        self.unmatched = unmatched;
+        self
+    }
+
+    /// Send a stable end-user identifier so OpenAI can attribute usage for abuse monitoring.
+    pub fn with_user(mut self, user: Option<String>) -> Self {
+        self.user = user;
+        self
+    }
+
+    /// Override the context window used for the prompt size check (e.g. for models not in the built-in list).
+    pub fn with_context_window(mut self, context_window: Option<usize>) -> Self {
+        self.context_window = context_window;
+        self
+    }
+
+    /// Add a prompt preset's instructions, from [`prompt_preset`], to the formatting prompt.
+    pub fn with_prompt_preset(mut self, instructions: &'static str) -> Self {
+        self.preset_instructions = instructions;
+        self
+    }
+
+    /// Read the answer from where the gateway puts it instead of `choices[0].message.content`.
+    pub fn with_response_shape(mut self, response_shape: ResponseShape) -> Self {
+        self.response_shape = response_shape;
+        self
+    }
+
+    /// Send the prompts to the Responses API instead of chat completions; set the response shape to match.
+    pub fn with_api(mut self, api: OpenAiApi) -> Self {
+        self.api = api;
+        self
+    }
+
+    /// Run at most this many independent calls, like summary chunks or translations, at once.
+    pub fn with_max_parallel(mut self, max_parallel: usize) -> Self {
+        self.max_parallel = max_parallel.max(1);
+        self
+    }
+
+    /// Format raw notes longer than this many characters chunk by chunk instead of in one call.
+    pub fn with_chunk_threshold(mut self, chunk_threshold: usize) -> Self {
+        self.chunk_threshold = chunk_threshold.max(1);
+        self
+    }
+
+    pub fn max_parallel(&self) -> usize {
+        self.max_parallel
+    }
+
+    /// Summarize the notes with a map-reduce pass instead of listing every line item.
+    pub fn with_summary(mut self, summary: Option<SummaryOptions>) -> Self {
+        self.summary = summary.map(Box::new);
+        self
+    }
+
+    pub async fn format_release_notes(&self, unformatted: &str) -> Result<String, OpenAiError> {
+        if let Some(summary) = &self.summary {
+            return self.summarize_release_notes(unformatted, summary).await;
+        }
+
+        if unformatted.chars().count() > self.chunk_threshold {
+            return self.format_in_chunks(unformatted).await;
+        }
+
+        let formatted_notes = self.request_chat_completion(&self.notes_prompt(unformatted)).await?;
+        Ok(formatted_notes)
+    }
+
+    /// Format notes too large for one call in chunks under the threshold, then merge their sections.
+    async fn format_in_chunks(&self, unformatted: &str) -> Result<String, OpenAiError> {
+        let chunks = summarize::chunk_by_size(unformatted, self.chunk_threshold);
+        println!("  The notes are {} characters; formatting them in {} chunks.", unformatted.chars().count(), chunks.len());
+
+        let partials = summarize::format_chunks(self, &chunks, self.max_parallel).await?;
+        Ok(summarize::merge_formatted(&partials, &self.unmatched))
+    }
+
+    /// Format the notes chunk by chunk, then merge the chunks into one concise summary.
+    ///
+    /// Every request counts toward `--max-llm-calls`, retries and model fallbacks included.
+    async fn summarize_release_notes(&self, unformatted: &str, summary: &SummaryOptions) -> Result<String, OpenAiError> {
+        let chunks = summarize::chunk_notes(unformatted, summary.chunk_lines, summary.max_chunks());
+        if chunks.is_empty() {
+            return Ok(String::new());
+        }
+
+        let budget = CallBudget::new(summary.max_calls);
+        let budgeted = BudgetedFormatter { client: self, budget: &budget };
+        let partials = summarize::format_chunks(&budgeted, &chunks, self.max_parallel).await?;
+
+        println!("  Summarizing {} chunks...", partials.len());
+        let prompt = summarize::build_reduce_prompt(&summary.reduce_prompt, &partials);
+        self.request_chat_completion_within(&Prompt::Fixed(&prompt), Some(&budget)).await
+    }
+
+    /// The formatting prompt for `notes`, with each model's preset filled in when it's sent.
+    fn notes_prompt<'a>(&'a self, notes: &'a str) -> Prompt<'a> {
+        Prompt::Notes(notes, &self.unmatched)
+    }
+
+    /// List the model IDs available to this API key.
+    pub async fn list_models(&self) -> Result<Vec<String>, OpenAiError> {
+        self.list_models_at(&self.base_url, &self.api_key).await
+    }
+
+    /// List the model IDs a gateway offers to `api_key`.
+    async fn list_models_at(&self, base_url: &str, api_key: &str) -> Result<Vec<String>, OpenAiError> {
+        let url = format!("{}/v1/models", base_url);
+
+        let request = self
+            .http_client
+            .get(&url)
+            .header("Authorization", format!("Bearer {}", api_key));
+        let resp = retry::send_with_retry(request, &self.retry).await?;
+        let status = resp.status();
+        let json_response = Self::read_json(resp).await?;
+
+        if !status.is_success() {
+            return Err(OpenAiError::from_status("Failed to list OpenAI models", status, &json_response));
+        }
+
+        Ok(json_response["data"]
+            .as_array()
+            .map(|models| {
+                models
+                    .iter()
+                    .filter_map(|model| model["id"].as_str().map(str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+
+    /// Fail early when neither the model nor any of its fallbacks is available to this API key.
+    pub async fn ensure_model_available(&self) -> Result<(), OpenAiError> {
+        let models = self.list_models().await?;
+        let mut available = self.model_chain().iter().any(|model| models.iter().any(|m| m == model));
+        // A fallback routed to another gateway is only listed there
+        for (model, settings) in &self.fallback_settings {
+            if !available && settings.base_url != self.base_url {
+                available = self.list_models_at(&settings.base_url, &settings.api_key).await?.contains(model);
+            }
+        }
+
+        if available {
+            Ok(())
+        } else {
+            Err(OpenAiError::ModelUnavailable(format!(
+                "Model {} is not available to this API key. Check --model, or pass --skip-model-check to skip this check.",
+                self.model
+            )))
+        }
+    }
+
+    /// Translate formatted notes into `language`, keeping the markdown, ticket IDs and URLs as they are.
+    pub async fn translate_notes(&self, notes: &str, language: &str) -> Result<String, OpenAiError> {
+        let prompt = Self::build_translation_prompt(notes, language);
+        self.request_chat_completion(&Prompt::Fixed(&prompt)).await
+    }
+
+    /// The configured model followed by its fallbacks, without duplicates.
+    fn model_chain(&self) -> Vec<&str> {
+        let mut chain: Vec<&str> = vec![&self.model];
+        for model in &self.model_fallbacks {
+            if !chain.contains(&model.as_str()) {
+                chain.push(model);
+            }
+        }
+        chain
+    }
+
+    /// Request a completion, moving down the model chain only when a model is unavailable.
+    async fn request_chat_completion(&self, prompt: &Prompt<'_>) -> Result<String, OpenAiError> {
+        self.request_chat_completion_within(prompt, None).await
+    }
+
+    /// Request a completion, taking every request it sends from `budget` when there is one.
+    async fn request_chat_completion_within(&self, prompt: &Prompt<'_>, budget: Option<&CallBudget>) -> Result<String, OpenAiError> {
+        let chain = self.model_chain();
+
+        for (index, model) in chain.iter().enumerate() {
+            match self.request_chat_completion_with_model(model, prompt, budget).await {
+                Ok(content) => return Ok(content),
+                Err(OpenAiError::ModelUnavailable(message)) => match chain.get(index + 1) {
+                    Some(next) => println!("{}", output::warning(format!("Model {} is unavailable ({}). Falling back to {}.", model, message, next))),
+                    None => return Err(OpenAiError::ModelUnavailable(format!("Model {} is unavailable: {}", model, message))),
+                },
+                Err(err) => return Err(err),
+            }
+        }
+
+        unreachable!("the model chain always contains the configured model")
+    }
+
+    /// Request a completion from one model; a "model not found" error comes back as `ModelUnavailable`.
+    async fn request_chat_completion_with_model(
+        &self,
+        model: &str,
+        prompt: &Prompt<'_>,
+        budget: Option<&CallBudget>,
+    ) -> Result<String, OpenAiError> {
+        let settings = self.settings_for(model);
+        let prompt = prompt.render(settings.preset_instructions);
+        let prompt = prompt.as_str();
+        // Don't waste a round-trip on a prompt that can't fit; unknown models are sent as-is
+        if let Some(window) = self.context_window.or_else(|| context_window(model)) {
+            check_token_budget(prompt, window, RESPONSE_TOKEN_RESERVE).map_err(OpenAiError::PromptTooLarge)?;
+        }
+
+        let url = format!("{}{}", settings.base_url, self.api.path());
+
+        // A 200 with blank content is retried too, nudging the temperature up a little each time
+        for attempt in 0..=self.retry.max_retries {
+            let mut body = self.request_body(model, prompt);
+            if attempt > 0 {
+                body["temperature"] = json!((0.5 + 0.1 * attempt as f64).min(1.0));
+            }
+
+            if let Some(budget) = budget.filter(|budget| !budget.take()) {
+                return Err(OpenAiError::CallCapReached(budget.max()));
+            }
+            let request = self
+                .http_client
+                .post(&url)
+                .header("Content-Type", "application/json")
+                .header("Authorization", format!("Bearer {}", settings.api_key))
+                .json(&body);
+            // A completion has no side effects, so it's safe to send again
+            let resp = retry::send_repeatable_with_retry(request, &self.retry, budget).await?;
+            let status = resp.status();
+
+            let json_response = Self::read_json(resp).await?;
+            if let Some(message) = Self::model_unavailable_message(status, &json_response) {
+                return Err(OpenAiError::ModelUnavailable(message));
+            }
+            if !status.is_success() {
+                let context = match self.api {
+                    OpenAiApi::Chat => "OpenAI chat completion failed",
+                    OpenAiApi::Responses => "OpenAI response failed",
+                };
+                return Err(OpenAiError::from_status(context, status, &json_response));
+            }
+
+            if let Some(usage) = TokenUsage::from_response(&json_response) {
+                let mut total = self.usage.lock().unwrap();
+                *total = total.add(usage);
+            }
+            let content = settings.response_shape.extract(&json_response)?;
+            if !content.trim().is_empty() {
+                return Ok(content);
+            }
+            if attempt < self.retry.max_retries {
+                println!("  {}", output::retry(format!("OpenAI returned empty content, retrying (attempt {}/{})", attempt + 1, self.retry.max_retries)));
+            }
+        }
+
+        Err(OpenAiError::EmptyContent(self.retry.max_retries + 1))
+    }
+
+    /// Tokens the completions of this client used so far.
+    pub fn usage(&self) -> TokenUsage {
+        *self.usage.lock().unwrap()
+    }
+
+    /// Read a response body as JSON; a body that isn't JSON is a bad response, not a transport failure.
+    async fn read_json(resp: reqwest::Response) -> Result<serde_json::Value, OpenAiError> {
+        let status = resp.status();
+        let body = resp.text().await?;
+        serde_json::from_str(&body).map_err(|e| {
+            OpenAiError::BadResponse(format!("OpenAI returned an unreadable response ({}): {}", status.as_u16(), e))
+        })
+    }
+
+    /// Detect OpenAI's "model not found" error, as opposed to any other failure.
+    fn model_unavailable_message(status: reqwest::StatusCode, response: &serde_json::Value) -> Option<String> {
+        let error = &response["error"];
+        let message = error["message"].as_str().unwrap_or("model not found").to_string();
+
+        let is_model_not_found = error["code"].as_str() == Some("model_not_found")
+            || (status == reqwest::StatusCode::NOT_FOUND && message.to_lowercase().contains("model"));
+
+        is_model_not_found.then_some(message)
+    }
+
+    /// Build the chat completion request body, leaving out optional settings that aren't configured.
+    fn request_body(&self, model: &str, prompt: &str) -> serde_json::Value {
+        let mut body = match self.api {
+            OpenAiApi::Chat => json!({
+                "model": model,
+                "messages": [{"role": "user", "content": prompt}],
+                "temperature": 0.5,
+            }),
+            // The Responses API has neither a seed nor penalties, which main rejects up front
+            OpenAiApi::Responses => json!({
+                "model": model,
+                "input": prompt,
+                "temperature": 0.5,
+            }),
+        };
+
+        if let Some(seed) = self.seed {
+            body["seed"] = json!(seed);
+        }
+        if let Some(presence_penalty) = self.presence_penalty {
+            body["presence_penalty"] = json!(presence_penalty);
+        }
+        if let Some(frequency_penalty) = self.frequency_penalty {
+            body["frequency_penalty"] = json!(frequency_penalty);
+        }
+        if let Some(user) = &self.user {
+            body["user"] = json!(user);
+        }
+
+        body
+    }
+
+    /// Build the prompt for translating formatted release notes.
+    fn build_translation_prompt(notes: &str, language: &str) -> String {
+        format!(
+            r#"Translate these GitHub release notes into {}.
+
+                ALWAYS FOLLOW THESE INSTRUCTIONS:
+                - Keep the markdown structure exactly as it is.
+                - Copy every ticket ID (e.g. PDE-3441), URL, @mention and code span verbatim.
+                - Only return the translated notes, without any introduction or headings of your own.
+
+                RELEASE NOTES:
+{}
+            "#,
+            language, notes
+        )
+    }
+
+    /// Build the prompt for release notes formatting.
+    fn build_release_notes_prompt(unformatted_notes: &str, unmatched: &UnmatchedLines, preset_instructions: &str) -> String {
+        // Indented like the rest of the rules
+        let preset_instruction: String = preset_instructions.lines().map(|line| format!("\n                {}", line)).collect();
+        let unmatched_instruction = match unmatched {
+            UnmatchedLines::List => String::new(),
+            UnmatchedLines::Heading(title) => format!(
+                "\n                - Put every line item without a ticket id under a final heading named \"{}\".",
+                title
+            ),
+            UnmatchedLines::Drop => {
+                "\n                - Leave out every line item without a ticket id.".to_string()
+            }
+        };
+
+        format!(
+            r#"TEMPLATE: https://onezelis.atlassian.net/browse/[Ticket ID]
+                EXAMPLE: https://onezelis.atlassian.net/browse/PRDY-3441
+                EXPECTED FORMAT EXAMPLE: * [PDE-3441](https://onezelis.atlassian.net/browse/PDE-3441) Fixed an issue by @Human-Glitch in https://github.com/mdx-dev/CostEngine/pull/2329
+                DEFAULT FORMAT EXAMPLE: * [commit message] by @[author]
+
+               INSTRUCTIONS:
+                - Please follow this template and deep link each item with the ticket url as shown in the example.
+                - If the information presented DOESN'T match up to the template, use the DEFAULT FORMAT with the following instructions:
+                    - Just return a list of commit messages instead.
+                - If the information presented DOES match up to the template, use the EXPECTED FORMAT with the following instructions:
+                    - Always print the answer in a way that Github Release Notes understands as raw text, so the formatting is preserved when editing Github Release Notes.
+                    - Create a heading for each Ticket ID Type: PD, PDE, PRDY
+                    - Assign each line item to one of these headings by the ticket id number ascending:\n\n{}
+
+                ALWAYS FOLLOW THESE INSTRUCTIONS:
+                - DO NOT MAKE UP ANY INFORMATION THAT IS NOT PRESENT IN THE UNFORMATTED NOTES.{}{}
+            "#,
+            unformatted_notes,
+            unmatched_instruction,
+            preset_instruction
+        )
+    }
+}
+
+/// A prompt to send; the formatting prompt takes the prompt preset of the model it goes to.
+enum Prompt<'a> {
+    Fixed(&'a str),
+    Notes(&'a str, &'a UnmatchedLines),
+}
+
+impl Prompt<'_> {
+    fn render(&self, preset_instructions: &str) -> String {
+        match self {
+            Prompt::Fixed(prompt) => prompt.to_string(),
+            Prompt::Notes(notes, unmatched) => OpenAIClient::build_release_notes_prompt(notes, unmatched, preset_instructions),
+        }
+    }
+}
+
+impl summarize::ChunkFormatter for OpenAIClient {
+    async fn format_chunk(&self, chunk: &str) -> Result<String, OpenAiError> {
+        self.request_chat_completion(&self.notes_prompt(chunk)).await
+    }
+}
+
+/// Formats the chunks of a summary, taking their requests from the summary's call budget.
+struct BudgetedFormatter<'a> {
+    client: &'a OpenAIClient,
+    budget: &'a CallBudget,
+}
+
+impl summarize::ChunkFormatter for BudgetedFormatter<'_> {
+    async fn format_chunk(&self, chunk: &str) -> Result<String, OpenAiError> {
+        self.client.request_chat_completion_within(&self.client.notes_prompt(chunk), Some(self.budget)).await
+    }
 }
 
-impl OpenAIClient {
-    pub fn new(http_client: Client, api_key: String, model: &str) -> Self {
-        OpenAIClient {
-            http_client,
-            api_key,
-            model: model.to_string(),
-            base_url: "https://api.openai.com".to_string(),
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::retry::RecordingSleeper;
+    use mockito::Matcher;
+    use tokio::runtime::Runtime;
+
+    #[test]
+    fn given_valid_credentials_when_creating_client_then_succeeds() {
+        let client = Client::new();
+        let api_key = "test_api_key".to_string();
+        let model = "gpt-4o";
+        let openai_client = OpenAIClient::new(client, api_key.clone(), model);
+        
+        // Verify the model was set correctly
+        assert_eq!(openai_client.model, model);
+        assert_eq!(openai_client.api_key, api_key);
+    }
+
+    #[test]
+    fn given_models_response_when_listing_models_then_returns_ids() {
+        let mut server = mockito::Server::new();
+
+        let mock = server.mock("GET", "/v1/models")
+            .match_header("authorization", "Bearer fake_api_key")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"object": "list", "data": [{"id": "gpt-4o", "object": "model"}, {"id": "gpt-4o-mini", "object": "model"}]}"#)
+            .create();
+
+        let openai_client = OpenAIClient::new_with_base_url(Client::new(), "fake_api_key".to_string(), "gpt-4o", server.url());
+
+        let rt = Runtime::new().unwrap();
+        let models = rt.block_on(async { openai_client.list_models().await.unwrap() });
+
+        assert_eq!(models, vec!["gpt-4o".to_string(), "gpt-4o-mini".to_string()]);
+        mock.assert();
+    }
+
+    #[test]
+    fn given_unknown_model_when_checking_availability_then_returns_error() {
+        let mut server = mockito::Server::new();
+
+        let mock = server.mock("GET", "/v1/models")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"object": "list", "data": [{"id": "gpt-4o", "object": "model"}]}"#)
+            .create();
+
+        let openai_client = OpenAIClient::new_with_base_url(Client::new(), "fake_api_key".to_string(), "gpt-4oo", server.url());
+
+        let rt = Runtime::new().unwrap();
+        let result = rt.block_on(async { openai_client.ensure_model_available().await });
+
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Model gpt-4oo is not available to this API key. Check --model, or pass --skip-model-check to skip this check."
+        );
+        mock.assert();
+    }
+
+    #[test]
+    fn given_unavailable_model_with_available_fallback_when_checking_availability_then_succeeds() {
+        let mut server = mockito::Server::new();
+
+        let mock = server.mock("GET", "/v1/models")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"object": "list", "data": [{"id": "gpt-4o-mini", "object": "model"}]}"#)
+            .create();
+
+        let openai_client = OpenAIClient::new_with_base_url(Client::new(), "fake_api_key".to_string(), "gpt-retired", server.url())
+            .with_model_fallbacks(vec!["gpt-4o-mini".to_string()]);
+
+        let rt = Runtime::new().unwrap();
+        let result = rt.block_on(async { openai_client.ensure_model_available().await });
+
+        assert!(result.is_ok());
+        mock.assert();
+    }
+
+    #[test]
+    fn given_prompt_at_budget_boundary_when_checking_token_budget_then_only_overflow_fails() {
+        // 1000 token window minus 200 reserved leaves 800 tokens, i.e. 3200 characters
+        let fits = "a".repeat(3200);
+        let overflows = "a".repeat(3201);
+
+        assert_eq!(check_token_budget(&fits, 1000, 200), Ok(800));
+        assert_eq!(
+            check_token_budget(&overflows, 1000, 200).unwrap_err(),
+            "The prompt is about 801 tokens, which exceeds the 800 tokens available (1000 token context window minus 200 for the response). Trim the release range or use a model with a larger context window."
+        );
+    }
+
+    #[test]
+    fn given_model_names_when_looking_up_context_window_then_matches_family() {
+        assert_eq!(context_window("gpt-4o-2024-08-06"), Some(128_000));
+        assert_eq!(context_window("gpt-4"), Some(8_192));
+        assert_eq!(context_window("my-finetune"), None);
+    }
+
+    #[test]
+    fn given_oversized_prompt_when_formatting_release_notes_then_errors_before_calling_api() {
+        let mut server = mockito::Server::new();
+
+        let mock = server.mock("POST", "/v1/chat/completions")
+            .expect(0)
+            .create();
+
+        let openai_client = OpenAIClient::new_with_base_url(Client::new(), "fake_api_key".to_string(), "gpt-4o", server.url())
+            .with_context_window(Some(RESPONSE_TOKEN_RESERVE + 10));
+
+        let rt = Runtime::new().unwrap();
+        let result = rt.block_on(async {
+            openai_client.format_release_notes(&"* PDE-1 Fix\n".repeat(100)).await
+        });
+
+        assert!(result.unwrap_err().to_string().contains("exceeds the 10 tokens available"));
+        mock.assert();
+    }
+
+    #[test]
+    fn given_responses_api_when_formatting_release_notes_then_posts_input_and_reads_the_output_array() {
+        let mut server = mockito::Server::new();
+
+        let mock = server.mock("POST", "/v1/responses")
+            .match_body(Matcher::PartialJson(json!({"model": "gpt-4o"})))
+            .match_body(Matcher::Regex(r#""input":"[^"]*PDE-1 Fix login"#.to_string()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r###"{
+                "id": "resp_1",
+                "object": "response",
+                "output": [
+                    {"type": "reasoning", "summary": []},
+                    {"type": "message", "role": "assistant", "content": [
+                        {"type": "output_text", "text": "## PDE\n", "annotations": []},
+                        {"type": "output_text", "text": "* [PDE-1](https://onezelis.atlassian.net/browse/PDE-1) Fix login", "annotations": []}
+                    ]}
+                ],
+                "usage": {"input_tokens": 120, "output_tokens": 30}
+            }"###)
+            .create();
+
+        let openai_client = OpenAIClient::new_with_base_url(Client::new(), "fake_api_key".to_string(), "gpt-4o", server.url())
+            .with_api(OpenAiApi::Responses)
+            .with_response_shape(OpenAiApi::Responses.response_shape());
+
+        let rt = Runtime::new().unwrap();
+        let notes = rt.block_on(openai_client.format_release_notes("* PDE-1 Fix login by @a")).unwrap();
+
+        assert_eq!(notes, "## PDE\n* [PDE-1](https://onezelis.atlassian.net/browse/PDE-1) Fix login");
+        assert_eq!(openai_client.usage(), TokenUsage { prompt_tokens: 120, completion_tokens: 30 });
+        mock.assert();
+    }
+
+    #[test]
+    fn given_no_seed_when_building_request_body_then_omits_seed() {
+        let openai_client = OpenAIClient::new(Client::new(), "test_api_key".to_string(), "gpt-4o");
+
+        let body = openai_client.request_body("gpt-4o", "prompt");
+
+        assert!(body.get("seed").is_none());
+    }
+
+    #[test]
+    fn given_penalties_when_building_request_body_then_includes_them() {
+        let openai_client = OpenAIClient::new(Client::new(), "test_api_key".to_string(), "gpt-4o")
+            .with_penalties(Some(0.5), Some(-1.25));
+
+        let body = openai_client.request_body("gpt-4o", "prompt");
+
+        assert_eq!(body["presence_penalty"], json!(0.5));
+        assert_eq!(body["frequency_penalty"], json!(-1.25));
+    }
+
+    #[test]
+    fn given_no_penalties_when_building_request_body_then_omits_them() {
+        let openai_client = OpenAIClient::new(Client::new(), "test_api_key".to_string(), "gpt-4o");
+
+        let body = openai_client.request_body("gpt-4o", "prompt");
+
+        assert!(body.get("presence_penalty").is_none());
+        assert!(body.get("frequency_penalty").is_none());
+    }
+
+    #[test]
+    fn given_penalty_values_when_parsing_then_accepts_only_allowed_range() {
+        assert_eq!(parse_penalty("2.0"), Ok(2.0));
+        assert_eq!(parse_penalty("-2"), Ok(-2.0));
+        assert_eq!(parse_penalty("0.7"), Ok(0.7));
+
+        assert!(parse_penalty("2.1").is_err());
+        assert!(parse_penalty("-3").is_err());
+        assert!(parse_penalty("high").is_err());
+    }
+
+    #[test]
+    fn given_unavailable_model_when_formatting_release_notes_then_falls_back_to_next_model() {
+        let mut server = mockito::Server::new();
+
+        let missing_model = server.mock("POST", "/v1/chat/completions")
+            .match_body(mockito::Matcher::PartialJson(json!({"model": "gpt-retired"})))
+            .with_status(404)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"error": {"message": "The model `gpt-retired` does not exist", "code": "model_not_found"}}"#)
+            .create();
+        let fallback_model = server.mock("POST", "/v1/chat/completions")
+            .match_body(mockito::Matcher::PartialJson(json!({"model": "gpt-4o-mini"})))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"choices": [{"message": {"role": "assistant", "content": "Notes from the fallback model"}}]}"#)
+            .create();
+
+        let client = Client::new();
+        let openai_client = OpenAIClient::new_with_base_url(
+            client,
+            "fake_api_key".to_string(),
+            "gpt-retired",
+            server.url()
+        )
+        .with_model_fallbacks(vec!["gpt-retired".to_string(), "gpt-4o-mini".to_string()]);
+
+        let rt = Runtime::new().unwrap();
+        let result = rt.block_on(async {
+            openai_client.format_release_notes("PDE-1234: Fixed bug").await.unwrap()
+        });
+
+        assert_eq!(result, "Notes from the fallback model");
+        missing_model.assert();
+        fallback_model.assert();
+    }
+
+    #[test]
+    fn given_fallback_with_its_own_settings_when_falling_back_then_calls_its_gateway_with_its_key_and_preset() {
+        let mut primary = mockito::Server::new();
+        let mut gateway = mockito::Server::new();
+
+        primary.mock("POST", "/v1/chat/completions")
+            .with_status(404)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"error": {"message": "The model `gpt-retired` does not exist", "code": "model_not_found"}}"#)
+            .create();
+        let concise = prompt_preset("concise").unwrap();
+        let fallback_model = gateway.mock("POST", "/v1/chat/completions")
+            .match_header("authorization", "Bearer gateway_key")
+            .match_body(mockito::Matcher::AllOf(vec![
+                mockito::Matcher::PartialJson(json!({"model": "llama3"})),
+                mockito::Matcher::Regex(regex::escape("Keep each line item to one short sentence")),
+            ]))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"choices": [{"message": {"role": "assistant", "content": "Notes from the gateway"}}]}"#)
+            .create();
+
+        let openai_client = OpenAIClient::new_with_base_url(Client::new(), "fake_api_key".to_string(), "gpt-retired", primary.url())
+            .with_model_fallbacks(vec!["llama3".to_string()])
+            .with_fallback_settings("llama3", ModelSettings {
+                base_url: format!("{}/", gateway.url()),
+                api_key: "gateway_key".to_string(),
+                preset_instructions: concise,
+                response_shape: ResponseShape::default(),
+            });
+
+        let rt = Runtime::new().unwrap();
+        let result = rt.block_on(async {
+            openai_client.format_release_notes("PDE-1234: Fixed bug").await.unwrap()
+        });
+
+        assert_eq!(result, "Notes from the gateway");
+        fallback_model.assert();
+    }
+
+    #[test]
+    fn given_generic_failure_when_formatting_release_notes_then_does_not_fall_back() {
+        let mut server = mockito::Server::new();
+
+        let failing = server.mock("POST", "/v1/chat/completions")
+            .match_body(mockito::Matcher::PartialJson(json!({"model": "gpt-4o"})))
+            .with_status(400)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"error": {"message": "Invalid request", "code": "invalid_request_error"}}"#)
+            .create();
+        let fallback_model = server.mock("POST", "/v1/chat/completions")
+            .match_body(mockito::Matcher::PartialJson(json!({"model": "gpt-4o-mini"})))
+            .expect(0)
+            .create();
+
+        let client = Client::new();
+        let openai_client = OpenAIClient::new_with_base_url(
+            client,
+            "fake_api_key".to_string(),
+            "gpt-4o",
+            server.url()
+        )
+        .with_model_fallbacks(vec!["gpt-4o-mini".to_string()]);
+
+        let rt = Runtime::new().unwrap();
+        let result = rt.block_on(async {
+            openai_client.format_release_notes("PDE-1234: Fixed bug").await
+        });
+
+        assert!(result.is_err());
+        failing.assert();
+        fallback_model.assert();
+    }
+
+    #[test]
+    fn given_empty_content_then_populated_when_formatting_release_notes_then_retries() {
+        let mut server = mockito::Server::new();
+
+        let empty = server.mock("POST", "/v1/chat/completions")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"choices": [{"message": {"role": "assistant", "content": "  \n"}}]}"#)
+            .expect(1)
+            .create();
+        let populated = server.mock("POST", "/v1/chat/completions")
+            .match_body(mockito::Matcher::PartialJson(json!({"temperature": 0.6})))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"choices": [{"message": {"role": "assistant", "content": "Formatted release notes"}}]}"#)
+            .create();
+
+        let openai_client = OpenAIClient::new_with_base_url(Client::new(), "fake_api_key".to_string(), "gpt-4o", server.url())
+            .with_retry_policy(RetryPolicy::new(1));
+
+        let rt = Runtime::new().unwrap();
+        let result = rt.block_on(async {
+            openai_client.format_release_notes("PDE-1234: Fixed bug").await.unwrap()
+        });
+
+        assert_eq!(result, "Formatted release notes");
+        empty.assert();
+        populated.assert();
     }
 
-    // Create a new client with a custom base URL (for testing)
-    pub fn new_with_base_url(http_client: Client, api_key: String, model: &str, base_url: String) -> Self {
-        OpenAIClient {
-            http_client,
-            api_key,
-            model: model.to_string(),
-            base_url,
-        }
+    #[test]
+    fn given_blank_answer_then_populated_when_formatting_release_notes_then_counts_the_tokens_of_both() {
+        let mut server = mockito::Server::new();
+
+        server.mock("POST", "/v1/chat/completions")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"choices": [{"message": {"role": "assistant", "content": "  \n"}}], "usage": {"prompt_tokens": 100, "completion_tokens": 1}}"#)
+            .expect(1)
+            .create();
+        server.mock("POST", "/v1/chat/completions")
+            .match_body(mockito::Matcher::PartialJson(json!({"temperature": 0.6})))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"choices": [{"message": {"role": "assistant", "content": "Formatted release notes"}}], "usage": {"prompt_tokens": 100, "completion_tokens": 20}}"#)
+            .create();
+
+        let openai_client = OpenAIClient::new_with_base_url(Client::new(), "fake_api_key".to_string(), "gpt-4o", server.url())
+            .with_retry_policy(RetryPolicy::new(1));
+
+        let rt = Runtime::new().unwrap();
+        rt.block_on(async { openai_client.format_release_notes("PDE-1234: Fixed bug").await.unwrap() });
+
+        // The blank answer's tokens were spent too
+        assert_eq!(openai_client.usage(), TokenUsage { prompt_tokens: 200, completion_tokens: 21 });
     }
 
-    pub async fn format_release_notes(&self, unformatted: &str) -> Result<String, Box<dyn Error>> {
-        let prompt = Self::build_release_notes_prompt(unformatted);
-        let formatted_notes = self.request_chat_completion(&prompt).await?;
-        Ok(formatted_notes)
+    #[test]
+    fn given_notes_over_the_chunk_threshold_when_formatting_then_formats_them_in_chunks() {
+        let mut server = mockito::Server::new();
+
+        let first = server.mock("POST", "/v1/chat/completions")
+            .match_body(mockito::Matcher::Regex("PDE-1 Alpha".to_string()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r###"{"choices": [{"message": {"role": "assistant", "content": "## PDE\n* [PDE-1](u) Alpha"}}]}"###)
+            .create();
+        let second = server.mock("POST", "/v1/chat/completions")
+            .match_body(mockito::Matcher::Regex("PDE-2 Beta".to_string()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r###"{"choices": [{"message": {"role": "assistant", "content": "## PDE\n* [PDE-2](u) Beta"}}]}"###)
+            .create();
+
+        let openai_client = OpenAIClient::new_with_base_url(Client::new(), "fake_api_key".to_string(), "gpt-4o", server.url())
+            .with_chunk_threshold(20);
+
+        let rt = Runtime::new().unwrap();
+        let result = rt.block_on(async {
+            openai_client.format_release_notes("* PDE-1 Alpha by @a\n* PDE-2 Beta by @b").await.unwrap()
+        });
+
+        assert_eq!(result, "## PDE\n* [PDE-1](u) Alpha\n* [PDE-2](u) Beta");
+        first.assert();
+        second.assert();
     }
 
-    async fn request_chat_completion(&self, prompt: &str) -> Result<String, Box<dyn Error>> {
-        let url = format!("{}/v1/chat/completions", self.base_url);
-        let body = json!({
-            "model": self.model,
-            "messages": [{"role": "user", "content": prompt}],
-            "temperature": 0.5,
+    #[test]
+    fn given_notes_under_the_chunk_threshold_when_formatting_then_makes_a_single_call() {
+        let mut server = mockito::Server::new();
+
+        let mock = server.mock("POST", "/v1/chat/completions")
+            .match_body(mockito::Matcher::Regex("PDE-1 Alpha by @a.*PDE-2 Beta by @b".to_string()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r###"{"choices": [{"message": {"role": "assistant", "content": "## PDE\n* [PDE-1](u) Alpha\n* [PDE-2](u) Beta"}}]}"###)
+            .expect(1)
+            .create();
+
+        let openai_client = OpenAIClient::new_with_base_url(Client::new(), "fake_api_key".to_string(), "gpt-4o", server.url())
+            .with_chunk_threshold(1000);
+
+        let rt = Runtime::new().unwrap();
+        rt.block_on(async {
+            openai_client.format_release_notes("* PDE-1 Alpha by @a\n* PDE-2 Beta by @b").await.unwrap()
         });
 
-        let resp = self
-            .http_client
-            .post(&url)
-            .header("Content-Type", "application/json")
-            .header("Authorization", format!("Bearer {}", self.api_key))
-            .json(&body)
-            .send()
-            .await?;
-
-        let json_response: serde_json::Value = resp.json().await?;
-        if let Some(content) = json_response["choices"][0]["message"]["content"].as_str() {
-            Ok(content.to_string())
-        } else {
-            Err("Failed to extract formatted release notes from OpenAI response.".into())
-        }
+        mock.assert();
     }
 
-    /// Build the prompt for release notes formatting.
-    fn build_release_notes_prompt(unformatted_notes: &str) -> String {
-        format!(
-            r#"TEMPLATE: https://onezelis.atlassian.net/browse/[Ticket ID]
-                EXAMPLE: https://onezelis.atlassian.net/browse/PRDY-3441
-                EXPECTED FORMAT EXAMPLE: * [PDE-3441](https://onezelis.atlassian.net/browse/PDE-3441) Fixed an issue by @Human-Glitch in https://github.com/mdx-dev/CostEngine/pull/2329
-                DEFAULT FORMAT EXAMPLE: * [commit message] by @[author]
+    #[test]
+    fn given_two_chunks_when_summarizing_then_merges_them_in_a_reduce_call() {
+        let mut server = mockito::Server::new();
 
-               INSTRUCTIONS:
-                - Please follow this template and deep link each item with the ticket url as shown in the example.
-                - If the information presented DOESN'T match up to the template, use the DEFAULT FORMAT with the following instructions:
-                    - Just return a list of commit messages instead.
-                - If the information presented DOES match up to the template, use the EXPECTED FORMAT with the following instructions:
-                    - Always print the answer in a way that Github Release Notes understands as raw text, so the formatting is preserved when editing Github Release Notes.
-                    - Create a heading for each Ticket ID Type: PD, PDE, PRDY
-                    - Assign each line item to one of these headings by the ticket id number ascending:\n\n{}
+        let first = server.mock("POST", "/v1/chat/completions")
+            .match_body(mockito::Matcher::Regex("Alpha change".to_string()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"choices": [{"message": {"role": "assistant", "content": "* alpha summary"}}]}"#)
+            .create();
+        let second = server.mock("POST", "/v1/chat/completions")
+            .match_body(mockito::Matcher::Regex("Beta change".to_string()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"choices": [{"message": {"role": "assistant", "content": "* beta summary"}}]}"#)
+            .create();
+        let reduce = server.mock("POST", "/v1/chat/completions")
+            .match_body(mockito::Matcher::Regex("Merge the parts.*PART 1:.*alpha summary.*PART 2:.*beta summary".to_string()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r###"{"choices": [{"message": {"role": "assistant", "content": "## Features\n* alpha and beta"}}]}"###)
+            .create();
 
-                ALWAYS FOLLOW THESE INSTRUCTIONS:
-                - DO NOT MAKE UP ANY INFORMATION THAT IS NOT PRESENT IN THE UNFORMATTED NOTES.
-            "#,
-            unformatted_notes
-        )
+        let openai_client = OpenAIClient::new_with_base_url(Client::new(), "fake_api_key".to_string(), "gpt-4o", server.url())
+            .with_summary(Some(SummaryOptions {
+                chunk_lines: 1,
+                max_calls: 3,
+                reduce_prompt: "Merge the parts.".to_string(),
+            }));
+
+        let rt = Runtime::new().unwrap();
+        let result = rt.block_on(async {
+            openai_client.format_release_notes("* Alpha change by @a\n* Beta change by @b").await.unwrap()
+        });
+
+        assert_eq!(result, "## Features\n* alpha and beta");
+        first.assert();
+        second.assert();
+        reduce.assert();
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use mockito;
-    use tokio::runtime::Runtime;
+    fn summary(max_calls: usize) -> Option<SummaryOptions> {
+        Some(SummaryOptions {
+            chunk_lines: 10,
+            max_calls,
+            reduce_prompt: "Merge the parts.".to_string(),
+        })
+    }
 
     #[test]
-    fn given_valid_credentials_when_creating_client_then_succeeds() {
+    fn given_empty_notes_when_summarizing_then_makes_no_calls() {
+        let mut server = mockito::Server::new();
+        let mock = server.mock("POST", "/v1/chat/completions").expect(0).create();
+
+        let openai_client = OpenAIClient::new_with_base_url(Client::new(), "fake_api_key".to_string(), "gpt-4o", server.url())
+            .with_summary(summary(3));
+
+        let rt = Runtime::new().unwrap();
+        let result = rt.block_on(async { openai_client.format_release_notes("\n\n").await.unwrap() });
+
+        assert_eq!(result, "");
+        mock.assert();
+    }
+
+    #[test]
+    fn given_model_fallback_when_summarizing_then_it_counts_toward_the_call_cap() {
+        let mut server = mockito::Server::new();
+
+        let missing_model = server.mock("POST", "/v1/chat/completions")
+            .match_body(mockito::Matcher::PartialJson(json!({"model": "gpt-retired"})))
+            .with_status(404)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"error": {"message": "The model `gpt-retired` does not exist", "code": "model_not_found"}}"#)
+            .create();
+        let fallback_model = server.mock("POST", "/v1/chat/completions")
+            .match_body(mockito::Matcher::PartialJson(json!({"model": "gpt-4o-mini"})))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r###"{"choices": [{"message": {"role": "assistant", "content": "## Features\n* alpha"}}]}"###)
+            .create();
+
+        let openai_client = OpenAIClient::new_with_base_url(Client::new(), "fake_api_key".to_string(), "gpt-retired", server.url())
+            .with_model_fallbacks(vec!["gpt-4o-mini".to_string()])
+            .with_summary(summary(2));
+
+        let rt = Runtime::new().unwrap();
+        let result = rt.block_on(async { openai_client.format_release_notes("* Alpha change by @a").await });
+
+        // The chunk took both calls, so the merge is never sent
+        assert!(matches!(result, Err(OpenAiError::CallCapReached(2))));
+        missing_model.assert();
+        fallback_model.assert();
+    }
+
+    #[test]
+    fn given_failing_chunk_when_summarizing_then_retries_stop_at_the_call_cap() {
+        let mut server = mockito::Server::new();
+
+        let failing = server.mock("POST", "/v1/chat/completions")
+            .with_status(500)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"error": {"message": "The server had an error"}}"#)
+            .expect(3)
+            .create();
+
+        let openai_client = OpenAIClient::new_with_base_url(Client::new(), "fake_api_key".to_string(), "gpt-4o", server.url())
+            .with_retry_policy(RetryPolicy {
+                max_retries: 5,
+                base_delay: std::time::Duration::from_millis(1),
+                max_delay: std::time::Duration::from_millis(1),
+                jitter: false,
+                sleeper: std::sync::Arc::new(RecordingSleeper::default()),
+            })
+            .with_summary(summary(3));
+
+        let rt = Runtime::new().unwrap();
+        let result = rt.block_on(async { openai_client.format_release_notes("* Alpha change by @a").await });
+
+        assert!(result.is_err());
+        failing.assert();
+    }
+
+    #[test]
+    fn given_content_stays_empty_when_formatting_release_notes_then_returns_error() {
+        let mut server = mockito::Server::new();
+
+        let mock = server.mock("POST", "/v1/chat/completions")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"choices": [{"message": {"role": "assistant", "content": ""}}]}"#)
+            .expect(2)
+            .create();
+
+        let openai_client = OpenAIClient::new_with_base_url(Client::new(), "fake_api_key".to_string(), "gpt-4o", server.url())
+            .with_retry_policy(RetryPolicy::new(1));
+
+        let rt = Runtime::new().unwrap();
+        let result = rt.block_on(async { openai_client.format_release_notes("PDE-1234: Fixed bug").await });
+
+        assert_eq!(result.unwrap_err().to_string(), "OpenAI returned empty content 2 times in a row.");
+        mock.assert();
+    }
+
+    #[test]
+    fn given_sample_payload_of_each_shape_when_extracting_then_returns_the_content() {
+        let chat = json!({"choices": [{"message": {"role": "assistant", "content": "## PDE\n* Fix"}}]});
+        let responses = json!({"id": "resp_1", "output_text": "## PDE\n* Fix"});
+        let responses_items = json!({"output": [
+            {"type": "reasoning", "summary": []},
+            {"type": "message", "content": [{"type": "output_text", "text": "## PDE\n"}, {"type": "output_text", "text": "* Fix"}]}
+        ]});
+        let completions = json!({"choices": [{"index": 0, "text": "## PDE\n* Fix"}]});
+
+        assert_eq!(ResponseShape::ChatCompletions.extract(&chat).unwrap(), "## PDE\n* Fix");
+        assert_eq!(ResponseShape::Responses.extract(&responses).unwrap(), "## PDE\n* Fix");
+        assert_eq!(ResponseShape::Responses.extract(&responses_items).unwrap(), "## PDE\n* Fix");
+        assert_eq!(ResponseShape::Completions.extract(&completions).unwrap(), "## PDE\n* Fix");
+    }
+
+    #[test]
+    fn given_wrong_shape_or_refusal_when_extracting_then_returns_error() {
+        let responses = json!({"output_text": "## PDE\n* Fix"});
+        let refusal = json!({"output": [{"type": "message", "content": [{"type": "refusal", "refusal": "Can't help with that."}]}]});
+
+        assert_eq!(
+            ResponseShape::ChatCompletions.extract(&responses).unwrap_err().to_string(),
+            "Failed to extract formatted release notes from OpenAI response (expected choices[0].message.content)."
+        );
+        assert!(matches!(ResponseShape::Responses.extract(&refusal), Err(OpenAiError::Refused(reason)) if reason == "Can't help with that."));
+    }
+
+    #[test]
+    fn given_user_when_building_request_body_then_includes_user() {
+        let openai_client = OpenAIClient::new(Client::new(), "test_api_key".to_string(), "gpt-4o")
+            .with_user(Some("release-bot".to_string()));
+
+        let body = openai_client.request_body("gpt-4o", "prompt");
+
+        assert_eq!(body["user"], json!("release-bot"));
+    }
+
+    #[test]
+    fn given_no_user_when_building_request_body_then_omits_user() {
+        let openai_client = OpenAIClient::new(Client::new(), "test_api_key".to_string(), "gpt-4o");
+
+        let body = openai_client.request_body("gpt-4o", "prompt");
+
+        assert!(body.get("user").is_none());
+    }
+
+    #[test]
+    fn given_seed_when_formatting_release_notes_then_sends_seed_in_body() {
+        let mut server = mockito::Server::new();
+
+        // Only match requests that carry the configured seed
+        let mock = server.mock("POST", "/v1/chat/completions")
+            .match_body(mockito::Matcher::PartialJson(json!({"seed": 42})))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"choices": [{"message": {"role": "assistant", "content": "Formatted release notes"}}]}"#)
+            .create();
+
         let client = Client::new();
-        let api_key = "test_api_key".to_string();
-        let model = "gpt-4o";
-        let openai_client = OpenAIClient::new(client, api_key.clone(), model);
-        
-        // Verify the model was set correctly
-        assert_eq!(openai_client.model, model);
-        assert_eq!(openai_client.api_key, api_key);
+        let openai_client = OpenAIClient::new_with_base_url(
+            client,
+            "fake_api_key".to_string(),
+            "gpt-4",
+            server.url()
+        )
+        .with_seed(Some(42));
+
+        let rt = Runtime::new().unwrap();
+        let result = rt.block_on(async {
+            openai_client.format_release_notes("PDE-1234: Fixed bug").await.unwrap()
+        });
+
+        assert_eq!(result, "Formatted release notes");
+        mock.assert();
     }
 
     #[test]
     fn given_unformatted_notes_when_building_prompt_then_returns_valid_prompt() {
         let unformatted_notes = "PDE-1234: Fixed bug\nPRDY-5678: Added feature";
-        let prompt = OpenAIClient::build_release_notes_prompt(unformatted_notes);
+        let prompt = OpenAIClient::build_release_notes_prompt(unformatted_notes, &UnmatchedLines::List, "");
         
         // Verify the prompt contains our unformatted notes
         assert!(prompt.contains(unformatted_notes));
@@ -114,6 +1518,37 @@ mod tests {
         assert!(prompt.contains("TEMPLATE: https://onezelis.atlassian.net/browse/[Ticket ID]"));
     }
 
+    #[test]
+    fn given_other_heading_when_building_prompt_then_names_the_heading() {
+        let prompt = OpenAIClient::build_release_notes_prompt(
+            "* Bump dependencies",
+            &UnmatchedLines::Heading("Maintenance".to_string()),
+            "",
+        );
+
+        assert!(prompt.contains("under a final heading named \"Maintenance\""));
+    }
+
+    #[test]
+    fn given_preset_when_building_prompt_then_includes_its_instructions() {
+        let concise = prompt_preset("concise").unwrap();
+        let prompt = OpenAIClient::build_release_notes_prompt("* PDE-1 Fix login by @a", &UnmatchedLines::List, concise);
+
+        assert!(prompt.contains(
+            "\n                - Keep each line item to one short sentence and drop filler words.\n                - Never add introductions"
+        ));
+        assert!(!prompt.contains("Keep the full description"));
+        assert_eq!(prompt_preset(DEFAULT_PROMPT_PRESET), Ok(""));
+    }
+
+    #[test]
+    fn given_unknown_preset_when_resolving_then_lists_the_known_ones() {
+        assert_eq!(
+            prompt_preset("verbose"),
+            Err("Unknown prompt preset 'verbose'. Known presets: standard, concise, detailed.".to_string())
+        );
+    }
+
     #[test]
     fn given_valid_input_when_formatting_release_notes_then_returns_formatted_notes() {
         let mut server = mockito::Server::new();
@@ -164,6 +1599,48 @@ mod tests {
         mock.assert();
     }
 
+    #[test]
+    fn given_rate_limited_response_when_formatting_release_notes_then_retries() {
+        let mut server = mockito::Server::new();
+
+        let rate_limited = server.mock("POST", "/v1/chat/completions")
+            .with_status(429)
+            .with_body(r#"{"error": {"message": "Rate limit reached"}}"#)
+            .expect(1)
+            .create();
+        let mock = server.mock("POST", "/v1/chat/completions")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"choices": [{"message": {"role": "assistant", "content": "Formatted release notes"}}]}"#)
+            .create();
+
+        let client = Client::new();
+        let openai_client = OpenAIClient::new_with_base_url(
+            client,
+            "fake_api_key".to_string(),
+            "gpt-4",
+            server.url()
+        )
+        .with_retry_policy(RetryPolicy {
+            max_retries: 1,
+            base_delay: std::time::Duration::from_millis(1),
+            max_delay: std::time::Duration::from_millis(1),
+            jitter: false,
+            sleeper: std::sync::Arc::new(RecordingSleeper::default()),
+        });
+
+        let rt = Runtime::new().unwrap();
+        let result = rt.block_on(async {
+            openai_client.format_release_notes("PDE-1234: Fixed bug").await.unwrap()
+        });
+
+        assert_eq!(result, "Formatted release notes");
+
+        // Verify the rate-limited call was retried exactly once
+        rate_limited.assert();
+        mock.assert();
+    }
+
     #[test]
     fn given_error_response_when_formatting_release_notes_then_handles_error() {
         let mut server = mockito::Server::new();
@@ -207,10 +1684,44 @@ mod tests {
             openai_client.format_release_notes(notes).await
         });
         
-        // Verify that we got an error
-        assert!(result.is_err());
+        // Verify that we got the specific error
+        assert!(matches!(result, Err(OpenAiError::BadResponse(_))));
         
         // Verify the mock was called
         mock.assert();
     }
+
+    #[test]
+    fn given_auth_rate_limit_and_refusal_responses_when_formatting_then_returns_matching_variants() {
+        let mut server = mockito::Server::new();
+        let openai_client = OpenAIClient::new_with_base_url(Client::new(), "fake_api_key".to_string(), "gpt-4o", server.url());
+        let rt = Runtime::new().unwrap();
+
+        let unauthorized = server.mock("POST", "/v1/chat/completions")
+            .with_status(401)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"error": {"message": "Incorrect API key provided", "code": "invalid_api_key"}}"#)
+            .create();
+        let result = rt.block_on(openai_client.format_release_notes("PDE-1 Fix"));
+        assert!(matches!(result, Err(OpenAiError::Auth { status: 401, .. })));
+        unauthorized.remove();
+
+        let rate_limited = server.mock("POST", "/v1/chat/completions")
+            .with_status(429)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"error": {"message": "Rate limit reached"}}"#)
+            .create();
+        let result = rt.block_on(openai_client.format_release_notes("PDE-1 Fix"));
+        assert!(matches!(result, Err(OpenAiError::RateLimited(ref message)) if message == "Rate limit reached"));
+        rate_limited.remove();
+
+        let refused = server.mock("POST", "/v1/chat/completions")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"choices": [{"message": {"role": "assistant", "content": null, "refusal": "I can't help with that."}}]}"#)
+            .create();
+        let result = rt.block_on(openai_client.format_release_notes("PDE-1 Fix"));
+        assert_eq!(result.unwrap_err().to_string(), "OpenAI refused the request: I can't help with that.");
+        refused.assert();
+    }
 }
@@ -0,0 +1,98 @@
+use std::fmt::Display;
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Whether status lines get ANSI colors; off until `init` decides otherwise.
+static COLOR: AtomicBool = AtomicBool::new(false);
+
+/// The kind of status line, which picks its icon and color.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Status {
+    Success,
+    Warning,
+    Info,
+    Retry,
+}
+
+impl Status {
+    fn icon(self) -> &'static str {
+        match self {
+            Status::Success => "✅",
+            Status::Warning => "⚠️ ",
+            Status::Info => "ℹ️",
+            Status::Retry => "↻",
+        }
+    }
+
+    fn ansi_code(self) -> &'static str {
+        match self {
+            Status::Success => "32",
+            Status::Warning | Status::Retry => "33",
+            Status::Info => "36",
+        }
+    }
+}
+
+/// Color only for a terminal, and never when `--no-color` or `NO_COLOR` (any value) is set.
+pub fn use_color(no_color_flag: bool, no_color_env: bool, is_terminal: bool) -> bool {
+    !no_color_flag && !no_color_env && is_terminal
+}
+
+/// Decide once at startup whether status lines are colored.
+pub fn init(no_color_flag: bool) {
+    let no_color_env = std::env::var_os("NO_COLOR").is_some();
+    COLOR.store(use_color(no_color_flag, no_color_env, std::io::stdout().is_terminal()), Ordering::Relaxed);
+}
+
+/// Render a status line: its icon, the message and, when enabled, its color.
+pub fn render(status: Status, message: &str, color: bool) -> String {
+    let line = format!("{} {}", status.icon(), message);
+    if color {
+        format!("\x1b[{}m{}\x1b[0m", status.ansi_code(), line)
+    } else {
+        line
+    }
+}
+
+fn styled(status: Status, message: impl Display) -> String {
+    render(status, &message.to_string(), COLOR.load(Ordering::Relaxed))
+}
+
+pub fn success(message: impl Display) -> String {
+    styled(Status::Success, message)
+}
+
+pub fn warning(message: impl Display) -> String {
+    styled(Status::Warning, message)
+}
+
+pub fn info(message: impl Display) -> String {
+    styled(Status::Info, message)
+}
+
+pub fn retry(message: impl Display) -> String {
+    styled(Status::Retry, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn given_piped_output_when_rendering_then_contains_no_ansi_escapes() {
+        let color = use_color(false, false, false);
+
+        for status in [Status::Success, Status::Warning, Status::Info, Status::Retry] {
+            assert!(!render(status, "Release ready (ID: 7).", color).contains('\x1b'));
+        }
+        assert_eq!(render(Status::Success, "Release ready (ID: 7).", color), "✅ Release ready (ID: 7).");
+    }
+
+    #[test]
+    fn given_terminal_when_choosing_color_then_flag_and_env_turn_it_off() {
+        assert!(use_color(false, false, true));
+        assert!(!use_color(true, false, true));
+        assert!(!use_color(false, true, true));
+        assert_eq!(render(Status::Success, "ok", true), "\x1b[32m✅ ok\x1b[0m");
+    }
+}
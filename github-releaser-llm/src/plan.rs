@@ -0,0 +1,108 @@
+use crate::github_client::{GitHubClient, GitHubRelease};
+
+/// One change a release run makes, in the order it makes them.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PlannedAction {
+    /// Delete the tag's existing release so it can be recreated
+    DeleteRelease { id: u64 },
+    /// Delete the tag; one that's already gone is fine
+    DeleteTag,
+    /// Create the annotated tag and its ref through the API, dated like the commit when asked
+    CreateTag { commit_date: bool },
+    /// Create a new tag object for the commit and force-move the existing ref to it
+    RetargetTag { commit_date: bool },
+    /// Tag the commit in the local clone and force-push it
+    PushTag,
+    /// Create the release, or pick up one a concurrent run just created
+    CreateRelease,
+    /// Replace the release body with the formatted notes
+    UpdateNotes,
+    /// Attach the `--asset` files
+    UploadAssets { names: Vec<String> },
+}
+
+/// What a release run found and what it will change, shared by `plan` and the release itself.
+pub struct ReleasePlan {
+    /// The tag asked for, which `tag` differs from when a prerelease is bumped
+    pub requested_tag: String,
+    pub tag: String,
+    pub branch: String,
+    pub commit_sha: String,
+    /// The release that's kept and updated, when it isn't recreated
+    pub existing_release: Option<GitHubRelease>,
+    pub actions: Vec<PlannedAction>,
+    /// Why a step has no action, keyed by its progress step name
+    pub skipped: Vec<(&'static str, String)>,
+}
+
+impl ReleasePlan {
+    pub fn has(&self, action: &PlannedAction) -> bool {
+        self.actions.contains(action)
+    }
+
+    /// Why the step was skipped, or an empty string when it wasn't.
+    pub fn skip_reason(&self, step: &str) -> String {
+        self.skipped
+            .iter()
+            .find(|(skipped, _)| *skipped == step)
+            .map(|(_, reason)| reason.clone())
+            .unwrap_or_default()
+    }
+
+    /// The requests behind every action, e.g. `DELETE https://api.github.com/repos/o/r/releases/12`.
+    pub fn calls(&self, gh_client: &GitHubClient) -> Vec<String> {
+        // The id of a release created by the run isn't known until it exists
+        let release_id = self
+            .existing_release
+            .as_ref()
+            .map_or("{new}".to_string(), |release| release.id.to_string());
+
+        self.actions
+            .iter()
+            .flat_map(|action| match action {
+                PlannedAction::DeleteRelease { id } => vec![format!("DELETE {}", gh_client.api_url(&format!("releases/{}", id)))],
+                PlannedAction::DeleteTag => vec![format!("DELETE {}", gh_client.api_url(&format!("git/refs/tags/{}", self.tag)))],
+                PlannedAction::CreateTag { commit_date } | PlannedAction::RetargetTag { commit_date } => {
+                    let mut calls = Vec::new();
+                    if *commit_date {
+                        calls.push(format!("GET {}", gh_client.api_url(&format!("git/commits/{}", self.commit_sha))));
+                        calls.push(format!("GET {}", gh_client.user_url()));
+                    }
+                    calls.push(format!("POST {}", gh_client.api_url("git/tags")));
+                    calls.push(match action {
+                        PlannedAction::RetargetTag { .. } => {
+                            format!("PATCH {}", gh_client.api_url(&format!("git/refs/tags/{}", self.tag)))
+                        }
+                        _ => format!("POST {}", gh_client.api_url("git/refs")),
+                    });
+                    calls
+                }
+                PlannedAction::PushTag => vec![format!("git push --force refs/tags/{} (local clone)", self.tag)],
+                PlannedAction::CreateRelease => vec![format!("POST {}", gh_client.api_url("releases"))],
+                PlannedAction::UpdateNotes => vec![format!("PATCH {}", gh_client.api_url(&format!("releases/{}", release_id)))],
+                PlannedAction::UploadAssets { names } => names
+                    .iter()
+                    .map(|name| format!("POST {}?name={}", gh_client.api_url(&format!("releases/{}/assets", release_id)), name))
+                    .collect(),
+            })
+            .collect()
+    }
+
+    /// A numbered list of the calls under a line naming the tag, branch and commit.
+    pub fn render(&self, gh_client: &GitHubClient) -> String {
+        let mut lines = vec![format!(
+            "Plan for {} {} (branch {} at {}):",
+            gh_client.repository(),
+            self.tag,
+            self.branch,
+            self.commit_sha
+        )];
+        lines.extend(
+            self.calls(gh_client)
+                .iter()
+                .enumerate()
+                .map(|(index, call)| format!("{:>3}. {}", index + 1, call)),
+        );
+        lines.join("\n")
+    }
+}
@@ -0,0 +1,220 @@
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// The steps `process_release` reports, in the order it runs them.
+pub const STEPS: [&str; 10] = [
+    "delete_release",
+    "delete_tag",
+    "resolve_branch",
+    "latest_commit",
+    "create_tag",
+    "create_release",
+    "fetch_notes",
+    "format_notes",
+    "update_release",
+    "upload_assets",
+];
+
+/// How a release step ended.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StepStatus {
+    Ok,
+    /// Not needed for this release, e.g. the tag is kept
+    Skipped,
+    /// The run stopped here with an error, given as the detail
+    Failed,
+}
+
+impl StepStatus {
+    fn as_str(self) -> &'static str {
+        match self {
+            StepStatus::Ok => "ok",
+            StepStatus::Skipped => "skipped",
+            StepStatus::Failed => "failed",
+        }
+    }
+}
+
+/// Told about each step of `process_release` as it completes.
+pub trait ProgressReporter: Sync {
+    fn step(&self, repository: &str, step: &str, status: StepStatus, detail: &str);
+
+    /// Told when a repository's run starts, before it's planned.
+    fn started(&self, _repository: &str) {}
+
+    /// Told when a repository's plan is ready, before its first step.
+    fn planned(&self, _repository: &str) {}
+}
+
+/// Reports nothing; the default without `--progress-json`.
+pub struct NoProgress;
+
+impl ProgressReporter for NoProgress {
+    fn step(&self, _repository: &str, _step: &str, _status: StepStatus, _detail: &str) {}
+}
+
+/// Writes one JSON object per step to stderr, so stdout keeps the human-readable log.
+pub struct JsonLinesProgress;
+
+impl ProgressReporter for JsonLinesProgress {
+    fn step(&self, repository: &str, step: &str, status: StepStatus, detail: &str) {
+        eprintln!("{}", event_line(repository, step, status, detail));
+    }
+}
+
+/// Remembers each repository's last reported step, passing every step on to `inner`.
+pub struct StepTracker<'a> {
+    inner: &'a dyn ProgressReporter,
+    last: Mutex<HashMap<String, &'static str>>,
+}
+
+impl<'a> StepTracker<'a> {
+    pub fn new(inner: &'a dyn ProgressReporter) -> Self {
+        StepTracker { inner, last: Mutex::new(HashMap::new()) }
+    }
+
+    /// The step the repository is in: the one after its last reported step, or planning before any.
+    pub fn current_step(&self, repository: &str) -> &'static str {
+        match self.last.lock().unwrap().get(repository) {
+            None => "planning",
+            Some(last) => STEPS
+                .iter()
+                .skip_while(|step| *step != last)
+                .nth(1)
+                .copied()
+                .unwrap_or("the end of the run"),
+        }
+    }
+}
+
+impl ProgressReporter for StepTracker<'_> {
+    fn step(&self, repository: &str, step: &str, status: StepStatus, detail: &str) {
+        if let Some(known) = STEPS.iter().find(|known| **known == step) {
+            self.last.lock().unwrap().insert(repository.to_string(), known);
+        }
+        self.inner.step(repository, step, status, detail);
+    }
+
+    fn started(&self, repository: &str) {
+        self.inner.started(repository);
+    }
+
+    fn planned(&self, repository: &str) {
+        self.inner.planned(repository);
+    }
+}
+
+/// How long each phase of a run took, in the order they ran.
+pub type Timings = Vec<(String, Duration)>;
+
+/// Times each repository's planning and steps, passing everything on to `inner`.
+///
+/// A step takes from the end of the one before it, or of planning, until it's reported.
+pub struct StepTimer<'a> {
+    inner: &'a dyn ProgressReporter,
+    /// When each repository's last phase ended, and the phases timed so far
+    runs: Mutex<HashMap<String, (Instant, Timings)>>,
+}
+
+impl<'a> StepTimer<'a> {
+    pub fn new(inner: &'a dyn ProgressReporter) -> Self {
+        StepTimer { inner, runs: Mutex::new(HashMap::new()) }
+    }
+
+    /// How long each phase of the repository's run took, in the order they ran.
+    pub fn timings(&self, repository: &str) -> Timings {
+        self.runs
+            .lock()
+            .unwrap()
+            .get(repository)
+            .map(|(_, timings)| timings.clone())
+            .unwrap_or_default()
+    }
+
+    fn finish(&self, repository: &str, phase: &str) {
+        let mut runs = self.runs.lock().unwrap();
+        // A run that never said it started is timed from its first phase
+        let now = Instant::now();
+        let (last, timings) = runs.entry(repository.to_string()).or_insert_with(|| (now, Vec::new()));
+        timings.push((phase.to_string(), now - *last));
+        *last = now;
+    }
+}
+
+impl ProgressReporter for StepTimer<'_> {
+    fn step(&self, repository: &str, step: &str, status: StepStatus, detail: &str) {
+        self.finish(repository, step);
+        self.inner.step(repository, step, status, detail);
+    }
+
+    fn started(&self, repository: &str) {
+        self.runs
+            .lock()
+            .unwrap()
+            .insert(repository.to_string(), (Instant::now(), Vec::new()));
+        self.inner.started(repository);
+    }
+
+    fn planned(&self, repository: &str) {
+        self.finish(repository, "planning");
+        self.inner.planned(repository);
+    }
+}
+
+/// A single progress event as one line of JSON.
+pub fn event_line(repository: &str, step: &str, status: StepStatus, detail: &str) -> String {
+    json!({
+        "repository": repository,
+        "step": step,
+        "status": status.as_str(),
+        "detail": detail,
+    })
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn given_reported_steps_when_tracking_then_the_current_step_is_the_next_one() {
+        let tracker = StepTracker::new(&NoProgress);
+
+        assert_eq!(tracker.current_step("o/r"), "planning");
+        tracker.step("o/r", "delete_release", StepStatus::Skipped, "");
+        tracker.step("o/r", "delete_tag", StepStatus::Ok, "");
+        assert_eq!(tracker.current_step("o/r"), "resolve_branch");
+        assert_eq!(tracker.current_step("o/other"), "planning");
+    }
+
+    #[test]
+    fn given_a_timed_run_when_reading_timings_then_lists_planning_and_each_step_in_order() {
+        let timer = StepTimer::new(&NoProgress);
+
+        timer.started("o/r");
+        std::thread::sleep(Duration::from_millis(20));
+        timer.planned("o/r");
+        timer.step("o/r", "delete_release", StepStatus::Skipped, "");
+        timer.step("o/r", "create_release", StepStatus::Ok, "");
+
+        let timings = timer.timings("o/r");
+        let phases: Vec<&str> = timings.iter().map(|(phase, _)| phase.as_str()).collect();
+        assert_eq!(phases, vec!["planning", "delete_release", "create_release"]);
+        assert!(timings[0].1 >= Duration::from_millis(20));
+        assert!(timer.timings("o/other").is_empty());
+    }
+
+    #[test]
+    fn given_step_when_rendering_event_then_is_one_line_of_json() {
+        let line = event_line("Human-Glitch/llm-playground", "delete_release", StepStatus::Ok, "deleted release 9\nagain");
+
+        assert!(!line.contains('\n'));
+        let event: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(event["step"], "delete_release");
+        assert_eq!(event["status"], "ok");
+        assert_eq!(event["detail"], "deleted release 9\nagain");
+        assert_eq!(event["repository"], "Human-Glitch/llm-playground");
+    }
+}
@@ -0,0 +1,223 @@
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::formatter::Ticket;
+use crate::openai_client::{TokenPrices, TokenUsage};
+use crate::progress::Timings;
+
+/// What a release run did to one repository, for the recap at the end of the run.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct ReleaseSummary {
+    pub requested_tag: String,
+    /// The tag released, which differs from the requested one when a prerelease was bumped
+    pub tag: String,
+    pub incremented: bool,
+    pub branch: Option<String>,
+    pub commit_sha: Option<String>,
+    pub release_id: Option<u64>,
+    /// The release page
+    pub release_url: Option<String>,
+    /// Where the raw notes came from: `release_body`, `regenerated`, `generated` or `compare`
+    pub notes_source: Option<&'static str>,
+    /// `openai`, `local` or `local_fallback`
+    pub formatter: Option<&'static str>,
+    pub tickets: Vec<Ticket>,
+    /// How long planning and each step took, with `--timings`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub timings: Vec<StepTiming>,
+}
+
+/// How long one phase of a release run took.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct StepTiming {
+    pub step: String,
+    pub millis: u64,
+}
+
+impl StepTiming {
+    pub fn from_durations(timings: Timings) -> Vec<StepTiming> {
+        timings
+            .into_iter()
+            .map(|(step, elapsed)| StepTiming { step, millis: elapsed.as_millis() as u64 })
+            .collect()
+    }
+}
+
+impl ReleaseSummary {
+    /// A summary that only knows the tag, for runs that don't go through the release steps.
+    pub fn for_tag(tag: &str) -> Self {
+        ReleaseSummary {
+            requested_tag: tag.to_string(),
+            tag: tag.to_string(),
+            ..Default::default()
+        }
+    }
+}
+
+/// A repository with its outcome and, when it went through the release steps, its summary; or its error.
+pub type RecapEntry<'a> = (String, Result<(&'a str, Option<&'a ReleaseSummary>), String>);
+
+/// The recap as one JSON object, for `--summary-json`.
+pub fn to_json(tag: &str, entries: &[RecapEntry], usage: Option<TokenUsage>, prices: Option<TokenPrices>) -> Value {
+    let repositories: Vec<Value> = entries
+        .iter()
+        .map(|(repository, entry)| match entry {
+            Ok((outcome, summary)) => {
+                let mut value = json!({"repository": repository, "outcome": outcome});
+                if let Some(Value::Object(fields)) = summary.map(|summary| json!(summary)) {
+                    value.as_object_mut().unwrap().extend(fields);
+                }
+                value
+            }
+            Err(error) => json!({"repository": repository, "error": error}),
+        })
+        .collect();
+
+    json!({
+        "tag": tag,
+        "repositories": repositories,
+        "usage": usage.map(|usage| json!({
+            "prompt_tokens": usage.prompt_tokens,
+            "completion_tokens": usage.completion_tokens,
+            "total_tokens": usage.total(),
+            "cost_usd": prices.map(|prices| usage.cost(prices)),
+        })),
+    })
+}
+
+/// The recap as an indented block, one entry per repository.
+pub fn to_text(tag: &str, entries: &[RecapEntry], usage: Option<TokenUsage>, prices: Option<TokenPrices>) -> String {
+    let mut lines = vec![format!("Summary for {}:", tag)];
+    for (repository, entry) in entries {
+        match entry {
+            Ok((outcome, summary)) => {
+                lines.push(format!("  {}: {}", repository, outcome));
+                if let Some(summary) = summary {
+                    lines.extend(summary_lines(summary).into_iter().map(|(name, value)| format!("    {:<14}{}", name, value)));
+                    lines.extend(timing_lines(&summary.timings));
+                }
+            }
+            Err(error) => lines.push(format!("  {}: failed: {}", repository, error)),
+        }
+    }
+    if let Some(usage) = usage {
+        let cost = prices.map(|prices| format!(", ${:.4}", usage.cost(prices))).unwrap_or_default();
+        lines.push(format!(
+            "  OpenAI usage: {} prompt + {} completion tokens ({} total{})",
+            usage.prompt_tokens,
+            usage.completion_tokens,
+            usage.total(),
+            cost
+        ));
+    }
+    lines.join("\n")
+}
+
+fn summary_lines(summary: &ReleaseSummary) -> Vec<(&'static str, String)> {
+    let tag = if summary.incremented {
+        format!("{} (requested {}, incremented)", summary.tag, summary.requested_tag)
+    } else {
+        summary.tag.clone()
+    };
+    let mut lines = vec![("tag:", tag)];
+    let optional = [
+        ("branch:", summary.branch.clone()),
+        ("commit:", summary.commit_sha.clone()),
+        ("release:", summary.release_id.map(|id| id.to_string())),
+        ("url:", summary.release_url.clone()),
+        ("notes source:", summary.notes_source.map(str::to_string)),
+        ("formatter:", summary.formatter.map(str::to_string)),
+    ];
+    lines.extend(optional.into_iter().filter_map(|(name, value)| Some((name, value?))));
+    if !summary.tickets.is_empty() {
+        let ids: Vec<String> = summary.tickets.iter().map(|t| format!("{}-{}", t.prefix, t.number)).collect();
+        lines.push(("tickets:", ids.join(", ")));
+    }
+    lines
+}
+
+/// The `--timings` breakdown, slowest phase marked, with the total last.
+fn timing_lines(timings: &[StepTiming]) -> Vec<String> {
+    if timings.is_empty() {
+        return Vec::new();
+    }
+    let slowest = timings.iter().map(|timing| timing.millis).max().unwrap_or(0);
+    let mut lines = vec!["    timings:".to_string()];
+    for timing in timings {
+        let marker = if timing.millis == slowest { " (slowest)" } else { "" };
+        lines.push(format!("      {:<16}{:>7}ms{}", timing.step, timing.millis, marker));
+    }
+    let total: u64 = timings.iter().map(|timing| timing.millis).sum();
+    lines.push(format!("      {:<16}{:>7}ms", "total", total));
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary() -> ReleaseSummary {
+        ReleaseSummary {
+            requested_tag: "v1.3.0".to_string(),
+            tag: "v1.3.1".to_string(),
+            incremented: true,
+            branch: Some("release/v1.3.x".to_string()),
+            commit_sha: Some("abc123".to_string()),
+            release_id: Some(9),
+            release_url: None,
+            notes_source: Some("release_body"),
+            formatter: Some("openai"),
+            tickets: vec![Ticket {
+                prefix: "PDE".to_string(),
+                number: "1".to_string(),
+                url: "https://onezelis.atlassian.net/browse/PDE-1".to_string(),
+            }],
+            timings: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn given_released_and_failed_repositories_when_rendering_recap_then_lists_the_key_fields() {
+        let summary = summary();
+        let entries: Vec<RecapEntry> = vec![
+            ("Human-Glitch/llm-playground".to_string(), Ok(("released", Some(&summary)))),
+            ("Human-Glitch/api".to_string(), Err("Branch not found".to_string())),
+        ];
+
+        let prices = TokenPrices { prompt: 2.5, completion: 10.0 };
+        let text = to_text("v1.3.0", &entries, Some(TokenUsage { prompt_tokens: 200, completion_tokens: 21 }), Some(prices));
+
+        assert_eq!(
+            text,
+            "Summary for v1.3.0:\n\
+            \x20 Human-Glitch/llm-playground: released\n\
+            \x20   tag:          v1.3.1 (requested v1.3.0, incremented)\n\
+            \x20   branch:       release/v1.3.x\n\
+            \x20   commit:       abc123\n\
+            \x20   release:      9\n\
+            \x20   notes source: release_body\n\
+            \x20   formatter:    openai\n\
+            \x20   tickets:      PDE-1\n\
+            \x20 Human-Glitch/api: failed: Branch not found\n\
+            \x20 OpenAI usage: 200 prompt + 21 completion tokens (221 total, $0.0007)"
+        );
+    }
+
+    #[test]
+    fn given_released_repository_when_rendering_recap_as_json_then_flattens_the_summary() {
+        let summary = summary();
+        let entries: Vec<RecapEntry> = vec![("Human-Glitch/llm-playground".to_string(), Ok(("released", Some(&summary))))];
+
+        let recap = to_json("v1.3.0", &entries, None, None);
+
+        let repository = &recap["repositories"][0];
+        assert_eq!(repository["outcome"], "released");
+        assert_eq!(repository["tag"], "v1.3.1");
+        assert_eq!(repository["incremented"], true);
+        assert_eq!(repository["commit_sha"], "abc123");
+        assert_eq!(repository["release_id"], 9);
+        assert_eq!(repository["formatter"], "openai");
+        assert_eq!(repository["tickets"][0]["number"], "1");
+        assert_eq!(recap["usage"], Value::Null);
+    }
+}
@@ -0,0 +1,57 @@
+use crate::github_client::GitHubRelease;
+use crate::version::Version;
+
+/// Which releases `list-releases` prints.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ReleaseFilter {
+    All,
+    PrereleasesOnly,
+    StableOnly,
+}
+
+impl ReleaseFilter {
+    pub fn from_flags(prereleases_only: bool, stable_only: bool) -> Self {
+        match (prereleases_only, stable_only) {
+            (true, _) => ReleaseFilter::PrereleasesOnly,
+            (_, true) => ReleaseFilter::StableOnly,
+            _ => ReleaseFilter::All,
+        }
+    }
+
+    fn keeps(self, release: &GitHubRelease) -> bool {
+        let prerelease = release.prerelease.unwrap_or(false);
+        match self {
+            ReleaseFilter::All => true,
+            ReleaseFilter::PrereleasesOnly => prerelease,
+            ReleaseFilter::StableOnly => !prerelease,
+        }
+    }
+}
+
+/// Filter the releases and sort them newest version first; tags that aren't semver go last.
+pub fn select(releases: Vec<GitHubRelease>, filter: ReleaseFilter, tag_prefix: &str) -> Vec<GitHubRelease> {
+    let mut releases: Vec<GitHubRelease> = releases.into_iter().filter(|release| filter.keeps(release)).collect();
+    let version = |release: &GitHubRelease| Version::parse_with_prefix(&release.tag_name, tag_prefix);
+    releases.sort_by(|a, b| match (version(a), version(b)) {
+        (Some(a), Some(b)) => b.cmp(&a),
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (None, None) => a.tag_name.cmp(&b.tag_name),
+    });
+    releases
+}
+
+/// One line per release with its tag, id, prerelease and draft flags.
+pub fn render(releases: &[GitHubRelease]) -> String {
+    let mut lines = vec![format!("{:<24} {:>12}  {:<10}  {}", "TAG", "ID", "PRERELEASE", "DRAFT")];
+    lines.extend(releases.iter().map(|release| {
+        format!(
+            "{:<24} {:>12}  {:<10}  {}",
+            release.tag_name,
+            release.id,
+            release.prerelease.unwrap_or(false),
+            release.draft.unwrap_or(false)
+        )
+    }));
+    lines.join("\n")
+}
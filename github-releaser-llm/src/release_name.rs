@@ -0,0 +1,75 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Fill in a `--name` template: `{tag}` becomes the release tag and `{date}` the date as YYYY-MM-DD.
+pub fn render(template: &str, tag: &str, date: &str) -> String {
+    template.replace("{tag}", tag).replace("{date}", date)
+}
+
+/// Today's date in UTC as YYYY-MM-DD.
+pub fn today() -> String {
+    let days = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs() / 86_400)
+        .unwrap_or_default();
+    date_from_days(days as i64)
+}
+
+/// The UTC time `ago` before now, formatted the way GitHub dates commits.
+pub fn timestamp_before(ago: Duration) -> String {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default();
+    timestamp(now.saturating_sub(ago.as_secs()))
+}
+
+/// ISO 8601 UTC timestamp for a number of seconds since the epoch, e.g. `2024-03-05T14:30:00Z`.
+fn timestamp(secs: u64) -> String {
+    let seconds_of_day = secs % 86_400;
+    format!(
+        "{}T{:02}:{:02}:{:02}Z",
+        date_from_days((secs / 86_400) as i64),
+        seconds_of_day / 3_600,
+        seconds_of_day / 60 % 60,
+        seconds_of_day % 60
+    )
+}
+
+/// Civil date for a number of days since 1970-01-01 (Howard Hinnant's `civil_from_days`).
+fn date_from_days(days: i64) -> String {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn given_placeholders_when_rendering_name_then_fills_tag_and_date() {
+        assert_eq!(render("Release {tag} ({date})", "v1.2.0", "2024-03-05"), "Release v1.2.0 (2024-03-05)");
+        assert_eq!(render("March 2024 Release", "v1.2.0", "2024-03-05"), "March 2024 Release");
+    }
+
+    #[test]
+    fn given_days_since_epoch_when_converting_then_returns_civil_date() {
+        assert_eq!(date_from_days(0), "1970-01-01");
+        assert_eq!(date_from_days(19_782), "2024-02-29");
+        assert_eq!(date_from_days(20_010), "2024-10-14");
+    }
+
+    #[test]
+    fn given_seconds_since_epoch_when_formatting_timestamp_then_matches_github_commit_dates() {
+        assert_eq!(timestamp(0), "1970-01-01T00:00:00Z");
+        assert_eq!(timestamp(1_709_649_000), "2024-03-05T14:30:00Z");
+    }
+}
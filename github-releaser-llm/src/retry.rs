@@ -0,0 +1,400 @@
+use futures::future::BoxFuture;
+use rand::Rng;
+use reqwest::{Method, RequestBuilder, Response, StatusCode};
+use std::fmt::Debug;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::output;
+
+/// Waits out the backoff between retries; swapped out in tests so they don't really sleep.
+pub trait Sleeper: Debug + Send + Sync {
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()>;
+}
+
+/// Sleeps on the tokio timer.
+#[derive(Debug)]
+pub struct TokioSleeper;
+
+impl Sleeper for TokioSleeper {
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()> {
+        Box::pin(tokio::time::sleep(duration))
+    }
+}
+
+/// Records each requested delay and returns immediately.
+#[cfg(test)]
+#[derive(Debug, Default)]
+pub struct RecordingSleeper {
+    pub slept: std::sync::Mutex<Vec<Duration>>,
+}
+
+#[cfg(test)]
+impl Sleeper for RecordingSleeper {
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()> {
+        self.slept.lock().unwrap().push(duration);
+        Box::pin(std::future::ready(()))
+    }
+}
+
+/// How often and how patiently a failed request is retried.
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    pub jitter: bool,
+    pub sleeper: Arc<dyn Sleeper>,
+}
+
+impl RetryPolicy {
+    /// Retry up to `max_retries` times with the standard backoff settings.
+    pub fn new(max_retries: u32) -> Self {
+        RetryPolicy {
+            max_retries,
+            ..Default::default()
+        }
+    }
+
+    /// The same policy, retrying at least `retries` times.
+    pub fn at_least(&self, retries: u32) -> Self {
+        RetryPolicy {
+            max_retries: self.max_retries.max(retries),
+            ..self.clone()
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    /// No retries, so a client behaves exactly like a plain `send()` unless configured.
+    fn default() -> Self {
+        RetryPolicy {
+            max_retries: 0,
+            base_delay: Duration::from_millis(200),
+            max_delay: Duration::from_secs(5),
+            jitter: true,
+            sleeper: Arc::new(TokioSleeper),
+        }
+    }
+}
+
+/// A cap on the requests a group of calls may send, retries included.
+#[derive(Debug)]
+pub struct CallBudget {
+    max: usize,
+    used: AtomicUsize,
+}
+
+impl CallBudget {
+    pub fn new(max: usize) -> Self {
+        CallBudget { max, used: AtomicUsize::new(0) }
+    }
+
+    /// Take one request from the budget; false once it's spent.
+    pub fn take(&self) -> bool {
+        self.used
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |used| (used < self.max).then_some(used + 1))
+            .is_ok()
+    }
+
+    pub fn max(&self) -> usize {
+        self.max
+    }
+}
+
+/// Compute the delay before retry number `attempt` (starting at 0): `min(base * 2^attempt, max)`.
+///
+/// With `jitter` the delay is drawn uniformly from `[0, delay]` ("full jitter").
+pub fn backoff_delay(attempt: u32, base: Duration, max: Duration, jitter: bool) -> Duration {
+    let delay = 2u32
+        .checked_pow(attempt)
+        .and_then(|factor| base.checked_mul(factor))
+        .map_or(max, |delay| delay.min(max));
+
+    if jitter {
+        let millis = delay.as_millis() as u64;
+        Duration::from_millis(rand::rng().random_range(0..=millis))
+    } else {
+        delay
+    }
+}
+
+/// Whether sending the request twice does no more than sending it once.
+fn is_idempotent(method: &Method) -> bool {
+    matches!(*method, Method::GET | Method::HEAD | Method::PUT | Method::DELETE | Method::OPTIONS)
+}
+
+/// Whether a failed attempt is worth retrying.
+///
+/// Rate limiting and refused connections mean the request was never handled, so any request is
+/// retried. A server error or a timeout may come after it went through, so only `repeatable` ones are.
+fn is_retryable(result: &Result<Response, reqwest::Error>, repeatable: bool) -> bool {
+    match result {
+        Ok(resp) => resp.status() == StatusCode::TOO_MANY_REQUESTS || (repeatable && resp.status().is_server_error()),
+        Err(e) => e.is_connect() || (repeatable && e.is_timeout()),
+    }
+}
+
+/// Send a request, retrying transient failures according to `policy`.
+///
+/// Server errors and timeouts are only retried for idempotent methods; a POST that created
+/// something must not run twice. The last response (or error) is returned as-is once retries
+/// are exhausted, so callers keep handling failures the same way they do today.
+pub async fn send_with_retry(
+    request: RequestBuilder,
+    policy: &RetryPolicy,
+) -> Result<Response, reqwest::Error> {
+    let repeatable = request
+        .try_clone()
+        .and_then(|probe| probe.build().ok())
+        .is_some_and(|probe| is_idempotent(probe.method()));
+    send(request, policy, repeatable, None).await
+}
+
+/// Like `send_with_retry`, for a request the caller knows is safe to send again whatever its
+/// method, taking every retry from `budget` and giving up once it's spent.
+///
+/// The first attempt isn't taken from the budget here, so the caller can fail before sending anything.
+pub async fn send_repeatable_with_retry(
+    request: RequestBuilder,
+    policy: &RetryPolicy,
+    budget: Option<&CallBudget>,
+) -> Result<Response, reqwest::Error> {
+    send(request, policy, true, budget).await
+}
+
+async fn send(
+    request: RequestBuilder,
+    policy: &RetryPolicy,
+    repeatable: bool,
+    budget: Option<&CallBudget>,
+) -> Result<Response, reqwest::Error> {
+    let mut attempt = 0;
+
+    loop {
+        // Requests with streaming bodies can't be cloned, so they're only sent once
+        let current = match request.try_clone() {
+            Some(current) => current,
+            None => return request.send().await,
+        };
+
+        let result = current.send().await;
+        if !is_retryable(&result, repeatable) || attempt >= policy.max_retries || budget.is_some_and(|budget| !budget.take()) {
+            return result;
+        }
+
+        let delay = backoff_delay(attempt, policy.base_delay, policy.max_delay, policy.jitter);
+        println!(
+            "  {}",
+            output::retry(format!(
+                "Request failed, retrying in {}ms (attempt {}/{})",
+                delay.as_millis(),
+                attempt + 1,
+                policy.max_retries
+            ))
+        );
+        policy.sleeper.sleep(delay).await;
+        attempt += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::Client;
+    use tokio::runtime::Runtime;
+
+    #[test]
+    fn given_no_jitter_when_computing_backoff_then_doubles_each_attempt() {
+        let base = Duration::from_millis(200);
+        let max = Duration::from_secs(5);
+
+        assert_eq!(backoff_delay(0, base, max, false), Duration::from_millis(200));
+        assert_eq!(backoff_delay(1, base, max, false), Duration::from_millis(400));
+        assert_eq!(backoff_delay(2, base, max, false), Duration::from_millis(800));
+    }
+
+    #[test]
+    fn given_no_jitter_when_backoff_exceeds_max_then_caps_at_max() {
+        let base = Duration::from_millis(200);
+        let max = Duration::from_millis(1000);
+
+        assert_eq!(backoff_delay(2, base, max, false), Duration::from_millis(800));
+        assert_eq!(backoff_delay(3, base, max, false), max);
+        // Large attempts must not overflow
+        assert_eq!(backoff_delay(64, base, max, false), max);
+    }
+
+    #[test]
+    fn given_jitter_when_computing_backoff_then_never_exceeds_max() {
+        let base = Duration::from_millis(200);
+        let max = Duration::from_millis(1000);
+
+        for attempt in 0..20 {
+            let delay = backoff_delay(attempt, base, max, true);
+            assert!(delay <= max, "attempt {} produced {:?}", attempt, delay);
+            assert!(delay <= backoff_delay(attempt, base, max, false));
+        }
+    }
+
+    #[test]
+    fn given_server_error_then_success_when_sending_with_retry_then_returns_success() {
+        let mut server = mockito::Server::new();
+
+        let failing = server.mock("GET", "/flaky")
+            .with_status(502)
+            .expect(1)
+            .create();
+        let succeeding = server.mock("GET", "/flaky")
+            .with_status(200)
+            .with_body("ok")
+            .create();
+
+        let policy = RetryPolicy {
+            max_retries: 2,
+            base_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(5),
+            jitter: false,
+            sleeper: Arc::new(RecordingSleeper::default()),
+        };
+
+        let rt = Runtime::new().unwrap();
+        let status = rt.block_on(async {
+            let request = Client::new().get(format!("{}/flaky", server.url()));
+            send_with_retry(request, &policy).await.unwrap().status()
+        });
+
+        assert_eq!(status, StatusCode::OK);
+        failing.assert();
+        succeeding.assert();
+    }
+
+    #[test]
+    fn given_three_failures_when_sending_with_retry_then_backs_off_without_really_sleeping() {
+        let mut server = mockito::Server::new();
+
+        let failing = server.mock("GET", "/flaky")
+            .with_status(503)
+            .expect(3)
+            .create();
+        let succeeding = server.mock("GET", "/flaky")
+            .with_status(200)
+            .create();
+
+        let sleeper = Arc::new(RecordingSleeper::default());
+        let policy = RetryPolicy {
+            max_retries: 3,
+            base_delay: Duration::from_millis(200),
+            max_delay: Duration::from_secs(5),
+            jitter: false,
+            sleeper: sleeper.clone(),
+        };
+
+        let rt = Runtime::new().unwrap();
+        let started = std::time::Instant::now();
+        let status = rt.block_on(async {
+            let request = Client::new().get(format!("{}/flaky", server.url()));
+            send_with_retry(request, &policy).await.unwrap().status()
+        });
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            *sleeper.slept.lock().unwrap(),
+            vec![Duration::from_millis(200), Duration::from_millis(400), Duration::from_millis(800)]
+        );
+        assert!(started.elapsed() < Duration::from_millis(1400));
+        failing.assert();
+        succeeding.assert();
+    }
+
+    #[test]
+    fn given_server_error_on_a_post_when_sending_with_retry_then_only_retries_when_repeatable() {
+        let mut server = mockito::Server::new();
+
+        let failing = server.mock("POST", "/releases")
+            .with_status(502)
+            // One attempt without retries, then one with all three
+            .expect(1 + (1 + 3))
+            .create();
+
+        let policy = RetryPolicy {
+            max_retries: 3,
+            base_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(5),
+            jitter: false,
+            sleeper: Arc::new(RecordingSleeper::default()),
+        };
+
+        let rt = Runtime::new().unwrap();
+        let (once, repeated) = rt.block_on(async {
+            let request = || Client::new().post(format!("{}/releases", server.url())).body("{}");
+            (
+                send_with_retry(request(), &policy).await.unwrap().status(),
+                send_repeatable_with_retry(request(), &policy, None).await.unwrap().status(),
+            )
+        });
+
+        assert_eq!(once, StatusCode::BAD_GATEWAY);
+        assert_eq!(repeated, StatusCode::BAD_GATEWAY);
+        failing.assert();
+    }
+
+    #[test]
+    fn given_rate_limited_post_when_sending_with_retry_then_retries_it() {
+        let mut server = mockito::Server::new();
+
+        let limited = server.mock("POST", "/releases")
+            .with_status(429)
+            .expect(1)
+            .create();
+        let created = server.mock("POST", "/releases")
+            .with_status(201)
+            .create();
+
+        let policy = RetryPolicy {
+            max_retries: 1,
+            base_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(5),
+            jitter: false,
+            sleeper: Arc::new(RecordingSleeper::default()),
+        };
+
+        let rt = Runtime::new().unwrap();
+        let status = rt.block_on(async {
+            let request = Client::new().post(format!("{}/releases", server.url())).body("{}");
+            send_with_retry(request, &policy).await.unwrap().status()
+        });
+
+        assert_eq!(status, StatusCode::CREATED);
+        limited.assert();
+        created.assert();
+    }
+
+    #[test]
+    fn given_client_error_when_sending_with_retry_then_does_not_retry() {
+        let mut server = mockito::Server::new();
+
+        let mock = server.mock("GET", "/missing")
+            .with_status(404)
+            .expect(1)
+            .create();
+
+        let policy = RetryPolicy {
+            max_retries: 3,
+            base_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(5),
+            jitter: false,
+            sleeper: Arc::new(RecordingSleeper::default()),
+        };
+
+        let rt = Runtime::new().unwrap();
+        let status = rt.block_on(async {
+            let request = Client::new().get(format!("{}/missing", server.url()));
+            send_with_retry(request, &policy).await.unwrap().status()
+        });
+
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        mock.assert();
+    }
+}
@@ -0,0 +1,68 @@
+use std::collections::{BTreeSet, HashMap};
+use std::error::Error;
+
+use crate::github_client::GitHubClient;
+use crate::label_groups::pull_request_number;
+
+/// End every line item linking a pull request with who approved it, e.g. `(approved by @bob, @carol)`.
+///
+/// `approvers` maps pull request numbers to their approvers; lines without any are left alone.
+pub fn annotate(notes: &str, approvers: &HashMap<u64, Vec<String>>) -> String {
+    let lines: Vec<String> = notes
+        .lines()
+        .map(|line| {
+            let line_approvers = pull_request_number(line)
+                .and_then(|number| approvers.get(&number))
+                .filter(|line_approvers| !line_approvers.is_empty());
+            match line_approvers {
+                Some(line_approvers) => {
+                    let handles: Vec<String> = line_approvers.iter().map(|login| format!("@{}", login)).collect();
+                    format!("{} (approved by {})", line, handles.join(", "))
+                }
+                None => line.to_string(),
+            }
+        })
+        .collect();
+
+    let mut annotated = lines.join("\n");
+    if notes.ends_with('\n') {
+        annotated.push('\n');
+    }
+    annotated
+}
+
+/// Look up the approvers of every pull request the notes link to, then annotate its lines with them.
+pub async fn annotate_notes(gh_client: &GitHubClient, notes: &str) -> Result<String, Box<dyn Error>> {
+    let numbers: BTreeSet<u64> = notes.lines().filter_map(pull_request_number).collect();
+
+    let mut approvers = HashMap::new();
+    for number in numbers {
+        approvers.insert(number, gh_client.pull_request_approvers(number).await?);
+    }
+    Ok(annotate(notes, &approvers))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn given_approvers_when_annotating_then_ends_each_pull_request_line_with_them() {
+        let notes = "## PDE\n\
+            * [PDE-1](https://t/PDE-1) Fix login by @a in https://github.com/o/r/pull/1\n\
+            * [PDE-2](https://t/PDE-2) Bump serde by @bot in https://github.com/o/r/pull/2\n\
+            * Tidy docs\n";
+        let approvers = HashMap::from([
+            (1, vec!["bob".to_string(), "carol".to_string()]),
+            (2, Vec::new()),
+        ]);
+
+        assert_eq!(
+            annotate(notes, &approvers),
+            "## PDE\n\
+            * [PDE-1](https://t/PDE-1) Fix login by @a in https://github.com/o/r/pull/1 (approved by @bob, @carol)\n\
+            * [PDE-2](https://t/PDE-2) Bump serde by @bot in https://github.com/o/r/pull/2\n\
+            * Tidy docs\n"
+        );
+    }
+}
@@ -0,0 +1,305 @@
+use regex::Regex;
+use std::sync::LazyLock;
+
+use crate::concurrency;
+use crate::formatter::UnmatchedLines;
+use crate::openai_client::OpenAiError;
+
+/// The number of the ticket a formatted line item starts with, as in `* [PDE-12](...) Fix`.
+static TICKET_ITEM_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^[*-]\s+\[?[A-Z][A-Z0-9]*-(\d+)\b").unwrap());
+
+/// Instructions for the final pass that merges the per-chunk notes into one summary.
+pub const DEFAULT_REDUCE_PROMPT: &str = r#"These are formatted release notes for one release, written in several parts.
+Merge them into a single concise summary for the GitHub release:
+- Group the changes under a short heading per category (e.g. Features, Fixes, Maintenance).
+- Combine related items into one bullet and keep every ticket link that is mentioned.
+- DO NOT MAKE UP ANY INFORMATION THAT IS NOT PRESENT IN THE PARTS."#;
+
+/// Settings for `--summarize`: format the notes chunk by chunk, then merge the chunks in a final call.
+#[derive(Clone, Debug)]
+pub struct SummaryOptions {
+    /// Line items per chunk, before the call cap is applied
+    pub chunk_lines: usize,
+    /// Most OpenAI calls one summary may use, including the final merge
+    pub max_calls: usize,
+    /// Instructions for the final merge
+    pub reduce_prompt: String,
+}
+
+impl SummaryOptions {
+    /// How many chunks fit under the call cap, keeping one call for the merge.
+    pub fn max_chunks(&self) -> usize {
+        self.max_calls.saturating_sub(1).max(1)
+    }
+}
+
+/// Split the notes' non-blank lines into chunks of `chunk_lines`, growing the chunks
+/// when needed so there are never more than `max_chunks`.
+///
+/// Chunks break between `##` sections; a section too long for one chunk is split with its heading
+/// repeated on every piece, so no item loses its heading.
+pub fn chunk_notes(notes: &str, chunk_lines: usize, max_chunks: usize) -> Vec<String> {
+    let sections = sections(notes);
+    let total: usize = sections.iter().map(Vec::len).sum();
+    if total == 0 {
+        return Vec::new();
+    }
+
+    let mut size = chunk_lines.max(1).max(total.div_ceil(max_chunks.max(1)));
+    loop {
+        // Section boundaries can leave chunks part-full, so grow them until the cap holds
+        let chunks = pack_sections(&sections, size);
+        if chunks.len() <= max_chunks.max(1) {
+            return chunks;
+        }
+        size += 1;
+    }
+}
+
+/// The notes' non-blank lines, grouped into runs that each start at a `##` heading.
+fn sections(notes: &str) -> Vec<Vec<&str>> {
+    let mut sections: Vec<Vec<&str>> = Vec::new();
+    for line in notes.lines().filter(|line| !line.trim().is_empty()) {
+        match sections.last_mut() {
+            Some(section) if !line.starts_with("## ") => section.push(line),
+            _ => sections.push(vec![line]),
+        }
+    }
+    sections
+}
+
+fn pack_sections(sections: &[Vec<&str>], size: usize) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut chunk: Vec<&str> = Vec::new();
+    for section in sections {
+        let pieces: Vec<Vec<&str>> = match section.split_first() {
+            _ if section.len() <= size => vec![section.clone()],
+            Some((heading, items)) if heading.starts_with("## ") => items
+                .chunks(size.saturating_sub(1).max(1))
+                .map(|items| std::iter::once(*heading).chain(items.iter().copied()).collect())
+                .collect(),
+            _ => section.chunks(size).map(<[&str]>::to_vec).collect(),
+        };
+        for piece in pieces {
+            if !chunk.is_empty() && chunk.len() + piece.len() > size {
+                chunks.push(chunk.join("\n"));
+                chunk.clear();
+            }
+            chunk.extend(piece);
+        }
+    }
+    if !chunk.is_empty() {
+        chunks.push(chunk.join("\n"));
+    }
+    chunks
+}
+
+/// Split the notes' non-blank lines into chunks of at most `max_chars` characters; a longer line is a chunk of its own.
+pub fn chunk_by_size(notes: &str, max_chars: usize) -> Vec<String> {
+    let mut chunks: Vec<String> = Vec::new();
+    let mut chunk = String::new();
+    for line in notes.lines().filter(|line| !line.trim().is_empty()) {
+        if !chunk.is_empty() && chunk.chars().count() + 1 + line.chars().count() > max_chars {
+            chunks.push(std::mem::take(&mut chunk));
+        }
+        if !chunk.is_empty() {
+            chunk.push('\n');
+        }
+        chunk.push_str(line);
+    }
+    if !chunk.is_empty() {
+        chunks.push(chunk);
+    }
+    chunks
+}
+
+/// Join separately formatted chunks, listing the lines of every `##` section once under its heading.
+///
+/// As the formatter orders one set of notes, each section's items are sorted by ticket number and
+/// the lines without a ticket, under `unmatched`'s heading or none, come last. The other sections
+/// keep the order they first appear in.
+pub fn merge_formatted(partials: &[String], unmatched: &UnmatchedLines) -> String {
+    let mut sections: Vec<(Option<String>, Vec<String>)> = vec![(None, Vec::new())];
+    for partial in partials {
+        let mut current = 0;
+        for line in partial.lines().filter(|line| !line.trim().is_empty()) {
+            if line.starts_with("## ") {
+                let heading = Some(line.trim().to_string());
+                current = match sections.iter().position(|(known, _)| *known == heading) {
+                    Some(index) => index,
+                    None => {
+                        sections.push((heading, Vec::new()));
+                        sections.len() - 1
+                    }
+                };
+            } else {
+                sections[current].1.push(line.to_string());
+            }
+        }
+    }
+
+    let other_heading = match unmatched {
+        UnmatchedLines::Heading(title) => Some(format!("## {}", title)),
+        _ => None,
+    };
+    sections.sort_by_key(|(heading, _)| match heading {
+        None => 2,
+        Some(_) if *heading == other_heading => 1,
+        Some(_) => 0,
+    });
+    for (_, lines) in &mut sections {
+        // Stable, so lines without a ticket keep their order after the ticket lines
+        lines.sort_by_key(|line| match TICKET_ITEM_RE.captures(line) {
+            Some(caps) => (false, caps[1].parse().unwrap_or(u64::MAX)),
+            None => (true, 0),
+        });
+    }
+
+    sections
+        .into_iter()
+        .filter(|(heading, lines)| heading.is_some() || !lines.is_empty())
+        .map(|(heading, lines)| heading.into_iter().chain(lines).collect::<Vec<_>>().join("\n"))
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// Formats one chunk of the notes; a seam so tests don't need OpenAI.
+pub trait ChunkFormatter {
+    async fn format_chunk(&self, chunk: &str) -> Result<String, OpenAiError>;
+}
+
+/// Format every chunk with at most `limit` calls in flight, returning the formatted chunks in order.
+///
+/// Every chunk is formatted even when one fails; the first failure is returned.
+pub async fn format_chunks<C: ChunkFormatter>(formatter: &C, chunks: &[String], limit: usize) -> Result<Vec<String>, OpenAiError> {
+    let calls = chunks
+        .iter()
+        .enumerate()
+        .map(|(index, chunk)| async move {
+            println!("  Formatting chunk {}/{}...", index + 1, chunks.len());
+            formatter.format_chunk(chunk).await
+        })
+        .collect();
+    concurrency::run_bounded(calls, limit).await.into_iter().collect()
+}
+
+/// Build the merge prompt from the instructions and the formatted chunks.
+pub fn build_reduce_prompt(instructions: &str, partials: &[String]) -> String {
+    let parts = partials
+        .iter()
+        .enumerate()
+        .map(|(index, partial)| format!("PART {}:\n{}", index + 1, partial.trim()))
+        .collect::<Vec<_>>()
+        .join("\n\n");
+
+    format!("{}\n\n{}\n", instructions.trim(), parts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+    use tokio::runtime::Runtime;
+
+    /// Echoes each chunk back in upper case, slower for earlier chunks, and counts calls in flight.
+    #[derive(Default)]
+    struct FakeFormatter {
+        in_flight: AtomicUsize,
+        max_in_flight: AtomicUsize,
+    }
+
+    impl ChunkFormatter for FakeFormatter {
+        async fn format_chunk(&self, chunk: &str) -> Result<String, OpenAiError> {
+            let now = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_in_flight.fetch_max(now, Ordering::SeqCst);
+            let delay = 40u64.saturating_sub(chunk.len() as u64 * 2);
+            tokio::time::sleep(Duration::from_millis(delay)).await;
+            self.in_flight.fetch_sub(1, Ordering::SeqCst);
+            Ok(chunk.to_uppercase())
+        }
+    }
+
+    #[test]
+    fn given_more_chunks_than_the_limit_when_formatting_then_caps_calls_and_keeps_the_order() {
+        let formatter = FakeFormatter::default();
+        let chunks: Vec<String> = (1..=5).map(|n| format!("* change {}", "x".repeat(n))).collect();
+
+        let rt = Runtime::new().unwrap();
+        let formatted = rt.block_on(format_chunks(&formatter, &chunks, 2)).unwrap();
+
+        assert_eq!(formatter.max_in_flight.load(Ordering::SeqCst), 2);
+        let expected: Vec<String> = chunks.iter().map(|chunk| chunk.to_uppercase()).collect();
+        assert_eq!(formatted, expected);
+    }
+
+    #[test]
+    fn given_more_chunks_than_the_cap_when_chunking_then_grows_the_chunks() {
+        let notes = (1..=10).map(|n| format!("* change {}", n)).collect::<Vec<_>>().join("\n");
+
+        assert_eq!(chunk_notes(&notes, 2, 10).len(), 5);
+        let capped = chunk_notes(&notes, 2, 3);
+        assert_eq!(capped.len(), 3);
+        assert_eq!(capped[0].lines().count(), 4);
+    }
+
+    #[test]
+    fn given_chunks_with_shared_headings_when_merging_then_lists_each_section_once() {
+        let partials = vec![
+            "## PDE\n* [PDE-1](u) Fix\n\n## PD\n* [PD-2](u) Add".to_string(),
+            "## PDE\n* [PDE-3](u) Tweak\n\n* Bump serde".to_string(),
+        ];
+
+        assert_eq!(
+            merge_formatted(&partials, &UnmatchedLines::List),
+            "## PDE\n* [PDE-1](u) Fix\n* [PDE-3](u) Tweak\n* Bump serde\n\n## PD\n* [PD-2](u) Add"
+        );
+    }
+
+    #[test]
+    fn given_chunks_split_mid_section_when_merging_then_orders_them_like_the_formatter() {
+        let partials = vec![
+            "## Maintenance\n* Bump serde\n\n## PDE\n* [PDE-5](u) Fix".to_string(),
+            "* Stray line\n## PDE\n* [PDE-2](u) Add\n\n## Maintenance\n* Tidy CI".to_string(),
+        ];
+
+        assert_eq!(
+            merge_formatted(&partials, &UnmatchedLines::Heading("Maintenance".to_string())),
+            "## PDE\n* [PDE-2](u) Add\n* [PDE-5](u) Fix\n\n## Maintenance\n* Bump serde\n* Tidy CI\n\n* Stray line"
+        );
+    }
+
+    #[test]
+    fn given_size_limit_when_chunking_by_size_then_keeps_chunks_under_it() {
+        let notes = "* aaaa\n* bbbb\n\n* cccc\n* a line longer than the limit";
+
+        assert_eq!(chunk_by_size(notes, 13), vec!["* aaaa\n* bbbb", "* cccc", "* a line longer than the limit"]);
+        assert_eq!(chunk_by_size(notes, 1000).len(), 1);
+    }
+
+    #[test]
+    fn given_multibyte_lines_when_chunking_by_size_then_counts_characters() {
+        // Each line is 6 characters but 12 bytes
+        let notes = "* äöüß\n* éèêë";
+
+        assert_eq!(chunk_by_size(notes, 13), vec!["* äöüß\n* éèêë"]);
+    }
+
+    #[test]
+    fn given_sections_when_chunking_then_keeps_every_item_under_its_heading() {
+        let notes = "## PDE\n* [PDE-1](u) Fix\n* [PDE-2](u) Add\n## PD\n* [PD-3](u) Tweak";
+
+        assert_eq!(chunk_notes(notes, 3, 5), vec!["## PDE\n* [PDE-1](u) Fix\n* [PDE-2](u) Add", "## PD\n* [PD-3](u) Tweak"]);
+        assert_eq!(
+            chunk_notes(notes, 2, 5),
+            vec!["## PDE\n* [PDE-1](u) Fix", "## PDE\n* [PDE-2](u) Add", "## PD\n* [PD-3](u) Tweak"]
+        );
+        assert_eq!(chunk_notes(notes, 2, 2).len(), 2);
+    }
+
+    #[test]
+    fn given_blank_lines_when_chunking_then_skips_them() {
+        assert_eq!(chunk_notes("* a\n\n* b\n", 1, 5), vec!["* a".to_string(), "* b".to_string()]);
+        assert!(chunk_notes("\n\n", 1, 5).is_empty());
+    }
+}
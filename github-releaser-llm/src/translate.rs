@@ -0,0 +1,210 @@
+use regex::Regex;
+use std::error::Error;
+use std::path::PathBuf;
+
+use crate::concurrency;
+use crate::openai_client::{OpenAIClient, TokenUsage};
+use crate::output;
+
+/// Translates formatted notes into additional languages after formatting.
+pub struct Translator {
+    client: OpenAIClient,
+    languages: Vec<String>,
+    output_dir: Option<PathBuf>,
+}
+
+/// The notes translated into one language.
+pub struct Translation {
+    /// Language code as given on the command line, e.g. "es"
+    pub language: String,
+    pub notes: String,
+}
+
+/// Heading used for a language code, falling back to the code itself.
+pub fn language_name(code: &str) -> &str {
+    match code.to_lowercase().as_str() {
+        "de" => "Deutsch",
+        "es" => "Español",
+        "fr" => "Français",
+        "it" => "Italiano",
+        "ja" => "日本語",
+        "ko" => "한국어",
+        "nl" => "Nederlands",
+        "pt" => "Português",
+        "zh" => "中文",
+        _ => code,
+    }
+}
+
+/// Ticket IDs and URLs from `original` that don't appear verbatim in `translated`.
+pub fn missing_references(original: &str, translated: &str) -> Vec<String> {
+    let reference_re = Regex::new(r"https?://[^\s()\[\]]+|\b[A-Z][A-Z0-9]*-\d+\b").unwrap();
+    let mut missing: Vec<String> = Vec::new();
+
+    for reference in reference_re.find_iter(original).map(|m| m.as_str()) {
+        if !translated.contains(reference) && !missing.iter().any(|m| m == reference) {
+            missing.push(reference.to_string());
+        }
+    }
+
+    missing
+}
+
+impl Translator {
+    pub fn new(client: OpenAIClient, languages: Vec<String>) -> Self {
+        Translator {
+            client,
+            languages,
+            output_dir: None,
+        }
+    }
+
+    /// Tokens the translations used so far.
+    pub fn usage(&self) -> TokenUsage {
+        self.client.usage()
+    }
+
+    /// Write each translation to its own file in `output_dir` instead of appending it to the notes.
+    pub fn with_output_dir(mut self, output_dir: Option<PathBuf>) -> Self {
+        self.output_dir = output_dir;
+        self
+    }
+
+    /// Translate the notes into every configured language, several at once up to the client's
+    /// parallel call limit.
+    ///
+    /// A translation that loses a ticket ID or URL is rejected rather than published.
+    pub async fn translate(&self, notes: &str) -> Result<Vec<Translation>, Box<dyn Error>> {
+        let calls = self
+            .languages
+            .iter()
+            .map(|language| {
+                let name = language_name(language);
+                println!("  Translating release notes to {}...", name);
+                self.client.translate_notes(notes, name)
+            })
+            .collect();
+        let results = concurrency::run_bounded(calls, self.client.max_parallel()).await;
+
+        let mut translations = Vec::new();
+        for (language, translated) in self.languages.iter().zip(results) {
+            let name = language_name(language);
+            let translated = translated?;
+
+            let missing = missing_references(notes, &translated);
+            if !missing.is_empty() {
+                return Err(format!(
+                    "Translation to {} changed or dropped: {}",
+                    name,
+                    missing.join(", ")
+                )
+                .into());
+            }
+
+            translations.push(Translation {
+                language: language.clone(),
+                notes: translated.trim().to_string(),
+            });
+        }
+
+        Ok(translations)
+    }
+
+    /// Translate the notes and either append the translations under language headings
+    /// or write them to `<output_dir>/<file_stem>.<language>.md`, returning the release body.
+    pub async fn apply(&self, notes: &str, file_stem: &str) -> Result<String, Box<dyn Error>> {
+        let translations = self.translate(notes).await?;
+
+        match &self.output_dir {
+            Some(dir) => {
+                std::fs::create_dir_all(dir)?;
+                for translation in &translations {
+                    let path = dir.join(format!("{}.{}.md", file_stem, translation.language));
+                    std::fs::write(&path, format!("{}\n", translation.notes))?;
+                    println!("  {}", output::success(format!("Wrote {}", path.display())));
+                }
+                Ok(notes.to_string())
+            }
+            None => {
+                let mut body = notes.trim_end().to_string();
+                for translation in &translations {
+                    body.push_str(&format!(
+                        "\n\n## {}\n{}",
+                        language_name(&translation.language),
+                        translation.notes
+                    ));
+                }
+                Ok(body)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::Client;
+    use serde_json::json;
+    use tokio::runtime::Runtime;
+
+    fn completion(content: &str) -> String {
+        json!({"choices": [{"message": {"role": "assistant", "content": content}}]}).to_string()
+    }
+
+    #[test]
+    fn given_mocked_translation_when_appending_then_keeps_original_links() {
+        let mut server = mockito::Server::new();
+
+        let mock = server.mock("POST", "/v1/chat/completions")
+            .match_body(mockito::Matcher::Regex("Español".to_string()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(completion("* [PDE-1](https://onezelis.atlassian.net/browse/PDE-1) Se corrigió un error"))
+            .create();
+
+        let client = OpenAIClient::new_with_base_url(Client::new(), "fake_api_key".to_string(), "gpt-4o", server.url());
+        let translator = Translator::new(client, vec!["es".to_string()]);
+        let notes = "## PDE\n* [PDE-1](https://onezelis.atlassian.net/browse/PDE-1) Fixed a bug";
+
+        let rt = Runtime::new().unwrap();
+        let body = rt.block_on(async { translator.apply(notes, "v1.0.0").await.unwrap() });
+
+        assert_eq!(
+            body,
+            "## PDE\n* [PDE-1](https://onezelis.atlassian.net/browse/PDE-1) Fixed a bug\n\n\
+            ## Español\n* [PDE-1](https://onezelis.atlassian.net/browse/PDE-1) Se corrigió un error"
+        );
+        mock.assert();
+    }
+
+    #[test]
+    fn given_translation_dropping_a_link_when_translating_then_returns_error() {
+        let mut server = mockito::Server::new();
+
+        let mock = server.mock("POST", "/v1/chat/completions")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(completion("* PDE-1 Correction d'un bug"))
+            .create();
+
+        let client = OpenAIClient::new_with_base_url(Client::new(), "fake_api_key".to_string(), "gpt-4o", server.url());
+        let translator = Translator::new(client, vec!["fr".to_string()]);
+        let notes = "* [PDE-1](https://onezelis.atlassian.net/browse/PDE-1) Fixed a bug";
+
+        let rt = Runtime::new().unwrap();
+        let result = rt.block_on(async { translator.translate(notes).await });
+
+        assert_eq!(
+            result.err().unwrap().to_string(),
+            "Translation to Français changed or dropped: https://onezelis.atlassian.net/browse/PDE-1"
+        );
+        mock.assert();
+    }
+
+    #[test]
+    fn given_unknown_language_code_when_naming_then_uses_the_code() {
+        assert_eq!(language_name("es"), "Español");
+        assert_eq!(language_name("FR"), "Français");
+        assert_eq!(language_name("sv"), "sv");
+    }
+}
@@ -0,0 +1,148 @@
+use std::cmp::Ordering;
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+/// Tag prefix in front of the version unless `--tag-prefix` says otherwise.
+pub const DEFAULT_TAG_PREFIX: &str = "v";
+
+/// A semver tag, the `v` optional and build metadata ignored.
+static VERSION_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^v?(\d+)\.(\d+)\.(\d+)(?:-([0-9A-Za-z.-]+))?(?:\+[0-9A-Za-z.-]+)?$").unwrap());
+
+/// The trailing numeric counter of a prerelease, as in `v1.2.0-rc.1`.
+static PRERELEASE_COUNTER_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(v?\d+\.\d+\.\d+-(?:[0-9A-Za-z-]+\.)*)(\d+)$").unwrap());
+
+/// A `v{major}.{minor}.{patch}[-prerelease]` tag, ordered by semver precedence.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Version {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+    /// Dot-separated prerelease identifiers, e.g. `["rc", "1"]` for `v1.2.0-rc.1`
+    pub pre: Vec<String>,
+}
+
+impl Version {
+    /// Parse a tag such as `v1.2.3` or `v1.2.3-rc.1`; anything else is `None`.
+    pub fn parse(tag: &str) -> Option<Version> {
+        let caps = VERSION_RE.captures(tag)?;
+
+        Some(Version {
+            major: caps[1].parse().ok()?,
+            minor: caps[2].parse().ok()?,
+            patch: caps[3].parse().ok()?,
+            pre: caps
+                .get(4)
+                .map(|pre| pre.as_str().split('.').map(str::to_string).collect())
+                .unwrap_or_default(),
+        })
+    }
+
+    /// Parse a tag that starts with `prefix`, e.g. `release-1.2.3` with `release-`; a bare `1.2.3` parses too.
+    pub fn parse_with_prefix(tag: &str, prefix: &str) -> Option<Version> {
+        let version = tag.strip_prefix(prefix).unwrap_or(tag);
+        // Nothing but the prefix may come before the number
+        if !version.starts_with(|c: char| c.is_ascii_digit()) {
+            return None;
+        }
+        Version::parse(version)
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.major, self.minor, self.patch)
+            .cmp(&(other.major, other.minor, other.patch))
+            .then_with(|| match (self.pre.is_empty(), other.pre.is_empty()) {
+                // A release ranks above its own prereleases
+                (true, true) => Ordering::Equal,
+                (true, false) => Ordering::Greater,
+                (false, true) => Ordering::Less,
+                (false, false) => compare_prerelease(&self.pre, &other.pre),
+            })
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Bump the trailing numeric prerelease counter of a tag, e.g. `v1.2.0-rc.1` -> `v1.2.0-rc.2`.
+///
+/// The counter is zero-padded to `pad` digits, or to its current width when it's already padded;
+/// tags without such a counter are `None`.
+pub fn increment_prerelease(tag: &str, pad: usize) -> Option<String> {
+    let caps = PRERELEASE_COUNTER_RE.captures(tag)?;
+    let counter = &caps[2];
+    let next = counter.parse::<u64>().ok()? + 1;
+    let width = if counter.len() > 1 && counter.starts_with('0') { pad.max(counter.len()) } else { pad };
+
+    Some(format!("{}{:0width$}", &caps[1], next, width = width))
+}
+
+/// Numeric identifiers compare as numbers and below alphanumeric ones, as in the semver spec.
+fn compare_prerelease(left: &[String], right: &[String]) -> Ordering {
+    for (l, r) in left.iter().zip(right) {
+        let ordering = match (l.parse::<u64>(), r.parse::<u64>()) {
+            (Ok(l), Ok(r)) => l.cmp(&r),
+            (Ok(_), Err(_)) => Ordering::Less,
+            (Err(_), Ok(_)) => Ordering::Greater,
+            (Err(_), Err(_)) => l.cmp(r),
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    left.len().cmp(&right.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn given_tags_when_sorting_then_follows_semver_precedence() {
+        let mut tags = vec!["v1.10.0", "v1.2.0", "v1.2.0-rc.10", "v1.2.0-rc.2", "v1.2.0-beta", "v2.0.0"];
+
+        tags.sort_by_key(|tag| Version::parse(tag).unwrap());
+
+        assert_eq!(tags, vec!["v1.2.0-beta", "v1.2.0-rc.2", "v1.2.0-rc.10", "v1.2.0", "v1.10.0", "v2.0.0"]);
+    }
+
+    #[test]
+    fn given_non_semver_tags_when_parsing_then_returns_none() {
+        assert_eq!(Version::parse("nightly"), None);
+        assert_eq!(Version::parse("v1.2"), None);
+        assert_eq!(Version::parse("v1.2.3").unwrap().pre, Vec::<String>::new());
+    }
+
+    #[test]
+    fn given_custom_prefix_when_parsing_then_strips_it() {
+        let version = Version::parse_with_prefix("release-1.2.3-rc.1", "release-").unwrap();
+
+        assert_eq!((version.major, version.minor, version.patch), (1, 2, 3));
+        assert_eq!(version.pre, vec!["rc", "1"]);
+        assert_eq!(Version::parse_with_prefix("v1.2.3", "release-"), None);
+        assert_eq!(Version::parse_with_prefix("release-v1.2.3", "release-"), None);
+        assert!(Version::parse_with_prefix("v1.2.3", DEFAULT_TAG_PREFIX).is_some());
+    }
+
+    #[test]
+    fn given_padded_counter_when_incrementing_prerelease_then_keeps_the_padding() {
+        assert_eq!(increment_prerelease("v1.2.0-rc.01", 2).as_deref(), Some("v1.2.0-rc.02"));
+        assert_eq!(increment_prerelease("v1.2.0-rc.1", 2).as_deref(), Some("v1.2.0-rc.02"));
+        assert_eq!(increment_prerelease("v1.2.0-rc.09", 0).as_deref(), Some("v1.2.0-rc.10"));
+    }
+
+    #[test]
+    fn given_no_pad_when_incrementing_prerelease_then_counter_is_unpadded() {
+        assert_eq!(increment_prerelease("v1.2.0-rc.1", 0).as_deref(), Some("v1.2.0-rc.2"));
+        assert_eq!(increment_prerelease("v1.2.0-rc.9", 0).as_deref(), Some("v1.2.0-rc.10"));
+        assert_eq!(increment_prerelease("v1.2.0-beta", 0), None);
+        assert_eq!(increment_prerelease("v1.2.0", 0), None);
+    }
+}